@@ -2,7 +2,7 @@ defmodule Mediasoup.Consumer do
   @moduledoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#Consumer
   """
-  alias Mediasoup.{Consumer, NifWrap, Nif, EventListener}
+  alias Mediasoup.{Consumer, NifWrap, Nif, EventListener, ObjectRegistry}
   require NifWrap
   use GenServer, restart: :temporary, shutdown: 1000
 
@@ -24,14 +24,14 @@ defmodule Mediasoup.Consumer do
   @type rtpParameters :: map
 
   @typedoc """
-    MediaKind("audio" or "video")
+    MediaKind(:audio or :video)
   """
-  @type kind :: String.t()
+  @type kind :: :audio | :video
 
   @typedoc """
     https://mediasoup.org/documentation/v3/mediasoup/api/#ConsumerType
   """
-  @type type :: String.t()
+  @type type :: :simple | :simulcast | :svc | :pipe
 
   @typedoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#ConsumerScore
@@ -92,12 +92,32 @@ defmodule Mediasoup.Consumer do
     GenServer.stop(pid)
   end
 
-  @spec dump(t) :: map | {:error, :terminated} | {:error, String.t()}
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the consumer like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%Consumer{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the consumer as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%Consumer{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
+  @spec dump(t, Mediasoup.timeout_ms()) ::
+          Dump.t() | {:error, :terminated | :timeout} | {:error, String.t()}
   @doc """
   Dump internal stat for Consumer.
   """
-  def dump(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
   @spec closed?(t) :: boolean
@@ -172,71 +192,231 @@ defmodule Mediasoup.Consumer do
     NifWrap.call(pid, {:current_layers, []})
   end
 
-  @spec get_stats(t) :: list() | {:error, :terminated} | {:error, reason :: term()}
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          [Mediasoup.Stats.rtp_stream()] | {:error, :terminated | :timeout} | {:error, term()}
   @doc """
-  Returns current RTC statistics of the consumer.
+  Returns current RTC statistics of the consumer: the stream it sends, then the streams its
+  producer receives, if any.
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-getStats
   """
-  def get_stats(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:get_stats, []})
+  def get_stats(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
   end
 
-  @spec pause(t) :: {:ok} | {:error, :terminated} | {:error}
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the consumer to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%Consumer{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
+  end
+
+  @spec pause(t, Mediasoup.timeout_ms()) :: {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Pauses the consumer (no RTP is sent to the consuming endpoint).
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-pause
   """
-  def pause(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:pause, []})
+  def pause(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:pause, []}, timeout_ms)
   end
 
-  @spec resume(t) :: {:ok} | {:error, :terminated} | {:error}
+  @spec resume(t, Mediasoup.timeout_ms()) :: {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Resumes the consumer (RTP is sent again to the consuming endpoint).
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-resume
   """
-  def resume(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:resume, []})
+  def resume(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:resume, []}, timeout_ms)
   end
 
-  @spec set_preferred_layers(t, map) :: {:ok} | {:error, :terminated} | {:error}
+  @spec set_preferred_layers(t, map, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Sets the preferred (highest) spatial and temporal layers to be sent to the consuming endpoint. Just valid for simulcast and SVC consumers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-setPreferredLayers
   """
-  def set_preferred_layers(%Consumer{pid: pid}, layer) do
-    NifWrap.call(pid, {:set_preferred_layers, [layer]})
+  def set_preferred_layers(
+        %Consumer{pid: pid},
+        layer,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    NifWrap.call(pid, {:set_preferred_layers, [layer]}, timeout_ms)
   end
 
-  @spec set_priority(t, integer) :: {:ok} | {:error, :terminated} | {:error}
+  @spec set_priority(t, integer, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Sets the priority for this consumer. It affects how the estimated outgoing bitrate in the transport (obtained via transport-cc or REMB) is distributed among all video consumers, by priorizing those with higher priority.
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-setPriority
   """
-  def set_priority(%Consumer{pid: pid}, priority) do
-    NifWrap.call(pid, {:set_priority, [priority]})
+  def set_priority(%Consumer{pid: pid}, priority, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:set_priority, [priority]}, timeout_ms)
   end
 
-  @spec unset_priority(t) :: {:ok} | {:error, :terminated} | {:error}
+  @spec unset_priority(t, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Unsets the priority for this consumer (it sets it to its default value 1).
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-unsetPriority
   """
-  def unset_priority(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:unset_priority, []})
+  def unset_priority(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:unset_priority, []}, timeout_ms)
   end
 
-  @spec request_key_frame(t) :: {:ok} | {:error, :terminated} | {:error}
+  @spec request_key_frame(t, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout} | {:error}
   @doc """
   Request a key frame to the associated producer. Just valid for video consumers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#consumer-requestKeyFrame
   """
-  def request_key_frame(%Consumer{pid: pid}) do
-    NifWrap.call(pid, {:request_key_frame, []})
+  def request_key_frame(%Consumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:request_key_frame, []}, timeout_ms)
+  end
+
+  @typedoc """
+  Result of the `*_all` functions: `{:ok}` when the operation succeeded on every consumer, the
+  reasons of the failures by consumer id otherwise. `{:error, :timeout}` when the operations did
+  not all complete within the timeout. Consumers owned by other nodes are handled on their node.
+  """
+  @type bulk_result ::
+          {:ok} | {:error, %{String.t() => String.t() | :terminated}} | {:error, :timeout}
+
+  @bulk_timeout_ms 5_000
+  # the nif replies {:error, :timeout} itself, this only guards against a reply that never comes
+  @bulk_reply_margin_ms 1_000
+
+  @spec pause_all([t], timeout_ms :: non_neg_integer()) :: bulk_result
+  @doc """
+  Pauses the consumers concurrently, see `pause/1`.
+  """
+  def pause_all(consumers, timeout_ms \\ @bulk_timeout_ms) do
+    bulk(consumers, &Nif.consumer_pause_all_async/3, [], timeout_ms)
+  end
+
+  @spec resume_all([t], timeout_ms :: non_neg_integer()) :: bulk_result
+  @doc """
+  Resumes the consumers concurrently, see `resume/1`.
+  """
+  def resume_all(consumers, timeout_ms \\ @bulk_timeout_ms) do
+    bulk(consumers, &Nif.consumer_resume_all_async/3, [], timeout_ms)
+  end
+
+  @spec set_priority_all([t], integer, timeout_ms :: non_neg_integer()) :: bulk_result
+  @doc """
+  Sets the priority of the consumers concurrently, see `set_priority/2`.
+  """
+  def set_priority_all(consumers, priority, timeout_ms \\ @bulk_timeout_ms) do
+    bulk(consumers, &Nif.consumer_set_priority_all_async/4, [priority], timeout_ms)
   end
 
+  @spec set_preferred_layers_all([t], map, timeout_ms :: non_neg_integer()) :: bulk_result
+  @doc """
+  Sets the preferred layers of the consumers concurrently, see `set_preferred_layers/2`.
+  """
+  def set_preferred_layers_all(consumers, layers, timeout_ms \\ @bulk_timeout_ms) do
+    bulk(consumers, &Nif.consumer_set_preferred_layers_all_async/4, [layers], timeout_ms)
+  end
+
+  @spec request_key_frame_all([t], timeout_ms :: non_neg_integer()) :: bulk_result
+  @doc """
+  Requests a key frame for each of the consumers concurrently, see `request_key_frame/1`.
+  """
+  def request_key_frame_all(consumers, timeout_ms \\ @bulk_timeout_ms) do
+    bulk(consumers, &Nif.consumer_request_key_frame_all_async/3, [], timeout_ms)
+  end
+
+  # The native references are only registered on the node that owns the consumer, so the bulk
+  # nif runs once per node: remote groups through erpc, the local group in the calling process.
+  defp bulk(consumers, nif, args, timeout_ms) do
+    {local, remote} =
+      consumers
+      |> Enum.group_by(&node(&1.pid))
+      |> Map.pop(node(), [])
+
+    requests =
+      Enum.map(remote, fn {node, consumers} ->
+        request =
+          :erpc.send_request(node, __MODULE__, :bulk_local, [consumers, nif, args, timeout_ms])
+
+        {consumers, request}
+      end)
+
+    local_result = bulk_local(local, nif, args, timeout_ms)
+
+    remote_results =
+      Enum.map(requests, fn {consumers, request} ->
+        receive_bulk_response(consumers, request, timeout_ms)
+      end)
+
+    merge_bulk_results([local_result | remote_results])
+  end
+
+  # Runs the bulk nif from the calling process: the native references are found by id, and the
+  # nif replies to the caller with the results in order.
+  @doc false
+  def bulk_local(consumers, nif, args, timeout_ms) do
+    {found, closed} =
+      consumers
+      |> Enum.map(&{&1.id, ObjectRegistry.find(&1.id)})
+      |> Enum.split_with(&match?({_id, {:consumer, _reference}}, &1))
+
+    tag = make_ref()
+    references = Enum.map(found, fn {_id, {:consumer, reference}} -> reference end)
+    apply(nif, [references | args] ++ [tag, timeout_ms])
+
+    receive do
+      {:mediasoup_async_nif_result, ^tag, {:error, :timeout}} ->
+        {:error, :timeout}
+
+      {:mediasoup_async_nif_result, ^tag, results} ->
+        failures =
+          found
+          |> Enum.zip(results)
+          |> Enum.flat_map(fn
+            {_, {:ok, _}} -> []
+            {{id, _}, {:error, reason}} -> [{id, reason}]
+          end)
+          |> Enum.concat(Enum.map(closed, fn {id, _} -> {id, :terminated} end))
+
+        if failures == [], do: {:ok}, else: {:error, Map.new(failures)}
+    after
+      timeout_ms + @bulk_reply_margin_ms ->
+        {:error, :timeout}
+    end
+  end
+
+  defp receive_bulk_response(consumers, request, timeout_ms) do
+    :erpc.receive_response(request, timeout_ms + 2 * @bulk_reply_margin_ms)
+  catch
+    :error, {:erpc, :timeout} ->
+      {:error, :timeout}
+
+    :error, {:erpc, :noconnection} ->
+      {:error, Map.new(consumers, &{&1.id, :terminated})}
+  end
+
+  defp merge_bulk_results(results) do
+    Enum.reduce(results, {:ok}, fn
+      _, {:error, :timeout} -> {:error, :timeout}
+      {:error, :timeout}, _ -> {:error, :timeout}
+      {:ok}, acc -> acc
+      {:error, failures}, {:ok} -> {:error, failures}
+      {:error, failures}, {:error, acc} -> {:error, Map.merge(acc, failures)}
+    end)
+  end
+
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_pause
           | :on_resume
           | :on_producer_resume
@@ -246,16 +426,20 @@ defmodule Mediasoup.Consumer do
           | :on_score
           | :on_layers_change
 
-  @spec event(t, pid, event_types :: [event_type]) ::
+  @spec event(t, pid, event_types :: [event_type], [Mediasoup.EventListener.option()]) ::
           {:ok} | {:error, :terminated}
   @doc """
   Starts observing event.
+
+  With `max_queue_len: n` in `opts`, value events are dropped while the mailbox of `listener`
+  holds `n` messages or more, see `Mediasoup.EventListener` and `dropped_events/2`.
   """
   def event(
         %Consumer{pid: pid},
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_pause,
           :on_resume,
           :on_producer_resume,
@@ -264,9 +448,18 @@ defmodule Mediasoup.Consumer do
           :on_transport_close,
           :on_score,
           :on_layers_change
-        ]
+        ],
+        opts \\ []
       ) do
-    NifWrap.call(pid, {:event, listener, event_types})
+    NifWrap.call(pid, {:event, listener, event_types, opts})
+  end
+
+  @spec dropped_events(t, pid) :: non_neg_integer() | {:error, :terminated}
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full, see `event/4`.
+  """
+  def dropped_events(%Consumer{pid: pid}, listener) do
+    NifWrap.call(pid, {:dropped_events, listener})
   end
 
   def link_pipe_producer(%Consumer{pid: pid}, %Mediasoup.Producer{pid: producer_pid}) do
@@ -306,20 +499,31 @@ defmodule Mediasoup.Consumer do
              optional(any()) => any()
            }}
   def init(%{reference: reference} = state) do
-    {:ok} =
-      Nif.consumer_event(reference, self(), [
-        :on_close,
-        :on_pause,
-        :on_resume,
-        :on_producer_resume,
-        :on_producer_pause,
-        :on_producer_close,
-        :on_transport_close,
-        :on_score,
-        :on_layers_change
-      ])
-
-    {:ok, Map.merge(state, %{listeners: EventListener.new(), linked_producer: nil})}
+    {:ok, subscription} =
+      Nif.consumer_event(
+        reference,
+        self(),
+        [
+          :on_close,
+          :on_close_reason,
+          :on_pause,
+          :on_resume,
+          :on_producer_resume,
+          :on_producer_pause,
+          :on_producer_close,
+          :on_transport_close,
+          :on_score,
+          :on_layers_change
+        ],
+        %Mediasoup.EventOptions{batch: true}
+      )
+
+    {:ok,
+     Map.merge(state, %{
+       listeners: EventListener.new(),
+       linked_producer: nil,
+       subscription: subscription
+     })}
   end
 
   @impl true
@@ -342,14 +546,18 @@ defmodule Mediasoup.Consumer do
 
   @impl true
   def handle_call(
-        {:event, listener, event_types},
+        {:event, listener, event_types, opts},
         _from,
         %{listeners: listeners} = state
       ) do
-    listeners = EventListener.add(listeners, listener, event_types)
+    listeners = EventListener.add(listeners, listener, event_types, opts)
     {:reply, {:ok}, Map.put(state, :listeners, listeners)}
   end
 
+  def handle_call({:dropped_events, listener}, _from, %{listeners: listeners} = state) do
+    {:reply, EventListener.dropped(listeners, listener), state}
+  end
+
   @impl true
   def handle_call(
         {:struct_from_pid, _arg},
@@ -360,6 +568,8 @@ defmodule Mediasoup.Consumer do
   end
 
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.consumer_stats_subscribe/4,
+    monitor_owner: &Nif.consumer_monitor_owner/2,
     closed?: &Nif.consumer_closed/1,
     paused?: &Nif.consumer_paused/1,
     producer_paused?: &Nif.consumer_producer_paused/1,
@@ -370,16 +580,24 @@ defmodule Mediasoup.Consumer do
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    set_preferred_layers: &Nif.consumer_set_preferred_layers_async/3,
-    set_priority: &Nif.consumer_set_priority_async/3,
-    request_key_frame: &Nif.consumer_request_key_frame_async/2,
-    unset_priority: &Nif.consumer_unset_priority_async/2,
-    dump: &Nif.consumer_dump_async/2,
-    get_stats: &Nif.consumer_get_stats_async/2,
-    pause: &Nif.consumer_pause_async/2,
-    resume: &Nif.consumer_resume_async/2
+    set_preferred_layers: &Nif.consumer_set_preferred_layers_async/4,
+    set_priority: &Nif.consumer_set_priority_async/4,
+    request_key_frame: &Nif.consumer_request_key_frame_async/3,
+    unset_priority: &Nif.consumer_unset_priority_async/3,
+    dump: &Nif.consumer_dump_async/3,
+    get_stats: &Nif.consumer_get_stats_async/3,
+    pause: &Nif.consumer_pause_async/3,
+    resume: &Nif.consumer_resume_async/3
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.consumer_close_async/3)
+
+  @impl true
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_func, from}, result},
@@ -407,11 +625,23 @@ defmodule Mediasoup.Consumer do
     end
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
+  @impl true
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   @impl true
   def handle_info({:on_close}, state) do
     # piped event
@@ -452,8 +682,8 @@ defmodule Mediasoup.Consumer do
   end
 
   @impl true
-  def terminate(_reason, %{reference: reference, listeners: listeners} = _state) do
-    EventListener.send(listeners, :on_close, {:on_close})
+  def terminate(reason, %{reference: reference, listeners: listeners} = state) do
+    EventListener.send_close(listeners, Mediasoup.Utility.close_reason(reason, state))
     Nif.consumer_close(reference)
     :ok
   end
@@ -500,4 +730,92 @@ defmodule Mediasoup.Consumer do
       }
     end
   end
+
+  defmodule BatchItem do
+    @moduledoc """
+    Options of one consumer created by `Mediasoup.WebRtcTransport.consume_batch/3`.
+    Like `Mediasoup.Consumer.Options`; the RTP capabilities are given once for the batch.
+    """
+    @enforce_keys [:producer_id]
+    defstruct [
+      :producer_id,
+      paused: nil,
+      preferred_layers: nil,
+      enable_rtx: nil,
+      ignore_dtx: nil,
+      mid: nil
+    ]
+
+    @type t :: %BatchItem{
+            producer_id: String.t(),
+            paused: boolean | nil,
+            preferred_layers: term | nil,
+            enable_rtx: boolean | nil,
+            ignore_dtx: boolean | nil,
+            mid: String.t() | nil
+          }
+
+    def from_map(%{} = map) do
+      map = for {key, val} <- map, into: %{}, do: {to_string(key), val}
+
+      %BatchItem{
+        producer_id: map["producerId"],
+        paused: map["paused"],
+        preferred_layers: map["preferredLayers"],
+        enable_rtx: map["enableRtx"],
+        ignore_dtx: map["ignoreDtx"],
+        mid: map["mid"]
+      }
+    end
+  end
+
+  defmodule Dump do
+    @moduledoc """
+    Dump of the consumer, see `Mediasoup.Consumer.dump/1`.
+
+    Layers are `nil` when not set.
+    """
+
+    defstruct [
+      :id,
+      :producer_id,
+      :kind,
+      :type,
+      :paused,
+      :producer_paused,
+      :priority,
+      :rtp_parameters,
+      :consumable_rtp_encodings,
+      :supported_codec_payload_types,
+      :rtp_streams,
+      :trace_event_types,
+      :preferred_spatial_layer,
+      :target_spatial_layer,
+      :current_spatial_layer,
+      :preferred_temporal_layer,
+      :target_temporal_layer,
+      :current_temporal_layer
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            producer_id: String.t(),
+            kind: :audio | :video,
+            type: :simple | :simulcast | :svc | :pipe,
+            paused: boolean(),
+            producer_paused: boolean(),
+            priority: non_neg_integer(),
+            rtp_parameters: map(),
+            consumable_rtp_encodings: [map()],
+            supported_codec_payload_types: [non_neg_integer()],
+            rtp_streams: [map()],
+            trace_event_types: [atom()],
+            preferred_spatial_layer: integer() | nil,
+            target_spatial_layer: integer() | nil,
+            current_spatial_layer: integer() | nil,
+            preferred_temporal_layer: integer() | nil,
+            target_temporal_layer: integer() | nil,
+            current_temporal_layer: integer() | nil
+          }
+  end
 end
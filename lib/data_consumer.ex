@@ -26,9 +26,9 @@ defmodule Mediasoup.DataConsumer do
 
   @typedoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#DataConsumerType
-   "sctp" or "direct"
+   :sctp or :direct
   """
-  @type type :: String.t()
+  @type type :: :sctp | :direct
 
   @spec id(t) :: String.t()
   def id(%{id: id}) do
@@ -65,6 +65,47 @@ defmodule Mediasoup.DataConsumer do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the data consumer like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%DataConsumer{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the data consumer as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%DataConsumer{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          [Mediasoup.Stats.DataConsumerStats.t()] | {:error, :terminated | :timeout}
+  @doc """
+  Returns current statistics of the data consumer.
+  https://mediasoup.org/documentation/v3/mediasoup/api/#dataConsumer-getStats
+  """
+  def get_stats(%DataConsumer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
+  end
+
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the data consumer to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%DataConsumer{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
+  end
+
   @spec closed?(t) :: boolean
   def closed?(%DataConsumer{pid: pid}) do
     !Process.alive?(pid) ||
@@ -74,9 +115,13 @@ defmodule Mediasoup.DataConsumer do
       end
   end
 
-  @type event_type :: :on_close
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`.
+  """
+  @type event_type :: :on_close | :on_close_reason | :on_owner_down
   @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
-  def event(%DataConsumer{pid: pid}, listener, event_types \\ [:on_close]) do
+  def event(%DataConsumer{pid: pid}, listener, event_types \\ [:on_close, :on_owner_down]) do
     NifWrap.call(pid, {:event, listener, event_types})
   end
 
@@ -111,11 +156,23 @@ defmodule Mediasoup.DataConsumer do
 
   @impl true
   def init(%{reference: reference} = state) do
-    Nif.data_consumer_event(reference, self(), [
-      :on_close
-    ])
-
-    {:ok, Map.merge(state, %{listeners: EventListener.new(), linked_producer: nil})}
+    {:ok, subscription} =
+      Nif.data_consumer_event(
+        reference,
+        self(),
+        [
+          :on_close,
+          :on_close_reason
+        ],
+        %Mediasoup.EventOptions{batch: true}
+      )
+
+    {:ok,
+     Map.merge(state, %{
+       listeners: EventListener.new(),
+       linked_producer: nil,
+       subscription: subscription
+     })}
   end
 
   @impl true
@@ -156,9 +213,17 @@ defmodule Mediasoup.DataConsumer do
   end
 
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.data_consumer_stats_subscribe/4,
+    monitor_owner: &Nif.data_consumer_monitor_owner/2,
     closed?: &Nif.data_consumer_closed/1
   })
 
+  NifWrap.def_handle_call_async_nif(%{
+    get_stats: &Nif.data_consumer_get_stats_async/3
+  })
+
+  NifWrap.def_handle_call_close_and_wait(&Nif.data_consumer_close_async/3)
+
   @impl true
   def handle_info(
         {:DOWN, monitor_ref, :process, pid, _reason},
@@ -185,14 +250,38 @@ defmodule Mediasoup.DataConsumer do
     {:stop, reason, state}
   end
 
+  @impl true
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
+  @impl true
+  def handle_info({:mediasoup_async_nif_result, {_, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:noreply, state}
+  end
+
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
   @impl true
-  def terminate(_reason, %{reference: reference, listeners: listeners} = _state) do
-    EventListener.send(listeners, :on_close, {:on_close})
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
+  @impl true
+  def terminate(reason, %{reference: reference, listeners: listeners} = state) do
+    EventListener.send_close(listeners, Mediasoup.Utility.close_reason(reason, state))
     Nif.data_consumer_close(reference)
     :ok
   end
@@ -0,0 +1,28 @@
+defmodule Mediasoup.EventOptions do
+  @moduledoc """
+  Limits for the events sent by a native event subscription.
+
+  They apply to value events only (`on_score`, `on_layers_change`, ICE/DTLS/SCTP state changes,
+  tuple changes and video orientation changes). Lifecycle events such as `on_close` are
+  never delayed or dropped.
+
+  * `min_interval_ms` - minimum interval between two events of the same type.
+    Within the interval only the last value is kept, and it is sent once the interval has passed.
+  * `coalesce` - replace a value that is still waiting for delivery instead of queueing
+    another one.
+  * `batch` - send the events queued together for the process as one
+    `{:nif_internal_event_batch, events}` message. Only for processes that handle it, see
+    `Mediasoup.NifWrap.def_handle_info_event_batch/0`. Otherwise each event is its own message.
+
+  A limit on the mailbox of a listener is set when it starts observing the events of an entity,
+  with the `:max_queue_len` option of `event/4`, see `Mediasoup.EventListener`.
+  """
+
+  defstruct min_interval_ms: nil, coalesce: nil, batch: nil
+
+  @type t :: %__MODULE__{
+          min_interval_ms: non_neg_integer() | nil,
+          coalesce: boolean() | nil,
+          batch: boolean() | nil
+        }
+end
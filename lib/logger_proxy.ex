@@ -17,6 +17,13 @@ defmodule Mediasoup.LoggerProxy do
     end
   ```
 
+  Besides `max_level`, records can be filtered in the native layer before they are sent:
+  `target_prefixes` keeps records whose target starts with one of the prefixes, and
+  `worker_class_prefixes` keeps worker records whose class, such as `"RTC::IceServer"`, starts
+  with one of the prefixes. Worker lines do not carry their `logTags` tag, only the class and
+  method that logged them. Several proxies can run at the same time when started with distinct
+  `name`s. Records are dropped while `max_pending` of them are waiting for delivery, see
+  `dropped/1`.
   """
 
   require Logger
@@ -25,20 +32,33 @@ defmodule Mediasoup.LoggerProxy do
   defmodule Record do
     @moduledoc """
     Struct of log record
+
+    `worker_id` and `worker_log_class` (e.g. `"RTC::IceServer"`) are set for records logged by
+    a mediasoup worker, and `router_id` and `transport_id` when the message mentions them.
     """
 
     defstruct [
       :level,
       :target,
+      :worker_id,
+      :worker_log_class,
+      :router_id,
+      :transport_id,
       :module_path,
       :file,
       :line,
-      :body
+      :body,
+      metadata: %{}
     ]
 
     @type t :: %__MODULE__{
             level: :error | :warn | :info | :debug,
             target: String.t(),
+            worker_id: String.t() | nil,
+            worker_log_class: String.t() | nil,
+            router_id: String.t() | nil,
+            transport_id: String.t() | nil,
+            metadata: %{String.t() => String.t()},
             module_path: String.t() | nil,
             file: String.t() | nil,
             line: integer() | nil,
@@ -46,24 +66,66 @@ defmodule Mediasoup.LoggerProxy do
           }
   end
 
+  defmodule SinkOptions do
+    @moduledoc """
+    Filters of a native log sink, see `Mediasoup.Nif.logger_add_sink/2`.
+    """
+
+    defstruct max_level: nil,
+              target_prefixes: nil,
+              worker_class_prefixes: nil,
+              max_pending: nil
+
+    @type t :: %__MODULE__{
+            max_level: :off | :error | :warn | :info | :debug | :trace | nil,
+            target_prefixes: [String.t()] | nil,
+            worker_class_prefixes: [String.t()] | nil,
+            max_pending: non_neg_integer() | nil
+          }
+  end
+
   @type filter_fun :: (Record.t() -> :log | {:log, Record.t()} | :stop | :ignore)
   @type config ::
           {:max_level, :off | :error | :warn | :info | :debug}
           | {:filters, [filter_fun()]}
+          | {:target_prefixes, [String.t()]}
+          | {:worker_class_prefixes, [String.t()]}
+          | {:max_pending, non_neg_integer()}
+          | {:name, GenServer.name()}
 
   @spec start_link([config]) :: :ignore | {:error, any} | {:ok, pid}
   def start_link(config \\ []) do
-    max_level = Keyword.get(config, :max_level, :error)
+    sink_options = %SinkOptions{
+      max_level: Keyword.get(config, :max_level, :error),
+      target_prefixes: Keyword.get(config, :target_prefixes),
+      worker_class_prefixes: Keyword.get(config, :worker_class_prefixes),
+      max_pending: Keyword.get(config, :max_pending)
+    }
+
     filters = Keyword.get(config, :filters, [])
 
-    GenServer.start_link(Mediasoup.LoggerProxy, %{max_level: max_level, filters: filters},
-      name: __MODULE__
+    GenServer.start_link(
+      Mediasoup.LoggerProxy,
+      %{sink_options: sink_options, filters: filters},
+      name: Keyword.get(config, :name, __MODULE__)
     )
   end
 
-  def init(init_arg) do
-    Mediasoup.Nif.set_logger_proxy_process(self(), init_arg[:max_level])
-    {:ok, init_arg}
+  @doc """
+  Returns the number of records dropped because too many were waiting for delivery.
+  """
+  @spec dropped(GenServer.server()) :: non_neg_integer()
+  def dropped(server \\ __MODULE__) do
+    GenServer.call(server, :dropped)
+  end
+
+  def init(%{sink_options: sink_options, filters: filters}) do
+    {:ok, sink} = Mediasoup.Nif.logger_add_sink(self(), sink_options)
+    {:ok, %{sink: sink, filters: filters}}
+  end
+
+  def handle_call(:dropped, _from, %{sink: sink} = state) do
+    {:reply, Mediasoup.Nif.logger_sink_dropped(sink), state}
   end
 
   def handle_info(%Mediasoup.LoggerProxy.Record{} = msg, %{filters: filters} = state) do
@@ -80,7 +142,12 @@ defmodule Mediasoup.LoggerProxy do
         line: msg.line,
         file: msg.file,
         mfa: msg.target,
-        module_path: msg.module_path
+        module_path: msg.module_path,
+        worker_id: msg.worker_id,
+        worker_log_class: msg.worker_log_class,
+        router_id: msg.router_id,
+        transport_id: msg.transport_id,
+        kv: msg.metadata
       })
     end
 
@@ -95,7 +162,7 @@ defmodule Mediasoup.LoggerProxy do
 
   def child_spec(opts) do
     %{
-      id: Mediasoup.LoggerProxy,
+      id: Keyword.get(opts, :name, Mediasoup.LoggerProxy),
       start: {__MODULE__, :start_link, [opts]},
       shutdown: 5_000,
       restart: :permanent,
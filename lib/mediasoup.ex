@@ -18,6 +18,12 @@ defmodule Mediasoup do
   @typedoc "https://mediasoup.org/documentation/v3/mediasoup/api/#TransportListenInfo"
   @type transport_listen_info :: Mediasoup.TransportListenInfo.t()
 
+  @typedoc """
+  Deadline of a request to the worker, in milliseconds. The request returns `{:error, :timeout}`
+  when it is exceeded; `nil` waits for the worker as long as it takes.
+  """
+  @type timeout_ms :: non_neg_integer() | nil
+
   @doc """
   Since the format of env_logger is different from that of elixir, it is recommended to use Mediasoup.LoggerProxy instead.
   Initialize the logger with env logger. Same as the env_logger::init() call.
@@ -0,0 +1,52 @@
+defmodule Mediasoup.Metrics do
+  @moduledoc """
+  Counters and latency histograms kept in the native layer, for export to a metrics system.
+
+  * `async_nifs` is keyed by NIF name, e.g. `"router_create_webrtc_transport_async"`.
+    `queue_time` is the time until the request starts running on the task pool, and `run_time`
+    the time it takes, which is mostly the round trip to the worker.
+  * `entities` counts the entities created and closed since the NIF was loaded, per type.
+    Creation and close rates are the rates of the `created` and `closed` counters.
+
+  Histogram `buckets` are `{upper_bound_us, cumulative_count}` pairs, as Prometheus histograms
+  expect; `count` also includes the values above the last bound.
+  """
+
+  @type histogram :: %{
+          buckets: [{non_neg_integer(), non_neg_integer()}],
+          count: non_neg_integer(),
+          sum_us: non_neg_integer()
+        }
+  @type async_nif :: %{
+          calls: non_neg_integer(),
+          timeouts: non_neg_integer(),
+          queue_time: histogram(),
+          run_time: histogram()
+        }
+  @type entity :: %{
+          live: non_neg_integer(),
+          created: non_neg_integer(),
+          closed: non_neg_integer()
+        }
+  @type entity_type ::
+          :worker
+          | :webrtc_server
+          | :router
+          | :webrtc_transport
+          | :plain_transport
+          | :pipe_transport
+          | :producer
+          | :consumer
+          | :data_producer
+          | :data_consumer
+  @type snapshot :: %{
+          async_nifs: %{String.t() => async_nif()},
+          entities: %{entity_type() => entity()}
+        }
+
+  @doc """
+  Returns all metrics in one call.
+  """
+  @spec snapshot() :: snapshot()
+  def snapshot(), do: Mediasoup.Nif.metrics_snapshot()
+end
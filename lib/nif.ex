@@ -58,13 +58,28 @@ defmodule Mediasoup.Nif do
 
   #  use Rustler, otp_app: :mediasoup_elixir, crate: :mediasoup_elixir
 
-  alias Mediasoup.{Worker, Router}
+  alias Mediasoup.{Worker, Router, EventOptions}
 
   # async nif functions
+  # Functions named `*_async` taking `from` reply `{:mediasoup_async_nif_result, from, result}`.
+  # With `timeout_ms`, they reply `{:error, :timeout}` once the deadline passes, and resources
+  # created after that are closed. A request that panics replies `{:error, :panicked}`.
 
   ## worker with async
-  defp create_worker_async(), do: :erlang.nif_error(:nif_not_loaded)
-  defp create_worker_async(_option), do: :erlang.nif_error(:nif_not_loaded)
+  # The reply is `{:mediasoup_async_nif_result, request, result}`, where `request` is the
+  # reference returned by the call.
+  @spec create_worker_async() :: {:ok, reference}
+  def create_worker_async(), do: :erlang.nif_error(:nif_not_loaded)
+  @spec create_worker_async(Worker.create_option()) :: {:ok, reference}
+  def create_worker_async(_option), do: :erlang.nif_error(:nif_not_loaded)
+  # Replies `{:error, :timeout}` after `timeout_ms`; a worker created later is closed.
+  @spec create_worker_async(Worker.create_option(), non_neg_integer()) :: {:ok, reference}
+  def create_worker_async(_option, _timeout_ms), do: :erlang.nif_error(:nif_not_loaded)
+
+  # Cancels a request returned by create_worker_async. No reply is sent for a cancelled
+  # request, and a worker created after the cancellation is closed.
+  @spec async_request_cancel(reference) :: {:ok} | {:error, :cancelled | :completed}
+  def async_request_cancel(_request), do: :erlang.nif_error(:nif_not_loaded)
 
   # plain transport
   ## properties
@@ -76,31 +91,46 @@ defmodule Mediasoup.Nif do
   ## methods
 
   ### plain tranasport call
-  def plain_transport_connect_async(_transport, _option, _from),
+  def plain_transport_connect_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec plain_transport_id(reference) :: String.t()
   def plain_transport_id(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def plain_transport_dump_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def plain_transport_dump_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def plain_transport_get_stats_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def plain_transport_get_stats_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec plain_transport_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def plain_transport_stats_subscribe(_transport, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def plain_transport_produce_async(_transport, _option, _from),
+  def plain_transport_produce_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def plain_transport_consume_async(_transport, _option, _from),
+  def plain_transport_consume_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec plain_transport_close(reference) :: {:ok} | {:error}
   def plain_transport_close(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
+  def plain_transport_close_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec plain_transport_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def plain_transport_monitor_owner(_transport, _owner), do: :erlang.nif_error(:nif_not_loaded)
+
   @spec plain_transport_closed(reference) :: boolean
   def plain_transport_closed(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
   ## plain transport event
-  @spec plain_transport_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def plain_transport_event(_transport, _pid, _event_types),
+  @spec plain_transport_event(reference, pid, [atom()], EventOptions.t()) ::
+          {:ok, reference} | {:error}
+  def plain_transport_event(transport, pid, event_types, options \\ %EventOptions{})
+  def plain_transport_event(_transport, _pid, _event_types, _options),
     do: :erlang.nif_error(:nif_not_loaded)
 
   # construct worker
@@ -113,28 +143,35 @@ defmodule Mediasoup.Nif do
   # worker
   @spec worker_create_router_async(reference, Router.create_option(), term()) ::
           :ok | :error
-  def worker_create_router_async(_worker, _option, _from),
+  def worker_create_router_async(_worker, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec worker_id(reference) :: String.t()
   def worker_id(_worker), do: :erlang.nif_error(:nif_not_loaded)
   @spec worker_close(reference) :: {:ok} | {:error}
   def worker_close(_worker), do: :erlang.nif_error(:nif_not_loaded)
-  @spec worker_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def worker_event(_worker, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
+  def worker_close_async(_worker, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec worker_event(reference, pid, [atom()], EventOptions.t()) :: {:ok, reference} | {:error}
+  def worker_event(worker, pid, event_types, options \\ %EventOptions{})
+  def worker_event(_worker, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
+  @spec worker_set_log_sink(reference, pid | nil, Mediasoup.LoggerProxy.SinkOptions.t()) :: {:ok}
+  def worker_set_log_sink(_worker, _pid, _options), do: :erlang.nif_error(:nif_not_loaded)
   @spec worker_closed(reference) :: boolean
   def worker_closed(_worker), do: :erlang.nif_error(:nif_not_loaded)
 
   @spec worker_update_settings_async(reference, Worker.update_option(), GenServer.from()) ::
           {:ok} | {:error}
-  def worker_update_settings_async(_worker, _option, _from),
+  def worker_update_settings_async(_worker, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def worker_create_webrtc_server_async(_worker, _option, _from),
+  def worker_create_webrtc_server_async(_worker, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec worker_dump_async(reference, term()) :: map | {:error}
-  def worker_dump_async(_worker, _from),
+  def worker_dump_async(_worker, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   # router
@@ -143,21 +180,48 @@ defmodule Mediasoup.Nif do
   @spec router_close(reference) :: {:ok} | {:error}
   def router_close(_router), do: :erlang.nif_error(:nif_not_loaded)
 
+  def router_close_async(_router, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec router_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def router_monitor_owner(_router, _owner), do: :erlang.nif_error(:nif_not_loaded)
+
   def router_closed(_router), do: :erlang.nif_error(:nif_not_loaded)
 
   def router_create_pipe_transport_async(
         _reference,
         _option,
-        _from
+        _from,
+        _timeout_ms \\ nil
       ),
       do: :erlang.nif_error(:nif_not_loaded)
 
-  def router_create_webrtc_transport_async(_router, _option, _from),
+  def router_create_webrtc_transport_async(_router, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def router_create_plain_transport_async(_router, _option, _from),
+  def router_create_plain_transport_async(_router, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def router_pipe_producer_to_router_async(
+        _router,
+        _producer_id,
+        _remote_router,
+        _option,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  def router_pipe_data_producer_to_router_async(
+        _router,
+        _data_producer_id,
+        _remote_router,
+        _option,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
   @spec router_can_consume(reference, String.t(), Router.rtpCapabilities()) :: boolean
   def router_can_consume(_router, _producer_id, _rtp_capabilities),
     do: :erlang.nif_error(:nif_not_loaded)
@@ -165,50 +229,82 @@ defmodule Mediasoup.Nif do
   @spec router_rtp_capabilities(reference) :: Router.rtpCapabilities()
   def router_rtp_capabilities(_router), do: :erlang.nif_error(:nif_not_loaded)
 
-  @spec router_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def router_event(_router, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
-  def router_dump_async(_router, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec router_event(reference, pid, [atom()], EventOptions.t()) :: {:ok, reference} | {:error}
+  def router_event(router, pid, event_types, options \\ %EventOptions{})
+  def router_event(_router, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def router_dump_async(_router, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def router_get_stats_async(_router, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # webrtc_server
   @spec webrtc_server_id(reference) :: String.t()
   def webrtc_server_id(_server), do: :erlang.nif_error(:nif_not_loaded)
   @spec webrtc_server_close(reference) :: {:ok} | {:error}
   def webrtc_server_close(_server), do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_server_close_async(_server, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @spec webrtc_server_closed(reference) :: boolean
   def webrtc_server_closed(_server), do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_server_dump_async(_server, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_server_dump_async(_server, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # webrtc_transport
   @spec webrtc_transport_id(reference) :: String.t()
   def webrtc_transport_id(_transport), do: :erlang.nif_error(:nif_not_loaded)
   @spec webrtc_transport_close(reference) :: {:ok} | {:error}
   def webrtc_transport_close(_transport), do: :erlang.nif_error(:nif_not_loaded)
+
+  def webrtc_transport_close_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec webrtc_transport_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def webrtc_transport_monitor_owner(_transport, _owner), do: :erlang.nif_error(:nif_not_loaded)
   @spec webrtc_transport_closed(reference) :: boolean
   def webrtc_transport_closed(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
   @spec webrtc_transport_consume_async(reference, any, term) ::
           {:ok, reference()} | {:error, String.t()}
-  def webrtc_transport_consume_async(_transport, _option, _from),
+  def webrtc_transport_consume_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  @spec webrtc_transport_consume_batch_async(
+          reference,
+          map(),
+          [Mediasoup.Consumer.BatchItem.t()],
+          term
+        ) ::
+          {:ok, [{:ok, reference()} | {:error, String.t()}]} | {:error, String.t()}
+  def webrtc_transport_consume_batch_async(
+        _transport,
+        _rtp_capabilities,
+        _items,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
   @spec webrtc_transport_consume_data_async(reference, any, term) ::
           {:ok, reference()} | {:error, String.t()}
-  def webrtc_transport_consume_data_async(_transport, _option, _from),
+  def webrtc_transport_consume_data_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec webrtc_transport_connect_async(reference, any, term) :: {:ok} | {:error, String.t()}
-  def webrtc_transport_connect_async(_transport, _option, _from),
+  def webrtc_transport_connect_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec webrtc_transport_produce_async(reference, any, term) ::
           {:ok, reference()} | {:error, String.t()}
-  def webrtc_transport_produce_async(_transport, _option, _from),
+  def webrtc_transport_produce_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   @spec webrtc_transport_produce_data_async(reference, any, term) ::
           {:ok, reference()} | {:error, String.t()}
-  def webrtc_transport_produce_data_async(_transport, _option, _from),
+  def webrtc_transport_produce_data_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   def webrtc_transport_ice_parameters(_transport), do: :erlang.nif_error(:nif_not_loaded)
@@ -216,15 +312,25 @@ defmodule Mediasoup.Nif do
   def webrtc_transport_ice_role(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def webrtc_transport_sctp_parameters(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_transport_set_max_incoming_bitrate_async(_transport, _bitrate, _from),
-    do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_transport_set_max_incoming_bitrate_async(
+        _transport,
+        _bitrate,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_transport_set_max_outgoing_bitrate_async(_transport, _bitrate, _from),
-    do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_transport_set_max_outgoing_bitrate_async(
+        _transport,
+        _bitrate,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
 
   def webrtc_transport_ice_state(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_transport_restart_ice_async(_transport, _from),
+  def webrtc_transport_restart_ice_async(_transport, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
   def webrtc_transport_ice_selected_tuple(_transport), do: :erlang.nif_error(:nif_not_loaded)
@@ -232,48 +338,76 @@ defmodule Mediasoup.Nif do
   def webrtc_transport_dtls_state(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def webrtc_transport_sctp_state(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_transport_get_stats_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_transport_get_stats_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec webrtc_transport_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def webrtc_transport_stats_subscribe(_transport, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  @spec webrtc_transport_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def webrtc_transport_event(_transport, _pid, _event_types),
+  @spec webrtc_transport_event(reference, pid, [atom()], EventOptions.t()) ::
+          {:ok, reference} | {:error}
+  def webrtc_transport_event(transport, pid, event_types, options \\ %EventOptions{})
+  def webrtc_transport_event(_transport, _pid, _event_types, _options),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def webrtc_transport_dump_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def webrtc_transport_dump_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # pipe_transport
   def pipe_transport_id(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def pipe_transport_close(_transport), do: :erlang.nif_error(:nif_not_loaded)
+
+  def pipe_transport_close_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec pipe_transport_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def pipe_transport_monitor_owner(_transport, _owner), do: :erlang.nif_error(:nif_not_loaded)
   @spec pipe_transport_closed(reference) :: boolean
   def pipe_transport_closed(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_consume_async(_transport, _option, _from),
+  def pipe_transport_consume_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_consume_data_async(_transport, _option, _from),
+  def pipe_transport_consume_data_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_connect_async(_transport, _option, _from),
+  def pipe_transport_connect_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_produce_async(_transport, _option, _from),
+  def pipe_transport_produce_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_set_max_incoming_bitrate_async(_transport, _bitrate, _from),
+  def pipe_transport_set_max_incoming_bitrate_async(
+        _transport,
+        _bitrate,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  def pipe_transport_produce_data_async(_transport, _option, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_produce_data_async(_transport, _option, _from),
+  def pipe_transport_get_stats_async(_transport, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_get_stats_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec pipe_transport_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def pipe_transport_stats_subscribe(_transport, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   def pipe_transport_tuple(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def pipe_transport_sctp_parameters(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def pipe_transport_sctp_state(_transport), do: :erlang.nif_error(:nif_not_loaded)
   def pipe_transport_srtp_parameters(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_dump_async(_transport, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def pipe_transport_dump_async(_transport, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def pipe_transport_event(_transport, _pid, _event_types),
+  def pipe_transport_event(transport, pid, event_types, options \\ %EventOptions{})
+  def pipe_transport_event(_transport, _pid, _event_types, _options),
     do: :erlang.nif_error(:nif_not_loaded)
 
   # consumer
@@ -290,10 +424,18 @@ defmodule Mediasoup.Nif do
   def consumer_rtp_parameters(_consumer), do: :erlang.nif_error(:nif_not_loaded)
   @spec consumer_close(reference) :: {:ok} | {:error}
   def consumer_close(_consumer), do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_close_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec consumer_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def consumer_monitor_owner(_consumer, _owner), do: :erlang.nif_error(:nif_not_loaded)
   @spec consumer_closed(reference) :: boolean
   def consumer_closed(_consumer), do: :erlang.nif_error(:nif_not_loaded)
-  @spec consumer_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def consumer_event(_consumer, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
+  @spec consumer_event(reference, pid, [atom()], EventOptions.t()) :: {:ok, reference} | {:error}
+  def consumer_event(consumer, pid, event_types, options \\ %EventOptions{})
+  def consumer_event(_consumer, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
   @spec consumer_paused(reference) :: boolean
   def consumer_paused(_consumer), do: :erlang.nif_error(:nif_not_loaded)
   @spec consumer_producer_paused(reference) :: boolean
@@ -303,22 +445,64 @@ defmodule Mediasoup.Nif do
   def consumer_preferred_layers(_consumer), do: :erlang.nif_error(:nif_not_loaded)
   def consumer_current_layers(_consumer), do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_get_stats_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def consumer_get_stats_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_pause_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
-  def consumer_resume_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec consumer_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def consumer_stats_subscribe(_consumer, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_pause_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def consumer_resume_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_set_preferred_layers_async(
+        _consumer,
+        _preferred_layers,
+        _from,
+        _timeout_ms \\ nil
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_set_priority_async(_consumer, _priority, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_unset_priority_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_request_key_frame_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def consumer_dump_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_set_preferred_layers_async(_consumer, _preferred_layers, _from),
+  @spec consumer_pause_all_async([reference], term, non_neg_integer() | nil) :: :ok
+  def consumer_pause_all_async(_consumers, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_set_priority_async(_consumer, _priority, _from),
+  @spec consumer_resume_all_async([reference], term, non_neg_integer() | nil) :: :ok
+  def consumer_resume_all_async(_consumers, _from, _timeout_ms \\ nil),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_unset_priority_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec consumer_set_priority_all_async([reference], integer, term, non_neg_integer() | nil) ::
+          :ok
+  def consumer_set_priority_all_async(_consumers, _priority, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_request_key_frame_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec consumer_set_preferred_layers_all_async(
+          [reference],
+          map,
+          term,
+          non_neg_integer() | nil
+        ) :: :ok
+  def consumer_set_preferred_layers_all_async(_consumers, _layers, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def consumer_dump_async(_consumer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  @spec consumer_request_key_frame_all_async([reference], term, non_neg_integer() | nil) :: :ok
+  def consumer_request_key_frame_all_async(_consumers, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # data_consumer
   @spec data_consumer_id(reference) :: String.t()
@@ -333,12 +517,29 @@ defmodule Mediasoup.Nif do
   def data_consumer_label(_consumer), do: :erlang.nif_error(:nif_not_loaded)
   @spec data_consumer_protocol(reference) :: term
   def data_consumer_protocol(_consumer), do: :erlang.nif_error(:nif_not_loaded)
+  def data_consumer_get_stats_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec data_consumer_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def data_consumer_stats_subscribe(_consumer, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @spec data_consumer_close(reference) :: {:ok} | {:error}
   def data_consumer_close(_consumer), do: :erlang.nif_error(:nif_not_loaded)
+
+  def data_consumer_close_async(_consumer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec data_consumer_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def data_consumer_monitor_owner(_consumer, _owner), do: :erlang.nif_error(:nif_not_loaded)
   @spec data_consumer_closed(reference) :: boolean
   def data_consumer_closed(_consumer), do: :erlang.nif_error(:nif_not_loaded)
-  @spec data_consumer_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def data_consumer_event(_consumer, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
+  @spec data_consumer_event(reference, pid, [atom()], EventOptions.t()) ::
+          {:ok, reference} | {:error}
+  def data_consumer_event(consumer, pid, event_types, options \\ %EventOptions{})
+  def data_consumer_event(_consumer, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # producer
   @spec producer_id(reference) :: String.t()
@@ -351,8 +552,16 @@ defmodule Mediasoup.Nif do
   def producer_rtp_parameters(_producer), do: :erlang.nif_error(:nif_not_loaded)
   @spec producer_close(reference) :: {:ok} | {:error}
   def producer_close(_consumer), do: :erlang.nif_error(:nif_not_loaded)
-  def producer_pause_async(_producer, _from), do: :erlang.nif_error(:nif_not_loaded)
-  def producer_resume_async(_producer, _from), do: :erlang.nif_error(:nif_not_loaded)
+
+  def producer_close_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec producer_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def producer_monitor_owner(_producer, _owner), do: :erlang.nif_error(:nif_not_loaded)
+  def producer_pause_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+  def producer_resume_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   @spec producer_closed(reference) :: boolean()
   def producer_closed(_producer), do: :erlang.nif_error(:nif_not_loaded)
@@ -362,12 +571,21 @@ defmodule Mediasoup.Nif do
   @spec producer_score(reference) :: list() | {:error}
   def producer_score(_producer), do: :erlang.nif_error(:nif_not_loaded)
 
-  def producer_get_stats_async(_producer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def producer_get_stats_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  @spec producer_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def producer_event(_producer, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
+  @spec producer_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def producer_stats_subscribe(_producer, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec producer_event(reference, pid, [atom()], EventOptions.t()) :: {:ok, reference} | {:error}
+  def producer_event(producer, pid, event_types, options \\ %EventOptions{})
+  def producer_event(_producer, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def producer_dump_async(_producer, _from), do: :erlang.nif_error(:nif_not_loaded)
+  def producer_dump_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   # data_producer
   @spec data_producer_id(reference) :: String.t()
@@ -376,15 +594,78 @@ defmodule Mediasoup.Nif do
   def data_producer_type(_producer), do: :erlang.nif_error(:nif_not_loaded)
   @spec data_producer_sctp_stream_parameters(reference) :: term
   def data_producer_sctp_stream_parameters(_producer), do: :erlang.nif_error(:nif_not_loaded)
+  def data_producer_get_stats_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec data_producer_stats_subscribe(reference, pid, pos_integer(), boolean()) ::
+          {:ok, reference} | {:error}
+  def data_producer_stats_subscribe(_producer, _pid, _interval_ms, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @spec data_producer_close(reference) :: {:ok} | {:error}
   def data_producer_close(_producer), do: :erlang.nif_error(:nif_not_loaded)
+
+  def data_producer_close_async(_producer, _from, _timeout_ms \\ nil),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec data_producer_monitor_owner(reference, pid) :: {:ok} | {:error, :terminated}
+  def data_producer_monitor_owner(_producer, _owner), do: :erlang.nif_error(:nif_not_loaded)
   @spec data_producer_closed(reference) :: boolean
   def data_producer_closed(_producer), do: :erlang.nif_error(:nif_not_loaded)
-  @spec data_producer_event(reference, pid, [atom()]) :: {:ok} | {:error}
-  def data_producer_event(_producer, _pid, _event_types), do: :erlang.nif_error(:nif_not_loaded)
+  @spec data_producer_event(reference, pid, [atom()], EventOptions.t()) ::
+          {:ok, reference} | {:error}
+  def data_producer_event(producer, pid, event_types, options \\ %EventOptions{})
+  def data_producer_event(_producer, _pid, _event_types, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  # event subscription
+  @spec unsubscribe(reference) :: {:ok}
+  def unsubscribe(_subscription), do: :erlang.nif_error(:nif_not_loaded)
+
+  # stats subscription
+  @spec stats_unsubscribe(reference) :: {:ok}
+  def stats_unsubscribe(_subscription), do: :erlang.nif_error(:nif_not_loaded)
+
+  # runtime
+  @spec init_runtime(Mediasoup.Runtime.Options.t()) :: {:ok} | {:error, :already_started}
+  def init_runtime(_options), do: :erlang.nif_error(:nif_not_loaded)
+  @spec runtime_stats() :: Mediasoup.Runtime.stats()
+  def runtime_stats(), do: :erlang.nif_error(:nif_not_loaded)
+
+  # metrics
+  @spec metrics_snapshot() :: Mediasoup.Metrics.snapshot()
+  def metrics_snapshot(), do: :erlang.nif_error(:nif_not_loaded)
+
+  # object registry
+  @spec registry_list(String.t() | nil) :: [Mediasoup.ObjectRegistry.info()]
+  def registry_list(_parent_id), do: :erlang.nif_error(:nif_not_loaded)
+  @spec registry_info(String.t()) :: Mediasoup.ObjectRegistry.info() | nil
+  def registry_info(_id), do: :erlang.nif_error(:nif_not_loaded)
+  @spec registry_set_app_data(String.t(), term) :: {:ok} | {:error, :not_found}
+  def registry_set_app_data(_id, _app_data), do: :erlang.nif_error(:nif_not_loaded)
+  @spec registry_find(String.t()) :: {Mediasoup.Metrics.entity_type(), reference} | nil
+  def registry_find(_id), do: :erlang.nif_error(:nif_not_loaded)
+  @spec worker_list_routers(reference) :: [reference]
+  def worker_list_routers(_worker), do: :erlang.nif_error(:nif_not_loaded)
+  @spec router_list_transports(reference) :: [{Mediasoup.Metrics.entity_type(), reference}]
+  def router_list_transports(_router), do: :erlang.nif_error(:nif_not_loaded)
+  @spec transport_list_producers(reference) :: [reference]
+  def transport_list_producers(_transport), do: :erlang.nif_error(:nif_not_loaded)
+  @spec transport_list_consumers(reference) :: [reference]
+  def transport_list_consumers(_transport), do: :erlang.nif_error(:nif_not_loaded)
+  @spec transport_list_data_producers(reference) :: [reference]
+  def transport_list_data_producers(_transport), do: :erlang.nif_error(:nif_not_loaded)
+  @spec transport_list_data_consumers(reference) :: [reference]
+  def transport_list_data_consumers(_transport), do: :erlang.nif_error(:nif_not_loaded)
 
   # logger proxy
   def set_logger_proxy_process(_pid, _max_level), do: :erlang.nif_error(:nif_not_loaded)
+  @spec logger_add_sink(pid, Mediasoup.LoggerProxy.SinkOptions.t()) :: {:ok, reference}
+  def logger_add_sink(_pid, _options), do: :erlang.nif_error(:nif_not_loaded)
+  @spec logger_remove_sink(reference) :: :ok
+  def logger_remove_sink(_sink), do: :erlang.nif_error(:nif_not_loaded)
+  @spec logger_sink_dropped(reference) :: non_neg_integer()
+  def logger_sink_dropped(_sink), do: :erlang.nif_error(:nif_not_loaded)
 
   # for test
   def debug_logger(_level, _msg), do: :erlang.nif_error(:nif_not_loaded)
@@ -394,12 +675,39 @@ defmodule Mediasoup.Nif do
   @spec get_supported_rtp_capabilities() :: map()
   def get_supported_rtp_capabilities(), do: :erlang.nif_error(:nif_not_loaded)
 
+  # ortc
+  @spec ortc_validate_rtp_capabilities(map, map) :: Mediasoup.Ortc.report()
+  def ortc_validate_rtp_capabilities(_router_rtp_capabilities, _rtp_capabilities),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec ortc_validate_rtp_parameters(map, map) :: Mediasoup.Ortc.report()
+  def ortc_validate_rtp_parameters(_router_rtp_capabilities, _rtp_parameters),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec ortc_consumable_rtp_parameters(map, map) :: {:ok, map} | {:error, String.t()}
+  def ortc_consumable_rtp_parameters(_router_rtp_capabilities, _rtp_parameters),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec ortc_consumer_rtp_parameters(map, map, boolean) :: {:ok, map} | {:error, String.t()}
+  def ortc_consumer_rtp_parameters(_consumable_rtp_parameters, _rtp_capabilities, _enable_rtx),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec ortc_intersect_rtp_capabilities(map, map) :: map
+  def ortc_intersect_rtp_capabilities(_local, _remote), do: :erlang.nif_error(:nif_not_loaded)
+
+  # sdp
+  @spec sdp_build(map) :: {:ok, String.t()} | {:error, String.t()}
+  def sdp_build(_options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec sdp_parse(String.t()) :: {:ok, Mediasoup.Sdp.parsed()} | {:error, String.t()}
+  def sdp_parse(_sdp), do: :erlang.nif_error(:nif_not_loaded)
+
   defp handle_async_nif_result(result) do
     case result do
-      {:ok, result_key} ->
+      {:ok, request} ->
         receive do
-          {^result_key, {:ok, {}}} -> {:ok}
-          {^result_key, msg} -> msg
+          {:mediasoup_async_nif_result, ^request, {:ok, {}}} -> {:ok}
+          {:mediasoup_async_nif_result, ^request, msg} -> msg
         end
 
       error ->
@@ -0,0 +1,53 @@
+defmodule Mediasoup.ObjectRegistry do
+  @moduledoc """
+  Entities that are open in the native layer, by id, without dumping the worker.
+
+  Every worker, WebRTC server, router, transport, producer, consumer, data producer and data
+  consumer is listed from its creation until it closes. `parent_id` is the id of the entity that
+  created it: the worker of a router, the router of a transport, the transport of a producer.
+
+  `find/1` returns a reference to the native object, for use with `Mediasoup.Nif`. It shares its
+  state with the reference held by the entity process: closing it closes the entity.
+  """
+
+  alias Mediasoup.{Metrics, Nif}
+
+  @type info :: %{
+          id: String.t(),
+          kind: Metrics.entity_type(),
+          parent_id: String.t() | nil,
+          created_at: non_neg_integer(),
+          app_data: term()
+        }
+
+  @doc """
+  Lists the open entities, or the children of `parent_id`, oldest first.
+  `created_at` is in milliseconds since the unix epoch.
+  """
+  @spec list(String.t() | nil) :: [info()]
+  def list(parent_id \\ nil), do: Nif.registry_list(parent_id)
+
+  @doc """
+  Returns the info of the open entity `id`, or `nil` once it has closed.
+  """
+  @spec info(String.t()) :: info() | nil
+  def info(id), do: Nif.registry_info(id)
+
+  @doc """
+  Attaches `app_data` to the open entity `id`, returned by `info/1` and `list/1`.
+  It must be serializable as JSON. Returns `{:error, :not_found}` once the entity has closed.
+  """
+  @spec put_app_data(String.t(), term()) :: {:ok} | {:error, :not_found}
+  def put_app_data(id, app_data), do: Nif.registry_set_app_data(id, app_data)
+
+  @spec find(String.t()) :: {Metrics.entity_type(), reference()} | nil
+  def find(id), do: Nif.registry_find(id)
+
+  @spec find_producer(String.t()) :: reference() | nil
+  def find_producer(id) do
+    case find(id) do
+      {:producer, reference} -> reference
+      _ -> nil
+    end
+  end
+end
@@ -0,0 +1,89 @@
+defmodule Mediasoup.Ortc do
+  @moduledoc """
+  RTP capabilities and parameters negotiation, run before any transport exists.
+
+  `router` is a `Mediasoup.Router` or its `Mediasoup.Router.rtp_capabilities/1`. Codecs are
+  matched as mediasoup does when producing and consuming: mime type, clock rate and channels,
+  then the H264 packetization mode and profile, the VP9 profile and the multiopus streams.
+  """
+
+  alias Mediasoup.{Consumer, Nif, Producer, Router}
+
+  defmodule Issue do
+    @moduledoc """
+    Why RTP capabilities or parameters do not fully work with a router. An `:error` prevents
+    producing or consuming; a `:warning` drops a codec, RTCP feedback or header extension.
+    `subject` is the codec mime type, header extension uri or encoding the issue is about.
+    """
+
+    @enforce_keys [:severity, :kind, :subject, :reason]
+    defstruct [:severity, :kind, :subject, :reason]
+
+    @type t :: %Issue{
+            severity: :error | :warning,
+            kind: :codec | :rtcp_feedback | :header_extension | :encoding,
+            subject: String.t(),
+            reason: String.t()
+          }
+  end
+
+  @type router :: Router.t() | Router.rtpCapabilities()
+  @type report :: {:ok, warnings :: [Issue.t()]} | {:error, [Issue.t()]}
+
+  @spec validate_rtp_capabilities(router, Router.rtpCapabilities()) :: report
+  @doc """
+  Checks what an endpoint with `rtp_capabilities` can consume from the router. It is an error
+  when they have no codec in common; router codecs, RTCP feedback and header extensions the
+  endpoint lacks are warnings.
+  """
+  def validate_rtp_capabilities(router, rtp_capabilities) do
+    Nif.ortc_validate_rtp_capabilities(router_rtp_capabilities(router), rtp_capabilities)
+  end
+
+  @spec validate_rtp_parameters(router, Producer.rtpParameters()) :: report
+  @doc """
+  Checks that the router can receive a producer with `rtp_parameters`: each codec, with its RTX
+  codec, must be a router codec. RTCP feedback and header extensions it ignores are warnings.
+  """
+  def validate_rtp_parameters(router, rtp_parameters) do
+    Nif.ortc_validate_rtp_parameters(router_rtp_capabilities(router), rtp_parameters)
+  end
+
+  @spec consumable_rtp_parameters(router, Producer.rtpParameters()) ::
+          {:ok, Consumer.rtpParameters()} | {:error, String.t()}
+  @doc """
+  The RTP parameters the router would use for the consumers of a producer with
+  `rtp_parameters`: router payload types and header extensions, and new ssrcs.
+  """
+  def consumable_rtp_parameters(router, rtp_parameters) do
+    Nif.ortc_consumable_rtp_parameters(router_rtp_capabilities(router), rtp_parameters)
+  end
+
+  @spec consumer_rtp_parameters(
+          Consumer.rtpParameters(),
+          Router.rtpCapabilities(),
+          enable_rtx :: boolean()
+        ) :: {:ok, Consumer.rtpParameters()} | {:error, String.t()}
+  @doc """
+  The RTP parameters of a consumer of `consumable_rtp_parameters` for an endpoint with
+  `rtp_capabilities`, as `consume` would build them. The error lists why each codec does not
+  match.
+  """
+  def consumer_rtp_parameters(consumable_rtp_parameters, rtp_capabilities, enable_rtx \\ true) do
+    Nif.ortc_consumer_rtp_parameters(consumable_rtp_parameters, rtp_capabilities, enable_rtx)
+  end
+
+  @spec intersect_rtp_capabilities(Router.rtpCapabilities(), Router.rtpCapabilities()) ::
+          Router.rtpCapabilities()
+  @doc """
+  The codecs and header extensions of `local` that `remote` supports too, with the payload
+  types and ids of `local`. H264 codecs take the `profile-level-id` to answer `remote` with,
+  and RTCP feedback is reduced to the one both support.
+  """
+  def intersect_rtp_capabilities(local, remote) do
+    Nif.ortc_intersect_rtp_capabilities(local, remote)
+  end
+
+  defp router_rtp_capabilities(%Router{} = router), do: Router.rtp_capabilities(router)
+  defp router_rtp_capabilities(rtp_capabilities), do: rtp_capabilities
+end
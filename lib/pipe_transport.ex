@@ -65,6 +65,57 @@ defmodule Mediasoup.PipeTransport do
     def normalize(%Options{} = option), do: option
   end
 
+  defmodule Dump do
+    @moduledoc """
+    Dump of the transport, see `Mediasoup.PipeTransport.dump/1`.
+
+    Keys of `recv_rtp_header_extensions` are the header extensions, e.g. `:mid`, and the
+    tables of `rtp_listener` are `:mid_table`, `:rid_table` and `:ssrc_table`.
+    """
+
+    defstruct [
+      :id,
+      :direct,
+      :producer_ids,
+      :consumer_ids,
+      :map_ssrc_consumer_id,
+      :map_rtx_ssrc_consumer_id,
+      :data_producer_ids,
+      :data_consumer_ids,
+      :recv_rtp_header_extensions,
+      :rtp_listener,
+      :max_message_size,
+      :sctp_parameters,
+      :sctp_state,
+      :sctp_listener,
+      :trace_event_types,
+      :tuple,
+      :rtx,
+      :srtp_parameters
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            direct: boolean(),
+            producer_ids: [String.t()],
+            consumer_ids: [String.t()],
+            map_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            map_rtx_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            data_producer_ids: [String.t()],
+            data_consumer_ids: [String.t()],
+            recv_rtp_header_extensions: %{atom() => non_neg_integer() | nil},
+            rtp_listener: %{atom() => %{(String.t() | non_neg_integer()) => String.t()}},
+            max_message_size: non_neg_integer(),
+            sctp_parameters: map() | nil,
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            sctp_listener: %{stream_id_table: %{non_neg_integer() => String.t()}} | nil,
+            trace_event_types: [atom()],
+            tuple: TransportTuple.t(),
+            rtx: boolean(),
+            srtp_parameters: map() | nil
+          }
+  end
+
   @typedoc """
   https://mediasoup.org/documentation/v3/mediasoup/sctp-parameters/#SctpParameters
   """
@@ -97,6 +148,25 @@ defmodule Mediasoup.PipeTransport do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the transport like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%PipeTransport{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the PipeTransport as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%PipeTransport{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
   @spec closed?(t) :: boolean
   @doc """
   Tells whether the given PipeTransport is closed on the local node.
@@ -109,79 +179,101 @@ defmodule Mediasoup.PipeTransport do
       end
   end
 
-  @spec consume(t, Consumer.Options.t() | map()) ::
-          {:ok, Consumer.t()} | {:error, String.t() | :terminated}
+  @spec consume(t, Consumer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Consumer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to send audio or video RTP (or SRTP depending on the transport class). This is the way to extract media from mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-consume
   """
-  def consume(%PipeTransport{pid: pid}, %Consumer.Options{} = option) do
-    NifWrap.call(pid, {:consume, [option]})
+  def consume(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def consume(%PipeTransport{pid: pid}, %Consumer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:consume, [option]}, timeout_ms)
   end
 
-  def consume(%PipeTransport{} = transport, option) do
-    consume(transport, Consumer.Options.from_map(option))
+  def consume(%PipeTransport{} = transport, option, timeout_ms) do
+    consume(transport, Consumer.Options.from_map(option), timeout_ms)
   end
 
-  @spec consume_data(t, DataConsumer.Options.t() | map()) ::
-          {:ok, DataConsumer.t()} | {:error, String.t() | :terminated}
+  @spec consume_data(t, DataConsumer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, DataConsumer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to send data messages to the endpoint via SCTP protocol or directly to the Rust process if the transport is a DirectTransport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-consumedata
   """
-  def consume_data(%PipeTransport{pid: pid}, %DataConsumer.Options{} = option) do
-    NifWrap.call(pid, {:consume_data, [option]})
+  def consume_data(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def consume_data(%PipeTransport{pid: pid}, %DataConsumer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:consume_data, [option]}, timeout_ms)
   end
 
-  def consume_data(%PipeTransport{} = transport, option) do
-    consume_data(transport, DataConsumer.Options.from_map(option))
+  def consume_data(%PipeTransport{} = transport, option, timeout_ms) do
+    consume_data(transport, DataConsumer.Options.from_map(option), timeout_ms)
   end
 
-  @spec connect(t, option :: connect_option()) :: {:ok} | {:error, String.t() | :terminated}
+  @spec connect(t, option :: connect_option(), Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Provides the pipe RTP transport with the remote parameters.
   https://mediasoup.org/documentation/v3/mediasoup/api/#pipeTransport-connect
   """
-  def connect(%PipeTransport{pid: pid}, option) do
-    NifWrap.call(pid, {:connect, [option]})
+  def connect(%PipeTransport{pid: pid}, option, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:connect, [option]}, timeout_ms)
   end
 
-  @spec produce(t, Producer.Options.t() | map()) ::
-          {:ok, Producer.t()} | {:error, String.t() | :terminated}
+  @spec produce(t, Producer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Producer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to receive audio or video RTP (or SRTP depending on the transport class). This is the way to inject media into mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-produce
   """
-  def produce(%PipeTransport{pid: pid}, %Producer.Options{} = option) do
-    NifWrap.call(pid, {:produce, [option]})
+  def produce(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def produce(%PipeTransport{pid: pid}, %Producer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:produce, [option]}, timeout_ms)
   end
 
-  def produce(%PipeTransport{} = transport, %{} = option) do
-    produce(transport, Producer.Options.from_map(option))
+  def produce(%PipeTransport{} = transport, %{} = option, timeout_ms) do
+    produce(transport, Producer.Options.from_map(option), timeout_ms)
   end
 
-  @spec produce_data(t, DataProducer.Options.t() | map()) ::
-          {:ok, DataProducer.t()} | {:error, String.t() | :terminated}
+  @spec produce_data(t, DataProducer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, DataProducer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to receive data messages. Those messages can be delivered by an endpoint via SCTP protocol or can be directly sent from the Node.js application if the transport is a DirectTransport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-producedata
   """
-  def produce_data(%PipeTransport{pid: pid}, %DataProducer.Options{} = option) do
-    NifWrap.call(pid, {:produce_data, [option]})
+  def produce_data(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def produce_data(%PipeTransport{pid: pid}, %DataProducer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:produce_data, [option]}, timeout_ms)
   end
 
-  def produce_data(%PipeTransport{} = transport, %{} = option) do
-    produce_data(transport, DataProducer.Options.from_map(option))
+  def produce_data(%PipeTransport{} = transport, %{} = option, timeout_ms) do
+    produce_data(transport, DataProducer.Options.from_map(option), timeout_ms)
   end
 
-  @type transport_stat :: map
-  @spec get_stats(t) :: list(transport_stat) | {:error, :terminated}
+  @type transport_stat :: Mediasoup.Stats.PipeTransportStats.t()
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          list(transport_stat) | {:error, :terminated | :timeout}
   @doc """
   Returns current RTC statistics of the pipe transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#pipeTransport-getStats
   """
-  def get_stats(%PipeTransport{pid: pid}) do
-    NifWrap.call(pid, {:get_stats, []})
+  def get_stats(%PipeTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
+  end
+
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the pipe transport to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%PipeTransport{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
   end
 
   @typedoc """
@@ -193,19 +285,7 @@ defmodule Mediasoup.PipeTransport do
   https://mediasoup.org/documentation/v3/mediasoup/api/#pipeTransport-tuple
   """
   def tuple(%PipeTransport{pid: pid}) do
-    case NifWrap.call(pid, {:tuple, []}) do
-      {:error, reason} ->
-        {:error, reason}
-
-      tuple ->
-        %TransportTuple{
-          local_port: tuple["localPort"],
-          protocol: TransportTuple.protocol_to_atom(tuple["protocol"]),
-          local_address: tuple["localAddress"],
-          remote_ip: tuple["remoteIp"],
-          remote_port: tuple["remotePort"]
-        }
-    end
+    NifWrap.call(pid, {:tuple, []})
   end
 
   @spec sctp_parameters(Mediasoup.PipeTransport.t()) ::
@@ -229,7 +309,8 @@ defmodule Mediasoup.PipeTransport do
     NifWrap.call(pid, {:srtp_parameters, []})
   end
 
-  @spec sctp_state(Mediasoup.PipeTransport.t()) :: String.t() | {:error, :terminated}
+  @spec sctp_state(Mediasoup.PipeTransport.t()) ::
+          Mediasoup.Transport.sctp_state() | nil | {:error, :terminated}
   @doc """
   Current SCTP state. Or undefined if SCTP is not enabled.
   https://mediasoup.org/documentation/v3/mediasoup/api/#pipeTransport-sctpState
@@ -238,35 +319,56 @@ defmodule Mediasoup.PipeTransport do
     NifWrap.call(pid, {:sctp_state, []})
   end
 
-  @spec dump(t) :: any | {:error, :terminated}
+  @spec dump(t, Mediasoup.timeout_ms()) :: Dump.t() | {:error, :terminated | :timeout}
   @doc """
   Dump internal stat for PipeTransport.
   """
-  def dump(%PipeTransport{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%PipeTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`. `:on_tuple` sends a `TransportTuple.t()` and `:on_sctp_state_change` sends
+  a `Mediasoup.Transport.sctp_state()`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_sctp_state_change
           | :on_tuple
 
-  @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
+  @spec event(t, pid, event_types :: [event_type], [Mediasoup.EventListener.option()]) ::
+          {:ok} | {:error, :terminated}
   @doc """
   Starts observing event.
+
+  With `max_queue_len: n` in `opts`, value events are dropped while the mailbox of `listener`
+  holds `n` messages or more, see `Mediasoup.EventListener` and `dropped_events/2`.
   """
   def event(
         transport,
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_sctp_state_change,
           :on_tuple
-        ]
+        ],
+        opts \\ []
       )
 
-  def event(%PipeTransport{pid: pid}, listener, event_types) do
-    NifWrap.call(pid, {:event, listener, event_types})
+  def event(%PipeTransport{pid: pid}, listener, event_types, opts) do
+    NifWrap.call(pid, {:event, listener, event_types, opts})
+  end
+
+  @spec dropped_events(t, pid) :: non_neg_integer() | {:error, :terminated}
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full, see `event/4`.
+  """
+  def dropped_events(%PipeTransport{pid: pid}, listener) do
+    NifWrap.call(pid, {:dropped_events, listener})
   end
 
   @spec struct_from_pid(pid()) :: PipeTransport.t()
@@ -296,14 +398,18 @@ defmodule Mediasoup.PipeTransport do
 
   @impl true
   def handle_call(
-        {:event, listener, event_types},
+        {:event, listener, event_types, opts},
         _from,
         %{listeners: listeners} = state
       ) do
-    listeners = EventListener.add(listeners, listener, event_types)
+    listeners = EventListener.add(listeners, listener, event_types, opts)
     {:reply, {:ok}, %{state | listeners: listeners}}
   end
 
+  def handle_call({:dropped_events, listener}, _from, %{listeners: listeners} = state) do
+    {:reply, EventListener.dropped(listeners, listener), state}
+  end
+
   @impl true
   def handle_call(
         {:struct_from_pid, _arg},
@@ -314,6 +420,8 @@ defmodule Mediasoup.PipeTransport do
   end
 
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.pipe_transport_stats_subscribe/4,
+    monitor_owner: &Nif.pipe_transport_monitor_owner/2,
     close: &Nif.pipe_transport_close/1,
     closed?: &Nif.pipe_transport_closed/1,
     sctp_state: &Nif.pipe_transport_sctp_state/1,
@@ -323,15 +431,17 @@ defmodule Mediasoup.PipeTransport do
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    connect: &Nif.pipe_transport_connect_async/3,
-    dump: &Nif.pipe_transport_dump_async/2,
-    get_stats: &Nif.pipe_transport_get_stats_async/2,
-    produce: &Nif.pipe_transport_produce_async/3,
-    produce_data: &Nif.pipe_transport_produce_data_async/3,
-    consume: &Nif.pipe_transport_consume_async/3,
-    consume_data: &Nif.pipe_transport_consume_data_async/3
+    connect: &Nif.pipe_transport_connect_async/4,
+    dump: &Nif.pipe_transport_dump_async/3,
+    get_stats: &Nif.pipe_transport_get_stats_async/3,
+    produce: &Nif.pipe_transport_produce_async/4,
+    produce_data: &Nif.pipe_transport_produce_data_async/4,
+    consume: &Nif.pipe_transport_consume_async/4,
+    consume_data: &Nif.pipe_transport_consume_data_async/4
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.pipe_transport_close_async/3)
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {message_tag, from}, result},
@@ -350,6 +460,11 @@ defmodule Mediasoup.PipeTransport do
     {:noreply, state}
   end
 
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
@@ -368,11 +483,23 @@ defmodule Mediasoup.PipeTransport do
     {:noreply, %{state | listeners: listeners}}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
+  @impl true
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   @payload_events [
     :on_sctp_state_change,
     :on_tuple
@@ -388,11 +515,15 @@ defmodule Mediasoup.PipeTransport do
   @impl true
   def terminate(
         reason,
-        %{reference: reference, supervisor: supervisor, listeners: listeners} = _state
+        %{reference: reference, supervisor: supervisor, listeners: listeners} = state
       ) do
-    EventListener.send(listeners, :on_close, {:on_close})
+    close_reason = Mediasoup.Utility.close_reason(reason, state)
+    EventListener.send_close(listeners, close_reason)
 
-    Mediasoup.Utility.supervisor_clean_stop(supervisor, reason)
+    Mediasoup.Utility.supervisor_clean_stop(
+      supervisor,
+      Mediasoup.Utility.child_exit_reason(close_reason)
+    )
     Nif.pipe_transport_close(reference)
     :ok
   end
@@ -90,7 +90,62 @@ defmodule Mediasoup.PlainTransport do
     def normalize(%Options{} = option), do: option
   end
 
-  @type transport_stat :: map()
+  defmodule Dump do
+    @moduledoc """
+    Dump of the transport, see `Mediasoup.PlainTransport.dump/1`.
+
+    Keys of `recv_rtp_header_extensions` are the header extensions, e.g. `:mid`, and the
+    tables of `rtp_listener` are `:mid_table`, `:rid_table` and `:ssrc_table`.
+    """
+
+    defstruct [
+      :id,
+      :direct,
+      :producer_ids,
+      :consumer_ids,
+      :map_ssrc_consumer_id,
+      :map_rtx_ssrc_consumer_id,
+      :data_producer_ids,
+      :data_consumer_ids,
+      :recv_rtp_header_extensions,
+      :rtp_listener,
+      :max_message_size,
+      :sctp_parameters,
+      :sctp_state,
+      :sctp_listener,
+      :trace_event_types,
+      :rtcp_mux,
+      :comedia,
+      :tuple,
+      :rtcp_tuple,
+      :srtp_parameters
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            direct: boolean(),
+            producer_ids: [String.t()],
+            consumer_ids: [String.t()],
+            map_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            map_rtx_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            data_producer_ids: [String.t()],
+            data_consumer_ids: [String.t()],
+            recv_rtp_header_extensions: %{atom() => non_neg_integer() | nil},
+            rtp_listener: %{atom() => %{(String.t() | non_neg_integer()) => String.t()}},
+            max_message_size: non_neg_integer(),
+            sctp_parameters: map() | nil,
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            sctp_listener: %{stream_id_table: %{non_neg_integer() => String.t()}} | nil,
+            trace_event_types: [atom()],
+            rtcp_mux: boolean(),
+            comedia: boolean(),
+            tuple: TransportTuple.t(),
+            rtcp_tuple: TransportTuple.t() | nil,
+            srtp_parameters: map() | nil
+          }
+  end
+
+  @type transport_stat :: Mediasoup.Stats.PlainTransportStats.t()
   @type connect_option :: map()
   @type create_option :: map | Options.t()
 
@@ -111,19 +166,7 @@ defmodule Mediasoup.PlainTransport do
   https://mediasoup.org/documentation/v3/mediasoup/api/#plainTransport-tuple
   """
   def tuple(%PlainTransport{pid: pid}) do
-    case NifWrap.call(pid, {:tuple, []}) do
-      {:error, reason} ->
-        {:error, reason}
-
-      tuple ->
-        %TransportTuple{
-          local_port: tuple["localPort"],
-          protocol: TransportTuple.protocol_to_atom(tuple["protocol"]),
-          local_address: tuple["localAddress"],
-          remote_ip: tuple["remoteIp"],
-          remote_port: tuple["remotePort"]
-        }
-    end
+    NifWrap.call(pid, {:tuple, []})
   end
 
   @spec sctp_parameters(t) :: map() | {:error, :terminated}
@@ -135,7 +178,7 @@ defmodule Mediasoup.PlainTransport do
     NifWrap.call(pid, {:sctp_parameters, []})
   end
 
-  @spec sctp_state(t) :: String.t() | {:error, :terminated}
+  @spec sctp_state(t) :: Mediasoup.Transport.sctp_state() | nil | {:error, :terminated}
   @doc """
   Current SCTP state. Or undefined if SCTP is not enabled.
   https://mediasoup.org/documentation/v3/mediasoup/api/#plainTransport-sctpState
@@ -156,22 +199,45 @@ defmodule Mediasoup.PlainTransport do
   # Mediasoup Plain Transport Methods
   # https://mediasoup.org/documentation/v3/mediasoup/api/#PlainTransport-methods
 
-  @spec get_stats(t) :: list(transport_stat) | {:error, :terminated}
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          list(transport_stat) | {:error, :terminated | :timeout}
   @doc """
   Returns current RTC statistics of the WebRTC transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#plainTransport-getStats
   """
-  def get_stats(%PlainTransport{pid: pid}) do
-    NifWrap.call(pid, {:get_stats, []})
+  def get_stats(%PlainTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
   end
 
-  @spec connect(t, connect_option()) :: {:ok} | {:error, String.t() | :terminated}
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the plain transport to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%PlainTransport{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
+  end
+
+  @spec dump(t, Mediasoup.timeout_ms()) :: Dump.t() | {:error, :terminated | :timeout}
+  @doc """
+  Dump internal stat for PlainTransport.
+  """
+  def dump(%PlainTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
+  end
+
+  @spec connect(t, connect_option(), Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Provides the plain transport with the endpoint parameters.
+  `ip`, `port` and `rtcpPort` must not be set on a transport created with `comedia: true`.
   https://mediasoup.org/documentation/v3/mediasoup/api/#plainTransport-connect
   """
-  def connect(%PlainTransport{pid: pid}, option) do
-    NifWrap.call(pid, {:connect, [option]})
+  def connect(%PlainTransport{pid: pid}, option, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:connect, [option]}, timeout_ms)
   end
 
   @spec close(t) :: :ok
@@ -183,6 +249,25 @@ defmodule Mediasoup.PlainTransport do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the transport like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%PlainTransport{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the PlainTransport as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%PlainTransport{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
   @spec closed?(t) :: boolean
   @doc """
   Tells whether the given PlainTransport is closed on the local node.
@@ -195,51 +280,69 @@ defmodule Mediasoup.PlainTransport do
       end
   end
 
-  @spec produce(t, Producer.Options.t() | map()) ::
-          {:ok, Producer.t()} | {:error, String.t() | :terminated}
+  @spec produce(t, Producer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Producer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to receive audio or video RTP (or SRTP depending on the transport class). This is the way to inject media into mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-produce
   """
-  def produce(%PlainTransport{pid: pid}, %Producer.Options{} = option) do
-    NifWrap.call(pid, {:produce, [option]})
+  def produce(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def produce(%PlainTransport{pid: pid}, %Producer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:produce, [option]}, timeout_ms)
   end
 
-  def produce(%PlainTransport{} = transport, %{} = option) do
-    produce(transport, Producer.Options.from_map(option))
+  def produce(%PlainTransport{} = transport, %{} = option, timeout_ms) do
+    produce(transport, Producer.Options.from_map(option), timeout_ms)
   end
 
-  @spec consume(t, Consumer.Options.t() | map()) ::
-          {:ok, Consumer.t()} | {:error, String.t() | :terminated}
+  @spec consume(t, Consumer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Consumer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to send audio or video RTP (or SRTP depending on the transport class). This is the way to extract media from mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-consume
   """
-  def consume(%PlainTransport{pid: pid}, %Consumer.Options{} = option) do
-    NifWrap.call(pid, {:consume, [option]})
+  def consume(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def consume(%PlainTransport{pid: pid}, %Consumer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:consume, [option]}, timeout_ms)
   end
 
-  def consume(%PlainTransport{} = transport, option) do
-    consume(transport, Consumer.Options.from_map(option))
+  def consume(%PlainTransport{} = transport, option, timeout_ms) do
+    consume(transport, Consumer.Options.from_map(option), timeout_ms)
   end
 
   # GenServer callbacks
 
   def start_link(opt) do
     reference = Keyword.fetch!(opt, :reference)
-    GenServer.start_link(__MODULE__, %{reference: reference}, opt)
+    comedia = Keyword.get(opt, :comedia, false)
+    GenServer.start_link(__MODULE__, %{reference: reference, comedia: comedia}, opt)
   end
 
   @impl true
   def init(%{reference: reference} = state) do
-    Nif.plain_transport_event(reference, self(), [
-      :on_close,
-      :on_tuple,
-      :on_sctp_state_change
-    ])
+    {:ok, subscription} =
+      Nif.plain_transport_event(
+        reference,
+        self(),
+        [
+          :on_close,
+          :on_close_reason,
+          :on_tuple,
+          :on_sctp_state_change
+        ],
+        %Mediasoup.EventOptions{batch: true}
+      )
 
     {:ok, supervisor} = DynamicSupervisor.start_link(strategy: :one_for_one)
-    {:ok, Map.merge(state, %{supervisor: supervisor, listeners: EventListener.new()})}
+
+    {:ok,
+     Map.merge(state, %{
+       supervisor: supervisor,
+       listeners: EventListener.new(),
+       subscription: subscription
+     })}
   end
 
   @spec struct_from_pid(pid()) :: PlainTransport.t()
@@ -256,14 +359,18 @@ defmodule Mediasoup.PlainTransport do
 
   @impl true
   def handle_call(
-        {:event, listener, event_types},
+        {:event, listener, event_types, opts},
         _from,
         %{listeners: listeners} = state
       ) do
-    listeners = EventListener.add(listeners, listener, event_types)
+    listeners = EventListener.add(listeners, listener, event_types, opts)
     {:reply, {:ok}, %{state | listeners: listeners}}
   end
 
+  def handle_call({:dropped_events, listener}, _from, %{listeners: listeners} = state) do
+    {:reply, EventListener.dropped(listeners, listener), state}
+  end
+
   @impl true
   def handle_call(
         {:struct_from_pid, _arg},
@@ -289,6 +396,11 @@ defmodule Mediasoup.PlainTransport do
     {:noreply, state}
   end
 
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
@@ -307,11 +419,23 @@ defmodule Mediasoup.PlainTransport do
     {:noreply, Map.put(state, :listeners, listeners)}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
+  @impl true
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   @payload_events [
     :on_sctp_state_change,
     :on_tuple
@@ -327,17 +451,33 @@ defmodule Mediasoup.PlainTransport do
   @impl true
   def terminate(
         reason,
-        %{reference: reference, supervisor: supervisor, listeners: listeners} = _state
+        %{reference: reference, supervisor: supervisor, listeners: listeners} = state
       ) do
-    EventListener.send(listeners, :on_close, {:on_close})
+    close_reason = Mediasoup.Utility.close_reason(reason, state)
+    EventListener.send_close(listeners, close_reason)
 
-    Mediasoup.Utility.supervisor_clean_stop(supervisor, reason)
+    Mediasoup.Utility.supervisor_clean_stop(
+      supervisor,
+      Mediasoup.Utility.child_exit_reason(close_reason)
+    )
     Nif.plain_transport_close(reference)
     :ok
   end
 
+  defguardp sets_remote_address(option, key)
+            when is_map_key(option, key) and :erlang.map_get(key, option) != nil
+
+  # mediasoup learns the remote address of a comedia transport from the first packet it receives.
+  def handle_call({:connect, [option], _timeout_ms}, _from, %{comedia: true} = state)
+      when sets_remote_address(option, :ip) or sets_remote_address(option, :port) or
+             sets_remote_address(option, :rtcpPort) do
+    {:reply, {:error, "ip, port and rtcp_port must not be set on a comedia transport"}, state}
+  end
+
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.plain_transport_stats_subscribe/4,
     # properties
+    monitor_owner: &Nif.plain_transport_monitor_owner/2,
     id: &Nif.plain_transport_id/1,
     tuple: &Nif.plain_transport_tuple/1,
     sctp_parameters: &Nif.plain_transport_sctp_parameters/1,
@@ -349,36 +489,59 @@ defmodule Mediasoup.PlainTransport do
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    connect: &Nif.plain_transport_connect_async/3,
-    dump: &Nif.plain_transport_dump_async/2,
-    get_stats: &Nif.plain_transport_get_stats_async/2,
-    produce: &Nif.plain_transport_produce_async/3,
-    consume: &Nif.plain_transport_consume_async/3
+    connect: &Nif.plain_transport_connect_async/4,
+    dump: &Nif.plain_transport_dump_async/3,
+    get_stats: &Nif.plain_transport_get_stats_async/3,
+    produce: &Nif.plain_transport_produce_async/4,
+    consume: &Nif.plain_transport_consume_async/4
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.plain_transport_close_async/3)
+
   # Mediasoup Plain Transport Events
   # https://mediasoup.org/documentation/v3/mediasoup/api/#PlainTransport-events
 
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`. `:on_tuple` sends a `TransportTuple.t()` and `:on_sctp_state_change` sends
+  a `Mediasoup.Transport.sctp_state()`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_tuple
           | :on_sctp_state_change
 
-  @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
+  @spec event(t, pid, event_types :: [event_type], [Mediasoup.EventListener.option()]) ::
+          {:ok} | {:error, :terminated}
   @doc """
   Starts observing event.
+
+  With `max_queue_len: n` in `opts`, value events are dropped while the mailbox of `listener`
+  holds `n` messages or more, see `Mediasoup.EventListener` and `dropped_events/2`.
   """
   def event(
         transport,
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_tuple,
           :on_sctp_state_change
-        ]
+        ],
+        opts \\ []
       )
 
-  def event(%PlainTransport{pid: pid}, listener, event_types) do
-    NifWrap.call(pid, {:event, listener, event_types})
+  def event(%PlainTransport{pid: pid}, listener, event_types, opts) do
+    NifWrap.call(pid, {:event, listener, event_types, opts})
+  end
+
+  @spec dropped_events(t, pid) :: non_neg_integer() | {:error, :terminated}
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full, see `event/4`.
+  """
+  def dropped_events(%PlainTransport{pid: pid}, listener) do
+    NifWrap.call(pid, {:dropped_events, listener})
   end
 end
@@ -23,12 +23,12 @@ defmodule Mediasoup.Producer do
   @typedoc """
     audio or video
   """
-  @type mediaKind :: String.t()
+  @type mediaKind :: :audio | :video
   @typedoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#ProducerType
-    "simple" or "simulcast" or "svc"
+    :simple or :simulcast or :svc
   """
-  @type producerType :: String.t()
+  @type producerType :: :simple | :simulcast | :svc
 
   @spec id(t) :: String.t()
   @doc """
@@ -73,30 +73,50 @@ defmodule Mediasoup.Producer do
     GenServer.stop(pid)
   end
 
-  @spec dump(t) :: map
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the producer like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%Producer{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the producer as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%Producer{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
+  @spec dump(t, Mediasoup.timeout_ms()) ::
+          Dump.t() | {:error, :terminated | :timeout} | {:error, String.t()}
   @doc """
   Dump internal stat for Producer.
   """
-  def dump(%Producer{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%Producer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
-  @spec pause(t) :: {:ok} | {:error}
+  @spec pause(t, Mediasoup.timeout_ms()) :: {:ok} | {:error} | {:error, :terminated | :timeout}
   @doc """
   Pauses the producer (no RTP is sent to its associated consumers). Triggers a "producerpause" event in all its associated consumers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#producer-pause
   """
-  def pause(%Producer{pid: pid}) do
-    NifWrap.call(pid, {:pause, []})
+  def pause(%Producer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:pause, []}, timeout_ms)
   end
 
-  @spec resume(t) :: {:ok} | {:error}
+  @spec resume(t, Mediasoup.timeout_ms()) :: {:ok} | {:error} | {:error, :terminated | :timeout}
   @doc """
   Resumes the producer (RTP is sent again to its associated consumers). Triggers a "producerresume" event in all its associated consumers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#producer-resume
   """
-  def resume(%Producer{pid: pid}) do
-    NifWrap.call(pid, {:resume, []})
+  def resume(%Producer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:resume, []}, timeout_ms)
   end
 
   @spec score(t) :: list() | {:error}
@@ -108,14 +128,27 @@ defmodule Mediasoup.Producer do
     NifWrap.call(pid, {:score, []})
   end
 
-  @spec get_stats(t) :: list() | {:error, reason :: term()}
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          [Mediasoup.Stats.RtpStreamRecv.t()] | {:error, reason :: term()}
   @doc """
   Returns current RTC statistics of the producer.
   Check the [RTC Statistics](https://mediasoup.org/documentation/v3/mediasoup/rtc-statistics/)
   section for more details (TypeScript-oriented, but concepts apply here as well).
   """
-  def get_stats(%Producer{pid: pid}) do
-    NifWrap.call(pid, {:get_stats, []})
+  def get_stats(%Producer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
+  end
+
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the producer to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%Producer{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
   end
 
   @spec closed?(t) :: boolean()
@@ -139,28 +172,48 @@ defmodule Mediasoup.Producer do
     NifWrap.call(pid, {:paused?, []})
   end
 
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_pause
           | :on_resume
           | :on_video_orientation_change
           | :on_score
 
-  @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
+  @spec event(t, pid, event_types :: [event_type], [Mediasoup.EventListener.option()]) ::
+          {:ok} | {:error, :terminated}
   @doc """
   Starts observing event.
+
+  With `max_queue_len: n` in `opts`, value events are dropped while the mailbox of `listener`
+  holds `n` messages or more, see `Mediasoup.EventListener` and `dropped_events/2`.
   """
   def event(
         %Producer{pid: pid},
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_pause,
           :on_resume,
           :on_score
-        ]
+        ],
+        opts \\ []
       ) do
-    NifWrap.call(pid, {:event, listener, event_types})
+    NifWrap.call(pid, {:event, listener, event_types, opts})
+  end
+
+  @spec dropped_events(t, pid) :: non_neg_integer() | {:error, :terminated}
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full, see `event/4`.
+  """
+  def dropped_events(%Producer{pid: pid}, listener) do
+    NifWrap.call(pid, {:dropped_events, listener})
   end
 
   @spec struct_from_pid(pid()) :: Producer.t()
@@ -187,16 +240,27 @@ defmodule Mediasoup.Producer do
 
   @impl true
   def init(%{reference: reference} = state) do
-    {:ok} =
-      Nif.producer_event(reference, self(), [
-        :on_close,
-        :on_pause,
-        :on_resume,
-        :on_video_orientation_change,
-        :on_score
-      ])
+    {:ok, subscription} =
+      Nif.producer_event(
+        reference,
+        self(),
+        [
+          :on_close,
+          :on_close_reason,
+          :on_pause,
+          :on_resume,
+          :on_video_orientation_change,
+          :on_score
+        ],
+        %Mediasoup.EventOptions{batch: true}
+      )
 
-    {:ok, Map.merge(state, %{listeners: EventListener.new(), linked_consumer: nil})}
+    {:ok,
+     Map.merge(state, %{
+       listeners: EventListener.new(),
+       linked_consumer: nil,
+       subscription: subscription
+     })}
   end
 
   @impl true
@@ -218,14 +282,18 @@ defmodule Mediasoup.Producer do
 
   @impl true
   def handle_call(
-        {:event, listener, event_types},
+        {:event, listener, event_types, opts},
         _from,
         %{listeners: listeners} = state
       ) do
-    listeners = EventListener.add(listeners, listener, event_types)
+    listeners = EventListener.add(listeners, listener, event_types, opts)
     {:reply, {:ok}, %{state | listeners: listeners}}
   end
 
+  def handle_call({:dropped_events, listener}, _from, %{listeners: listeners} = state) do
+    {:reply, EventListener.dropped(listeners, listener), state}
+  end
+
   @impl true
   def handle_call(
         {:struct_from_pid, _arg},
@@ -236,18 +304,22 @@ defmodule Mediasoup.Producer do
   end
 
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.producer_stats_subscribe/4,
+    monitor_owner: &Nif.producer_monitor_owner/2,
     closed?: &Nif.producer_closed/1,
     paused?: &Nif.producer_paused/1,
     score: &Nif.producer_score/1
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    pause: &Nif.producer_pause_async/2,
-    resume: &Nif.producer_resume_async/2,
-    get_stats: &Nif.producer_get_stats_async/2,
-    dump: &Nif.producer_dump_async/2
+    pause: &Nif.producer_pause_async/3,
+    resume: &Nif.producer_resume_async/3,
+    get_stats: &Nif.producer_get_stats_async/3,
+    dump: &Nif.producer_dump_async/3
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.producer_close_async/3)
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, nil, _},
@@ -256,6 +328,12 @@ defmodule Mediasoup.Producer do
     {:noreply, state}
   end
 
+  @impl true
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
@@ -305,11 +383,23 @@ defmodule Mediasoup.Producer do
     {:stop, reason, state}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
+  @impl true
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   @simple_events [
     :on_resume,
     :on_pause
@@ -334,8 +424,8 @@ defmodule Mediasoup.Producer do
   end
 
   @impl true
-  def terminate(_reason, %{reference: reference, listeners: listeners} = _state) do
-    EventListener.send(listeners, :on_close, {:on_close})
+  def terminate(reason, %{reference: reference, listeners: listeners} = state) do
+    EventListener.send_close(listeners, Mediasoup.Utility.close_reason(reason, state))
     Nif.producer_close(reference)
     :ok
   end
@@ -375,6 +465,34 @@ defmodule Mediasoup.Producer do
       }
     end
   end
+
+  defmodule Dump do
+    @moduledoc """
+    Dump of the producer, see `Mediasoup.Producer.dump/1`.
+    """
+
+    defstruct [
+      :id,
+      :kind,
+      :type,
+      :paused,
+      :rtp_mapping,
+      :rtp_parameters,
+      :rtp_streams,
+      :trace_event_types
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            kind: :audio | :video,
+            type: :simple | :simulcast | :svc,
+            paused: boolean(),
+            rtp_mapping: map(),
+            rtp_parameters: map(),
+            rtp_streams: [map()],
+            trace_event_types: [atom()]
+          }
+  end
 end
 
 defmodule Mediasoup.PipedProducer do
@@ -47,11 +47,77 @@ defmodule Mediasoup.Router do
     end
   end
 
+  defmodule Dump do
+    @moduledoc """
+    Dump of the router, see `Mediasoup.Router.dump/1`.
+    """
+
+    defstruct [
+      :id,
+      :map_consumer_id_producer_id,
+      :map_data_consumer_id_data_producer_id,
+      :map_data_producer_id_data_consumer_ids,
+      :map_producer_id_consumer_ids,
+      :map_producer_id_observer_ids,
+      :rtp_observer_ids,
+      :transport_ids
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            map_consumer_id_producer_id: %{String.t() => String.t()},
+            map_data_consumer_id_data_producer_id: %{String.t() => String.t()},
+            map_data_producer_id_data_consumer_ids: %{String.t() => [String.t()]},
+            map_producer_id_consumer_ids: %{String.t() => [String.t()]},
+            map_producer_id_observer_ids: %{String.t() => [String.t()]},
+            rtp_observer_ids: [String.t()],
+            transport_ids: [String.t()]
+          }
+  end
+
+  defmodule TransportStats do
+    @moduledoc """
+    Stats of a transport of the router and of its children, see `Mediasoup.Router.get_stats/1`.
+
+    Each entry is `{:ok, stats}`, or `{:error, reason}` when the stats of that entity could not
+    be read, e.g. because it closed meanwhile.
+    """
+
+    defstruct [
+      :kind,
+      :stats,
+      producers: %{},
+      consumers: %{},
+      data_producers: %{},
+      data_consumers: %{}
+    ]
+
+    @type result(stats) :: {:ok, [stats]} | {:error, String.t()}
+
+    @type t :: %__MODULE__{
+            kind: :webrtc_transport | :plain_transport | :pipe_transport,
+            stats:
+              result(
+                Mediasoup.Stats.WebRtcTransportStats.t()
+                | Mediasoup.Stats.PlainTransportStats.t()
+                | Mediasoup.Stats.PipeTransportStats.t()
+              ),
+            producers: %{String.t() => result(Mediasoup.Stats.RtpStreamRecv.t())},
+            consumers: %{String.t() => result(Mediasoup.Stats.rtp_stream())},
+            data_producers: %{String.t() => result(Mediasoup.Stats.DataProducerStats.t())},
+            data_consumers: %{String.t() => result(Mediasoup.Stats.DataConsumerStats.t())}
+          }
+  end
+
   defmodule PipeToRouterOptions do
     @moduledoc """
     https://mediasoup.org/documentation/v3/mediasoup/api/#PipeToRouterOptions
     without producerId.
     producerId is the argument of the function
+
+    When both routers live on the same node, the pipe transport pair is created and cached
+    by the worker itself and listens on 127.0.0.1, so `get_remote_node_ip` and
+    `get_listen_ip` are only used for routers on different nodes.
     """
     @type num_sctp_streams :: %{OS: integer(), MIS: integer()}
 
@@ -109,6 +175,25 @@ defmodule Mediasoup.Router do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the router like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%Router{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the router as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%Router{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
   @spec closed?(t) :: boolean
   @doc """
   Tells whether the given router is closed on the local node.
@@ -121,124 +206,203 @@ defmodule Mediasoup.Router do
       end
   end
 
-  @spec create_webrtc_transport(t, WebRtcTransport.create_option()) ::
-          {:ok, WebRtcTransport.t()} | {:error, String.t()}
+  @spec create_webrtc_transport(t, WebRtcTransport.create_option(), Mediasoup.timeout_ms()) ::
+          {:ok, WebRtcTransport.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Creates a new webrtc transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#router-createWebRtcTransport
   """
+  def create_webrtc_transport(router, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
   def create_webrtc_transport(
         %Router{pid: pid},
-        %WebRtcTransport.Options{} = option
+        %WebRtcTransport.Options{} = option,
+        timeout_ms
       ) do
     option = WebRtcTransport.Options.normalize(option)
-    NifWrap.call(pid, {:create_webrtc_transport, [option]})
+    NifWrap.call(pid, {:create_webrtc_transport, [option]}, timeout_ms)
   end
 
-  def create_webrtc_transport(%Router{} = router, %{} = option) do
-    create_webrtc_transport(router, WebRtcTransport.Options.from_map(option))
+  def create_webrtc_transport(%Router{} = router, %{} = option, timeout_ms) do
+    create_webrtc_transport(router, WebRtcTransport.Options.from_map(option), timeout_ms)
   end
 
-  @spec create_plain_transport(t, PlainTransport.create_option()) ::
-          {:ok, PlainTransport.t()} | {:error, String.t()}
+  @spec create_plain_transport(t, PlainTransport.create_option(), Mediasoup.timeout_ms()) ::
+          {:ok, PlainTransport.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Creates a new webrtc transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#router-createPlainTransport
   """
+  def create_plain_transport(router, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
   def create_plain_transport(
         %Router{pid: pid},
-        %Mediasoup.PlainTransport.Options{} = option
+        %Mediasoup.PlainTransport.Options{} = option,
+        timeout_ms
       ) do
     option = Mediasoup.PlainTransport.Options.normalize(option)
-    NifWrap.call(pid, {:create_plain_transport, [option]})
+    NifWrap.call(pid, {:create_plain_transport, [option]}, timeout_ms)
   end
 
-  def create_plain_transport(%Router{} = router, %{} = option) do
-    create_plain_transport(router, Mediasoup.PlainTransport.Options.from_map(option))
+  def create_plain_transport(%Router{} = router, %{} = option, timeout_ms) do
+    create_plain_transport(router, Mediasoup.PlainTransport.Options.from_map(option), timeout_ms)
   end
 
-  @spec pipe_producer_to_router(t, producer_id :: String.t(), PipeToRouterOptions.t()) ::
-          {:ok, PipeToRouterResult.t()} | {:error, String.t()}
+  @spec pipe_producer_to_router(
+          t,
+          producer_id :: String.t(),
+          PipeToRouterOptions.t(),
+          Mediasoup.timeout_ms()
+        ) :: {:ok, PipeToRouterResult.t()} | {:error, String.t()}
 
   @doc """
-  Pipes the given media producer into another router.
+  Pipes the given media producer into another router. `timeout_ms` applies to each request to
+  the workers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#router-pipeToRouter
   """
   def pipe_producer_to_router(
         %Router{} = router,
         producer_id,
-        %PipeToRouterOptions{} = option
+        %PipeToRouterOptions{} = option,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
       ) do
     try do
-      do_pipe_producer_to_router(router, producer_id, option)
+      do_pipe_producer_to_router(router, producer_id, option, timeout_ms)
     catch
       reason, msg -> {:error, {reason, msg}}
     end
   end
 
   defp do_pipe_producer_to_router(
+         %Router{pid: pid} = router,
+         producer_id,
+         %PipeToRouterOptions{router: remote_router} = option,
+         timeout_ms
+       ) do
+    if get_node(router) == get_node(remote_router) do
+      with {:ok, pipe_consumer, pipe_producer_ref} <-
+             NifWrap.call(
+               pid,
+               {:pipe_producer_to_router, [producer_id, to_ref(remote_router), option]},
+               timeout_ms
+             ),
+           {:ok, pipe_producer} <-
+             adopt_pipe_producer(remote_router, Mediasoup.Producer, pipe_producer_ref) do
+        Mediasoup.Consumer.link_pipe_producer(pipe_consumer, pipe_producer)
+        {:ok, %{pipe_producer: pipe_producer, pipe_consumer: pipe_consumer}}
+      end
+    else
+      do_pipe_producer_to_remote_router(router, producer_id, option, timeout_ms)
+    end
+  end
+
+  defp do_pipe_producer_to_remote_router(
          %Router{} = router,
          producer_id,
-         %PipeToRouterOptions{} = option
+         %PipeToRouterOptions{} = option,
+         timeout_ms
        ) do
     alias Mediasoup.{Consumer, Producer, Transport}
 
     with {:ok, %{local: local_pipe_transport, remote: remote_pipe_transport}} <-
-           get_or_create_pipe_transport_pair(router, option),
+           get_or_create_pipe_transport_pair(router, option, timeout_ms),
          {:ok, pipe_consumer} <-
-           Transport.consume(local_pipe_transport, %Consumer.Options{
-             producer_id: producer_id,
-             rtp_capabilities: Router.rtp_capabilities(router)
-           }),
+           Transport.consume(
+             local_pipe_transport,
+             %Consumer.Options{
+               producer_id: producer_id,
+               rtp_capabilities: Router.rtp_capabilities(router)
+             },
+             timeout_ms
+           ),
          {:ok, pipe_producer} <-
-           Transport.produce(remote_pipe_transport, %Producer.Options{
-             id: producer_id,
-             kind: Consumer.kind(pipe_consumer),
-             rtp_parameters: Consumer.rtp_parameters(pipe_consumer),
-             paused: Consumer.producer_paused?(pipe_consumer)
-           }) do
+           Transport.produce(
+             remote_pipe_transport,
+             %Producer.Options{
+               id: producer_id,
+               kind: Consumer.kind(pipe_consumer),
+               rtp_parameters: Consumer.rtp_parameters(pipe_consumer),
+               paused: Consumer.producer_paused?(pipe_consumer)
+             },
+             timeout_ms
+           ) do
       Consumer.link_pipe_producer(pipe_consumer, pipe_producer)
 
       {:ok, %{pipe_producer: pipe_producer, pipe_consumer: pipe_consumer}}
     end
   end
 
-  @spec pipe_data_producer_to_router(t, data_producer_id :: String.t(), PipeToRouterOptions.t()) ::
-          {:ok, PipeToRouterResult.t()} | {:error, String.t()}
+  @spec pipe_data_producer_to_router(
+          t,
+          data_producer_id :: String.t(),
+          PipeToRouterOptions.t(),
+          Mediasoup.timeout_ms()
+        ) :: {:ok, PipeToRouterResult.t()} | {:error, String.t()}
   @doc """
-  Pipes the given data producer into another router.
+  Pipes the given data producer into another router. `timeout_ms` applies to each request to
+  the workers.
   https://mediasoup.org/documentation/v3/mediasoup/api/#router-pipeToRouter
   """
   def pipe_data_producer_to_router(
         %Router{} = router,
         data_producer_id,
-        %PipeToRouterOptions{} = option
+        %PipeToRouterOptions{} = option,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
       ) do
     try do
-      do_pipe_data_producer_to_router(router, data_producer_id, option)
+      do_pipe_data_producer_to_router(router, data_producer_id, option, timeout_ms)
     catch
       reason, msg -> {:error, {reason, msg}}
     end
   end
 
   defp do_pipe_data_producer_to_router(
+         %Router{pid: pid} = router,
+         data_producer_id,
+         %PipeToRouterOptions{router: remote_router} = option,
+         timeout_ms
+       ) do
+    if get_node(router) == get_node(remote_router) do
+      with {:ok, pipe_consumer, pipe_producer_ref} <-
+             NifWrap.call(
+               pid,
+               {:pipe_data_producer_to_router, [data_producer_id, to_ref(remote_router), option]},
+               timeout_ms
+             ),
+           {:ok, pipe_producer} <-
+             adopt_pipe_producer(remote_router, Mediasoup.DataProducer, pipe_producer_ref) do
+        Mediasoup.DataConsumer.link_pipe_producer(pipe_consumer, pipe_producer)
+        {:ok, %{pipe_data_producer: pipe_producer, pipe_data_consumer: pipe_consumer}}
+      end
+    else
+      do_pipe_data_producer_to_remote_router(router, data_producer_id, option, timeout_ms)
+    end
+  end
+
+  defp do_pipe_data_producer_to_remote_router(
          %Router{} = router,
          data_producer_id,
-         %PipeToRouterOptions{} = option
+         %PipeToRouterOptions{} = option,
+         timeout_ms
        ) do
     alias Mediasoup.{DataConsumer, DataProducer, Transport}
 
     with {:ok, %{local: local_pipe_transport, remote: remote_pipe_transport}} <-
-           get_or_create_pipe_transport_pair(router, option),
+           get_or_create_pipe_transport_pair(router, option, timeout_ms),
          {:ok, pipe_consumer} <-
-           Transport.consume_data(local_pipe_transport, %DataConsumer.Options{
-             data_producer_id: data_producer_id,
-             ordered: true
-           }),
+           Transport.consume_data(
+             local_pipe_transport,
+             %DataConsumer.Options{data_producer_id: data_producer_id, ordered: true},
+             timeout_ms
+           ),
          {:ok, pipe_producer} <-
-           Transport.produce_data(remote_pipe_transport, %DataProducer.Options{
-             sctp_stream_parameters: DataConsumer.sctp_stream_parameters(pipe_consumer)
-           }) do
+           Transport.produce_data(
+             remote_pipe_transport,
+             %DataProducer.Options{
+               sctp_stream_parameters: DataConsumer.sctp_stream_parameters(pipe_consumer)
+             },
+             timeout_ms
+           ) do
       DataConsumer.link_pipe_producer(pipe_consumer, pipe_producer)
 
       {:ok, %{pipe_data_producer: pipe_producer, pipe_data_consumer: pipe_consumer}}
@@ -247,14 +411,20 @@ defmodule Mediasoup.Router do
 
   @spec create_pipe_transport(
           Router.t(),
-          PipeTransport.Options.t()
-        ) :: {:ok, PipeTransport.t()} | {:error, String.t()}
+          PipeTransport.Options.t(),
+          Mediasoup.timeout_ms()
+        ) :: {:ok, PipeTransport.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Creates a new pipe transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#router-createPipeTransport
   """
-  def create_pipe_transport(%Router{pid: pid}, %PipeTransport.Options{} = option) do
-    NifWrap.call(pid, {:create_pipe_transport, [PipeTransport.Options.normalize(option)]})
+  def create_pipe_transport(
+        %Router{pid: pid},
+        %PipeTransport.Options{} = option,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    option = PipeTransport.Options.normalize(option)
+    NifWrap.call(pid, {:create_pipe_transport, [option]}, timeout_ms)
   end
 
   @spec can_consume?(t, String.t(), rtpCapabilities) :: boolean
@@ -275,16 +445,34 @@ defmodule Mediasoup.Router do
     NifWrap.call(pid, {:rtp_capabilities, []})
   end
 
-  @spec dump(t) :: map | {:error}
+  @spec get_stats(t, Mediasoup.timeout_ms()) ::
+          %{String.t() => TransportStats.t()} | {:error, :terminated | :timeout}
+  @doc """
+  Returns the stats of every transport of the router, keyed by transport id, with the stats of
+  their producers, consumers, data producers and data consumers. All of them are requested
+  concurrently in the native layer, in a single call.
+  """
+  def get_stats(%Router{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
+  end
+
+  @spec dump(t, Mediasoup.timeout_ms()) ::
+          Dump.t() | {:error, :terminated | :timeout} | {:error, String.t()}
   @doc """
   Dump internal stat for Router.
   """
-  def dump(%Router{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%Router{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_dead
 
   @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
@@ -296,6 +484,7 @@ defmodule Mediasoup.Router do
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_dead
         ]
       )
@@ -316,6 +505,10 @@ defmodule Mediasoup.Router do
     }
   end
 
+  def to_ref(%Router{pid: pid}) do
+    GenServer.call(pid, :to_ref)
+  end
+
   # GenServer callbacks
 
   def start_link(opt) do
@@ -327,13 +520,22 @@ defmodule Mediasoup.Router do
   def init(%{reference: reference} = state) do
     {:ok, supervisor} = DynamicSupervisor.start_link(strategy: :one_for_one)
 
-    {:ok} =
-      Nif.router_event(reference, self(), [
-        :on_close,
-        :on_dead
-      ])
+    {:ok, subscription} =
+      Nif.router_event(
+        reference,
+        self(),
+        [
+          :on_close,
+          :on_close_reason,
+          :on_dead
+        ],
+        %Mediasoup.EventOptions{batch: true}
+      )
 
-    {:ok, Map.put(state, :supervisor, supervisor) |> Map.put(:listeners, EventListener.new())}
+    {:ok,
+     Map.put(state, :supervisor, supervisor)
+     |> Map.put(:listeners, EventListener.new())
+     |> Map.put(:subscription, subscription)}
   end
 
   @impl true
@@ -355,21 +557,42 @@ defmodule Mediasoup.Router do
     {:reply, struct_from_pid_and_ref(self(), reference), state}
   end
 
+  def handle_call(
+        :to_ref,
+        _from,
+        %{reference: reference} = state
+      ) do
+    {:reply, reference, state}
+  end
+
+  def handle_call(
+        {:adopt_pipe_producer, module, reference},
+        _from,
+        %{supervisor: supervisor} = state
+      ) do
+    {:reply, NifWrap.handle_create_result({:ok, reference}, module, supervisor), state}
+  end
+
   NifWrap.def_handle_call_nif(%{
+    monitor_owner: &Nif.router_monitor_owner/2,
     closed?: &Nif.router_closed/1,
     can_consume?: &Nif.router_can_consume/3,
     rtp_capabilities: &Nif.router_rtp_capabilities/1
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    dump: &Nif.router_dump_async/2,
-    create_pipe_transport: &Nif.router_create_pipe_transport_async/3,
-    create_plain_transport: &Nif.router_create_plain_transport_async/3
+    dump: &Nif.router_dump_async/3,
+    get_stats: &Nif.router_get_stats_async/3,
+    create_pipe_transport: &Nif.router_create_pipe_transport_async/4,
+    pipe_producer_to_router: &Nif.router_pipe_producer_to_router_async/6,
+    pipe_data_producer_to_router: &Nif.router_pipe_data_producer_to_router_async/6
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.router_close_async/3)
+
   @impl true
   def handle_call(
-        {:create_webrtc_transport, [option]},
+        {:create_webrtc_transport, [option], timeout_ms},
         from,
         %{reference: reference} = state
       ) do
@@ -382,13 +605,36 @@ defmodule Mediasoup.Router do
         end
       end)
 
-    case Nif.router_create_webrtc_transport_async(
-           reference,
-           option,
-           {:create_webrtc_transport, from}
-         ) do
-      :ok -> {:noreply, state}
-      error -> {:reply, error, state}
+    try do
+      case Nif.router_create_webrtc_transport_async(
+             reference,
+             option,
+             {:create_webrtc_transport, from},
+             timeout_ms
+           ) do
+        :ok -> {:noreply, state}
+        error -> {:reply, error, state}
+      end
+    rescue
+      e -> {:reply, {:raise_error, e}, state}
+    end
+  end
+
+  def handle_call(
+        {:create_plain_transport, [option], timeout_ms},
+        from,
+        %{reference: reference} = state
+      ) do
+    # The transport process keeps comedia, see `Mediasoup.PlainTransport.connect/3`.
+    tag = {:create_plain_transport, from, option.comedia == true}
+
+    try do
+      case Nif.router_create_plain_transport_async(reference, option, tag, timeout_ms) do
+        :ok -> {:noreply, state}
+        error -> {:reply, error, state}
+      end
+    rescue
+      e -> {:reply, {:raise_error, e}, state}
     end
   end
 
@@ -419,19 +665,18 @@ defmodule Mediasoup.Router do
     {:reply, :ok, Map.put(state, :mapped_pipe_transports, %{id => pair})}
   end
 
+  @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {operation, from}, result},
         %{supervisor: supervisor} = state
       )
       when operation in [
              :create_pipe_transport,
-             :create_plain_transport,
              :create_webrtc_transport
            ] do
     module =
       case operation do
         :create_pipe_transport -> PipeTransport
-        :create_plain_transport -> PlainTransport
         :create_webrtc_transport -> WebRtcTransport
       end
 
@@ -439,6 +684,45 @@ defmodule Mediasoup.Router do
     {:noreply, state}
   end
 
+  def handle_info(
+        {:mediasoup_async_nif_result, {:create_plain_transport, from, comedia}, result},
+        %{supervisor: supervisor} = state
+      ) do
+    GenServer.reply(
+      from,
+      NifWrap.handle_create_result(result, PlainTransport, supervisor, comedia: comedia)
+    )
+
+    {:noreply, state}
+  end
+
+  def handle_info(
+        {:mediasoup_async_nif_result, {operation, from}, result},
+        %{supervisor: supervisor} = state
+      )
+      when operation in [:pipe_producer_to_router, :pipe_data_producer_to_router] do
+    module =
+      case operation do
+        :pipe_producer_to_router -> Mediasoup.Consumer
+        :pipe_data_producer_to_router -> Mediasoup.DataConsumer
+      end
+
+    reply =
+      with {:ok, {pipe_consumer_ref, pipe_producer_ref}} <- result,
+           {:ok, pipe_consumer} <-
+             NifWrap.handle_create_result({:ok, pipe_consumer_ref}, module, supervisor) do
+        {:ok, pipe_consumer, pipe_producer_ref}
+      end
+
+    GenServer.reply(from, reply)
+    {:noreply, state}
+  end
+
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
@@ -457,6 +741,9 @@ defmodule Mediasoup.Router do
     {:noreply, Map.put(state, :listeners, listeners)}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   def handle_info(
         {:nif_internal_event, :on_close},
         state
@@ -464,21 +751,33 @@ defmodule Mediasoup.Router do
     {:stop, :normal, state}
   end
 
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   def handle_info(
         {:nif_internal_event, :on_dead, message},
         %{listeners: listeners} = state
       ) do
     EventListener.send(listeners, :on_dead, {:on_dead, message})
-    {:stop, :shutdown, state}
+    {:stop, :shutdown, Map.put(state, :close_reason, :worker_died)}
   end
 
   @impl true
   def terminate(
         reason,
-        %{reference: reference, supervisor: supervisor, listeners: listeners} = _state
+        %{reference: reference, supervisor: supervisor, listeners: listeners} = state
       ) do
-    EventListener.send(listeners, :on_close, {:on_close})
-    Mediasoup.Utility.supervisor_clean_stop(supervisor, reason)
+    close_reason = Mediasoup.Utility.close_reason(reason, state)
+    EventListener.send_close(listeners, close_reason)
+    Mediasoup.Utility.supervisor_clean_stop(
+      supervisor,
+      Mediasoup.Utility.child_exit_reason(close_reason)
+    )
 
     Nif.router_close(reference)
     :ok
@@ -488,6 +787,10 @@ defmodule Mediasoup.Router do
     node(pid)
   end
 
+  defp adopt_pipe_producer(%Router{pid: pid}, module, reference) do
+    NifWrap.call(pid, {:adopt_pipe_producer, module, reference})
+  end
+
   defp get_pipe_transport_pair(
          %Router{pid: pid},
          %PipeToRouterOptions{router: remote_router}
@@ -505,11 +808,12 @@ defmodule Mediasoup.Router do
 
   defp get_or_create_pipe_transport_pair(
          %Router{} = router,
-         %PipeToRouterOptions{router: %Router{pid: _pid2}} = option
+         %PipeToRouterOptions{router: %Router{pid: _pid2}} = option,
+         timeout_ms
        ) do
     case get_pipe_transport_pair(router, option) do
       nil ->
-        create_pipe_transport_pair(router, option)
+        create_pipe_transport_pair(router, option, timeout_ms)
 
       pair ->
         {:ok, pair}
@@ -526,7 +830,8 @@ defmodule Mediasoup.Router do
            enable_srtp: enable_srtp,
            get_remote_node_ip: get_remote_node_ip,
            get_listen_ip: get_listen_ip
-         } = option
+         } = option,
+         timeout_ms
        ) do
     local_node = get_node(router)
     remote_node = get_node(remote_router)
@@ -536,28 +841,44 @@ defmodule Mediasoup.Router do
          {:ok, local_listen_ip} <- get_listen_ip.(local_node, remote_node),
          {:ok, remote_listen_ip} <- get_listen_ip.(remote_node, local_node),
          {:ok, local_pipe_transport} <-
-           Router.create_pipe_transport(router, %PipeTransport.Options{
-             listen_ip: %{ip: local_listen_ip},
-             enable_sctp: enable_sctp,
-             num_sctp_streams: num_sctp_streams,
-             enable_rtx: enable_rtx,
-             enable_srtp: enable_srtp
-           }),
+           Router.create_pipe_transport(
+             router,
+             %PipeTransport.Options{
+               listen_ip: %{ip: local_listen_ip},
+               enable_sctp: enable_sctp,
+               num_sctp_streams: num_sctp_streams,
+               enable_rtx: enable_rtx,
+               enable_srtp: enable_srtp
+             },
+             timeout_ms
+           ),
          {:ok, remote_pipe_transport} <-
-           Router.create_pipe_transport(remote_router, %PipeTransport.Options{
-             listen_ip: %{ip: remote_listen_ip},
-             enable_sctp: enable_sctp,
-             num_sctp_streams: num_sctp_streams,
-             enable_rtx: enable_rtx,
-             enable_srtp: enable_srtp
-           }) do
+           Router.create_pipe_transport(
+             remote_router,
+             %PipeTransport.Options{
+               listen_ip: %{ip: remote_listen_ip},
+               enable_sctp: enable_sctp,
+               num_sctp_streams: num_sctp_streams,
+               enable_rtx: enable_rtx,
+               enable_srtp: enable_srtp
+             },
+             timeout_ms
+           ) do
       %{local_port: local_port} = PipeTransport.tuple(local_pipe_transport)
       %{local_port: remote_port} = PipeTransport.tuple(remote_pipe_transport)
 
       with {:ok} <-
-             PipeTransport.connect(local_pipe_transport, %{ip: remote_ip, port: remote_port}),
+             PipeTransport.connect(
+               local_pipe_transport,
+               %{ip: remote_ip, port: remote_port},
+               timeout_ms
+             ),
            {:ok} <-
-             PipeTransport.connect(remote_pipe_transport, %{ip: local_ip, port: local_port}) do
+             PipeTransport.connect(
+               remote_pipe_transport,
+               %{ip: local_ip, port: local_port},
+               timeout_ms
+             ) do
         pair = %{local: local_pipe_transport, remote: remote_pipe_transport}
         put_pipe_transport_pair(router, option, pair)
         {:ok, pair}
@@ -0,0 +1,72 @@
+defmodule Mediasoup.Runtime do
+  @moduledoc """
+  Native thread pools of mediasoup_elixir.
+
+  Two pools are used: the task pool runs the `*_async` requests, and the worker manager pool
+  runs the communication with mediasoup workers. Both are started on first use, with one
+  thread per CPU (at least 2) by default.
+  """
+
+  alias Mediasoup.Nif
+
+  defmodule Options do
+    @moduledoc """
+    Thread pool settings for `Mediasoup.Runtime.init/1`. Unset fields keep their default.
+
+    * `task_threads`, `worker_manager_threads` - number of threads of each pool.
+    * `task_thread_name`, `worker_manager_thread_name` - thread names are `<name>-<index>`.
+      Defaults are `"ex-mediasoup-task"` and `"ex-mediasoup-wm"`.
+    * `stack_size` - stack size in bytes of the pool threads.
+    """
+
+    defstruct task_threads: nil,
+              task_thread_name: nil,
+              worker_manager_threads: nil,
+              worker_manager_thread_name: nil,
+              stack_size: nil
+
+    @type t :: %__MODULE__{
+            task_threads: pos_integer() | nil,
+            task_thread_name: String.t() | nil,
+            worker_manager_threads: pos_integer() | nil,
+            worker_manager_thread_name: String.t() | nil,
+            stack_size: pos_integer() | nil
+          }
+  end
+
+  @type thread_stats :: %{name: String.t(), busy_time_us: non_neg_integer()}
+  @typedoc """
+  `queued_tasks` and `active_tasks` count the `*_async` requests that are waiting to run and
+  that have not completed yet; tasks spawned by mediasoup itself on the worker manager pool are
+  not counted there. `busy_time_us` is the time a thread spent running any task, those included.
+
+  A panic in a task or in the executor of a pool thread is logged as an error through
+  `Mediasoup.LoggerProxy` and counted in `panics`. The task is dropped and the thread keeps
+  running; `restarts` counts the times a thread had to restart its executor. The caller of an
+  `*_async` request that panicked receives `{:error, :panicked}`.
+  """
+  @type pool_stats :: %{
+          name: String.t(),
+          queued_tasks: non_neg_integer(),
+          active_tasks: non_neg_integer(),
+          panics: non_neg_integer(),
+          restarts: non_neg_integer(),
+          threads: [thread_stats()]
+        }
+  @type stats :: %{task: pool_stats(), worker_manager: pool_stats()}
+
+  @doc """
+  Configures the thread pools.
+  Must be called before the first worker is created, usually from the application start.
+  Returns `{:error, :already_started}` once the pools are running.
+  """
+  @spec init(Options.t()) :: {:ok} | {:error, :already_started}
+  def init(%Options{} = options), do: Nif.init_runtime(options)
+
+  @doc """
+  Returns the stats of the thread pools. A pool that is not running yet is not started by this
+  call; it reports zeros for its configured threads.
+  """
+  @spec stats() :: stats()
+  def stats(), do: Nif.runtime_stats()
+end
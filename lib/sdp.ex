@@ -0,0 +1,93 @@
+defmodule Mediasoup.Sdp do
+  @moduledoc """
+  SDP offers and answers for endpoints that don't run mediasoup-client, such as SIP gateways,
+  OBS (WHIP) or embedded devices.
+
+  Every media section is bundled over one `Mediasoup.WebRtcTransport`. A `Mediasoup.Producer`
+  is described as `recvonly` and a `Mediasoup.Consumer` as `sendonly`, with the transport's ICE
+  candidates and DTLS fingerprints. `parse/1` reads the remote description back into the
+  parameters `WebRtcTransport.connect/2`, `WebRtcTransport.produce/2` and
+  `WebRtcTransport.consume/2` expect.
+  """
+
+  alias Mediasoup.{Consumer, Nif, Producer, WebRtcTransport}
+
+  @typedoc """
+  A producer, a consumer, or a map describing the section:
+  `%{kind: "audio" | "video", direction: "sendonly" | "recvonly" | "sendrecv" | "inactive",
+  rtpParameters: map}`, with optional `mid`, `streamId` and `trackId`.
+  """
+  @type media :: Producer.t() | Consumer.t() | map
+
+  @typedoc """
+  `session_id` defaults to a random one, `session_version` to 1. Increase the version when
+  renegotiating the same session.
+  """
+  @type build_option :: {:session_id, non_neg_integer} | {:session_version, non_neg_integer}
+
+  @typedoc """
+  `remoteParameters` are given to `WebRtcTransport.connect/2` and `rtpCapabilities` to
+  `WebRtcTransport.consume/2`. Each entry of `media` has the `mid`, `kind`, `direction` (as the
+  remote endpoint wrote it) and `rtpParameters` of a section.
+  """
+  @type parsed :: %{String.t() => map | [map]}
+
+  @spec offer(WebRtcTransport.t(), [media], [build_option]) ::
+          {:ok, String.t()} | {:error, String.t() | :terminated}
+  @doc """
+  Builds an SDP offer for `media`. The DTLS role is left to the remote endpoint
+  (`a=setup:actpass`) unless the transport is already connected.
+  """
+  def offer(%WebRtcTransport{} = transport, media, opts \\ []) do
+    build("offer", transport, media, opts)
+  end
+
+  @spec answer(WebRtcTransport.t(), [media], [build_option]) ::
+          {:ok, String.t()} | {:error, String.t() | :terminated}
+  @doc """
+  Builds an SDP answer for `media`, to send once the transport is connected with the
+  `remoteParameters` of the parsed offer.
+  """
+  def answer(%WebRtcTransport{} = transport, media, opts \\ []) do
+    build("answer", transport, media, opts)
+  end
+
+  @spec parse(String.t()) :: {:ok, parsed} | {:error, String.t()}
+  @doc """
+  Parses a remote SDP. Codecs and header extensions mediasoup does not support are dropped,
+  as are data channel and rejected sections.
+  """
+  def parse(sdp) when is_binary(sdp) do
+    Nif.sdp_parse(sdp)
+  end
+
+  defp build(type, transport, media, opts) do
+    with %{} = ice_parameters <- WebRtcTransport.ice_parameters(transport),
+         candidates when is_list(candidates) <- WebRtcTransport.ice_candidates(transport),
+         %{} = dtls_parameters <- WebRtcTransport.dtls_parameters(transport) do
+      %{
+        type: type,
+        iceParameters: ice_parameters,
+        iceCandidates: candidates,
+        dtlsParameters: dtls_parameters,
+        media: Enum.map(media, &media_section/1)
+      }
+      |> put_option(:sessionId, opts[:session_id])
+      |> put_option(:sessionVersion, opts[:session_version])
+      |> Nif.sdp_build()
+    end
+  end
+
+  defp media_section(%Producer{id: id, kind: kind, rtp_parameters: rtp_parameters}) do
+    %{kind: to_string(kind), direction: "recvonly", rtpParameters: rtp_parameters, trackId: id}
+  end
+
+  defp media_section(%Consumer{id: id, kind: kind, rtp_parameters: rtp_parameters}) do
+    %{kind: to_string(kind), direction: "sendonly", rtpParameters: rtp_parameters, trackId: id}
+  end
+
+  defp media_section(%{} = media), do: media
+
+  defp put_option(options, _key, nil), do: options
+  defp put_option(options, key, value), do: Map.put(options, key, value)
+end
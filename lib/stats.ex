@@ -0,0 +1,398 @@
+defmodule Mediasoup.Stats do
+  @moduledoc """
+  Structs returned by the `get_stats/1` functions and pushed by the `stats_subscribe/4`
+  functions, see `Sample`.
+
+  Keys are snake_case and enum values are atoms, e.g. `ice_state: :completed`.
+  https://mediasoup.org/documentation/v3/mediasoup/rtc-statistics/
+  """
+
+  defmodule RtpStreamRecv do
+    @moduledoc """
+    Stats of a stream received by a producer, `"inbound-rtp"` in mediasoup.
+
+    `bitrate_by_layer` is keyed by `"spatial.temporal"` layer.
+    """
+
+    defstruct [
+      :timestamp,
+      :ssrc,
+      :rtx_ssrc,
+      :rid,
+      :kind,
+      :mime_type,
+      :packets_lost,
+      :fraction_lost,
+      :packets_discarded,
+      :packets_retransmitted,
+      :packets_repaired,
+      :nack_count,
+      :nack_packet_count,
+      :pli_count,
+      :fir_count,
+      :score,
+      :packet_count,
+      :byte_count,
+      :bitrate,
+      :round_trip_time,
+      :rtx_packets_discarded,
+      :jitter,
+      bitrate_by_layer: %{}
+    ]
+
+    @type t :: %__MODULE__{
+            timestamp: non_neg_integer(),
+            ssrc: non_neg_integer(),
+            rtx_ssrc: non_neg_integer() | nil,
+            rid: String.t() | nil,
+            kind: :audio | :video,
+            mime_type: String.t(),
+            packets_lost: non_neg_integer(),
+            fraction_lost: non_neg_integer(),
+            packets_discarded: non_neg_integer(),
+            packets_retransmitted: non_neg_integer(),
+            packets_repaired: non_neg_integer(),
+            nack_count: non_neg_integer(),
+            nack_packet_count: non_neg_integer(),
+            pli_count: non_neg_integer(),
+            fir_count: non_neg_integer(),
+            score: non_neg_integer(),
+            packet_count: non_neg_integer(),
+            byte_count: non_neg_integer(),
+            bitrate: non_neg_integer(),
+            round_trip_time: float() | nil,
+            rtx_packets_discarded: non_neg_integer() | nil,
+            jitter: non_neg_integer(),
+            bitrate_by_layer: %{String.t() => non_neg_integer()}
+          }
+  end
+
+  defmodule RtpStreamSend do
+    @moduledoc """
+    Stats of a stream sent by a consumer, `"outbound-rtp"` in mediasoup.
+    """
+
+    defstruct [
+      :timestamp,
+      :ssrc,
+      :rtx_ssrc,
+      :kind,
+      :mime_type,
+      :packets_lost,
+      :fraction_lost,
+      :packets_discarded,
+      :packets_retransmitted,
+      :packets_repaired,
+      :nack_count,
+      :nack_packet_count,
+      :pli_count,
+      :fir_count,
+      :score,
+      :packet_count,
+      :byte_count,
+      :bitrate,
+      :round_trip_time
+    ]
+
+    @type t :: %__MODULE__{
+            timestamp: non_neg_integer(),
+            ssrc: non_neg_integer(),
+            rtx_ssrc: non_neg_integer() | nil,
+            kind: :audio | :video,
+            mime_type: String.t(),
+            packets_lost: non_neg_integer(),
+            fraction_lost: non_neg_integer(),
+            packets_discarded: non_neg_integer(),
+            packets_retransmitted: non_neg_integer(),
+            packets_repaired: non_neg_integer(),
+            nack_count: non_neg_integer(),
+            nack_packet_count: non_neg_integer(),
+            pli_count: non_neg_integer(),
+            fir_count: non_neg_integer(),
+            score: non_neg_integer(),
+            packet_count: non_neg_integer(),
+            byte_count: non_neg_integer(),
+            bitrate: non_neg_integer(),
+            round_trip_time: float() | nil
+          }
+  end
+
+  defmodule WebRtcTransportStats do
+    @moduledoc """
+    Stats of a `Mediasoup.WebRtcTransport`. Bitrates are in bits per second.
+    """
+
+    defstruct [
+      :transport_id,
+      :timestamp,
+      :sctp_state,
+      :bytes_received,
+      :recv_bitrate,
+      :bytes_sent,
+      :send_bitrate,
+      :rtp_bytes_received,
+      :rtp_recv_bitrate,
+      :rtp_bytes_sent,
+      :rtp_send_bitrate,
+      :rtx_bytes_received,
+      :rtx_recv_bitrate,
+      :rtx_bytes_sent,
+      :rtx_send_bitrate,
+      :probation_bytes_sent,
+      :probation_send_bitrate,
+      :available_outgoing_bitrate,
+      :available_incoming_bitrate,
+      :max_incoming_bitrate,
+      :max_outgoing_bitrate,
+      :min_outgoing_bitrate,
+      :rtp_packet_loss_received,
+      :rtp_packet_loss_sent,
+      :ice_role,
+      :ice_state,
+      :ice_selected_tuple,
+      :dtls_state
+    ]
+
+    @type t :: %__MODULE__{
+            transport_id: String.t(),
+            timestamp: non_neg_integer(),
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            bytes_received: non_neg_integer(),
+            recv_bitrate: non_neg_integer(),
+            bytes_sent: non_neg_integer(),
+            send_bitrate: non_neg_integer(),
+            rtp_bytes_received: non_neg_integer(),
+            rtp_recv_bitrate: non_neg_integer(),
+            rtp_bytes_sent: non_neg_integer(),
+            rtp_send_bitrate: non_neg_integer(),
+            rtx_bytes_received: non_neg_integer(),
+            rtx_recv_bitrate: non_neg_integer(),
+            rtx_bytes_sent: non_neg_integer(),
+            rtx_send_bitrate: non_neg_integer(),
+            probation_bytes_sent: non_neg_integer(),
+            probation_send_bitrate: non_neg_integer(),
+            available_outgoing_bitrate: non_neg_integer() | nil,
+            available_incoming_bitrate: non_neg_integer() | nil,
+            max_incoming_bitrate: non_neg_integer() | nil,
+            max_outgoing_bitrate: non_neg_integer() | nil,
+            min_outgoing_bitrate: non_neg_integer() | nil,
+            rtp_packet_loss_received: float() | nil,
+            rtp_packet_loss_sent: float() | nil,
+            ice_role: :controlled | :controlling,
+            ice_state: :new | :connected | :completed | :disconnected,
+            ice_selected_tuple: TransportTuple.t() | nil,
+            dtls_state: :new | :connecting | :connected | :failed | :closed
+          }
+  end
+
+  defmodule PlainTransportStats do
+    @moduledoc """
+    Stats of a `Mediasoup.PlainTransport`. Bitrates are in bits per second.
+    """
+
+    defstruct [
+      :transport_id,
+      :timestamp,
+      :sctp_state,
+      :bytes_received,
+      :recv_bitrate,
+      :bytes_sent,
+      :send_bitrate,
+      :rtp_bytes_received,
+      :rtp_recv_bitrate,
+      :rtp_bytes_sent,
+      :rtp_send_bitrate,
+      :rtx_bytes_received,
+      :rtx_recv_bitrate,
+      :rtx_bytes_sent,
+      :rtx_send_bitrate,
+      :probation_bytes_sent,
+      :probation_send_bitrate,
+      :available_outgoing_bitrate,
+      :available_incoming_bitrate,
+      :max_incoming_bitrate,
+      :max_outgoing_bitrate,
+      :min_outgoing_bitrate,
+      :rtp_packet_loss_received,
+      :rtp_packet_loss_sent,
+      :rtcp_mux,
+      :comedia,
+      :tuple,
+      :rtcp_tuple
+    ]
+
+    @type t :: %__MODULE__{
+            transport_id: String.t(),
+            timestamp: non_neg_integer(),
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            bytes_received: non_neg_integer(),
+            recv_bitrate: non_neg_integer(),
+            bytes_sent: non_neg_integer(),
+            send_bitrate: non_neg_integer(),
+            rtp_bytes_received: non_neg_integer(),
+            rtp_recv_bitrate: non_neg_integer(),
+            rtp_bytes_sent: non_neg_integer(),
+            rtp_send_bitrate: non_neg_integer(),
+            rtx_bytes_received: non_neg_integer(),
+            rtx_recv_bitrate: non_neg_integer(),
+            rtx_bytes_sent: non_neg_integer(),
+            rtx_send_bitrate: non_neg_integer(),
+            probation_bytes_sent: non_neg_integer(),
+            probation_send_bitrate: non_neg_integer(),
+            available_outgoing_bitrate: non_neg_integer() | nil,
+            available_incoming_bitrate: non_neg_integer() | nil,
+            max_incoming_bitrate: non_neg_integer() | nil,
+            max_outgoing_bitrate: non_neg_integer() | nil,
+            min_outgoing_bitrate: non_neg_integer() | nil,
+            rtp_packet_loss_received: float() | nil,
+            rtp_packet_loss_sent: float() | nil,
+            rtcp_mux: boolean(),
+            comedia: boolean(),
+            tuple: TransportTuple.t(),
+            rtcp_tuple: TransportTuple.t() | nil
+          }
+  end
+
+  defmodule PipeTransportStats do
+    @moduledoc """
+    Stats of a `Mediasoup.PipeTransport`. Bitrates are in bits per second.
+    """
+
+    defstruct [
+      :transport_id,
+      :timestamp,
+      :sctp_state,
+      :bytes_received,
+      :recv_bitrate,
+      :bytes_sent,
+      :send_bitrate,
+      :rtp_bytes_received,
+      :rtp_recv_bitrate,
+      :rtp_bytes_sent,
+      :rtp_send_bitrate,
+      :rtx_bytes_received,
+      :rtx_recv_bitrate,
+      :rtx_bytes_sent,
+      :rtx_send_bitrate,
+      :probation_bytes_sent,
+      :probation_send_bitrate,
+      :available_outgoing_bitrate,
+      :available_incoming_bitrate,
+      :max_incoming_bitrate,
+      :max_outgoing_bitrate,
+      :min_outgoing_bitrate,
+      :rtp_packet_loss_received,
+      :rtp_packet_loss_sent,
+      :tuple
+    ]
+
+    @type t :: %__MODULE__{
+            transport_id: String.t(),
+            timestamp: non_neg_integer(),
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            bytes_received: non_neg_integer(),
+            recv_bitrate: non_neg_integer(),
+            bytes_sent: non_neg_integer(),
+            send_bitrate: non_neg_integer(),
+            rtp_bytes_received: non_neg_integer(),
+            rtp_recv_bitrate: non_neg_integer(),
+            rtp_bytes_sent: non_neg_integer(),
+            rtp_send_bitrate: non_neg_integer(),
+            rtx_bytes_received: non_neg_integer(),
+            rtx_recv_bitrate: non_neg_integer(),
+            rtx_bytes_sent: non_neg_integer(),
+            rtx_send_bitrate: non_neg_integer(),
+            probation_bytes_sent: non_neg_integer(),
+            probation_send_bitrate: non_neg_integer(),
+            available_outgoing_bitrate: non_neg_integer() | nil,
+            available_incoming_bitrate: non_neg_integer() | nil,
+            max_incoming_bitrate: non_neg_integer() | nil,
+            max_outgoing_bitrate: non_neg_integer() | nil,
+            min_outgoing_bitrate: non_neg_integer() | nil,
+            rtp_packet_loss_received: float() | nil,
+            rtp_packet_loss_sent: float() | nil,
+            tuple: TransportTuple.t()
+          }
+  end
+
+  defmodule DataProducerStats do
+    @moduledoc """
+    Stats of a `Mediasoup.DataProducer`.
+    """
+
+    defstruct [:timestamp, :label, :protocol, :messages_received, :bytes_received]
+
+    @type t :: %__MODULE__{
+            timestamp: non_neg_integer(),
+            label: String.t(),
+            protocol: String.t(),
+            messages_received: non_neg_integer(),
+            bytes_received: non_neg_integer()
+          }
+  end
+
+  defmodule DataConsumerStats do
+    @moduledoc """
+    Stats of a `Mediasoup.DataConsumer`.
+    """
+
+    defstruct [
+      :timestamp,
+      :label,
+      :protocol,
+      :messages_sent,
+      :bytes_sent,
+      :buffered_amount
+    ]
+
+    @type t :: %__MODULE__{
+            timestamp: non_neg_integer(),
+            label: String.t(),
+            protocol: String.t(),
+            messages_sent: non_neg_integer(),
+            bytes_sent: non_neg_integer(),
+            buffered_amount: non_neg_integer()
+          }
+  end
+
+  defmodule Sample do
+    @moduledoc """
+    Stats pushed as `{:on_stats, sample}` by a `stats_subscribe/4` function, e.g.
+    `Mediasoup.Producer.stats_subscribe/4`.
+
+    With `delta: true`, counters such as `packet_count`, `packets_lost` or `bytes_sent` are the
+    change since the previous sample, `elapsed_ms` ago. Gauges such as `bitrate` or `score` are
+    the current values. The first sample has the counters since the entity was created, with
+    `delta: false` and `elapsed_ms: nil`.
+
+    Once the entity closes, `{:on_stats_end, id}` is sent and no more samples follow.
+    Every sample is its own message, never part of a `{:nif_internal_event_batch, events}`.
+    """
+
+    defstruct [:id, :entity, :delta, :elapsed_ms, stats: []]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            entity: Mediasoup.Metrics.entity_type(),
+            delta: boolean(),
+            elapsed_ms: non_neg_integer() | nil,
+            stats: [struct()]
+          }
+  end
+
+  @type rtp_stream :: RtpStreamRecv.t() | RtpStreamSend.t()
+
+  @typedoc """
+  `delta: true` sends counters as the change since the previous sample, see `Sample`.
+  """
+  @type subscribe_option :: {:delta, boolean()}
+
+  @spec unsubscribe(reference) :: {:ok}
+  @doc """
+  Stops a subscription returned by a `stats_subscribe/4` function. Samples already queued for
+  the process may still arrive. A subscription also stops when it is garbage-collected.
+  """
+  def unsubscribe(subscription) do
+    Mediasoup.Nif.stats_unsubscribe(subscription)
+  end
+end
@@ -2,20 +2,33 @@ defprotocol Mediasoup.Transport do
   @moduledoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#Transport
   """
+
+  @typedoc """
+  https://mediasoup.org/documentation/v3/mediasoup/api/#SctpState
+  """
+  @type sctp_state :: :new | :connecting | :connected | :failed | :closed
+
   def id(transport)
   def close(transport)
   def closed?(transport)
   def consume(transport, option)
+  def consume(transport, option, timeout_ms)
   def consume_data(transport, option)
+  def consume_data(transport, option, timeout_ms)
   def produce(transport, option)
+  def produce(transport, option, timeout_ms)
   def produce_data(transport, option)
+  def produce_data(transport, option, timeout_ms)
   def sctp_parameters(transport)
   def sctp_state(transport)
 
   def get_stats(transport)
+  def get_stats(transport, timeout_ms)
+  def stats_subscribe(transport, listener, interval_ms, opts)
   def event(transport, listener)
 
   def dump(transport)
+  def dump(transport, timeout_ms)
 end
 
 defimpl Mediasoup.Transport, for: [Mediasoup.WebRtcTransport, Mediasoup.PipeTransport] do
@@ -23,14 +36,30 @@ defimpl Mediasoup.Transport, for: [Mediasoup.WebRtcTransport, Mediasoup.PipeTran
   def close(transport), do: @for.close(transport)
   def closed?(transport), do: @for.closed?(transport)
   def consume(transport, option), do: @for.consume(transport, option)
+  def consume(transport, option, timeout_ms), do: @for.consume(transport, option, timeout_ms)
   def consume_data(transport, option), do: @for.consume_data(transport, option)
+
+  def consume_data(transport, option, timeout_ms),
+    do: @for.consume_data(transport, option, timeout_ms)
+
   def produce(transport, option), do: @for.produce(transport, option)
+  def produce(transport, option, timeout_ms), do: @for.produce(transport, option, timeout_ms)
   def produce_data(transport, option), do: @for.produce_data(transport, option)
+
+  def produce_data(transport, option, timeout_ms),
+    do: @for.produce_data(transport, option, timeout_ms)
+
   def sctp_parameters(transport), do: @for.sctp_parameters(transport)
   def sctp_state(transport), do: @for.sctp_state(transport)
   def get_stats(transport), do: @for.get_stats(transport)
+  def get_stats(transport, timeout_ms), do: @for.get_stats(transport, timeout_ms)
+
+  def stats_subscribe(transport, listener, interval_ms, opts),
+    do: @for.stats_subscribe(transport, listener, interval_ms, opts)
+
   def event(transport, listener), do: @for.event(transport, listener)
   def dump(transport), do: @for.dump(transport)
+  def dump(transport, timeout_ms), do: @for.dump(transport, timeout_ms)
 end
 
 defmodule TransportTuple do
@@ -48,6 +77,7 @@ defmodule TransportTuple do
           :protocol => :udp | :tcp
         }
 
+  @deprecated "NIFs return the protocol as an atom already"
   def protocol_to_atom("udp"), do: :udp
   def protocol_to_atom("tcp"), do: :tcp
 end
@@ -53,7 +53,36 @@ defmodule Mediasoup.Utility do
     {:ok, "0.0.0.0"}
   end
 
+  @typedoc """
+  Why an entity closed, see the `:on_close_reason` event:
+  * `:explicit` - closed with `close/1`, `close_and_wait/1` or by its own handle being released
+  * `:parent_closed` - closed with its parent, e.g. the transport of a producer
+  * `:worker_died` - its worker died
+  * `:owner_down` - its owner exited, see `monitor_owner/2`
+  """
+  @type close_reason :: :explicit | :parent_closed | :worker_died | :owner_down
+
+  @close_reasons [:explicit, :parent_closed, :worker_died, :owner_down]
+
+  @doc false
+  # The close reason of a stopping entity process: the reason its parent stopped it with, see
+  # `child_exit_reason/1`, or the one it received from the native layer.
+  def close_reason({:shutdown, reason}, _state) when reason in @close_reasons, do: reason
+  def close_reason(_exit_reason, state), do: Map.get(state, :close_reason, :explicit)
+
+  @doc false
+  # The exit reason for the processes of the children of an entity that closed for `reason`.
+  def child_exit_reason(:worker_died), do: {:shutdown, :worker_died}
+  def child_exit_reason(_reason), do: {:shutdown, :parent_closed}
+
   def supervisor_clean_stop(supervisor, reason) do
+    supervisor_stop_children(supervisor, reason)
+    DynamicSupervisor.stop(supervisor, reason)
+  end
+
+  @doc false
+  # Stops the processes of the children under `supervisor`, which closes their entities.
+  def supervisor_stop_children(supervisor, reason) do
     try do
       DynamicSupervisor.which_children(supervisor)
       |> Enum.each(fn {:undefined, pid, _type, _modules} when is_pid(pid) ->
@@ -66,8 +95,6 @@ defmodule Mediasoup.Utility do
     catch
       _kind, _error -> :ok
     end
-
-    DynamicSupervisor.stop(supervisor, reason)
   end
 end
 
@@ -75,6 +102,11 @@ defmodule Mediasoup.EventListener do
   @moduledoc """
   Event listener module for rustler because rustler(nif) can only use local pid.
   This module is used to add, remove, and send events to the listener.
+
+  A listener added with `max_queue_len: n` does not receive value events (scores, layers, state
+  and tuple changes) while its mailbox holds `n` messages or more, as reported by
+  `Process.info(listener, :message_queue_len)`. Dropped events are counted, see `dropped/2`.
+  Lifecycle events such as `on_close` are always sent.
   """
 
   defstruct [:listeners]
@@ -84,31 +116,55 @@ defmodule Mediasoup.EventListener do
   end
 
   @type t() :: %__MODULE__{
-          listeners: %{pid() => %{event_types: [atom()], monitor_ref: reference(), tag: any()}}
+          listeners: %{
+            pid() => %{
+              event_types: [atom()],
+              monitor_ref: reference(),
+              max_queue_len: non_neg_integer() | nil,
+              dropped: :counters.counters_ref()
+            }
+          }
         }
 
+  @type option :: {:max_queue_len, non_neg_integer() | nil}
+
+  @value_events [
+    :on_score,
+    :on_layers_change,
+    :on_video_orientation_change,
+    :on_ice_state_change,
+    :on_ice_selected_tuple_change,
+    :on_dtls_state_change,
+    :on_sctp_state_change,
+    :on_tuple
+  ]
+
   @doc """
   Add a listener to the event listener.
   If the listener is already added, the event types will be updated.
   If the listener is not added, a monitor will be created and the listener will be added.
   The calling process handles the :DOWN message and calls remove
   """
-  def add(%__MODULE__{listeners: listeners}, listener, event_types) do
+  @spec add(t(), pid(), [atom()], [option()]) :: t()
+  def add(%__MODULE__{listeners: listeners}, listener, event_types, opts \\ []) do
     prev = Map.get(listeners, listener, nil)
+    max_queue_len = Keyword.get(opts, :max_queue_len)
 
     listeners =
       if prev do
         Map.put(
           listeners,
           listener,
-          Map.put(prev, :event_types, event_types)
+          Map.merge(prev, %{event_types: event_types, max_queue_len: max_queue_len})
         )
       else
         monitor_ref = Process.monitor(listener)
 
         Map.put(listeners, listener, %{
           event_types: event_types,
-          monitor_ref: monitor_ref
+          monitor_ref: monitor_ref,
+          max_queue_len: max_queue_len,
+          dropped: :counters.new(1, [])
         })
       end
 
@@ -133,8 +189,43 @@ defmodule Mediasoup.EventListener do
   end
 
   def send(%__MODULE__{listeners: listeners}, event_name, message) do
-    for {listener, %{event_types: event_types}} <- listeners,
-        event_name in event_types,
-        do: send(listener, message)
+    for {listener, %{event_types: event_types} = entry} <- listeners,
+        event_name in event_types do
+      if event_name in @value_events and queue_full?(listener, entry) do
+        :counters.add(entry.dropped, 1, 1)
+      else
+        send(listener, message)
+      end
+    end
+  end
+
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full.
+  """
+  @spec dropped(t(), pid()) :: non_neg_integer()
+  def dropped(%__MODULE__{listeners: listeners}, listener) do
+    case Map.get(listeners, listener) do
+      %{dropped: dropped} -> :counters.get(dropped, 1)
+      nil -> 0
+    end
+  end
+
+  defp queue_full?(_listener, %{max_queue_len: nil}), do: false
+  defp queue_full?(listener, _entry) when node(listener) != node(), do: false
+
+  defp queue_full?(listener, %{max_queue_len: max_queue_len}) do
+    case Process.info(listener, :message_queue_len) do
+      {:message_queue_len, len} -> len >= max_queue_len
+      nil -> false
+    end
+  end
+
+  @doc """
+  Sends `{:on_close_reason, reason}` to the listeners of `:on_close_reason`, then `{:on_close}`
+  to the listeners of `:on_close`.
+  """
+  def send_close(%__MODULE__{} = event_listener, reason) do
+    send(event_listener, :on_close_reason, {:on_close_reason, reason})
+    send(event_listener, :on_close, {:on_close})
   end
 end
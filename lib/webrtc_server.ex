@@ -60,6 +60,32 @@ defmodule Mediasoup.WebRtcServer do
     end
   end
 
+  defmodule Dump do
+    @moduledoc """
+    Dump of the webrtc server, see `Mediasoup.WebRtcServer.dump/1`.
+    """
+
+    defstruct [
+      :id,
+      :udp_sockets,
+      :tcp_servers,
+      :webrtc_transport_ids,
+      :local_ice_username_fragments,
+      :tuple_hashes
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            udp_sockets: [%{ip: String.t(), port: non_neg_integer()}],
+            tcp_servers: [%{ip: String.t(), port: non_neg_integer()}],
+            webrtc_transport_ids: [String.t()],
+            local_ice_username_fragments: [
+              %{local_ice_username_fragment: String.t(), webrtc_transport_id: String.t()}
+            ],
+            tuple_hashes: [%{tuple_hash: non_neg_integer(), webrtc_transport_id: String.t()}]
+          }
+  end
+
   @type create_option :: map | Options.t()
 
   @doc """
@@ -77,6 +103,15 @@ defmodule Mediasoup.WebRtcServer do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the server like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%WebRtcServer{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
   @spec closed?(t) :: boolean
   @doc """
   Tells whether the given WebRtcServer is closed on the local node.
@@ -89,12 +124,12 @@ defmodule Mediasoup.WebRtcServer do
       end
   end
 
-  @spec dump(t) :: any | {:error, :terminated}
+  @spec dump(t, Mediasoup.timeout_ms()) :: Dump.t() | {:error, :terminated | :timeout}
   @doc """
   Dump internal stat for WebRtcServer.
   """
-  def dump(%WebRtcServer{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%WebRtcServer{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
   @spec struct_from_pid(pid()) :: WebRtcServer.t()
@@ -131,9 +166,11 @@ defmodule Mediasoup.WebRtcServer do
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    dump: &Nif.webrtc_server_dump_async/2
+    dump: &Nif.webrtc_server_dump_async/3
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.webrtc_server_close_async/3)
+
   def handle_call(
         :to_ref,
         _from,
@@ -143,6 +180,11 @@ defmodule Mediasoup.WebRtcServer do
   end
 
   @impl true
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
         state
@@ -190,12 +190,81 @@ defmodule Mediasoup.WebRtcTransport do
     end
   end
 
+  defmodule Dump do
+    @moduledoc """
+    Dump of the transport, see `Mediasoup.WebRtcTransport.dump/1`.
+
+    Keys of `recv_rtp_header_extensions` are the header extensions, e.g. `:mid`, and the
+    tables of `rtp_listener` are `:mid_table`, `:rid_table` and `:ssrc_table`.
+    """
+
+    defstruct [
+      :id,
+      :direct,
+      :producer_ids,
+      :consumer_ids,
+      :map_ssrc_consumer_id,
+      :map_rtx_ssrc_consumer_id,
+      :data_producer_ids,
+      :data_consumer_ids,
+      :recv_rtp_header_extensions,
+      :rtp_listener,
+      :max_message_size,
+      :sctp_parameters,
+      :sctp_state,
+      :sctp_listener,
+      :trace_event_types,
+      :dtls_parameters,
+      :dtls_state,
+      :ice_candidates,
+      :ice_parameters,
+      :ice_role,
+      :ice_state,
+      :ice_selected_tuple
+    ]
+
+    @type t :: %__MODULE__{
+            id: String.t(),
+            direct: boolean(),
+            producer_ids: [String.t()],
+            consumer_ids: [String.t()],
+            map_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            map_rtx_ssrc_consumer_id: %{non_neg_integer() => String.t()},
+            data_producer_ids: [String.t()],
+            data_consumer_ids: [String.t()],
+            recv_rtp_header_extensions: %{atom() => non_neg_integer() | nil},
+            rtp_listener: %{atom() => %{(String.t() | non_neg_integer()) => String.t()}},
+            max_message_size: non_neg_integer(),
+            sctp_parameters: map() | nil,
+            sctp_state: :new | :connecting | :connected | :failed | :closed | nil,
+            sctp_listener: %{stream_id_table: %{non_neg_integer() => String.t()}} | nil,
+            trace_event_types: [atom()],
+            dtls_parameters: map(),
+            dtls_state: :new | :connecting | :connected | :failed | :closed,
+            ice_candidates: [map()],
+            ice_parameters: map(),
+            ice_role: :controlled | :controlling,
+            ice_state: :new | :connected | :completed | :disconnected,
+            ice_selected_tuple: TransportTuple.t() | nil
+          }
+  end
+
   @type create_option :: map | Options.t()
 
   @type connect_option :: map
 
   @type ice_parameter :: map()
 
+  @typedoc """
+  https://mediasoup.org/documentation/v3/mediasoup/api/#WebRtcTransportIceState
+  """
+  @type ice_state :: :new | :connected | :completed | :disconnected | :closed
+
+  @typedoc """
+  https://mediasoup.org/documentation/v3/mediasoup/api/#WebRtcTransportDtlsState
+  """
+  @type dtls_state :: :new | :connecting | :connected | :failed | :closed
+
   @spec id(t) :: String.t()
   @doc """
   WebRtcTransport identifier.
@@ -213,6 +282,25 @@ defmodule Mediasoup.WebRtcTransport do
     GenServer.stop(pid)
   end
 
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the transport like `close/1`, and returns once the worker has confirmed the close.
+  """
+  def close_and_wait(%WebRtcTransport{pid: pid}, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec monitor_owner(t, pid) :: {:ok} | {:error, :terminated}
+  @doc """
+  Closes the WebRtcTransport as soon as the `owner` process exits, e.g. the signalling process
+  of the peer. Listeners of `:on_owner_down` receive `{:on_owner_down, owner}` before
+  `{:on_close}`. Calling it again replaces the owner.
+  """
+  def monitor_owner(%WebRtcTransport{pid: pid}, owner) when is_pid(owner) do
+    NifWrap.call(pid, {:monitor_owner, [owner]})
+  end
+
   @spec closed?(t) :: boolean
   @doc """
   Tells whether the given WebRtcTransport is closed on the local node.
@@ -225,69 +313,103 @@ defmodule Mediasoup.WebRtcTransport do
       end
   end
 
-  @spec consume(t, Consumer.Options.t() | map()) ::
-          {:ok, Consumer.t()} | {:error, String.t() | :terminated}
+  @spec consume(t, Consumer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Consumer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to send audio or video RTP (or SRTP depending on the transport class). This is the way to extract media from mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-consume
   """
-  def consume(%WebRtcTransport{pid: pid}, %Consumer.Options{} = option) do
-    NifWrap.call(pid, {:consume, [option]})
+  def consume(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def consume(%WebRtcTransport{pid: pid}, %Consumer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:consume, [option]}, timeout_ms)
+  end
+
+  def consume(%WebRtcTransport{} = transport, option, timeout_ms) do
+    consume(transport, Consumer.Options.from_map(option), timeout_ms)
   end
 
-  def consume(%WebRtcTransport{} = transport, option) do
-    consume(transport, Consumer.Options.from_map(option))
+  @spec consume_batch(
+          t,
+          rtp_capabilities :: map(),
+          [Consumer.BatchItem.t() | map()],
+          Mediasoup.timeout_ms()
+        ) :: [{:ok, Consumer.t()} | {:error, String.t()}] | {:error, :terminated | :timeout}
+  @doc """
+  Creates a consumer for each of `items`, all with the same `rtp_capabilities`, in a single call.
+  The consumers are created concurrently. Returns a result per item, in the order of `items`.
+  """
+  def consume_batch(
+        %WebRtcTransport{pid: pid},
+        rtp_capabilities,
+        items,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    items =
+      Enum.map(items, fn
+        %Consumer.BatchItem{} = item -> item
+        item -> Consumer.BatchItem.from_map(item)
+      end)
+
+    NifWrap.call(pid, {:consume_batch, [rtp_capabilities, items]}, timeout_ms)
   end
 
-  @spec consume_data(t, DataConsumer.Options.t() | map()) ::
-          {:ok, DataConsumer.t()} | {:error, String.t() | :terminated}
+  @spec consume_data(t, DataConsumer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, DataConsumer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to send data messages to the endpoint via SCTP protocol or directly to the Node.js process if the transport is a DirectTransport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-consumedata
   """
-  def consume_data(%WebRtcTransport{pid: pid}, %DataConsumer.Options{} = option) do
-    NifWrap.call(pid, {:consume_data, [option]})
+  def consume_data(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def consume_data(%WebRtcTransport{pid: pid}, %DataConsumer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:consume_data, [option]}, timeout_ms)
   end
 
-  def consume_data(%WebRtcTransport{} = transport, option) do
-    consume_data(transport, DataConsumer.Options.from_map(option))
+  def consume_data(%WebRtcTransport{} = transport, option, timeout_ms) do
+    consume_data(transport, DataConsumer.Options.from_map(option), timeout_ms)
   end
 
-  @spec produce(t, Producer.Options.t() | map()) ::
-          {:ok, Producer.t()} | {:error, String.t() | :terminated}
+  @spec produce(t, Producer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, Producer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to receive audio or video RTP (or SRTP depending on the transport class). This is the way to inject media into mediasoup.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-produce
   """
-  def produce(%WebRtcTransport{pid: pid}, %Producer.Options{} = option) do
-    NifWrap.call(pid, {:produce, [option]})
+  def produce(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def produce(%WebRtcTransport{pid: pid}, %Producer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:produce, [option]}, timeout_ms)
   end
 
-  def produce(%WebRtcTransport{} = transport, %{} = option) do
-    produce(transport, Producer.Options.from_map(option))
+  def produce(%WebRtcTransport{} = transport, %{} = option, timeout_ms) do
+    produce(transport, Producer.Options.from_map(option), timeout_ms)
   end
 
-  @spec produce_data(t, DataProducer.Options.t() | map()) ::
-          {:ok, DataProducer.t()} | {:error, String.t() | :terminated}
+  @spec produce_data(t, DataProducer.Options.t() | map(), Mediasoup.timeout_ms()) ::
+          {:ok, DataProducer.t()} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Instructs the router to receive data messages. Those messages can be delivered by an endpoint via SCTP protocol or can be directly sent from the Node.js application if the transport is a DirectTransport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#transport-producedata
   """
-  def produce_data(%WebRtcTransport{pid: pid}, %DataProducer.Options{} = option) do
-    NifWrap.call(pid, {:produce_data, [option]})
+  def produce_data(transport, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def produce_data(%WebRtcTransport{pid: pid}, %DataProducer.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:produce_data, [option]}, timeout_ms)
   end
 
-  def produce_data(%WebRtcTransport{} = transport, %{} = option) do
-    produce_data(transport, DataProducer.Options.from_map(option))
+  def produce_data(%WebRtcTransport{} = transport, %{} = option, timeout_ms) do
+    produce_data(transport, DataProducer.Options.from_map(option), timeout_ms)
   end
 
-  @spec connect(t, connect_option()) :: {:ok} | {:error, String.t() | :terminated}
+  @spec connect(t, connect_option(), Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, String.t() | :terminated | :timeout}
   @doc """
   Provides the WebRTC transport with the endpoint parameters.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-connect
   """
-  def connect(%WebRtcTransport{pid: pid}, option) do
-    NifWrap.call(pid, {:connect, [option]})
+  def connect(%WebRtcTransport{pid: pid}, option, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:connect, [option]}, timeout_ms)
   end
 
   @spec ice_parameters(t) :: ice_parameter() | {:error, :terminated}
@@ -318,27 +440,37 @@ defmodule Mediasoup.WebRtcTransport do
     NifWrap.call(pid, {:ice_candidates, []})
   end
 
-  @spec ice_role(t) :: String.t() | {:error, :terminated}
+  @spec ice_role(t) :: :controlled | :controlling | {:error, :terminated}
 
   @doc """
-  Local ICE role. Due to the mediasoup ICE Lite design, this is always "controlled".
+  Local ICE role. Due to the mediasoup ICE Lite design, this is always :controlled.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-iceRole
   """
   def ice_role(%WebRtcTransport{pid: pid}) do
     NifWrap.call(pid, {:ice_role, []})
   end
 
-  @spec set_max_incoming_bitrate(t, integer) :: {:ok} | {:error, :terminated}
-  def set_max_incoming_bitrate(%WebRtcTransport{pid: pid}, bitrate) do
-    NifWrap.call(pid, {:set_max_incoming_bitrate, [bitrate]})
+  @spec set_max_incoming_bitrate(t, integer, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout}
+  def set_max_incoming_bitrate(
+        %WebRtcTransport{pid: pid},
+        bitrate,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    NifWrap.call(pid, {:set_max_incoming_bitrate, [bitrate]}, timeout_ms)
   end
 
-  @spec set_max_outgoing_bitrate(t, integer) :: {:ok} | {:error, :terminated}
-  def set_max_outgoing_bitrate(%WebRtcTransport{pid: pid}, bitrate) do
-    NifWrap.call(pid, {:set_max_outgoing_bitrate, [bitrate]})
+  @spec set_max_outgoing_bitrate(t, integer, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error, :terminated | :timeout}
+  def set_max_outgoing_bitrate(
+        %WebRtcTransport{pid: pid},
+        bitrate,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    NifWrap.call(pid, {:set_max_outgoing_bitrate, [bitrate]}, timeout_ms)
   end
 
-  @spec ice_state(t) :: String.t() | {:error, :terminated}
+  @spec ice_state(t) :: ice_state() | {:error, :terminated}
 
   @doc """
   Current ICE state.
@@ -348,17 +480,18 @@ defmodule Mediasoup.WebRtcTransport do
     NifWrap.call(pid, {:ice_state, []})
   end
 
-  @spec restart_ice(t) :: {:ok, ice_parameter} | {:error, :terminated}
+  @spec restart_ice(t, Mediasoup.timeout_ms()) ::
+          {:ok, ice_parameter} | {:error, :terminated | :timeout}
 
   @doc """
   Current ICE state.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-iceState
   """
-  def restart_ice(%WebRtcTransport{pid: pid}) do
-    NifWrap.call(pid, {:restart_ice, []})
+  def restart_ice(%WebRtcTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:restart_ice, []}, timeout_ms)
   end
 
-  @spec ice_selected_tuple(t) :: String.t() | nil | {:error, :terminated}
+  @spec ice_selected_tuple(t) :: TransportTuple.t() | nil | {:error, :terminated}
   @doc """
   The selected transport tuple if ICE is in "connected" or "completed" state. It is undefined if ICE is not established (no working candidate pair was found).
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-iceSelectedTuple
@@ -376,7 +509,7 @@ defmodule Mediasoup.WebRtcTransport do
     NifWrap.call(pid, {:dtls_parameters, []})
   end
 
-  @spec dtls_state(t) :: String.t() | {:error, :terminated}
+  @spec dtls_state(t) :: dtls_state() | {:error, :terminated}
   @doc """
   Current DTLS state.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-dtlsState
@@ -385,7 +518,7 @@ defmodule Mediasoup.WebRtcTransport do
     NifWrap.call(pid, {:dtls_state, []})
   end
 
-  @spec sctp_state(t) :: String.t() | {:error, :terminated}
+  @spec sctp_state(t) :: Mediasoup.Transport.sctp_state() | nil | {:error, :terminated}
   @doc """
   Current SCTP state. Or undefined if SCTP is not enabled.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-sctpState
@@ -394,49 +527,82 @@ defmodule Mediasoup.WebRtcTransport do
     NifWrap.call(pid, {:sctp_state, []})
   end
 
-  @type transport_stat :: map
-  @spec get_stats(t) :: list(transport_stat) | {:error, reason :: term()}
+  @type transport_stat :: Mediasoup.Stats.WebRtcTransportStats.t()
+  @spec get_stats(t, Mediasoup.timeout_ms()) :: list(transport_stat) | {:error, reason :: term()}
   @doc """
   Returns current RTC statistics of the WebRTC transport.
   https://mediasoup.org/documentation/v3/mediasoup/api/#webRtcTransport-getStats
   """
-  def get_stats(%WebRtcTransport{pid: pid}) do
-    NifWrap.call(pid, {:get_stats, []})
+  def get_stats(%WebRtcTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:get_stats, []}, timeout_ms)
   end
 
-  @spec dump(t) :: any | {:error, :terminated}
+  @spec stats_subscribe(t, pid, pos_integer(), [Mediasoup.Stats.subscribe_option()]) ::
+          {:ok, reference} | {:error, :terminated}
+  @doc """
+  Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the webrtc transport to
+  `listener` every `interval_ms`, until `Mediasoup.Stats.unsubscribe/1` is called with the
+  returned reference. See `Mediasoup.Stats.Sample`.
+  """
+  def stats_subscribe(%WebRtcTransport{pid: pid}, listener, interval_ms, opts \\ []) do
+    delta = Keyword.get(opts, :delta, false)
+    NifWrap.call(pid, {:stats_subscribe, [listener, interval_ms, delta]})
+  end
+
+  @spec dump(t, Mediasoup.timeout_ms()) :: Dump.t() | {:error, :terminated | :timeout}
   @doc """
   Dump internal stat for WebRtcTransport.
   """
-  def dump(%WebRtcTransport{pid: pid}) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(%WebRtcTransport{pid: pid}, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`. State changes are sent as atoms, e.g. `{:on_ice_state_change, ice_state()}`,
+  and `:on_ice_selected_tuple_change` sends a `TransportTuple.t()`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
+          | :on_owner_down
           | :on_sctp_state_change
           | :on_ice_state_change
           | :on_dtls_state_change
           | :on_ice_selected_tuple_change
 
-  @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
+  @spec event(t, pid, event_types :: [event_type], [Mediasoup.EventListener.option()]) ::
+          {:ok} | {:error, :terminated}
   @doc """
   Starts observing event.
+
+  With `max_queue_len: n` in `opts`, value events are dropped while the mailbox of `listener`
+  holds `n` messages or more, see `Mediasoup.EventListener` and `dropped_events/2`.
   """
   def event(
         transport,
         listener,
         event_types \\ [
           :on_close,
+          :on_owner_down,
           :on_sctp_state_change,
           :on_ice_state_change,
           :on_dtls_state_change,
           :on_ice_selected_tuple_change
-        ]
+        ],
+        opts \\ []
       )
 
-  def event(%WebRtcTransport{pid: pid}, listener, event_types) do
-    NifWrap.call(pid, {:event, listener, event_types})
+  def event(%WebRtcTransport{pid: pid}, listener, event_types, opts) do
+    NifWrap.call(pid, {:event, listener, event_types, opts})
+  end
+
+  @spec dropped_events(t, pid) :: non_neg_integer() | {:error, :terminated}
+  @doc """
+  Number of value events not sent to `listener` because its mailbox was full, see `event/4`.
+  """
+  def dropped_events(%WebRtcTransport{pid: pid}, listener) do
+    NifWrap.call(pid, {:dropped_events, listener})
   end
 
   @spec struct_from_pid(pid()) :: WebRtcTransport.t()
@@ -466,14 +632,18 @@ defmodule Mediasoup.WebRtcTransport do
 
   @impl true
   def handle_call(
-        {:event, listener, event_types},
+        {:event, listener, event_types, opts},
         _from,
         %{listeners: listeners} = state
       ) do
-    listeners = EventListener.add(listeners, listener, event_types)
+    listeners = EventListener.add(listeners, listener, event_types, opts)
     {:reply, {:ok}, %{state | listeners: listeners}}
   end
 
+  def handle_call({:dropped_events, listener}, _from, %{listeners: listeners} = state) do
+    {:reply, EventListener.dropped(listeners, listener), state}
+  end
+
   @impl true
   def handle_call(
         {:struct_from_pid, _arg},
@@ -484,6 +654,8 @@ defmodule Mediasoup.WebRtcTransport do
   end
 
   NifWrap.def_handle_call_nif(%{
+    stats_subscribe: &Nif.webrtc_transport_stats_subscribe/4,
+    monitor_owner: &Nif.webrtc_transport_monitor_owner/2,
     close: &Nif.webrtc_transport_close/1,
     closed?: &Nif.webrtc_transport_closed/1,
     sctp_state: &Nif.webrtc_transport_sctp_state/1,
@@ -498,18 +670,22 @@ defmodule Mediasoup.WebRtcTransport do
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    produce: &Nif.webrtc_transport_produce_async/3,
-    consume: &Nif.webrtc_transport_consume_async/3,
-    produce_data: &Nif.webrtc_transport_produce_data_async/3,
-    consume_data: &Nif.webrtc_transport_consume_data_async/3,
-    connect: &Nif.webrtc_transport_connect_async/3,
-    get_stats: &Nif.webrtc_transport_get_stats_async/2,
-    dump: &Nif.webrtc_transport_dump_async/2,
-    set_max_incoming_bitrate: &Nif.webrtc_transport_set_max_incoming_bitrate_async/3,
-    set_max_outgoing_bitrate: &Nif.webrtc_transport_set_max_outgoing_bitrate_async/3,
-    restart_ice: &Nif.webrtc_transport_restart_ice_async/2
+    produce: &Nif.webrtc_transport_produce_async/4,
+    consume: &Nif.webrtc_transport_consume_async/4,
+    consume_batch: &Nif.webrtc_transport_consume_batch_async/5,
+    produce_data: &Nif.webrtc_transport_produce_data_async/4,
+    consume_data: &Nif.webrtc_transport_consume_data_async/4,
+    connect: &Nif.webrtc_transport_connect_async/4,
+    get_stats: &Nif.webrtc_transport_get_stats_async/3,
+    dump: &Nif.webrtc_transport_dump_async/3,
+    set_max_incoming_bitrate: &Nif.webrtc_transport_set_max_incoming_bitrate_async/4,
+    set_max_outgoing_bitrate: &Nif.webrtc_transport_set_max_outgoing_bitrate_async/4,
+    restart_ice: &Nif.webrtc_transport_restart_ice_async/3
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.webrtc_transport_close_async/3)
+
+  @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {message_tag, from}, result},
         %{supervisor: supervisor} = state
@@ -527,6 +703,23 @@ defmodule Mediasoup.WebRtcTransport do
     {:noreply, state}
   end
 
+  def handle_info(
+        {:mediasoup_async_nif_result, {:consume_batch, from}, result},
+        %{supervisor: supervisor} = state
+      ) do
+    reply =
+      case result do
+        {:ok, results} ->
+          Enum.map(results, &NifWrap.handle_create_result(&1, Consumer, supervisor))
+
+        error ->
+          error
+      end
+
+    GenServer.reply(from, reply)
+    {:noreply, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {unwrap_ok_func, from}, result},
@@ -543,6 +736,11 @@ defmodule Mediasoup.WebRtcTransport do
     {:noreply, state}
   end
 
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   @impl true
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
@@ -561,11 +759,23 @@ defmodule Mediasoup.WebRtcTransport do
     {:noreply, %{state | listeners: listeners}}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   @impl true
   def handle_info({:nif_internal_event, :on_close}, state) do
     {:stop, :normal, state}
   end
 
+  @impl true
+  def handle_info(
+        {:nif_internal_event, :on_owner_down, owner},
+        %{listeners: listeners} = state
+      ) do
+    EventListener.send(listeners, :on_owner_down, {:on_owner_down, owner})
+    {:stop, :shutdown, Map.put(state, :close_reason, :owner_down)}
+  end
+
   @payload_events [
     :on_dtls_state_change,
     :on_ice_state_change,
@@ -583,11 +793,15 @@ defmodule Mediasoup.WebRtcTransport do
   @impl true
   def terminate(
         reason,
-        %{reference: reference, supervisor: supervisor, listeners: listeners} = _state
+        %{reference: reference, supervisor: supervisor, listeners: listeners} = state
       ) do
-    EventListener.send(listeners, :on_close, {:on_close})
+    close_reason = Mediasoup.Utility.close_reason(reason, state)
+    EventListener.send_close(listeners, close_reason)
 
-    Mediasoup.Utility.supervisor_clean_stop(supervisor, reason)
+    Mediasoup.Utility.supervisor_clean_stop(
+      supervisor,
+      Mediasoup.Utility.child_exit_reason(close_reason)
+    )
 
     Nif.webrtc_transport_close(reference)
     :ok
@@ -2,7 +2,7 @@ defmodule Mediasoup.Worker do
   @moduledoc """
   https://mediasoup.org/documentation/v3/mediasoup/api/#Worker
   """
-  alias Mediasoup.{Worker, Router, NifWrap, Nif, WebRtcServer, EventListener}
+  alias Mediasoup.{Worker, Router, NifWrap, Nif, WebRtcServer, EventListener, EventOptions}
   require Mediasoup.NifWrap
   use GenServer
 
@@ -64,6 +64,22 @@ defmodule Mediasoup.Worker do
     end
   end
 
+  defmodule Dump do
+    @moduledoc """
+    Dump of the worker, see `Mediasoup.Worker.dump/1`.
+    """
+
+    defstruct [
+      :router_ids,
+      :webrtc_server_ids
+    ]
+
+    @type t :: %__MODULE__{
+            router_ids: [String.t()],
+            webrtc_server_ids: [String.t()]
+          }
+  end
+
   @type t :: pid
 
   @type log_level :: :debug | :warn | :error | :none
@@ -114,41 +130,60 @@ defmodule Mediasoup.Worker do
     GenServer.stop(pid)
   end
 
-  @spec create_router(t, Router.create_option()) ::
-          {:ok, Router.t()} | {:error} | {:error, :terminated}
+  @spec close_and_wait(t, timeout_ms :: non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  @doc """
+  Closes the worker like `close/1`, and returns once it is closed.
+  """
+  def close_and_wait(pid, timeout_ms \\ 4_000) do
+    NifWrap.close_and_wait(pid, timeout_ms)
+  end
+
+  @spec create_router(t, Router.create_option(), Mediasoup.timeout_ms()) ::
+          {:ok, Router.t()} | {:error} | {:error, :terminated | :timeout}
   @doc """
     Creates a new router.
     https://mediasoup.org/documentation/v3/mediasoup/api/#worker-createRouter
   """
-  def create_router(pid, %Router.Options{} = option) do
-    NifWrap.call(pid, {:create_router, [option]})
+  def create_router(worker, option, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def create_router(pid, %Router.Options{} = option, timeout_ms) do
+    NifWrap.call(pid, {:create_router, [option]}, timeout_ms)
   end
 
-  def create_router(worker, option) do
-    create_router(worker, Router.Options.from_map(option))
+  def create_router(worker, option, timeout_ms) do
+    create_router(worker, Router.Options.from_map(option), timeout_ms)
   end
 
-  @spec create_webrtc_server(t(), Mediasoup.WebRtcServer.Options.t()) ::
-          {:ok, WebRtcServer.t()} | {:error, :terminated}
+  @spec create_webrtc_server(t(), Mediasoup.WebRtcServer.Options.t(), Mediasoup.timeout_ms()) ::
+          {:ok, WebRtcServer.t()} | {:error, :terminated | :timeout}
   @doc """
     Creates a new WebRTC server.
     https://mediasoup.org/documentation/v3/mediasoup/api/#worker-createWebRtcServer
   """
-  def create_webrtc_server(pid, %WebRtcServer.Options{} = option) do
-    NifWrap.call(pid, {:create_webrtc_server, [WebRtcServer.Options.normalize(option)]})
+  def create_webrtc_server(
+        pid,
+        %WebRtcServer.Options{} = option,
+        timeout_ms \\ NifWrap.async_nif_timeout_ms()
+      ) do
+    option = WebRtcServer.Options.normalize(option)
+    NifWrap.call(pid, {:create_webrtc_server, [option]}, timeout_ms)
   end
 
-  @spec update_settings(t, update_option) :: {:ok} | {:error} | {:error, :terminated}
+  @spec update_settings(t, update_option, Mediasoup.timeout_ms()) ::
+          {:ok} | {:error} | {:error, :terminated | :timeout}
   @doc """
     Updates the worker settings in runtime. Just a subset of the worker settings can be updated.
     https://mediasoup.org/documentation/v3/mediasoup/api/#worker-updateSettings
   """
-  def update_settings(pid, %UpdateableSettings{} = settings) do
-    NifWrap.call(pid, {:update_settings, [settings]})
+  def update_settings(worker, settings, timeout_ms \\ NifWrap.async_nif_timeout_ms())
+
+  def update_settings(pid, %UpdateableSettings{} = settings, timeout_ms) do
+    NifWrap.call(pid, {:update_settings, [settings]}, timeout_ms)
   end
 
-  def update_settings(worker, settings) do
-    update_settings(worker, UpdateableSettings.from_map(settings))
+  def update_settings(worker, settings, timeout_ms) do
+    update_settings(worker, UpdateableSettings.from_map(settings), timeout_ms)
   end
 
   @spec closed?(t) :: boolean
@@ -159,16 +194,32 @@ defmodule Mediasoup.Worker do
     !Process.alive?(pid)
   end
 
-  @spec dump(t) :: map | {:error, :terminated}
+  @spec dump(t, Mediasoup.timeout_ms()) :: Dump.t() | {:error, :terminated | :timeout}
   @doc """
   Dump internal stat for Worker.
   """
-  def dump(pid) do
-    NifWrap.call(pid, {:dump, []})
+  def dump(pid, timeout_ms \\ NifWrap.async_nif_timeout_ms()) do
+    NifWrap.call(pid, {:dump, []}, timeout_ms)
   end
 
+  @spec set_log_sink(t, pid | nil, Mediasoup.LoggerProxy.SinkOptions.t()) ::
+          {:ok} | {:error, :terminated}
+  @doc """
+  Sends the log records of this worker to `listener` as `Mediasoup.LoggerProxy.Record`s,
+  in addition to the other logger sinks. Records reach it only up to the worker `logLevel`.
+  `nil` removes the sink; it is also removed when the worker closes.
+  """
+  def set_log_sink(pid, listener, options \\ %Mediasoup.LoggerProxy.SinkOptions{}) do
+    NifWrap.call(pid, {:set_log_sink, [listener, options]})
+  end
+
+  @typedoc """
+  `:on_close_reason` sends `{:on_close_reason, Mediasoup.Utility.close_reason()}` right before
+  `{:on_close}`.
+  """
   @type event_type ::
           :on_close
+          | :on_close_reason
           | :on_dead
   @spec event(t, pid, event_types :: [event_type]) :: {:ok} | {:error, :terminated}
   @doc """
@@ -206,23 +257,36 @@ defmodule Mediasoup.Worker do
       Registry.register(Mediasoup.Worker.Registry, :id, Nif.worker_id(worker))
     end
 
-    {:ok} = Nif.worker_event(worker, self(), [:on_close, :on_dead])
+    {:ok, subscription} =
+      Nif.worker_event(worker, self(), [:on_close, :on_close_reason, :on_dead], %EventOptions{
+        batch: true
+      })
 
     {:ok, supervisor} = DynamicSupervisor.start_link(strategy: :one_for_one)
-    {:ok, %{reference: worker, supervisor: supervisor, listeners: EventListener.new()}}
+
+    {:ok,
+     %{
+       reference: worker,
+       supervisor: supervisor,
+       listeners: EventListener.new(),
+       subscription: subscription
+     }}
   end
 
   NifWrap.def_handle_call_nif(%{
-    id: &Nif.worker_id/1
+    id: &Nif.worker_id/1,
+    set_log_sink: &Nif.worker_set_log_sink/3
   })
 
   NifWrap.def_handle_call_async_nif(%{
-    update_settings: &Nif.worker_update_settings_async/3,
-    create_router: &Nif.worker_create_router_async/3,
-    create_webrtc_server: &Nif.worker_create_webrtc_server_async/3,
-    dump: &Nif.worker_dump_async/2
+    update_settings: &Nif.worker_update_settings_async/4,
+    create_router: &Nif.worker_create_router_async/4,
+    create_webrtc_server: &Nif.worker_create_webrtc_server_async/4,
+    dump: &Nif.worker_dump_async/3
   })
 
+  NifWrap.def_handle_call_close_and_wait(&Nif.worker_close_async/3)
+
   def handle_info(
         {:mediasoup_async_nif_result, {:create_router, from}, result},
         %{supervisor: supervisor} = state
@@ -248,6 +312,11 @@ defmodule Mediasoup.Worker do
     {:noreply, state}
   end
 
+  def handle_info({:mediasoup_async_nif_result, {:close_and_wait, from}, result}, state) do
+    GenServer.reply(from, result |> Nif.unwrap_ok())
+    {:stop, :normal, state}
+  end
+
   def handle_info(
         {:mediasoup_async_nif_result, {_, from}, result},
         state
@@ -256,6 +325,9 @@ defmodule Mediasoup.Worker do
     {:noreply, state}
   end
 
+  NifWrap.def_handle_info_event_batch()
+  NifWrap.def_handle_info_close_events()
+
   def handle_info(
         {:nif_internal_event, :on_close},
         state
@@ -268,7 +340,7 @@ defmodule Mediasoup.Worker do
         %{listeners: listeners} = state
       ) do
     EventListener.send(listeners, :on_dead, {:on_dead, message})
-    {:stop, :shutdown, state}
+    {:stop, :shutdown, Map.put(state, :close_reason, :worker_died)}
   end
 
   def handle_info(
@@ -300,11 +372,15 @@ defmodule Mediasoup.Worker do
 
   def terminate(
         reason,
-        %{reference: reference, supervisor: supervisor, listeners: listeners} = _state
+        %{reference: reference, supervisor: supervisor, listeners: listeners} = state
       ) do
-    EventListener.send(listeners, :on_close, {:on_close})
+    close_reason = Mediasoup.Utility.close_reason(reason, state)
+    EventListener.send_close(listeners, close_reason)
 
-    Mediasoup.Utility.supervisor_clean_stop(supervisor, reason)
+    Mediasoup.Utility.supervisor_clean_stop(
+      supervisor,
+      Mediasoup.Utility.child_exit_reason(close_reason)
+    )
     Nif.worker_close(reference)
     :ok
   end
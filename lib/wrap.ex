@@ -5,6 +5,23 @@ defmodule Mediasoup.NifWrap do
 
   # Utilities for wrap Nif
 
+  @async_nif_timeout_ms 5_000
+
+  @doc """
+  Default timeout of the async NIFs of `def_handle_call_async_nif/1`, for the wrappers whose
+  `timeout_ms` is omitted.
+  """
+  @spec async_nif_timeout_ms() :: non_neg_integer()
+  def async_nif_timeout_ms(), do: @async_nif_timeout_ms
+
+  @doc """
+  `GenServer.call/3` timeout for an async NIF given `timeout_ms`. It is a second longer, so the
+  caller receives `{:error, :timeout}` from the NIF rather than giving up first.
+  """
+  @spec call_timeout(Mediasoup.timeout_ms()) :: timeout()
+  def call_timeout(nil), do: :infinity
+  def call_timeout(timeout_ms), do: timeout_ms + 1_000
+
   @spec def_handle_call_nif(any) ::
           {:__block__, [], [{:@, [...], [...]} | {:def, [...], [...]}, ...]}
   defmacro def_handle_call_nif(nif_call_map) do
@@ -36,13 +53,14 @@ defmodule Mediasoup.NifWrap do
       @nif_keylist Map.keys(@nif_map)
 
       def handle_call(
-            {function, arg},
+            {function, arg, timeout_ms},
             from,
             %{reference: reference} = state
           )
           when function in @nif_keylist do
         try do
-          result = apply(Map.fetch!(@nif_map, function), [reference | arg] ++ [{function, from}])
+          args = [reference | arg] ++ [{function, from}, timeout_ms]
+          result = apply(Map.fetch!(@nif_map, function), args)
           {:noreply, state}
         rescue
           e -> {:reply, {:raise_error, e}, state}
@@ -51,6 +69,62 @@ defmodule Mediasoup.NifWrap do
     end
   end
 
+  @doc """
+  Defines the `close_and_wait` call, see `close_and_wait/2`. The process stops once it has
+  replied with the result of `close_async_nif`, which it receives as
+  `{:mediasoup_async_nif_result, {:close_and_wait, from}, result}`.
+
+  Children hold their parent open, so the processes of the children under `state.supervisor`
+  are stopped first.
+  """
+  defmacro def_handle_call_close_and_wait(close_async_nif) do
+    quote do
+      def handle_call({:close_and_wait, [timeout_ms]}, from, %{reference: reference} = state) do
+        with %{supervisor: supervisor} <- state do
+          Mediasoup.Utility.supervisor_stop_children(supervisor, {:shutdown, :parent_closed})
+        end
+
+        unquote(close_async_nif).(reference, {:close_and_wait, from}, timeout_ms)
+        {:noreply, Map.merge(state, %{closing: true, close_reason: :explicit})}
+      end
+    end
+  end
+
+  @doc """
+  Defines the `handle_info/2` clauses for the close events of the native resource: the close
+  reason is kept for `terminate/2`, and `:on_close` is ignored while `close_and_wait` waits for
+  its result.
+  """
+  defmacro def_handle_info_close_events() do
+    quote do
+      def handle_info({:nif_internal_event, :on_close_reason, reason}, state) do
+        {:noreply, Map.put_new(state, :close_reason, reason)}
+      end
+
+      def handle_info({:nif_internal_event, :on_close}, %{closing: true} = state) do
+        {:noreply, state}
+      end
+    end
+  end
+
+  @doc """
+  Defines a `handle_info/2` clause that unpacks batched nif events
+  and handles them one by one, in order. Batches are only sent to subscriptions made with
+  `%Mediasoup.EventOptions{batch: true}`.
+  """
+  defmacro def_handle_info_event_batch() do
+    quote do
+      def handle_info({:nif_internal_event_batch, events}, state) do
+        Enum.reduce_while(events, {:noreply, state}, fn event, {:noreply, state} ->
+          case handle_info(event, state) do
+            {:noreply, state} -> {:cont, {:noreply, state}}
+            result -> {:halt, result}
+          end
+        end)
+      end
+    end
+  end
+
   defmacro call(pid, args) do
     quote do
       try do
@@ -65,12 +139,47 @@ defmodule Mediasoup.NifWrap do
     end
   end
 
-  def handle_create_result(create_result, module, supervisor) do
+  @doc """
+  Calls an async NIF of `def_handle_call_async_nif/1` with `timeout_ms`, see `call_timeout/1`.
+  """
+  defmacro call(pid, {function, arg}, timeout_ms) do
+    quote do
+      timeout_ms = unquote(timeout_ms)
+
+      try do
+        case GenServer.call(
+               unquote(pid),
+               {unquote(function), unquote(arg), timeout_ms},
+               Mediasoup.NifWrap.call_timeout(timeout_ms)
+             ) do
+          {:raise_error, e} -> raise e
+          {:error} -> :error
+          result -> result
+        end
+      catch
+        :exit, _ -> {:error, :terminated}
+      end
+    end
+  end
+
+  @doc """
+  Closes the resource hosted by `pid` and waits until the worker has confirmed it, for at most
+  `timeout_ms`. See `def_handle_call_close_and_wait/1`.
+  """
+  @spec close_and_wait(pid, non_neg_integer()) ::
+          {:ok} | {:error, :timeout | :terminated | String.t()}
+  def close_and_wait(pid, timeout_ms) do
+    GenServer.call(pid, {:close_and_wait, [timeout_ms]}, timeout_ms + 1_000)
+  catch
+    :exit, _ -> {:error, :terminated}
+  end
+
+  def handle_create_result(create_result, module, supervisor, opts \\ []) do
     with {:ok, ref} <- create_result,
          {:ok, pid} <-
            DynamicSupervisor.start_child(
              supervisor,
-             {module, [reference: ref]}
+             {module, [reference: ref] ++ opts}
            ) do
       {:ok, module.struct_from_pid_and_ref(pid, ref)}
     else
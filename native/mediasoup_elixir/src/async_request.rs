@@ -0,0 +1,60 @@
+use crate::atoms;
+use rustler::{Encoder, Env, NifResult, ResourceArc, Term};
+use std::sync::Mutex;
+
+enum RequestState {
+    Pending,
+    Cancelled,
+    Completed,
+}
+
+/// A request started by an async NIF without a `from` term.
+/// The reference is returned to the caller and included in the reply, so concurrent requests
+/// can be told apart.
+pub struct AsyncRequestRef {
+    state: Mutex<RequestState>,
+}
+#[rustler::resource_impl]
+impl rustler::Resource for AsyncRequestRef {}
+
+impl AsyncRequestRef {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(RequestState::Pending),
+        }
+    }
+
+    /// Marks the request as completed. Returns false if the request was cancelled,
+    /// in which case the result must be dropped instead of replied.
+    pub fn complete(&self) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        match *state {
+            RequestState::Pending => {
+                *state = RequestState::Completed;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Cancels a pending request. Its result is dropped when it arrives and no reply is sent;
+/// a created worker is closed.
+#[rustler::nif]
+pub fn async_request_cancel(env: Env, request: ResourceArc<AsyncRequestRef>) -> NifResult<Term> {
+    let mut state = request
+        .state
+        .lock()
+        .map_err(|_| rustler::Error::Term(Box::new(atoms::poison_error())))?;
+    let result = match *state {
+        RequestState::Pending => {
+            *state = RequestState::Cancelled;
+            (atoms::ok(),).encode(env)
+        }
+        RequestState::Cancelled => (atoms::error(), atoms::cancelled()).encode(env),
+        RequestState::Completed => (atoms::error(), atoms::completed()).encode(env),
+    };
+    Ok(result)
+}
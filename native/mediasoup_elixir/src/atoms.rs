@@ -25,8 +25,44 @@ rustler::atoms! {
     on_producer_close,
     on_transport_close,
     on_layers_change,
+    on_owner_down,
+    on_close_reason,
+    explicit,
+    parent_closed,
+    worker_died,
+    owner_down,
     audio,
     video,
     mediasoup_async_nif_result,
     nif_internal_event,
+    nif_internal_event_batch,
+    already_started,
+    cancelled,
+    completed,
+    timeout,
+    panicked,
+    not_found,
+    on_stats,
+    on_stats_end,
+    id,
+    entity,
+    delta,
+    elapsed_ms,
+    stats,
+    new,
+    connecting,
+    connected,
+    disconnected,
+    failed,
+    closed,
+    controlled,
+    controlling,
+    tcp,
+    udp,
+    simple,
+    simulcast,
+    svc,
+    pipe,
+    sctp,
+    direct,
 }
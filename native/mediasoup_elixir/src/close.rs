@@ -0,0 +1,210 @@
+//! Why entities close, and closes confirmed by the worker.
+//!
+//! Dropping the last handle of a mediasoup object sends its close request to the worker without
+//! waiting for the response. `confirm` waits until the object is closed and then asks the dump
+//! of its `Lister` until the object is no longer in it.
+
+use crate::entity::{Entity, EntityKind, Lister};
+use crate::event_dispatcher;
+use crate::subscription::EventSink;
+use crate::{atoms, task};
+use event_listener_primitives::HandlerId;
+use mediasoup::prelude::*;
+use mediasoup::worker::{RequestError, WorkerId};
+use once_cell::sync::Lazy;
+use rustler::Atom;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const CONFIRM_INTERVAL: Duration = Duration::from_millis(10);
+const CONFIRM_ATTEMPTS: u32 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    /// Closed through one of its resources, or when its last handle was dropped.
+    Explicit,
+    ParentClosed,
+    WorkerDied,
+    OwnerDown,
+}
+
+impl CloseReason {
+    pub fn atom(self) -> Atom {
+        match self {
+            CloseReason::Explicit => atoms::explicit(),
+            CloseReason::ParentClosed => atoms::parent_closed(),
+            CloseReason::WorkerDied => atoms::worker_died(),
+            CloseReason::OwnerDown => atoms::owner_down(),
+        }
+    }
+}
+
+/// The reason an entity closed, shared by its resources. The first reason set is kept.
+#[derive(Default)]
+pub struct CloseReasonCell(Mutex<Option<CloseReason>>);
+
+impl CloseReasonCell {
+    pub fn set(&self, reason: CloseReason) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(reason);
+    }
+
+    pub fn get(&self) -> CloseReason {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .unwrap_or(CloseReason::Explicit)
+    }
+}
+
+/// Workers that died and are closing their routers. The closes of the children run while the
+/// worker closes, so the worker is removed afterwards.
+static DEAD_WORKERS: Lazy<Mutex<HashSet<WorkerId>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn dead_workers() -> std::sync::MutexGuard<'static, HashSet<WorkerId>> {
+    DEAD_WORKERS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn track_worker(worker: &Worker) {
+    let id = worker.id();
+    worker
+        .on_dead(move |_| {
+            dead_workers().insert(id);
+        })
+        .detach();
+    worker
+        .on_close(move || {
+            task::spawn(async move {
+                dead_workers().remove(&id);
+            })
+            .detach();
+        })
+        .detach();
+}
+
+/// Sets the reason in `cell` when `entity` closes with its parent.
+pub fn watch<T: Entity>(entity: &T, cell: &Arc<CloseReasonCell>) {
+    let worker_id = entity.lister().map(|lister| lister.worker_id());
+    let cell = Arc::clone(cell);
+    entity.on_parent_close_detached(move || {
+        let worker_died = match worker_id {
+            Some(worker_id) => dead_workers().contains(&worker_id),
+            None => true,
+        };
+        cell.set(if worker_died {
+            CloseReason::WorkerDied
+        } else {
+            CloseReason::ParentClosed
+        });
+    });
+}
+
+/// Sends `{:nif_internal_event, :on_close_reason, reason}` then `{:nif_internal_event, :on_close}`
+/// when `entity` closes, for the ones of the two in `event_types`.
+pub fn reg_close_events<T: Entity>(
+    sink: &Arc<EventSink>,
+    entity: &T,
+    cell: Arc<CloseReasonCell>,
+    event_types: &[Atom],
+) -> Option<HandlerId> {
+    let on_close = event_types.contains(&atoms::on_close());
+    let on_close_reason = event_types.contains(&atoms::on_close_reason());
+    if !on_close && !on_close_reason {
+        return None;
+    }
+    let sink = sink.clone();
+    Some(entity.add_close_handler(move || {
+        if on_close_reason {
+            sink.send_payload(atoms::on_close_reason(), cell.get().atom());
+        }
+        if on_close {
+            sink.send(atoms::on_close());
+        }
+    }))
+}
+
+fn sleep(duration: Duration) -> impl Future<Output = ()> {
+    let (mut sender, receiver) = async_oneshot::oneshot();
+    event_dispatcher::schedule(Instant::now() + duration, move || {
+        let _ = sender.send(());
+    });
+    async move {
+        let _ = receiver.await;
+    }
+}
+
+fn contains<Id: Display>(ids: impl IntoIterator<Item = Id>, id: &str) -> bool {
+    ids.into_iter().any(|listed| listed.to_string() == id)
+}
+
+impl Lister {
+    fn closed(&self) -> bool {
+        match self {
+            Lister::Worker(worker) => worker.closed(),
+            Lister::Router(router) => router.closed(),
+        }
+    }
+
+    async fn lists(&self, kind: EntityKind, id: &str) -> Result<bool, RequestError> {
+        Ok(match self {
+            Lister::Worker(worker) => {
+                let dump = worker.dump().await?;
+                match kind {
+                    EntityKind::WebRtcServer => contains(dump.webrtc_server_ids, id),
+                    _ => contains(dump.router_ids, id),
+                }
+            }
+            Lister::Router(router) => {
+                let dump = router.dump().await?;
+                match kind {
+                    EntityKind::Producer => contains(dump.map_producer_id_consumer_ids.keys(), id),
+                    EntityKind::Consumer => contains(dump.map_consumer_id_producer_id.keys(), id),
+                    EntityKind::DataProducer => {
+                        contains(dump.map_data_producer_id_data_consumer_ids.keys(), id)
+                    }
+                    EntityKind::DataConsumer => {
+                        contains(dump.map_data_consumer_id_data_producer_id.keys(), id)
+                    }
+                    _ => contains(dump.transport_ids, id),
+                }
+            }
+        })
+    }
+}
+
+/// Resolves once `entity` is closed, which is when its last handle is dropped, and the worker
+/// no longer lists it. A worker has no lister: it resolves once the worker is closed.
+pub fn confirm<T: Entity>(entity: &T) -> impl Future<Output = Result<(), String>> + Send {
+    let (mut sender, closed) = async_oneshot::oneshot();
+    let handler = entity.add_close_handler(move || {
+        let _ = sender.send(());
+    });
+    let id = entity.id();
+    let lister = entity.lister();
+
+    async move {
+        let _ = closed.await;
+        drop(handler);
+        let Some(lister) = lister else {
+            return Ok(());
+        };
+        for _ in 0..CONFIRM_ATTEMPTS {
+            // Closing the lister closes the entity too.
+            if lister.closed() {
+                return Ok(());
+            }
+            match lister.lists(T::KIND, &id).await {
+                Ok(false) => return Ok(()),
+                Ok(true) => sleep(CONFIRM_INTERVAL).await,
+                Err(_) if lister.closed() => return Ok(()),
+                Err(error) => return Err(format!("{}", error)),
+            }
+        }
+        Err(format!("close of {} {} not confirmed", T::KIND.name(), id))
+    }
+}
@@ -1,50 +1,49 @@
-use crate::json_serde::JsonSerdeWrap;
-use crate::{
-    atoms, send_async_nif_result_with_from, send_msg_from_other_thread, DisposableResourceWrapper,
-};
-use mediasoup::consumer::{
-    Consumer, ConsumerId, ConsumerLayers, ConsumerOptions, ConsumerScore, ConsumerType,
-};
-use mediasoup::prelude::{MediaKind, RtpCapabilities, RtpParameters};
+use crate::close;
+use crate::json_serde::{AsAtom, JsonSerdeWrap};
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::{atoms, dump, send_async_nif_result_with_from, stats, task, DisposableResourceWrapper};
+use mediasoup::consumer::{Consumer, ConsumerId, ConsumerLayers, ConsumerOptions, ConsumerScore};
+use mediasoup::prelude::{RtpCapabilities, RtpParameters, Transport};
 use mediasoup::producer::ProducerId;
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Encoder, Env, NifResult, ResourceArc, Term};
+use std::future::Future;
 
 pub type ConsumerRef = DisposableResourceWrapper<Consumer>;
 #[rustler::resource_impl]
-impl rustler::Resource for ConsumerRef {}
+impl rustler::Resource for ConsumerRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn consumer_id(consumer: ResourceArc<ConsumerRef>) -> NifResult<JsonSerdeWrap<ConsumerId>> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.id().into())
+    consumer.with_resource(|consumer| consumer.id().into())
 }
 
 #[rustler::nif]
 pub fn consumer_producer_id(
     consumer: ResourceArc<ConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<ProducerId>> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.producer_id().into())
+    consumer.with_resource(|consumer| consumer.producer_id().into())
 }
 
 #[rustler::nif]
-pub fn consumer_kind(consumer: ResourceArc<ConsumerRef>) -> NifResult<JsonSerdeWrap<MediaKind>> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.kind().into())
+pub fn consumer_kind(consumer: ResourceArc<ConsumerRef>) -> NifResult<Atom> {
+    consumer.with_resource(|consumer| consumer.kind().as_atom())
 }
 
 #[rustler::nif]
-pub fn consumer_type(consumer: ResourceArc<ConsumerRef>) -> NifResult<JsonSerdeWrap<ConsumerType>> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.r#type().into())
+pub fn consumer_type(consumer: ResourceArc<ConsumerRef>) -> NifResult<Atom> {
+    consumer.with_resource(|consumer| consumer.r#type().as_atom())
 }
 
 #[rustler::nif]
 pub fn consumer_rtp_parameters(
     consumer: ResourceArc<ConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<RtpParameters>> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.rtp_parameters().clone().into())
+    consumer.with_resource(|consumer| consumer.rtp_parameters().clone().into())
 }
 
 #[rustler::nif]
@@ -52,6 +51,29 @@ pub fn consumer_close(consumer: ResourceArc<ConsumerRef>) -> NifResult<(Atom,)>
     consumer.close();
     Ok((atoms::ok(),))
 }
+
+/// Closes the consumer and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "consumer_close_async")]
+pub fn consumer_close_confirmed(
+    env: Env,
+    consumer: ResourceArc<ConsumerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = consumer.close_confirmed()?;
+    send_async_nif_result_with_from(env, "consumer_close_async", from, timeout_ms, confirmed)
+}
+
+/// Closes the consumer when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn consumer_monitor_owner(
+    env: Env,
+    consumer: ResourceArc<ConsumerRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&consumer, env, owner)?;
+    Ok((atoms::ok(),))
+}
 #[rustler::nif]
 pub fn consumer_closed(consumer: ResourceArc<ConsumerRef>) -> NifResult<bool> {
     match consumer.get_resource() {
@@ -62,40 +84,34 @@ pub fn consumer_closed(consumer: ResourceArc<ConsumerRef>) -> NifResult<bool> {
 
 #[rustler::nif]
 pub fn consumer_paused(consumer: ResourceArc<ConsumerRef>) -> NifResult<bool> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.paused())
+    consumer.with_resource(|consumer| consumer.paused())
 }
 
 #[rustler::nif]
 pub fn consumer_producer_paused(consumer: ResourceArc<ConsumerRef>) -> NifResult<bool> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.producer_paused())
+    consumer.with_resource(|consumer| consumer.producer_paused())
 }
 #[rustler::nif]
 pub fn consumer_priority(consumer: ResourceArc<ConsumerRef>) -> NifResult<u8> {
-    let consumer = consumer.get_resource()?;
-    Ok(consumer.priority())
+    consumer.with_resource(|consumer| consumer.priority())
 }
 #[rustler::nif]
 pub fn consumer_score(
     consumer: ResourceArc<ConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<ConsumerScore>> {
-    let consumer = consumer.get_resource()?;
-    Ok(JsonSerdeWrap::new(consumer.score()))
+    consumer.with_resource(|consumer| JsonSerdeWrap::new(consumer.score()))
 }
 #[rustler::nif]
 pub fn consumer_preferred_layers(
     consumer: ResourceArc<ConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<Option<ConsumerLayers>>> {
-    let consumer = consumer.get_resource()?;
-    Ok(JsonSerdeWrap::new(consumer.preferred_layers()))
+    consumer.with_resource(|consumer| JsonSerdeWrap::new(consumer.preferred_layers()))
 }
 #[rustler::nif]
 pub fn consumer_current_layers(
     consumer: ResourceArc<ConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<Option<ConsumerLayers>>> {
-    let consumer = consumer.get_resource()?;
-    Ok(JsonSerdeWrap::new(consumer.current_layers()))
+    consumer.with_resource(|consumer| JsonSerdeWrap::new(consumer.current_layers()))
 }
 
 #[rustler::nif(name = "consumer_get_stats_async")]
@@ -103,22 +119,45 @@ pub fn consumer_get_stats(
     env: Env,
     consumer: ResourceArc<ConsumerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        consumer
-            .get_stats()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "consumer_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            consumer
+                .get_stats()
+                .await
+                .map(|list| stats::consumer_stats(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn consumer_stats_subscribe(
+    consumer: ResourceArc<ConsumerRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&consumer, pid, interval_ms, delta)
 }
 #[rustler::nif(name = "consumer_pause_async")]
-pub fn consumer_pause(env: Env, consumer: ResourceArc<ConsumerRef>, from: Term) -> NifResult<Atom> {
+pub fn consumer_pause(
+    env: Env,
+    consumer: ResourceArc<ConsumerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "consumer_pause_async", from, timeout_ms, async move {
         consumer.pause().await.map_err(|error| format!("{}", error))
     })
 }
@@ -127,10 +166,11 @@ pub fn consumer_resume(
     env: Env,
     consumer: ResourceArc<ConsumerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "consumer_resume_async", from, timeout_ms, async move {
         consumer
             .resume()
             .await
@@ -144,15 +184,22 @@ pub fn consumer_set_preferred_layers(
     consumer: ResourceArc<ConsumerRef>,
     layer: JsonSerdeWrap<ConsumerLayers>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        consumer
-            .set_preferred_layers(*layer)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "consumer_set_preferred_layers_async",
+        from,
+        timeout_ms,
+        async move {
+            consumer
+                .set_preferred_layers(*layer)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "consumer_set_priority_async")]
@@ -161,30 +208,44 @@ pub fn consumer_set_priority(
     consumer: ResourceArc<ConsumerRef>,
     priority: u8,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        consumer
-            .set_priority(priority)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "consumer_set_priority_async",
+        from,
+        timeout_ms,
+        async move {
+            consumer
+                .set_priority(priority)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 #[rustler::nif(name = "consumer_unset_priority_async")]
 pub fn consumer_unset_priority(
     env: Env,
     consumer: ResourceArc<ConsumerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        consumer
-            .unset_priority()
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "consumer_unset_priority_async",
+        from,
+        timeout_ms,
+        async move {
+            consumer
+                .unset_priority()
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "consumer_request_key_frame_async")]
@@ -192,26 +253,175 @@ pub fn consumer_request_key_frame(
     env: Env,
     consumer: ResourceArc<ConsumerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        consumer
-            .request_key_frame()
+    send_async_nif_result_with_from(
+        env,
+        "consumer_request_key_frame_async",
+        from,
+        timeout_ms,
+        async move {
+            consumer
+                .request_key_frame()
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Why an operation of a `consume_all` NIF failed on one consumer.
+enum BulkError {
+    Terminated,
+    Failed(String),
+}
+
+impl Encoder for BulkError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            BulkError::Terminated => atoms::terminated().encode(env),
+            BulkError::Failed(reason) => reason.encode(env),
+        }
+    }
+}
+
+/// Runs `operation` on each of `consumers` concurrently, and resolves to their results in order.
+fn consume_all<F, Fut, E>(
+    consumers: &[ResourceArc<ConsumerRef>],
+    operation: F,
+) -> impl Future<Output = Vec<Result<(), BulkError>>>
+where
+    F: Fn(Consumer) -> Fut,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: std::fmt::Display,
+{
+    let futures: Vec<_> = consumers
+        .iter()
+        .map(|consumer| {
+            let operation = consumer.get_resource().ok().map(&operation);
+            async move {
+                match operation {
+                    Some(operation) => operation
+                        .await
+                        .map_err(|error| BulkError::Failed(format!("{}", error))),
+                    None => Err(BulkError::Terminated),
+                }
+            }
+        })
+        .collect();
+    let results = task::join_all(futures);
+    async move {
+        results
             .await
-            .map_err(|error| format!("{}", error))
-    })
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(BulkError::Failed("panicked".into()))))
+            .collect()
+    }
+}
+
+#[rustler::nif(name = "consumer_pause_all_async")]
+pub fn consumer_pause_all(
+    env: Env,
+    consumers: Vec<ResourceArc<ConsumerRef>>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let results = consume_all(
+        &consumers,
+        move |consumer| async move { consumer.pause().await },
+    );
+    send_async_nif_result_with_from(env, "consumer_pause_all_async", from, timeout_ms, results)
+}
+
+#[rustler::nif(name = "consumer_resume_all_async")]
+pub fn consumer_resume_all(
+    env: Env,
+    consumers: Vec<ResourceArc<ConsumerRef>>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let results = consume_all(
+        &consumers,
+        |consumer| async move { consumer.resume().await },
+    );
+    send_async_nif_result_with_from(env, "consumer_resume_all_async", from, timeout_ms, results)
+}
+
+#[rustler::nif(name = "consumer_set_priority_all_async")]
+pub fn consumer_set_priority_all(
+    env: Env,
+    consumers: Vec<ResourceArc<ConsumerRef>>,
+    priority: u8,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let results = consume_all(&consumers, move |consumer| async move {
+        consumer.set_priority(priority).await
+    });
+    send_async_nif_result_with_from(
+        env,
+        "consumer_set_priority_all_async",
+        from,
+        timeout_ms,
+        results,
+    )
+}
+
+#[rustler::nif(name = "consumer_set_preferred_layers_all_async")]
+pub fn consumer_set_preferred_layers_all(
+    env: Env,
+    consumers: Vec<ResourceArc<ConsumerRef>>,
+    layers: JsonSerdeWrap<ConsumerLayers>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let layers = *layers;
+    let results = consume_all(&consumers, move |consumer| async move {
+        consumer.set_preferred_layers(layers).await
+    });
+    send_async_nif_result_with_from(
+        env,
+        "consumer_set_preferred_layers_all_async",
+        from,
+        timeout_ms,
+        results,
+    )
+}
+
+#[rustler::nif(name = "consumer_request_key_frame_all_async")]
+pub fn consumer_request_key_frame_all(
+    env: Env,
+    consumers: Vec<ResourceArc<ConsumerRef>>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let results = consume_all(&consumers, move |consumer| async move {
+        consumer.request_key_frame().await
+    });
+    send_async_nif_result_with_from(
+        env,
+        "consumer_request_key_frame_all_async",
+        from,
+        timeout_ms,
+        results,
+    )
 }
 
 #[rustler::nif(name = "consumer_dump_async")]
-pub fn consumer_dump(env: Env, consumer: ResourceArc<ConsumerRef>, from: Term) -> NifResult<Atom> {
+pub fn consumer_dump(
+    env: Env,
+    consumer: ResourceArc<ConsumerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let consumer = consumer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "consumer_dump_async", from, timeout_ms, async move {
         consumer
             .dump()
             .await
-            .map(JsonSerdeWrap::new)
+            .map(dump::ConsumerDumpStruct::from)
             .map_err(|error| format!("{}", error))
     })
 }
@@ -221,68 +431,59 @@ pub fn consumer_event(
     consumer: ResourceArc<ConsumerRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = consumer.close_reason();
     let consumer = consumer.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, consumer, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &consumer,
+        close_reason,
+        &event_types,
+    ));
     if event_types.contains(&atoms::on_pause()) {
-        crate::reg_callback!(pid, consumer, on_pause);
+        handlers.push(crate::reg_callback!(sink, consumer, on_pause));
     }
     if event_types.contains(&atoms::on_resume()) {
-        crate::reg_callback!(pid, consumer, on_resume);
+        handlers.push(crate::reg_callback!(sink, consumer, on_resume));
     }
     if event_types.contains(&atoms::on_producer_pause()) {
-        crate::reg_callback!(pid, consumer, on_producer_pause);
+        handlers.push(crate::reg_callback!(sink, consumer, on_producer_pause));
     }
     if event_types.contains(&atoms::on_producer_resume()) {
-        crate::reg_callback!(pid, consumer, on_producer_resume);
+        handlers.push(crate::reg_callback!(sink, consumer, on_producer_resume));
     }
 
     if event_types.contains(&atoms::on_producer_close()) {
-        crate::reg_callback!(pid, consumer, on_producer_close);
+        handlers.push(crate::reg_callback!(sink, consumer, on_producer_close));
     }
 
     if event_types.contains(&atoms::on_transport_close()) {
-        crate::reg_callback!(pid, consumer, on_transport_close);
+        handlers.push(crate::reg_callback!(sink, consumer, on_transport_close));
     }
 
     if event_types.contains(&atoms::on_layers_change()) {
-        consumer
-            .on_layers_change(move |layer| {
-                send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_layers_change(),
-                        JsonSerdeWrap::new(*layer),
-                    ),
-                );
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(consumer.on_layers_change(move |layer| {
+            sink.send_value(atoms::on_layers_change(), JsonSerdeWrap::new(*layer));
+        }));
     }
     if event_types.contains(&atoms::on_score()) {
-        //let pid = pid.clone();
-        consumer
-            .on_score(move |score| {
-                send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_score(),
-                        JsonSerdeWrap::new(score.clone()),
-                    ),
-                );
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(consumer.on_score(move |score| {
+            sink.send_value(atoms::on_score(), JsonSerdeWrap::new(score.clone()));
+        }));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.Consumer.Options"]
 pub struct ConsumerOptionsStruct {
     producer_id: JsonSerdeWrap<ProducerId>,
     rtp_capabilities: JsonSerdeWrap<RtpCapabilities>,
@@ -294,6 +495,21 @@ pub struct ConsumerOptionsStruct {
     mid: Option<String>,
 }
 
+crate::nif_struct_decoder!(
+    ConsumerOptionsStruct,
+    "Mediasoup.Consumer.Options",
+    [
+        producer_id,
+        rtp_capabilities,
+        paused,
+        enable_rtx,
+        ignore_dtx,
+        preferred_layers,
+        pipe,
+        mid
+    ]
+);
+
 impl ConsumerOptionsStruct {
     pub fn to_option(&self) -> ConsumerOptions {
         let mut option = ConsumerOptions::new(*self.producer_id, self.rtp_capabilities.clone());
@@ -312,3 +528,83 @@ impl ConsumerOptionsStruct {
         option
     }
 }
+
+/// Options of one consumer of a batch, see `consume_batch`.
+pub struct ConsumeBatchItemStruct {
+    /// Parsed per item, so that a malformed id only fails its own item.
+    producer_id: String,
+    paused: Option<bool>,
+    enable_rtx: Option<bool>,
+    ignore_dtx: Option<bool>,
+    preferred_layers: JsonSerdeWrap<Option<ConsumerLayers>>,
+    mid: Option<String>,
+}
+
+crate::nif_struct_decoder!(
+    ConsumeBatchItemStruct,
+    "Mediasoup.Consumer.BatchItem",
+    [
+        producer_id,
+        paused,
+        enable_rtx,
+        ignore_dtx,
+        preferred_layers,
+        mid
+    ]
+);
+
+impl ConsumeBatchItemStruct {
+    fn to_option(&self, rtp_capabilities: &RtpCapabilities) -> Result<ConsumerOptions, String> {
+        let producer_id: ProducerId = self
+            .producer_id
+            .parse()
+            .map_err(|error| format!("invalid producer id {:?}: {}", self.producer_id, error))?;
+        let mut option = ConsumerOptions::new(producer_id, rtp_capabilities.clone());
+        if let Some(paused) = self.paused {
+            option.paused = paused;
+        }
+        option.enable_rtx = self.enable_rtx;
+        if let Some(ignore_dtx) = self.ignore_dtx {
+            option.ignore_dtx = ignore_dtx;
+        }
+        option.preferred_layers = *self.preferred_layers;
+        option.mid.clone_from(&self.mid);
+        Ok(option)
+    }
+}
+
+/// Creates a consumer for each of `items` on `transport`, all sharing `rtp_capabilities`.
+/// The requests run concurrently; the results are in the order of `items`, with the error of a
+/// failed item in its place.
+pub fn consume_batch<T>(
+    transport: T,
+    rtp_capabilities: &RtpCapabilities,
+    items: &[ConsumeBatchItemStruct],
+) -> impl Future<Output = Vec<Result<ResourceArc<ConsumerRef>, String>>>
+where
+    T: Transport + Clone + 'static,
+{
+    let requests: Vec<_> = items
+        .iter()
+        .map(|item| {
+            let transport = transport.clone();
+            let option = item.to_option(rtp_capabilities);
+            async move {
+                transport
+                    .consume(option?)
+                    .await
+                    .map(ConsumerRef::new)
+                    .map(ResourceArc::new)
+                    .map_err(|error| format!("{}", error))
+            }
+        })
+        .collect();
+    let results = task::join_all(requests);
+    async move {
+        results
+            .await
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err("consume task panicked".into())))
+            .collect()
+    }
+}
@@ -1,58 +1,92 @@
-use crate::json_serde::JsonSerdeWrap;
-use crate::{atoms, DisposableResourceWrapper};
-use mediasoup::data_consumer::DataConsumerType;
+use crate::close;
+use crate::json_serde::{AsAtom, JsonSerdeWrap};
+use crate::stats;
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::{atoms, send_async_nif_result_with_from, DisposableResourceWrapper};
 use mediasoup::data_producer::DataProducerId;
 use mediasoup::prelude::SctpStreamParameters;
 use mediasoup::prelude::{DataConsumer, DataConsumerId, DataConsumerOptions};
-use rustler::{Atom, NifResult, NifStruct, ResourceArc};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type DataConsumerRef = DisposableResourceWrapper<DataConsumer>;
 
 #[rustler::resource_impl]
-impl rustler::Resource for DataConsumerRef {}
+impl rustler::Resource for DataConsumerRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn data_consumer_id(
     data_consumer: ResourceArc<DataConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<DataConsumerId>> {
-    let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.id().into())
+    data_consumer.with_resource(|data_consumer| data_consumer.id().into())
 }
 
 #[rustler::nif]
 pub fn data_consumer_producer_id(
     data_consumer: ResourceArc<DataConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<DataProducerId>> {
-    let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.data_producer_id().into())
+    data_consumer.with_resource(|data_consumer| data_consumer.data_producer_id().into())
 }
 
 #[rustler::nif]
-pub fn data_consumer_type(
-    data_consumer: ResourceArc<DataConsumerRef>,
-) -> NifResult<JsonSerdeWrap<DataConsumerType>> {
-    let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.r#type().into())
+pub fn data_consumer_type(data_consumer: ResourceArc<DataConsumerRef>) -> NifResult<Atom> {
+    data_consumer.with_resource(|data_consumer| data_consumer.r#type().as_atom())
 }
 
 #[rustler::nif]
 pub fn data_consumer_sctp_stream_parameters(
     data_consumer: ResourceArc<DataConsumerRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SctpStreamParameters>>> {
-    let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.sctp_stream_parameters().into())
+    data_consumer.with_resource(|data_consumer| data_consumer.sctp_stream_parameters().into())
 }
 
 #[rustler::nif]
 pub fn data_consumer_label(data_consumer: ResourceArc<DataConsumerRef>) -> NifResult<String> {
-    let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.label().into())
+    data_consumer.with_resource(|data_consumer| data_consumer.label().into())
 }
 
 #[rustler::nif]
 pub fn data_consumer_protocol(data_consumer: ResourceArc<DataConsumerRef>) -> NifResult<String> {
+    data_consumer.with_resource(|data_consumer| data_consumer.protocol().into())
+}
+
+#[rustler::nif(name = "data_consumer_get_stats_async")]
+pub fn data_consumer_get_stats(
+    env: Env,
+    data_consumer: ResourceArc<DataConsumerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let data_consumer = data_consumer.get_resource()?;
-    Ok(data_consumer.protocol().into())
+
+    send_async_nif_result_with_from(
+        env,
+        "data_consumer_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            data_consumer
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::DataConsumerStats>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn data_consumer_stats_subscribe(
+    data_consumer: ResourceArc<DataConsumerRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&data_consumer, pid, interval_ms, delta)
 }
 
 #[rustler::nif]
@@ -61,6 +95,35 @@ pub fn data_consumer_close(data_consumer: ResourceArc<DataConsumerRef>) -> NifRe
     Ok((atoms::ok(),))
 }
 
+/// Closes the data consumer and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "data_consumer_close_async")]
+pub fn data_consumer_close_confirmed(
+    env: Env,
+    data_consumer: ResourceArc<DataConsumerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = data_consumer.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "data_consumer_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
+/// Closes the data consumer when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn data_consumer_monitor_owner(
+    env: Env,
+    data_consumer: ResourceArc<DataConsumerRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&data_consumer, env, owner)?;
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif]
 pub fn data_consumer_closed(data_consumer: ResourceArc<DataConsumerRef>) -> NifResult<bool> {
     match data_consumer.get_resource() {
@@ -74,22 +137,36 @@ pub fn data_consumer_event(
     data_consumer: ResourceArc<DataConsumerRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = data_consumer.close_reason();
     let data_consumer = data_consumer.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, data_consumer, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &data_consumer,
+        close_reason,
+        &event_types,
+    ));
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.DataConsumer.Options"]
 pub struct DataConsumerOptionsStruct {
     data_producer_id: JsonSerdeWrap<DataProducerId>,
 }
 
+crate::nif_struct_decoder!(
+    DataConsumerOptionsStruct,
+    "Mediasoup.DataConsumer.Options",
+    [data_producer_id]
+);
+
 impl DataConsumerOptionsStruct {
     pub fn to_option(&self) -> DataConsumerOptions {
         DataConsumerOptions::new_sctp(*self.data_producer_id)
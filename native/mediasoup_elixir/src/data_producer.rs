@@ -1,36 +1,77 @@
 use crate::atoms;
-use crate::{json_serde::JsonSerdeWrap, DisposableResourceWrapper};
-use mediasoup::data_producer::{DataProducerOptions, DataProducerType};
+use crate::close;
+use crate::stats;
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::{
+    json_serde::{AsAtom, JsonSerdeWrap},
+    send_async_nif_result_with_from, DisposableResourceWrapper,
+};
+use mediasoup::data_producer::DataProducerOptions;
 use mediasoup::prelude::SctpStreamParameters;
 use mediasoup::prelude::{DataProducer, DataProducerId};
-use rustler::{Atom, NifResult, NifStruct, ResourceArc};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type DataProducerRef = DisposableResourceWrapper<DataProducer>;
 #[rustler::resource_impl]
-impl rustler::Resource for DataProducerRef {}
+impl rustler::Resource for DataProducerRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn data_producer_id(
     data_producer: ResourceArc<DataProducerRef>,
 ) -> NifResult<JsonSerdeWrap<DataProducerId>> {
-    let data_producer = data_producer.get_resource()?;
-    Ok(data_producer.id().into())
+    data_producer.with_resource(|data_producer| data_producer.id().into())
 }
 
 #[rustler::nif]
-pub fn data_producer_type(
-    data_producer: ResourceArc<DataProducerRef>,
-) -> NifResult<JsonSerdeWrap<DataProducerType>> {
-    let data_producer = data_producer.get_resource()?;
-    Ok(data_producer.r#type().into())
+pub fn data_producer_type(data_producer: ResourceArc<DataProducerRef>) -> NifResult<Atom> {
+    data_producer.with_resource(|data_producer| data_producer.r#type().as_atom())
 }
 
 #[rustler::nif]
 pub fn data_producer_sctp_stream_parameters(
     data_producer: ResourceArc<DataProducerRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SctpStreamParameters>>> {
+    data_producer.with_resource(|data_producer| data_producer.sctp_stream_parameters().into())
+}
+
+#[rustler::nif(name = "data_producer_get_stats_async")]
+pub fn data_producer_get_stats(
+    env: Env,
+    data_producer: ResourceArc<DataProducerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let data_producer = data_producer.get_resource()?;
-    Ok(data_producer.sctp_stream_parameters().into())
+
+    send_async_nif_result_with_from(
+        env,
+        "data_producer_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            data_producer
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::DataProducerStats>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn data_producer_stats_subscribe(
+    data_producer: ResourceArc<DataProducerRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&data_producer, pid, interval_ms, delta)
 }
 
 #[rustler::nif]
@@ -39,6 +80,35 @@ pub fn data_producer_close(data_producer: ResourceArc<DataProducerRef>) -> NifRe
     Ok((atoms::ok(),))
 }
 
+/// Closes the data producer and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "data_producer_close_async")]
+pub fn data_producer_close_confirmed(
+    env: Env,
+    data_producer: ResourceArc<DataProducerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = data_producer.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "data_producer_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
+/// Closes the data producer when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn data_producer_monitor_owner(
+    env: Env,
+    data_producer: ResourceArc<DataProducerRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&data_producer, env, owner)?;
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif]
 pub fn data_producer_closed(data_producer: ResourceArc<DataProducerRef>) -> NifResult<bool> {
     match data_producer.get_resource() {
@@ -52,22 +122,36 @@ pub fn data_producer_event(
     data_producer: ResourceArc<DataProducerRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(rustler::Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(rustler::Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = data_producer.close_reason();
     let data_producer = data_producer.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, data_producer, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &data_producer,
+        close_reason,
+        &event_types,
+    ));
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.DataProducer.Options"]
 pub struct DataProducerOptionsStruct {
     pub sctp_stream_parameters: Option<JsonSerdeWrap<SctpStreamParameters>>,
 }
 
+crate::nif_struct_decoder!(
+    DataProducerOptionsStruct,
+    "Mediasoup.DataProducer.Options",
+    [sctp_stream_parameters]
+);
+
 impl DataProducerOptionsStruct {
     pub fn to_option(&self) -> DataProducerOptions {
         match &self.sctp_stream_parameters {
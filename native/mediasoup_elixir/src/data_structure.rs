@@ -1,4 +1,8 @@
+use crate::json_serde::AsAtom;
+use crate::validation::{ensure_positive, ValidationResult};
+use mediasoup::types::data_structures::TransportTuple;
 use mediasoup::types::sctp_parameters::NumSctpStreams;
+use rustler::{Atom, NifStruct};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -15,4 +19,40 @@ impl SerNumSctpStreams {
             mis: self.mis,
         }
     }
+
+    pub fn validate(&self) -> ValidationResult {
+        ensure_positive(Some(self.os), "OS")?;
+        ensure_positive(Some(self.mis), "MIS")
+    }
+}
+
+/// `%TransportTuple{}`, see `lib/transport.ex`.
+#[derive(Clone, NifStruct)]
+#[module = "TransportTuple"]
+pub struct TransportTupleStruct {
+    local_address: String,
+    local_port: u16,
+    remote_ip: Option<String>,
+    remote_port: Option<u16>,
+    protocol: Atom,
+}
+
+impl From<&TransportTuple> for TransportTupleStruct {
+    fn from(tuple: &TransportTuple) -> Self {
+        let (remote_ip, remote_port) = match tuple {
+            TransportTuple::WithRemote {
+                remote_ip,
+                remote_port,
+                ..
+            } => (Some(remote_ip.to_string()), Some(*remote_port)),
+            TransportTuple::LocalOnly { .. } => (None, None),
+        };
+        Self {
+            local_address: tuple.local_address().clone(),
+            local_port: tuple.local_port(),
+            remote_ip,
+            remote_port,
+            protocol: tuple.protocol().as_atom(),
+        }
+    }
 }
@@ -0,0 +1,396 @@
+//! Dumps returned by the `*_dump_async` NIFs, as the `Dump` struct of each entity module,
+//! e.g. `Mediasoup.Router.Dump`.
+//!
+//! Keys are the snake_case field names and enums are atoms, see `AsAtom` and `EnumAtom`. RTP,
+//! SCTP, ICE and DTLS parameters keep the shape of their getters, e.g. `Producer.rtp_parameters/1`.
+
+use crate::data_structure::TransportTupleStruct;
+use crate::json_serde::{reserialize, AsAtom, EnumAtom, JsonSerdeWrap};
+use mediasoup::consumer::ConsumerDump;
+use mediasoup::consumer::RtpStream;
+use mediasoup::pipe_transport::PipeTransportDump;
+use mediasoup::plain_transport::PlainTransportDump;
+use mediasoup::prelude::{
+    DtlsParameters, IceCandidate, IceParameters, RtpEncodingParameters, RtpParameters,
+};
+use mediasoup::producer::{ProducerDump, RtpStreamRecv};
+use mediasoup::router::RouterDump;
+use mediasoup::types::sctp_parameters::SctpParameters;
+use mediasoup::types::srtp_parameters::SrtpParameters;
+use mediasoup::webrtc_server::WebRtcServerDump;
+use mediasoup::webrtc_transport::WebRtcTransportDump;
+use mediasoup::worker::WorkerDump;
+use rustler::{Atom, NifMap, NifStruct};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+fn ids<Id: Display>(ids: impl IntoIterator<Item = Id>) -> Vec<String> {
+    ids.into_iter().map(|id| id.to_string()).collect()
+}
+
+fn id_map<K, V, R>(
+    map: impl IntoIterator<Item = (K, V)>,
+    value: impl Fn(V) -> R,
+) -> HashMap<String, R>
+where
+    K: Display,
+{
+    map.into_iter()
+        .map(|(key, item)| (key.to_string(), value(item)))
+        .collect()
+}
+
+#[derive(NifStruct)]
+#[module = "Mediasoup.Worker.Dump"]
+pub struct WorkerDumpStruct {
+    router_ids: Vec<String>,
+    webrtc_server_ids: Vec<String>,
+}
+
+impl From<WorkerDump> for WorkerDumpStruct {
+    fn from(dump: WorkerDump) -> Self {
+        Self {
+            router_ids: ids(dump.router_ids),
+            webrtc_server_ids: ids(dump.webrtc_server_ids),
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct IpPort {
+    ip: String,
+    port: u16,
+}
+
+#[derive(NifMap)]
+pub struct IceUsernameFragment {
+    local_ice_username_fragment: String,
+    webrtc_transport_id: String,
+}
+
+#[derive(NifMap)]
+pub struct TupleHash {
+    tuple_hash: u64,
+    webrtc_transport_id: String,
+}
+
+#[derive(NifStruct)]
+#[module = "Mediasoup.WebRtcServer.Dump"]
+pub struct WebRtcServerDumpStruct {
+    id: String,
+    udp_sockets: Vec<IpPort>,
+    tcp_servers: Vec<IpPort>,
+    webrtc_transport_ids: Vec<String>,
+    local_ice_username_fragments: Vec<IceUsernameFragment>,
+    tuple_hashes: Vec<TupleHash>,
+}
+
+impl From<WebRtcServerDump> for WebRtcServerDumpStruct {
+    fn from(dump: WebRtcServerDump) -> Self {
+        let ip_ports = |ip_ports: Vec<mediasoup::webrtc_server::WebRtcServerIpPort>| {
+            ip_ports
+                .into_iter()
+                .map(|ip_port| IpPort {
+                    ip: ip_port.ip.to_string(),
+                    port: ip_port.port,
+                })
+                .collect()
+        };
+        Self {
+            id: dump.id.to_string(),
+            udp_sockets: ip_ports(dump.udp_sockets),
+            tcp_servers: ip_ports(dump.tcp_servers),
+            webrtc_transport_ids: ids(dump.webrtc_transport_ids),
+            local_ice_username_fragments: dump
+                .local_ice_username_fragments
+                .into_iter()
+                .map(|fragment| IceUsernameFragment {
+                    local_ice_username_fragment: fragment.local_ice_username_fragment,
+                    webrtc_transport_id: fragment.webrtc_transport_id.to_string(),
+                })
+                .collect(),
+            tuple_hashes: dump
+                .tuple_hashes
+                .into_iter()
+                .map(|tuple_hash| TupleHash {
+                    tuple_hash: tuple_hash.tuple_hash,
+                    webrtc_transport_id: tuple_hash.webrtc_transport_id.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(NifStruct)]
+#[module = "Mediasoup.Router.Dump"]
+pub struct RouterDumpStruct {
+    id: String,
+    map_consumer_id_producer_id: HashMap<String, String>,
+    map_data_consumer_id_data_producer_id: HashMap<String, String>,
+    map_data_producer_id_data_consumer_ids: HashMap<String, Vec<String>>,
+    map_producer_id_consumer_ids: HashMap<String, Vec<String>>,
+    map_producer_id_observer_ids: HashMap<String, Vec<String>>,
+    rtp_observer_ids: Vec<String>,
+    transport_ids: Vec<String>,
+}
+
+impl From<RouterDump> for RouterDumpStruct {
+    fn from(dump: RouterDump) -> Self {
+        Self {
+            id: dump.id.to_string(),
+            map_consumer_id_producer_id: id_map(dump.map_consumer_id_producer_id, |id| {
+                id.to_string()
+            }),
+            map_data_consumer_id_data_producer_id: id_map(
+                dump.map_data_consumer_id_data_producer_id,
+                |id| id.to_string(),
+            ),
+            map_data_producer_id_data_consumer_ids: id_map(
+                dump.map_data_producer_id_data_consumer_ids,
+                ids,
+            ),
+            map_producer_id_consumer_ids: id_map(dump.map_producer_id_consumer_ids, ids),
+            map_producer_id_observer_ids: id_map(dump.map_producer_id_observer_ids, ids),
+            rtp_observer_ids: ids(dump.rtp_observer_ids),
+            transport_ids: ids(dump.transport_ids),
+        }
+    }
+}
+
+/// Header extension ids the transport reads from received RTP packets.
+#[derive(NifMap, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RecvRtpHeaderExtensions {
+    mid: Option<u8>,
+    rid: Option<u8>,
+    rrid: Option<u8>,
+    abs_send_time: Option<u8>,
+    transport_wide_cc01: Option<u8>,
+}
+
+/// Producer ids by MID, RID and SSRC.
+#[derive(NifMap)]
+pub struct RtpListener {
+    mid_table: HashMap<String, String>,
+    rid_table: HashMap<String, String>,
+    ssrc_table: HashMap<u32, String>,
+}
+
+impl From<mediasoup::transport::RtpListener> for RtpListener {
+    fn from(listener: mediasoup::transport::RtpListener) -> Self {
+        Self {
+            mid_table: id_map(listener.mid_table, |id| id.to_string()),
+            rid_table: id_map(listener.rid_table, |id| id.to_string()),
+            ssrc_table: listener
+                .ssrc_table
+                .into_iter()
+                .map(|(ssrc, id)| (ssrc, id.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Data producer ids by SCTP stream id.
+#[derive(NifMap)]
+pub struct SctpListener {
+    stream_id_table: HashMap<u16, String>,
+}
+
+impl From<&mediasoup::transport::SctpListener> for SctpListener {
+    fn from(listener: &mediasoup::transport::SctpListener) -> Self {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Table {
+            stream_id_table: Vec<(u16, String)>,
+        }
+        let table: Option<Table> = reserialize(listener).ok();
+        Self {
+            stream_id_table: table
+                .map(|table| table.stream_id_table.into_iter().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Defines the dump struct of a transport: the fields common to all transports, then the
+/// fields listed in the call.
+macro_rules! transport_dump {
+    (
+        $name: ident, $module: literal, |$dump: ident: $dump_type: ty|
+        {$($field: ident: $t: ty = $value: expr),* $(,)?}
+    ) => {
+        #[derive(NifStruct)]
+        #[module = $module]
+        pub struct $name {
+            id: String,
+            direct: bool,
+            producer_ids: Vec<String>,
+            consumer_ids: Vec<String>,
+            map_ssrc_consumer_id: HashMap<u32, String>,
+            map_rtx_ssrc_consumer_id: HashMap<u32, String>,
+            data_producer_ids: Vec<String>,
+            data_consumer_ids: Vec<String>,
+            recv_rtp_header_extensions: RecvRtpHeaderExtensions,
+            rtp_listener: RtpListener,
+            max_message_size: u32,
+            sctp_parameters: Option<JsonSerdeWrap<SctpParameters>>,
+            sctp_state: Option<Atom>,
+            sctp_listener: Option<SctpListener>,
+            trace_event_types: Vec<EnumAtom>,
+            $($field: $t,)*
+        }
+
+        impl From<$dump_type> for $name {
+            fn from($dump: $dump_type) -> Self {
+                Self {
+                    id: $dump.id.to_string(),
+                    direct: $dump.direct,
+                    producer_ids: ids(&$dump.producer_ids),
+                    consumer_ids: ids(&$dump.consumer_ids),
+                    map_ssrc_consumer_id: $dump
+                        .map_ssrc_consumer_id
+                        .iter()
+                        .map(|(ssrc, id)| (*ssrc, id.to_string()))
+                        .collect(),
+                    map_rtx_ssrc_consumer_id: $dump
+                        .map_rtx_ssrc_consumer_id
+                        .iter()
+                        .map(|(ssrc, id)| (*ssrc, id.to_string()))
+                        .collect(),
+                    data_producer_ids: ids(&$dump.data_producer_ids),
+                    data_consumer_ids: ids(&$dump.data_consumer_ids),
+                    recv_rtp_header_extensions: reserialize(&$dump.recv_rtp_header_extensions)
+                        .unwrap_or_default(),
+                    rtp_listener: $dump.rtp_listener.clone().into(),
+                    max_message_size: $dump.max_message_size,
+                    sctp_parameters: $dump.sctp_parameters.map(JsonSerdeWrap::new),
+                    sctp_state: $dump.sctp_state.as_ref().map(AsAtom::as_atom),
+                    sctp_listener: $dump.sctp_listener.as_ref().map(Into::into),
+                    trace_event_types: $dump.trace_event_types.iter().map(EnumAtom::new).collect(),
+                    $($field: $value,)*
+                }
+            }
+        }
+    };
+}
+
+transport_dump!(
+    WebRtcTransportDumpStruct,
+    "Mediasoup.WebRtcTransport.Dump",
+    |dump: WebRtcTransportDump| {
+        dtls_parameters: JsonSerdeWrap<DtlsParameters> =
+            JsonSerdeWrap::new(dump.dtls_parameters.clone()),
+        dtls_state: Atom = dump.dtls_state.as_atom(),
+        ice_candidates: JsonSerdeWrap<Vec<IceCandidate>> =
+            JsonSerdeWrap::new(dump.ice_candidates.clone()),
+        ice_parameters: JsonSerdeWrap<IceParameters> =
+            JsonSerdeWrap::new(dump.ice_parameters.clone()),
+        ice_role: Atom = dump.ice_role.as_atom(),
+        ice_state: Atom = dump.ice_state.as_atom(),
+        ice_selected_tuple: Option<TransportTupleStruct> =
+            dump.ice_selected_tuple.as_ref().map(Into::into),
+    }
+);
+
+transport_dump!(
+    PlainTransportDumpStruct,
+    "Mediasoup.PlainTransport.Dump",
+    |dump: PlainTransportDump| {
+        rtcp_mux: bool = dump.rtcp_mux,
+        comedia: bool = dump.comedia,
+        tuple: TransportTupleStruct = (&dump.tuple).into(),
+        rtcp_tuple: Option<TransportTupleStruct> = dump.rtcp_tuple.as_ref().map(Into::into),
+        srtp_parameters: Option<JsonSerdeWrap<SrtpParameters>> =
+            dump.srtp_parameters.clone().map(JsonSerdeWrap::new),
+    }
+);
+
+transport_dump!(
+    PipeTransportDumpStruct,
+    "Mediasoup.PipeTransport.Dump",
+    |dump: PipeTransportDump| {
+        tuple: TransportTupleStruct = (&dump.tuple).into(),
+        rtx: bool = dump.rtx,
+        srtp_parameters: Option<JsonSerdeWrap<SrtpParameters>> =
+            dump.srtp_parameters.clone().map(JsonSerdeWrap::new),
+    }
+);
+
+#[derive(NifStruct)]
+#[module = "Mediasoup.Producer.Dump"]
+pub struct ProducerDumpStruct {
+    id: String,
+    kind: Atom,
+    r#type: Atom,
+    paused: bool,
+    rtp_mapping: JsonSerdeWrap<serde_json::Value>,
+    rtp_parameters: JsonSerdeWrap<RtpParameters>,
+    rtp_streams: JsonSerdeWrap<Vec<RtpStreamRecv>>,
+    trace_event_types: Vec<EnumAtom>,
+}
+
+impl From<ProducerDump> for ProducerDumpStruct {
+    fn from(dump: ProducerDump) -> Self {
+        Self {
+            id: dump.id.to_string(),
+            kind: dump.kind.as_atom(),
+            r#type: dump.r#type.as_atom(),
+            paused: dump.paused,
+            rtp_mapping: JsonSerdeWrap::new(
+                serde_json::to_value(&dump.rtp_mapping).unwrap_or_default(),
+            ),
+            rtp_parameters: JsonSerdeWrap::new(dump.rtp_parameters),
+            rtp_streams: JsonSerdeWrap::new(dump.rtp_streams),
+            trace_event_types: dump.trace_event_types.iter().map(EnumAtom::new).collect(),
+        }
+    }
+}
+
+#[derive(NifStruct)]
+#[module = "Mediasoup.Consumer.Dump"]
+pub struct ConsumerDumpStruct {
+    id: String,
+    producer_id: String,
+    kind: Atom,
+    r#type: Atom,
+    paused: bool,
+    producer_paused: bool,
+    priority: u8,
+    rtp_parameters: JsonSerdeWrap<RtpParameters>,
+    consumable_rtp_encodings: JsonSerdeWrap<Vec<RtpEncodingParameters>>,
+    supported_codec_payload_types: Vec<u8>,
+    rtp_streams: JsonSerdeWrap<Vec<RtpStream>>,
+    trace_event_types: Vec<EnumAtom>,
+    preferred_spatial_layer: Option<i16>,
+    target_spatial_layer: Option<i16>,
+    current_spatial_layer: Option<i16>,
+    preferred_temporal_layer: Option<i16>,
+    target_temporal_layer: Option<i16>,
+    current_temporal_layer: Option<i16>,
+}
+
+impl From<ConsumerDump> for ConsumerDumpStruct {
+    fn from(dump: ConsumerDump) -> Self {
+        Self {
+            id: dump.id.to_string(),
+            producer_id: dump.producer_id.to_string(),
+            kind: dump.kind.as_atom(),
+            r#type: dump.r#type.as_atom(),
+            paused: dump.paused,
+            producer_paused: dump.producer_paused,
+            priority: dump.priority,
+            rtp_parameters: JsonSerdeWrap::new(dump.rtp_parameters),
+            consumable_rtp_encodings: JsonSerdeWrap::new(dump.consumable_rtp_encodings),
+            supported_codec_payload_types: dump.supported_codec_payload_types,
+            rtp_streams: JsonSerdeWrap::new(dump.rtp_streams),
+            trace_event_types: dump.trace_event_types.iter().map(EnumAtom::new).collect(),
+            preferred_spatial_layer: dump.preferred_spatial_layer,
+            target_spatial_layer: dump.target_spatial_layer,
+            current_spatial_layer: dump.current_spatial_layer,
+            preferred_temporal_layer: dump.preferred_temporal_layer,
+            target_temporal_layer: dump.target_temporal_layer,
+            current_temporal_layer: dump.current_temporal_layer,
+        }
+    }
+}
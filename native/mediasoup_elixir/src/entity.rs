@@ -0,0 +1,187 @@
+//! The mediasoup objects wrapped in resources, see `DisposableResourceWrapper`.
+
+use event_listener_primitives::HandlerId;
+use mediasoup::prelude::*;
+use mediasoup::worker::WorkerId;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Worker,
+    WebRtcServer,
+    Router,
+    WebRtcTransport,
+    PlainTransport,
+    PipeTransport,
+    Producer,
+    Consumer,
+    DataProducer,
+    DataConsumer,
+}
+
+pub const ENTITY_KINDS: usize = EntityKind::DataConsumer as usize + 1;
+
+impl EntityKind {
+    /// The name used for the kind in elixir, as an atom or a map key.
+    pub fn name(self) -> &'static str {
+        match self {
+            EntityKind::Worker => "worker",
+            EntityKind::WebRtcServer => "webrtc_server",
+            EntityKind::Router => "router",
+            EntityKind::WebRtcTransport => "webrtc_transport",
+            EntityKind::PlainTransport => "plain_transport",
+            EntityKind::PipeTransport => "pipe_transport",
+            EntityKind::Producer => "producer",
+            EntityKind::Consumer => "consumer",
+            EntityKind::DataProducer => "data_producer",
+            EntityKind::DataConsumer => "data_consumer",
+        }
+    }
+}
+
+/// The object whose dump lists an entity: the worker of routers and webrtc servers, the router
+/// of everything else.
+pub enum Lister {
+    Worker(Worker),
+    Router(Router),
+}
+
+impl Lister {
+    pub fn worker_id(&self) -> WorkerId {
+        match self {
+            Lister::Worker(worker) => worker.id(),
+            Lister::Router(router) => router.worker().id(),
+        }
+    }
+}
+
+/// A mediasoup object wrapped in a resource. It is counted in the metrics and listed in the
+/// registry while it is open.
+pub trait Entity: Send + Sync + 'static {
+    const KIND: EntityKind;
+    fn id(&self) -> String;
+    /// The id of the object that created this one, e.g. the transport of a producer.
+    fn parent_id(&self) -> Option<String>;
+    /// `None` for a worker.
+    fn lister(&self) -> Option<Lister>;
+    fn closed(&self) -> bool;
+    fn add_close_handler(&self, callback: impl FnOnce() + Send + 'static) -> HandlerId;
+    /// Calls `callback` right before the entity closes because its parent closed, e.g. the
+    /// transport of a producer or the producer of a consumer. For a worker, when it dies.
+    fn on_parent_close_detached(&self, callback: impl Fn() + Clone + Send + Sync + 'static);
+}
+
+macro_rules! impl_entity {
+    ($($t: ty => $kind: ident, $parent: expr, $lister: expr, [$($parent_close: ident),+]),* $(,)?) => {
+        $(
+            impl Entity for $t {
+                const KIND: EntityKind = EntityKind::$kind;
+                fn id(&self) -> String {
+                    <$t>::id(self).to_string()
+                }
+                fn parent_id(&self) -> Option<String> {
+                    let parent: fn(&$t) -> Option<String> = $parent;
+                    parent(self)
+                }
+                fn lister(&self) -> Option<Lister> {
+                    let lister: fn(&$t) -> Lister = $lister;
+                    Some(lister(self))
+                }
+                fn closed(&self) -> bool {
+                    <$t>::closed(self)
+                }
+                fn add_close_handler(&self, callback: impl FnOnce() + Send + 'static) -> HandlerId {
+                    <$t>::on_close(self, callback)
+                }
+                fn on_parent_close_detached(&self, callback: impl Fn() + Clone + Send + Sync + 'static) {
+                    $({
+                        let callback = callback.clone();
+                        <$t>::$parent_close(self, move || callback()).detach();
+                    })+
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_transport_entity {
+    ($($t: ty => $kind: ident),* $(,)?) => {
+        $(
+            impl Entity for $t {
+                const KIND: EntityKind = EntityKind::$kind;
+                fn id(&self) -> String {
+                    Transport::id(self).to_string()
+                }
+                fn parent_id(&self) -> Option<String> {
+                    Some(Transport::router(self).id().to_string())
+                }
+                fn lister(&self) -> Option<Lister> {
+                    Some(Lister::Router(Transport::router(self).clone()))
+                }
+                fn closed(&self) -> bool {
+                    Transport::closed(self)
+                }
+                fn add_close_handler(&self, callback: impl FnOnce() + Send + 'static) -> HandlerId {
+                    Transport::on_close(self, Box::new(callback))
+                }
+                fn on_parent_close_detached(&self, callback: impl Fn() + Clone + Send + Sync + 'static) {
+                    Transport::on_router_close(self, Box::new(callback)).detach();
+                }
+            }
+        )*
+    };
+}
+
+impl Entity for Worker {
+    const KIND: EntityKind = EntityKind::Worker;
+    fn id(&self) -> String {
+        Worker::id(self).to_string()
+    }
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+    fn lister(&self) -> Option<Lister> {
+        None
+    }
+    fn closed(&self) -> bool {
+        Worker::closed(self)
+    }
+    fn add_close_handler(&self, callback: impl FnOnce() + Send + 'static) -> HandlerId {
+        Worker::on_close(self, callback)
+    }
+    fn on_parent_close_detached(&self, callback: impl Fn() + Clone + Send + Sync + 'static) {
+        Worker::on_dead(self, move |_| callback()).detach();
+    }
+}
+
+impl_entity!(
+    WebRtcServer => WebRtcServer,
+        |server| Some(server.worker().id().to_string()),
+        |server| Lister::Worker(server.worker().clone()),
+        [on_worker_close],
+    Router => Router,
+        |router| Some(router.worker().id().to_string()),
+        |router| Lister::Worker(router.worker().clone()),
+        [on_worker_close],
+    Producer => Producer,
+        |producer| Some(producer.transport().id().to_string()),
+        |producer| Lister::Router(producer.transport().router().clone()),
+        [on_transport_close],
+    Consumer => Consumer,
+        |consumer| Some(consumer.transport().id().to_string()),
+        |consumer| Lister::Router(consumer.transport().router().clone()),
+        [on_transport_close, on_producer_close],
+    DataProducer => DataProducer,
+        |producer| Some(producer.transport().id().to_string()),
+        |producer| Lister::Router(producer.transport().router().clone()),
+        [on_transport_close],
+    DataConsumer => DataConsumer,
+        |consumer| Some(consumer.transport().id().to_string()),
+        |consumer| Lister::Router(consumer.transport().router().clone()),
+        [on_transport_close, on_data_producer_close],
+);
+
+impl_transport_entity!(
+    WebRtcTransport => WebRtcTransport,
+    PlainTransport => PlainTransport,
+    PipeTransport => PipeTransport,
+);
@@ -0,0 +1,320 @@
+//! Delivers events from mediasoup callbacks to elixir processes.
+//!
+//! Callbacks only push the event to a per-pid queue. A single long-lived thread drains the
+//! queues with one reused `OwnedEnv`. Events for a pid are always delivered in the order they
+//! were pushed. Batchable events queued together for a process are sent as one
+//! `{:nif_internal_event_batch, events}` message; every other event is sent on its own.
+
+use crate::atoms;
+use once_cell::sync::Lazy;
+use rustler::{Atom, Encoder, Env, LocalPid, OwnedEnv, Term};
+use std::cmp::Reverse;
+use std::collections::binary_heap::PeekMut;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+/// Upper bound of events sent in a single batch message.
+const MAX_BATCH_SIZE: usize = 64;
+
+pub trait QueuedEvent: Send {
+    fn encode_event<'a>(&self, env: Env<'a>) -> Term<'a>;
+}
+
+impl<T> QueuedEvent for T
+where
+    T: Encoder + Send,
+{
+    fn encode_event<'a>(&self, env: Env<'a>) -> Term<'a> {
+        self.encode(env)
+    }
+}
+
+pub type BoxedEvent = Box<dyn QueuedEvent>;
+
+/// An event waiting in the queue of a pid.
+pub struct Envelope {
+    event: BoxedEvent,
+    /// A queued event with the same key is replaced instead of queueing another one.
+    coalesce_key: Option<(usize, Atom)>,
+    /// Incremented while the event is queued.
+    queued: Option<Arc<AtomicUsize>>,
+    /// Sent in a batch with the batchable events queued right after it. Only for processes
+    /// that handle `{:nif_internal_event_batch, events}`.
+    batchable: bool,
+}
+
+impl Envelope {
+    pub fn new(event: BoxedEvent) -> Self {
+        Self {
+            event,
+            coalesce_key: None,
+            queued: None,
+            batchable: false,
+        }
+    }
+
+    pub fn batchable(mut self, batchable: bool) -> Self {
+        self.batchable = batchable;
+        self
+    }
+
+    pub fn coalesce_by(mut self, key: (usize, Atom)) -> Self {
+        self.coalesce_key = Some(key);
+        self
+    }
+
+    pub fn count_in(mut self, queued: Arc<AtomicUsize>) -> Self {
+        self.queued = Some(queued);
+        self
+    }
+}
+
+type Callback = Box<dyn FnOnce() + Send>;
+
+/// Identifies a callback passed to `schedule`, to `cancel` it.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TimerId(u64);
+
+#[derive(Default)]
+struct Queues {
+    events: BTreeMap<LocalPid, VecDeque<Envelope>>,
+    ready: VecDeque<LocalPid>,
+    /// Min-heap on the deadline. A cancelled timer stays in it, without a callback, until its
+    /// deadline.
+    timers: BinaryHeap<Reverse<(Instant, TimerId)>>,
+    callbacks: HashMap<TimerId, Callback>,
+    next_timer_id: u64,
+}
+
+impl Queues {
+    fn add_timer(&mut self, deadline: Instant, callback: Callback) -> TimerId {
+        let id = TimerId(self.next_timer_id);
+        self.next_timer_id += 1;
+        self.timers.push(Reverse((deadline, id)));
+        self.callbacks.insert(id, callback);
+        id
+    }
+
+    fn pop_due_timer(&mut self, now: Instant) -> Option<Callback> {
+        while let Some(timer) = self.timers.peek_mut().filter(|timer| timer.0 .0 <= now) {
+            let Reverse((_, id)) = PeekMut::pop(timer);
+            if let Some(callback) = self.callbacks.remove(&id) {
+                return Some(callback);
+            }
+        }
+        None
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.timers.peek().map(|Reverse((deadline, _))| *deadline)
+    }
+}
+
+struct EventDispatcher {
+    queues: Mutex<Queues>,
+    wakeup: Condvar,
+}
+
+static DISPATCHER: Lazy<&'static EventDispatcher> = Lazy::new(|| {
+    let dispatcher: &'static EventDispatcher = Box::leak(Box::new(EventDispatcher {
+        queues: Mutex::new(Queues::default()),
+        wakeup: Condvar::new(),
+    }));
+
+    std::thread::Builder::new()
+        .name("ex-mediasoup-event".into())
+        .spawn(move || dispatcher.run())
+        .expect("failed to spawn event dispatcher thread");
+
+    dispatcher
+});
+
+/// Queues `value` to be sent to `pid`.
+pub fn dispatch<T>(pid: LocalPid, value: T)
+where
+    T: Encoder + Send + 'static,
+{
+    DISPATCHER.push(pid, Envelope::new(Box::new(value)));
+}
+
+/// Queues an envelope to be sent to `pid`.
+pub fn dispatch_envelope(pid: LocalPid, envelope: Envelope) {
+    DISPATCHER.push(pid, envelope);
+}
+
+/// Runs `callback` on the dispatcher thread once `deadline` has passed.
+pub fn schedule<F>(deadline: Instant, callback: F) -> TimerId
+where
+    F: FnOnce() + Send + 'static,
+{
+    DISPATCHER.schedule(deadline, Box::new(callback))
+}
+
+/// Drops the callback of a timer that has not run yet.
+pub fn cancel(timer: TimerId) {
+    DISPATCHER.lock().callbacks.remove(&timer);
+}
+
+impl EventDispatcher {
+    /// Locks the queues. Every update leaves them consistent, so a lock poisoned by a panic is
+    /// recovered rather than stopping the delivery.
+    fn lock(&self) -> MutexGuard<'_, Queues> {
+        self.queues.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn push(&self, pid: LocalPid, envelope: Envelope) {
+        let mut queues = self.lock();
+        let queue = queues.events.entry(pid).or_default();
+
+        if let Some(key) = envelope.coalesce_key {
+            if let Some(queued) = queue
+                .iter_mut()
+                .rev()
+                .find(|queued| queued.coalesce_key == Some(key))
+            {
+                queued.event = envelope.event;
+                return;
+            }
+        }
+
+        if let Some(counter) = &envelope.queued {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        let was_idle = queue.is_empty();
+        queue.push_back(envelope);
+        if was_idle {
+            queues.ready.push_back(pid);
+            self.wakeup.notify_one();
+        }
+    }
+
+    fn schedule(&self, deadline: Instant, callback: Callback) -> TimerId {
+        let id = self.lock().add_timer(deadline, callback);
+        self.wakeup.notify_one();
+        id
+    }
+
+    /// Takes the next event of the next ready pid, running due timers first. A batchable event
+    /// is taken with the batchable events that follow it, up to `MAX_BATCH_SIZE`.
+    /// A pid that still has events left is put back at the end of the ready list.
+    fn next_batch(&self) -> (LocalPid, Vec<Envelope>) {
+        let mut queues = self.lock();
+        loop {
+            let now = Instant::now();
+            if let Some(callback) = queues.pop_due_timer(now) {
+                // The callback may push events, which takes the lock again.
+                drop(queues);
+                // A panicking callback must not stop the delivery of every other event.
+                let _ = panic::catch_unwind(AssertUnwindSafe(callback));
+                queues = self.lock();
+                continue;
+            }
+
+            if let Some(pid) = queues.ready.pop_front() {
+                // A ready pid without events has nothing to deliver, the others still do.
+                let Some(queue) = queues.events.get_mut(&pid) else {
+                    continue;
+                };
+                let len = match queue.front() {
+                    Some(envelope) if envelope.batchable => queue
+                        .iter()
+                        .take(MAX_BATCH_SIZE)
+                        .take_while(|envelope| envelope.batchable)
+                        .count(),
+                    _ => 1,
+                };
+                let batch: Vec<_> = queue.drain(..len.min(queue.len())).collect();
+                if queue.is_empty() {
+                    queues.events.remove(&pid);
+                } else {
+                    queues.ready.push_back(pid);
+                }
+                return (pid, batch);
+            }
+
+            queues = match queues.next_deadline() {
+                Some(deadline) => {
+                    self.wakeup
+                        .wait_timeout(queues, deadline - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .wakeup
+                    .wait(queues)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+
+    fn run(&self) {
+        let mut env = OwnedEnv::new();
+        loop {
+            let (pid, batch) = self.next_batch();
+            for envelope in &batch {
+                if let Some(counter) = &envelope.queued {
+                    counter.fetch_sub(1, Ordering::Relaxed);
+                }
+            }
+
+            // A process that has exited is not an error; its events are simply dropped.
+            let _ = env.send_and_clear(&pid, |env| match batch.as_slice() {
+                [envelope] => envelope.event.encode_event(env),
+                envelopes => (
+                    atoms::nif_internal_event_batch(),
+                    envelopes
+                        .iter()
+                        .map(|envelope| envelope.event.encode_event(env))
+                        .collect::<Vec<_>>(),
+                )
+                    .encode(env),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn due_timers_pop_by_deadline() {
+        let now = Instant::now();
+        let mut queues = Queues::default();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for offset in [30, 10, 20, 50] {
+            let sender = sender.clone();
+            queues.add_timer(
+                now + Duration::from_millis(offset),
+                Box::new(move || sender.send(offset).unwrap()),
+            );
+        }
+
+        while let Some(callback) = queues.pop_due_timer(now + Duration::from_millis(40)) {
+            callback();
+        }
+        assert_eq!(vec![10, 20, 30], receiver.try_iter().collect::<Vec<_>>());
+        assert_eq!(
+            Some(now + Duration::from_millis(50)),
+            queues.next_deadline()
+        );
+    }
+
+    #[test]
+    fn cancelled_timers_do_not_run() {
+        let now = Instant::now();
+        let mut queues = Queues::default();
+        let cancelled = queues.add_timer(now, Box::new(|| panic!("cancelled timer ran")));
+        let kept = queues.add_timer(now, Box::new(|| {}));
+        assert!(cancelled != kept);
+
+        queues.callbacks.remove(&cancelled);
+        assert!(queues.pop_due_timer(now).is_some());
+        assert!(queues.pop_due_timer(now).is_none());
+        assert!(queues.timers.is_empty());
+    }
+}
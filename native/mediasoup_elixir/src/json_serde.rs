@@ -1,14 +1,13 @@
 use crate::atoms;
-use rustler::{Env, NifResult, Term};
+use mediasoup::consumer::ConsumerType;
+use mediasoup::data_consumer::DataConsumerType;
+use mediasoup::data_producer::DataProducerType;
+use mediasoup::prelude::MediaKind;
+use mediasoup::producer::ProducerType;
+use mediasoup::types::data_structures::{DtlsState, IceRole, IceState, Protocol, SctpState};
+use rustler::{Atom, Decoder, Encoder, Env, NifException, NifResult, ResourceArc, Term};
+use serde::Deserialize;
 
-fn to_json(term: Term) -> Result<std::vec::Vec<u8>, serde_json::Error> {
-    let de = rustler::serde::Deserializer::from(term);
-    let mut writer = Vec::with_capacity(128);
-    let mut se = serde_json::Serializer::new(&mut writer);
-    serde_transcode::transcode(de, &mut se)?;
-
-    Ok(writer)
-}
 fn from_json(env: Env, vec: Vec<u8>) -> NifResult<Term> {
     let mut de = serde_json::Deserializer::from_slice(&vec);
     let se = rustler::serde::Serializer::from(env);
@@ -28,12 +27,181 @@ where
         Err(error) => rustler::Encoder::encode(&(atoms::error(), format!("{:?}", error)), env), // TODO:
     };
 }
-pub fn json_decode<'de, 'a: 'de, T>(term: Term<'a>) -> NifResult<T>
+pub fn json_decode<T>(term: Term) -> Result<T, InvalidArgument>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let de = rustler::serde::Deserializer::from(term);
+    let value =
+        serde_json::Value::deserialize(de).map_err(|err| InvalidArgument::new(err.to_string()))?;
+    serde_path_to_error::deserialize(value).map_err(|err| InvalidArgument {
+        path: path_to_string(err.path()),
+        message: err.inner().to_string(),
+    })
+}
+
+fn path_to_string(path: &serde_path_to_error::Path) -> String {
+    if path.iter().next().is_none() {
+        String::new()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Describes why an argument was rejected, with the path of the offending field.
+/// Raised to elixir as `ArgumentError` with a message like
+/// `rtp_parameters.encodings[1].scalabilityMode: invalid value`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidArgument {
+    path: String,
+    message: String,
+}
+
+impl InvalidArgument {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            path: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Prefixes the path with the name of the field that contains the value.
+    pub fn within(mut self, field: &str) -> Self {
+        self.path = if self.path.is_empty() {
+            field.to_string()
+        } else if self.path.starts_with('[') {
+            format!("{}{}", field, self.path)
+        } else {
+            format!("{}.{}", field, self.path)
+        };
+        self
+    }
+}
+
+impl std::fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+#[derive(NifException)]
+#[module = "ArgumentError"]
+struct ArgumentErrorException {
+    message: String,
+}
+
+impl From<InvalidArgument> for rustler::Error {
+    fn from(error: InvalidArgument) -> Self {
+        rustler::Error::RaiseTerm(Box::new(ArgumentErrorException {
+            message: error.to_string(),
+        }))
+    }
+}
+
+/// Decoder that reports which part of the term is invalid.
+/// Implemented for the field types of option structs, see `nif_struct_decoder!`.
+pub trait DecodeField<'a>: Sized {
+    fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument>;
+}
+
+impl<'a, T> DecodeField<'a> for JsonSerdeWrap<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument> {
+        json_decode(term).map(Self::new)
+    }
+}
+
+impl<'a, T> DecodeField<'a> for Option<T>
+where
+    T: DecodeField<'a>,
+{
+    fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument> {
+        match term.decode::<Atom>() {
+            Ok(atom) if atom == rustler::types::atom::nil() => Ok(None),
+            _ => T::decode_field(term).map(Some),
+        }
+    }
+}
+
+impl<'a, T> DecodeField<'a> for Vec<T>
+where
+    T: DecodeField<'a>,
+{
+    fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument> {
+        let items: Vec<Term<'a>> = term
+            .decode()
+            .map_err(|_| InvalidArgument::new("invalid type, expected a list"))?;
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                T::decode_field(item).map_err(|error| error.within(&format!("[{}]", index)))
+            })
+            .collect()
+    }
+}
+
+impl<'a, T> DecodeField<'a> for ResourceArc<T>
+where
+    T: rustler::Resource,
+{
+    fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument> {
+        term.decode()
+            .map_err(|_| InvalidArgument::new("invalid type, expected a resource reference"))
+    }
+}
+
+macro_rules! impl_decode_field {
+    ($($t: ty => $expected: literal),* $(,)?) => {
+        $(
+            impl<'a> DecodeField<'a> for $t {
+                fn decode_field(term: Term<'a>) -> Result<Self, InvalidArgument> {
+                    term.decode()
+                        .map_err(|_| InvalidArgument::new(concat!("invalid type, expected ", $expected)))
+                }
+            }
+        )*
+    };
+}
+
+impl_decode_field!(
+    bool => "a boolean",
+    u8 => "an integer between 0 and 255",
+    u16 => "an integer between 0 and 65535",
+    u32 => "an integer between 0 and 4294967295",
+    String => "a string",
+);
+
+/// Checks that `term` is a struct of the elixir module `module`.
+pub fn check_struct_module(term: Term, module: &str) -> Result<(), InvalidArgument> {
+    let expected = Atom::from_str(term.get_env(), module)
+        .map_err(|_| InvalidArgument::new("invalid struct module"))?;
+    match term.map_get(rustler::types::atom::__struct__()) {
+        Ok(actual) if actual.decode::<Atom>().is_ok_and(|atom| atom == expected) => Ok(()),
+        _ => Err(InvalidArgument::new(format!(
+            "expected %{}{{}}",
+            module.trim_start_matches("Elixir.")
+        ))),
+    }
+}
+
+/// Decodes the field `name` of an elixir struct, prefixing errors with the field name.
+pub fn decode_struct_field<'a, T>(term: Term<'a>, name: &str) -> Result<T, InvalidArgument>
 where
-    T: serde::de::DeserializeOwned + serde::Serialize + 'a,
+    T: DecodeField<'a>,
 {
-    let json = to_json(term).map_err(|_| rustler::Error::BadArg)?;
-    serde_json::from_slice(&json).map_err(|_| rustler::Error::BadArg)
+    let key = Atom::from_str(term.get_env(), name)
+        .map_err(|_| InvalidArgument::new("invalid field name").within(name))?;
+    let value = term
+        .map_get(key)
+        .map_err(|_| InvalidArgument::new("missing field").within(name))?;
+    T::decode_field(value).map_err(|error| error.within(name))
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -45,7 +213,7 @@ impl<T> JsonSerdeWrap<T> {
     }
 }
 
-impl<'de, 'a: 'de, T> rustler::Encoder for JsonSerdeWrap<T>
+impl<T> rustler::Encoder for JsonSerdeWrap<T>
 where
     T: serde::Serialize,
 {
@@ -55,11 +223,10 @@ where
 }
 impl<'a, T> rustler::Decoder<'a> for JsonSerdeWrap<T>
 where
-    T: serde::de::DeserializeOwned + serde::Serialize + 'a,
+    T: serde::de::DeserializeOwned + 'a,
 {
     fn decode(term: Term<'a>) -> rustler::NifResult<Self> {
-        let v: T = json_decode(term)?;
-        Ok(Self::new(v))
+        Ok(Self::decode_field(term)?)
     }
 }
 impl<T> std::ops::Deref for JsonSerdeWrap<T> {
@@ -75,6 +242,132 @@ impl<T> From<T> for JsonSerdeWrap<T> {
     }
 }
 
+/// Converts between two serde types of the same shape, e.g. to read the private fields of a
+/// mediasoup type into a local one.
+pub fn reserialize<T, U>(value: &T) -> Result<U, serde_json::Error>
+where
+    T: serde::Serialize,
+    U: serde::de::DeserializeOwned,
+{
+    serde_json::from_value(serde_json::to_value(value)?)
+}
+
+/// Encodes a unit enum variant as an atom: the name serde gives the variant, in snake_case,
+/// e.g. `"inbound-rtp"` as `:inbound_rtp`. This goes through serde and an atom table lookup, so
+/// enums that getters and events return use `AsAtom` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnumAtom(String);
+
+impl EnumAtom {
+    pub fn new<T: serde::Serialize>(value: &T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(name)) => Self(snake_case(&name)),
+            _ => Self(String::from("unknown")),
+        }
+    }
+}
+
+impl Encoder for EnumAtom {
+    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+        match Atom::from_str(env, &self.0) {
+            Ok(atom) => atom.encode(env),
+            Err(_) => self.0.encode(env),
+        }
+    }
+}
+
+impl<'a> Decoder<'a> for EnumAtom {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(Self(term.atom_to_string()?))
+    }
+}
+
+/// Maps each variant of an enum to a static atom, e.g. `IceState::Completed` to `:completed`.
+pub trait AsAtom {
+    fn as_atom(&self) -> Atom;
+}
+
+macro_rules! impl_as_atom {
+    ($type: ty { $($variant: ident => $atom: ident),* $(,)? }) => {
+        impl AsAtom for $type {
+            fn as_atom(&self) -> Atom {
+                match self {
+                    $(Self::$variant => atoms::$atom(),)*
+                }
+            }
+        }
+    };
+}
+
+impl_as_atom!(IceRole {
+    Controlled => controlled,
+    Controlling => controlling,
+});
+impl_as_atom!(IceState {
+    New => new,
+    Connected => connected,
+    Completed => completed,
+    Disconnected => disconnected,
+});
+impl_as_atom!(DtlsState {
+    New => new,
+    Connecting => connecting,
+    Connected => connected,
+    Failed => failed,
+    Closed => closed,
+});
+impl_as_atom!(SctpState {
+    New => new,
+    Connecting => connecting,
+    Connected => connected,
+    Failed => failed,
+    Closed => closed,
+});
+impl_as_atom!(Protocol {
+    Tcp => tcp,
+    Udp => udp,
+});
+impl_as_atom!(MediaKind {
+    Audio => audio,
+    Video => video,
+});
+impl_as_atom!(ProducerType {
+    Simple => simple,
+    Simulcast => simulcast,
+    Svc => svc,
+});
+impl_as_atom!(ConsumerType {
+    Simple => simple,
+    Simulcast => simulcast,
+    Svc => svc,
+    Pipe => pipe,
+});
+impl_as_atom!(DataProducerType {
+    Sctp => sctp,
+    Direct => direct,
+});
+impl_as_atom!(DataConsumerType {
+    Sctp => sctp,
+    Direct => direct,
+});
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (index, c) in name.chars().enumerate() {
+        match c {
+            '-' | ' ' => snake.push('_'),
+            c if c.is_ascii_uppercase() => {
+                if index > 0 && !snake.ends_with('_') {
+                    snake.push('_');
+                }
+                snake.push(c.to_ascii_lowercase());
+            }
+            c => snake.push(c),
+        }
+    }
+    snake
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +399,48 @@ mod tests {
 
         assert_eq!("value", p);
     }
+
+    #[test]
+    fn enum_atom_names_are_snake_case() {
+        use mediasoup::prelude::MediaKind;
+        use mediasoup::types::data_structures::IceState;
+
+        assert_eq!(
+            EnumAtom(String::from("completed")),
+            EnumAtom::new(&IceState::Completed)
+        );
+        assert_eq!(
+            EnumAtom(String::from("audio")),
+            EnumAtom::new(&MediaKind::Audio)
+        );
+        assert_eq!("inbound_rtp", snake_case("inbound-rtp"));
+        assert_eq!("transport_wide_cc01", snake_case("transportWideCc01"));
+    }
+
+    #[test]
+    fn decode_error_path() {
+        let error = InvalidArgument {
+            path: String::from("encodings[1].scalabilityMode"),
+            message: String::from("invalid value"),
+        };
+        assert_eq!(
+            "rtp_parameters.encodings[1].scalabilityMode: invalid value",
+            error.within("rtp_parameters").to_string()
+        );
+
+        let error = InvalidArgument {
+            path: String::from("[0].ip"),
+            message: String::from("missing field `ip`"),
+        };
+        assert_eq!(
+            "listen_infos[0].ip: missing field `ip`",
+            error.within("listen_infos").to_string()
+        );
+
+        let error = InvalidArgument::new("invalid type, expected a boolean").within("paused");
+        assert_eq!(
+            "paused: invalid type, expected a boolean",
+            error.to_string()
+        );
+    }
 }
@@ -1,70 +1,123 @@
+mod async_request;
 mod atoms;
+mod close;
 mod consumer;
 mod data_consumer;
 mod data_producer;
 mod data_structure;
+mod dump;
+mod entity;
+mod event_dispatcher;
 mod json_serde;
 mod logger;
 mod macros;
+mod metrics;
+mod ortc;
 mod pipe_transport;
 mod plain_transport;
 mod producer;
+mod registry;
 mod resource;
 mod router;
+mod runtime;
+mod sdp;
+mod stats;
+mod stats_subscription;
+mod subscription;
 mod supported_rtp_capabilities;
 mod task;
 mod term_box;
+mod validation;
 mod webrtc_server;
 mod webrtc_transport;
 mod worker;
 
+use crate::async_request::AsyncRequestRef;
 use crate::resource::DisposableResourceWrapper;
 use crate::term_box::TermBox;
 
 use futures_lite::future;
-use rustler::{Atom, Encoder, Env, LocalPid, NifResult, OwnedEnv};
+use rustler::{Atom, Encoder, Env, LocalPid, NifResult, OwnedEnv, ResourceArc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub fn send_msg_from_other_thread<T>(pid: LocalPid, value: T)
 where
     T: rustler::Encoder + Send + 'static,
 {
-    let mut my_env = OwnedEnv::new();
-    task::spawn(async move {
-        let _ = my_env.send_and_clear(&pid, |env| value.encode(env));
-    })
-    .detach();
+    event_dispatcher::dispatch(pid, value);
 }
 
-pub fn send_async_nif_result<T, E, Fut>(env: Env, future: Fut) -> NifResult<(Atom, Atom)>
+/// Runs `future` and replies `{:mediasoup_async_nif_result, request, result}`.
+/// Returns `{:ok, request}`; see `AsyncRequestRef`. With `timeout_ms`, `{:error, :timeout}` is
+/// replied once the deadline passes and the late result is dropped, as for a cancelled request.
+pub fn send_async_nif_result<T, E, Fut>(
+    env: Env,
+    name: &'static str,
+    timeout_ms: Option<u32>,
+    future: Fut,
+) -> NifResult<(Atom, ResourceArc<AsyncRequestRef>)>
 where
-    T: Encoder,
+    T: Encoder + Send + 'static,
     E: Encoder,
     Fut: future::Future<Output = Result<T, E>> + Send + 'static,
 {
     let pid = env.pid();
     let mut my_env = OwnedEnv::new();
-    let result_key = atoms::mediasoup_async_nif_result();
-    task::spawn(async move {
-        let result = future.await;
-        match result {
-            Ok(worker) => {
-                let _ = my_env
-                    .send_and_clear(&pid, |env| (result_key, (atoms::ok(), worker)).encode(env));
-            }
-            Err(err) => {
-                let _ = my_env
-                    .send_and_clear(&pid, |env| (result_key, (atoms::error(), err)).encode(env));
+    let request = ResourceArc::new(AsyncRequestRef::new());
+    let reply_request = request.clone();
+
+    let timeout = timeout_ms.map(|timeout_ms| {
+        let request = request.clone();
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms.into());
+        event_dispatcher::schedule(deadline, move || {
+            if !request.complete() {
+                return;
             }
+            metrics::async_nif_timed_out(name);
+            let _ = OwnedEnv::new().send_and_clear(&pid, |env| {
+                let result = (atoms::error(), atoms::timeout());
+                (atoms::mediasoup_async_nif_result(), request, result).encode(env)
+            });
+        })
+    });
+
+    let timer = metrics::AsyncNifTimer::start(name);
+    task::spawn(async move {
+        let timer = timer.running();
+        let result = task::catch_panic(name, future).await;
+        timer.finished();
+        if let Some(timeout) = timeout {
+            event_dispatcher::cancel(timeout);
+        }
+        if !reply_request.complete() {
+            // Cancelled: dropping the result releases what it created.
+            return;
         }
+        let _ = my_env.send_and_clear(&pid, |env| {
+            let result = match result {
+                Some(Ok(value)) => (atoms::ok(), value).encode(env),
+                Some(Err(err)) => (atoms::error(), err).encode(env),
+                None => (atoms::error(), atoms::panicked()).encode(env),
+            };
+            (atoms::mediasoup_async_nif_result(), reply_request, result).encode(env)
+        });
     })
     .detach();
 
-    Ok((atoms::ok(), result_key))
+    Ok((atoms::ok(), request))
 }
 
+/// Runs `future` and replies `{:mediasoup_async_nif_result, from, result}`, or
+/// `{:error, :panicked}` as the result if the future panicked. With `timeout_ms`, `{:error, :timeout}` is replied once the deadline passes. The future still
+/// runs to completion so the worker state stays consistent, and its late result is dropped,
+/// which closes any resource it created.
 pub fn send_async_nif_result_with_from<T, Fut>(
     env: Env,
+    name: &'static str,
     from: rustler::Term,
+    timeout_ms: Option<u32>,
     future: Fut,
 ) -> NifResult<Atom>
 where
@@ -72,13 +125,43 @@ where
     Fut: future::Future<Output = T> + Send + 'static,
 {
     let pid = env.pid();
-    let mut my_env = OwnedEnv::new();
+    let from = Arc::new(TermBox::new(from));
+    let replied = Arc::new(AtomicBool::new(false));
 
-    let from = TermBox::new(from);
+    let timeout = timeout_ms.map(|timeout_ms| {
+        let from = Arc::clone(&from);
+        let replied = Arc::clone(&replied);
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms.into());
+        event_dispatcher::schedule(deadline, move || {
+            if replied.swap(true, Ordering::AcqRel) {
+                return;
+            }
+            metrics::async_nif_timed_out(name);
+            let _ = OwnedEnv::new().send_and_clear(&pid, |env| {
+                let result = (atoms::error(), atoms::timeout());
+                (atoms::mediasoup_async_nif_result(), from.get(env), result).encode(env)
+            });
+        })
+    });
+
+    let mut my_env = OwnedEnv::new();
+    let timer = metrics::AsyncNifTimer::start(name);
     task::spawn(async move {
-        let result = future.await;
+        let timer = timer.running();
+        let result = task::catch_panic(name, future).await;
+        timer.finished();
+        if let Some(timeout) = timeout {
+            event_dispatcher::cancel(timeout);
+        }
+        if replied.swap(true, Ordering::AcqRel) {
+            return;
+        }
 
         let _ = my_env.send_and_clear(&pid, |env| {
+            let result = match result {
+                Some(result) => result.encode(env),
+                None => (atoms::error(), atoms::panicked()).encode(env),
+            };
             (atoms::mediasoup_async_nif_result(), from.get(env), result).encode(env)
         });
     })
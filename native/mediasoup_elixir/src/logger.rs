@@ -1,58 +1,261 @@
 use crate::atoms;
-use crate::send_msg_from_other_thread;
-use log::{Level, Metadata, Record};
-use rustler::{NifStruct, NifUnitEnum};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Mutex;
+use crate::event_dispatcher::{self, Envelope};
+use log::kv::{Key, Value, VisitSource};
+use log::{Level, LevelFilter, Metadata, Record};
+use once_cell::sync::Lazy;
+use rustler::{Atom, LocalPid, NifResult, NifStruct, NifUnitEnum, ResourceArc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Records waiting for delivery to a sink before new ones are dropped, unless configured.
+const DEFAULT_MAX_PENDING: usize = 1000;
+
+/// Filters of a log sink. A record is sent when it passes all of them.
+pub struct LogSinkOptionsStruct {
+    max_level: Option<NifLevelFilter>,
+    /// Targets the record target must start with. Empty or unset accepts every target.
+    target_prefixes: Option<Vec<String>>,
+    /// Classes (`RTC::IceServer`, `RTC::Dtls`, ...) worker records must start with.
+    /// Unset accepts every class; records without a class are not affected.
+    worker_class_prefixes: Option<Vec<String>>,
+    max_pending: Option<u32>,
+}
+
+crate::nif_struct_decoder!(
+    LogSinkOptionsStruct,
+    "Mediasoup.LoggerProxy.SinkOptions",
+    [
+        max_level,
+        target_prefixes,
+        worker_class_prefixes,
+        max_pending
+    ]
+);
+
+struct LogSink {
+    id: usize,
+    pid: LocalPid,
+    max_level: LevelFilter,
+    target_prefixes: Vec<String>,
+    worker_class_prefixes: Option<Vec<String>>,
+    max_pending: usize,
+    /// Sink registered by `set_logger_proxy_process`, replaced by the next call.
+    legacy: bool,
+    /// Only records attributed to this worker are sent. See `set_worker_sink`.
+    worker_id: Option<String>,
+    queued: Arc<AtomicUsize>,
+    dropped: AtomicU64,
+}
+
+impl LogSink {
+    fn new(pid: LocalPid, options: LogSinkOptionsStruct) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            pid,
+            max_level: options
+                .max_level
+                .map_or(LevelFilter::Info, LevelFilter::from),
+            target_prefixes: options.target_prefixes.unwrap_or_default(),
+            worker_class_prefixes: options.worker_class_prefixes,
+            max_pending: options
+                .max_pending
+                .map_or(DEFAULT_MAX_PENDING, |len| len as usize),
+            legacy: false,
+            worker_id: None,
+            queued: Arc::new(AtomicUsize::new(0)),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn accepts(&self, record: &Record, ids: &RecordIds) -> bool {
+        if record.level() > self.max_level {
+            return false;
+        }
+        if self.worker_id.is_some() && self.worker_id != ids.worker_id {
+            return false;
+        }
+        if !self.target_prefixes.is_empty()
+            && !self
+                .target_prefixes
+                .iter()
+                .any(|prefix| record.target().starts_with(prefix.as_str()))
+        {
+            return false;
+        }
+        match (&self.worker_class_prefixes, &ids.worker_log_class) {
+            (Some(prefixes), Some(class)) => prefixes
+                .iter()
+                .any(|prefix| class.starts_with(prefix.as_str())),
+            _ => true,
+        }
+    }
+
+    fn send(&self, record: LoggerProxyRecord) {
+        if self.queued.load(Ordering::Relaxed) >= self.max_pending {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let envelope = Envelope::new(Box::new(record)).count_in(Arc::clone(&self.queued));
+        event_dispatcher::dispatch_envelope(self.pid, envelope);
+    }
+}
 
 struct LoggerProxy {
-    pid: Mutex<Option<rustler::LocalPid>>,
-    max_level: AtomicUsize,
+    sinks: RwLock<Vec<Arc<LogSink>>>,
+    installed: AtomicBool,
 }
 
 impl LoggerProxy {
-    fn logger_proxy_process(&self) -> Option<rustler::LocalPid> {
-        let guard = self.pid.lock().unwrap();
-        *guard
+    fn add_sink(&self, sink: Arc<LogSink>) {
+        if !self.installed.swap(true, Ordering::Relaxed) {
+            let _ = log::set_logger(&*LOGGER_PROXY);
+        }
+        if let Ok(mut sinks) = self.sinks.write() {
+            if sink.legacy {
+                sinks.retain(|registered| !registered.legacy);
+            }
+            if sink.worker_id.is_some() {
+                sinks.retain(|registered| registered.worker_id != sink.worker_id);
+            }
+            sinks.push(sink);
+            Self::update_max_level(&sinks);
+        }
     }
-    fn set_proxy_process(&self, pid: rustler::LocalPid) {
-        if let Ok(ref mut mutex) = self.pid.lock() {
-            **mutex = Some(pid);
+
+    fn remove_sink(&self, id: usize) {
+        self.remove_sinks(|sink| sink.id == id);
+    }
+
+    fn remove_sinks(&self, predicate: impl Fn(&LogSink) -> bool) {
+        if let Ok(mut sinks) = self.sinks.write() {
+            sinks.retain(|registered| !predicate(registered));
+            Self::update_max_level(&sinks);
         }
     }
-    fn set_max_level(&self, filter: log::LevelFilter) {
-        log::set_max_level(filter);
-        self.max_level.store(filter as usize, Ordering::Relaxed);
+
+    fn update_max_level(sinks: &[Arc<LogSink>]) {
+        let max_level = sinks
+            .iter()
+            .map(|sink| sink.max_level)
+            .max()
+            .unwrap_or(LevelFilter::Off);
+        log::set_max_level(max_level);
     }
 }
 
-fn ignore_log(record: &Record) -> bool {
+fn ignore_log(body: &str) -> bool {
     // Ignore this as it is an error log with no way to deal with it.
-    record
-        .args()
-        .to_string()
-        .ends_with("Channel already closed")
+    body.ends_with("Channel already closed")
+}
+
+/// Worker, router and transport a record is about, parsed from its body.
+/// Worker lines look like `[id:<worker id>] RTC::IceServer::ProcessStunPacket() | ...`; the
+/// worker writes neither the log tag nor, unless built to, the file and line.
+#[derive(Default, PartialEq, Debug)]
+struct RecordIds {
+    worker_id: Option<String>,
+    worker_log_class: Option<String>,
+    router_id: Option<String>,
+    transport_id: Option<String>,
+}
+
+impl RecordIds {
+    fn parse(target: &str, body: &str) -> Self {
+        let mut ids = Self {
+            router_id: id_after(body, "routerId"),
+            transport_id: id_after(body, "transportId"),
+            ..Self::default()
+        };
+        if !target.starts_with("mediasoup::worker") {
+            return ids;
+        }
+        let Some((prefix, text)) = body.split_once("] ") else {
+            return ids;
+        };
+        if let Some((_, worker_id)) = prefix.split_once("[id:") {
+            ids.worker_id = Some(worker_id.to_string());
+        }
+        ids.worker_log_class = worker_log_class(text);
+        ids
+    }
+}
+
+/// The class of the method that logged a worker line, such as `RTC::IceServer`.
+fn worker_log_class(text: &str) -> Option<String> {
+    let method = text
+        .trim_start_matches("(trace) ")
+        .split(" | ")
+        .find(|part| part.ends_with("()"))?;
+    let (class, _) = method.strip_suffix("()")?.rsplit_once("::")?;
+    Some(class.to_string())
+}
+
+/// The uuid following `key` in `body`, such as the router id in `routerId: "..."`.
+fn id_after(body: &str, key: &str) -> Option<String> {
+    let (_, rest) = body.split_once(key)?;
+    let rest = rest.trim_start_matches([':', '=', '"', ' ']);
+    let id: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_hexdigit() || *c == '-')
+        .collect();
+    (id.len() == 36).then_some(id)
+}
+
+#[derive(Default)]
+struct KeyValues(HashMap<String, String>);
+
+impl<'kvs> VisitSource<'kvs> for KeyValues {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
 }
 
 impl log::Log for LoggerProxy {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        (metadata.level() as usize) < self.max_level.load(Ordering::Relaxed)
+        metadata.level() <= log::max_level()
     }
 
     fn log(&self, record: &Record) {
-        if ignore_log(record) {
+        if !self.enabled(record.metadata()) {
             return;
         }
-        if let Option::Some(pid) = self.logger_proxy_process() {
-            let rec = LoggerProxyRecord {
-                level: record.level().into(),
-                target: record.target().to_string(),
-                body: record.args().to_string(),
-                module_path: record.module_path().map(str::to_string),
-                file: record.file().map(str::to_string),
-                line: record.line(),
-            };
-            send_msg_from_other_thread(pid, rec);
+        let body = record.args().to_string();
+        if ignore_log(&body) {
+            return;
+        }
+        let Ok(sinks) = self.sinks.read() else {
+            return;
+        };
+        let ids = RecordIds::parse(record.target(), &body);
+        let receivers: Vec<_> = sinks
+            .iter()
+            .filter(|sink| sink.accepts(record, &ids))
+            .collect();
+        if receivers.is_empty() {
+            return;
+        }
+
+        let mut key_values = KeyValues::default();
+        let _ = record.key_values().visit(&mut key_values);
+        let rec = LoggerProxyRecord {
+            level: record.level().into(),
+            target: record.target().to_string(),
+            worker_id: ids.worker_id,
+            worker_log_class: ids.worker_log_class,
+            router_id: ids.router_id,
+            transport_id: ids.transport_id,
+            metadata: key_values.0,
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            body,
+        };
+        for sink in receivers {
+            sink.send(rec.clone());
         }
     }
     fn flush(&self) {}
@@ -63,24 +266,85 @@ pub fn init_env_logger() {
     env_logger::init();
 }
 
-static LOGGER_PROXY: LoggerProxy = LoggerProxy {
-    pid: Mutex::new(None),
-    max_level: AtomicUsize::new(log::LevelFilter::Info as usize),
-};
+static LOGGER_PROXY: Lazy<LoggerProxy> = Lazy::new(|| LoggerProxy {
+    sinks: RwLock::new(Vec::new()),
+    installed: AtomicBool::new(false),
+});
+
+/// A sink registered by `logger_add_sink`. It is removed by `logger_remove_sink`,
+/// or when the resource is garbage-collected.
+pub struct LogSinkRef {
+    sink: Arc<LogSink>,
+}
+#[rustler::resource_impl]
+impl rustler::Resource for LogSinkRef {}
+
+impl Drop for LogSinkRef {
+    fn drop(&mut self) {
+        LOGGER_PROXY.remove_sink(self.sink.id);
+    }
+}
+
+/// Sends every record up to `filter` to `pid`, replacing the pid set by the previous call.
+#[rustler::nif]
+pub fn set_logger_proxy_process(pid: LocalPid, filter: NifLevelFilter) -> NifResult<Atom> {
+    let options = LogSinkOptionsStruct {
+        max_level: Some(filter),
+        target_prefixes: None,
+        worker_class_prefixes: None,
+        max_pending: None,
+    };
+    let sink = LogSink {
+        legacy: true,
+        ..LogSink::new(pid, options)
+    };
+    LOGGER_PROXY.add_sink(Arc::new(sink));
+
+    Ok(atoms::ok())
+}
 
 #[rustler::nif]
-pub fn set_logger_proxy_process(
-    pid: rustler::LocalPid,
-    filter: NifLevelFilter,
-) -> rustler::NifResult<rustler::Atom> {
-    let _ = log::set_logger(&LOGGER_PROXY);
+pub fn logger_add_sink(
+    pid: LocalPid,
+    options: LogSinkOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<LogSinkRef>)> {
+    let sink = Arc::new(LogSink::new(pid, options));
+    LOGGER_PROXY.add_sink(Arc::clone(&sink));
 
-    LOGGER_PROXY.set_proxy_process(pid);
-    LOGGER_PROXY.set_max_level(filter.into());
+    Ok((atoms::ok(), ResourceArc::new(LogSinkRef { sink })))
+}
 
+#[rustler::nif]
+pub fn logger_remove_sink(sink: ResourceArc<LogSinkRef>) -> NifResult<Atom> {
+    LOGGER_PROXY.remove_sink(sink.sink.id);
     Ok(atoms::ok())
 }
 
+/// Sends the records attributed to `worker_id` to `pid`, replacing the previous sink of the
+/// worker. `None` removes the sink.
+pub fn set_worker_sink(
+    worker_id: String,
+    pid: Option<LocalPid>,
+    options: Option<LogSinkOptionsStruct>,
+) {
+    match pid.zip(options) {
+        Some((pid, options)) => {
+            let sink = LogSink {
+                worker_id: Some(worker_id),
+                ..LogSink::new(pid, options)
+            };
+            LOGGER_PROXY.add_sink(Arc::new(sink));
+        }
+        _ => LOGGER_PROXY.remove_sinks(|sink| sink.worker_id.as_ref() == Some(&worker_id)),
+    }
+}
+
+/// Number of records dropped because the sink had `max_pending` records waiting.
+#[rustler::nif]
+pub fn logger_sink_dropped(sink: ResourceArc<LogSinkRef>) -> u64 {
+    sink.sink.dropped.load(Ordering::Relaxed)
+}
+
 #[rustler::nif]
 pub fn debug_logger(level: NifLevel, message: String) -> rustler::NifResult<rustler::Atom> {
     match level {
@@ -93,18 +357,23 @@ pub fn debug_logger(level: NifLevel, message: String) -> rustler::NifResult<rust
     Ok(atoms::ok())
 }
 
-#[derive(NifStruct)]
+#[derive(NifStruct, Clone)]
 #[module = "Mediasoup.LoggerProxy.Record"]
 pub struct LoggerProxyRecord {
     level: NifLevel,
     target: String,
     body: String,
+    worker_id: Option<String>,
+    worker_log_class: Option<String>,
+    router_id: Option<String>,
+    transport_id: Option<String>,
+    metadata: HashMap<String, String>,
     module_path: Option<String>,
     file: Option<String>,
     line: Option<u32>,
 }
 
-#[derive(NifUnitEnum)]
+#[derive(NifUnitEnum, Clone, Copy)]
 enum NifLevel {
     Error,
     Warn,
@@ -113,7 +382,7 @@ enum NifLevel {
 }
 
 #[derive(NifUnitEnum)]
-enum NifLevelFilter {
+pub enum NifLevelFilter {
     Off,
     Error,
     Warn,
@@ -146,3 +415,57 @@ impl From<NifLevelFilter> for log::LevelFilter {
         }
     }
 }
+
+impl<'a> crate::json_serde::DecodeField<'a> for NifLevelFilter {
+    fn decode_field(term: rustler::Term<'a>) -> Result<Self, crate::json_serde::InvalidArgument> {
+        term.decode().map_err(|_| {
+            crate::json_serde::InvalidArgument::new(
+                "invalid value, expected :off, :error, :warn, :info, :debug or :trace",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordIds;
+
+    #[test]
+    fn parses_worker_record_ids() {
+        let ids = RecordIds::parse(
+            "mediasoup::worker",
+            "[id:d9e6b7f3] RTC::IceServer::ProcessStunPacket() | transition from checking",
+        );
+        assert_eq!(Some("d9e6b7f3"), ids.worker_id.as_deref());
+        assert_eq!(Some("RTC::IceServer"), ids.worker_log_class.as_deref());
+
+        let ids = RecordIds::parse(
+            "mediasoup::worker",
+            "[id:d9e6b7f3] ../src/RTC/Router.cpp:42 | RTC::Router::Close()",
+        );
+        assert_eq!(Some("RTC::Router"), ids.worker_log_class.as_deref());
+
+        let ids = RecordIds::parse("mediasoup::worker", "[id:d9e6b7f3] worker started");
+        assert_eq!(Some("d9e6b7f3"), ids.worker_id.as_deref());
+        assert_eq!(None, ids.worker_log_class);
+
+        let body = "[id:d9e6b7f3] RTC::IceServer::ProcessStunPacket() | ...";
+        assert_eq!(
+            RecordIds::default(),
+            RecordIds::parse("mediasoup::router", body)
+        );
+    }
+
+    #[test]
+    fn parses_entity_ids() {
+        let ids = RecordIds::parse(
+            "mediasoup::router",
+            "closing [routerId: \"3c6a5c7e-9a38-4bfc-a1d5-3b6f0e1a2c4d\", transportId:bad]",
+        );
+        assert_eq!(
+            Some("3c6a5c7e-9a38-4bfc-a1d5-3b6f0e1a2c4d"),
+            ids.router_id.as_deref()
+        );
+        assert_eq!(None, ids.transport_id);
+    }
+}
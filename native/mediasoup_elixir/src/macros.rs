@@ -1,73 +1,70 @@
 #[doc(hidden)]
 #[macro_export]
 macro_rules! reg_callback {
-    ($pid: ident, $value: ident, $event_name: ident) => {{
-        let pid = $pid.clone();
-        $value
-            .$event_name(move || {
-                let pid = pid.clone();
-                $crate::send_msg_from_other_thread(
-                    pid,
-                    (atoms::nif_internal_event(), atoms::$event_name()),
-                )
-            })
-            .detach();
+    ($sink: ident, $value: ident, $event_name: ident) => {{
+        let sink = $sink.clone();
+        $value.$event_name(move || sink.send(atoms::$event_name()))
     }};
 }
 #[doc(hidden)]
 #[macro_export]
 macro_rules! reg_callback_once {
-    ($pid: ident, $value: ident, $event_name: ident) => {{
-        let pid = $pid.clone();
-        $value
-            .$event_name(Box::new(move || {
-                $crate::send_msg_from_other_thread(
-                    pid,
-                    (atoms::nif_internal_event(), atoms::$event_name()),
-                )
-            }))
-            .detach();
+    ($sink: ident, $value: ident, $event_name: ident) => {{
+        let sink = $sink.clone();
+        $value.$event_name(Box::new(move || sink.send(atoms::$event_name())))
     }};
 }
 
+/// Sends the enum passed to the callback as an atom, see `AsAtom`.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! reg_callback_json_param {
-    ($pid: ident, $value: ident, $event_name: ident) => {{
-        let pid = $pid.clone();
-        $value
-            .$event_name(move |arg| {
-                let pid = pid.clone();
-                $crate::send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::$event_name(),
-                        $crate::json_serde::JsonSerdeWrap::new(arg),
-                    ),
-                )
-            })
-            .detach();
+macro_rules! reg_callback_atom_param {
+    ($sink: ident, $value: ident, $event_name: ident) => {{
+        let sink = $sink.clone();
+        $value.$event_name(move |arg| {
+            sink.send_value(
+                atoms::$event_name(),
+                $crate::json_serde::AsAtom::as_atom(&arg),
+            )
+        })
     }};
 }
 
+/// Sends the transport tuple passed to the callback as a `%TransportTuple{}`.
 #[doc(hidden)]
 #[macro_export]
-macro_rules! reg_callback_json_clone_param {
-    ($pid: ident, $value: ident, $event_name: ident) => {{
-        let pid = $pid.clone();
-        $value
-            .$event_name(move |arg| {
-                let pid = pid.clone();
-                $crate::send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::$event_name(),
-                        $crate::json_serde::JsonSerdeWrap::new(arg.clone()),
-                    ),
-                )
-            })
-            .detach();
+macro_rules! reg_callback_tuple_param {
+    ($sink: ident, $value: ident, $event_name: ident) => {{
+        let sink = $sink.clone();
+        $value.$event_name(move |tuple| {
+            sink.send_value(
+                atoms::$event_name(),
+                $crate::data_structure::TransportTupleStruct::from(tuple),
+            )
+        })
     }};
 }
+
+/// Implements `rustler::Decoder` for an option struct decoded from an elixir struct.
+/// Unlike `NifStruct`, errors report the path of the invalid field.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! nif_struct_decoder {
+    ($struct_name: ident, $module: literal, [$($field: ident),* $(,)?]) => {
+        impl<'a> $crate::json_serde::DecodeField<'a> for $struct_name {
+            fn decode_field(
+                term: rustler::Term<'a>,
+            ) -> Result<Self, $crate::json_serde::InvalidArgument> {
+                $crate::json_serde::check_struct_module(term, concat!("Elixir.", $module))?;
+                Ok(Self {
+                    $($field: $crate::json_serde::decode_struct_field(term, stringify!($field))?,)*
+                })
+            }
+        }
+        impl<'a> rustler::Decoder<'a> for $struct_name {
+            fn decode(term: rustler::Term<'a>) -> rustler::NifResult<Self> {
+                Ok(<Self as $crate::json_serde::DecodeField<'a>>::decode_field(term)?)
+            }
+        }
+    };
+}
@@ -0,0 +1,270 @@
+//! Counters and latency histograms kept in the crate, returned by `metrics_snapshot`.
+
+use crate::entity::{Entity, EntityKind, ENTITY_KINDS};
+use once_cell::sync::Lazy;
+use rustler::NifMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the histogram buckets, in microseconds.
+const BUCKET_BOUNDS_US: [u64; 14] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 5_000_000,
+];
+
+#[derive(Default)]
+struct Histogram {
+    /// One count per bound, plus one for values above the last bound.
+    counts: [AtomicU64; BUCKET_BOUNDS_US.len() + 1],
+    sum_us: AtomicU64,
+}
+
+impl Histogram {
+    fn record(&self, duration: Duration) {
+        let us = duration.as_micros() as u64;
+        let index = BUCKET_BOUNDS_US
+            .iter()
+            .position(|bound| us <= *bound)
+            .unwrap_or(BUCKET_BOUNDS_US.len());
+        self.counts[index].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> HistogramMap {
+        let counts = self
+            .counts
+            .each_ref()
+            .map(|count| count.load(Ordering::Relaxed));
+        let mut cumulative = 0;
+        let buckets = BUCKET_BOUNDS_US
+            .iter()
+            .zip(counts.iter())
+            .map(|(bound, count)| {
+                cumulative += count;
+                (*bound, cumulative)
+            })
+            .collect();
+        HistogramMap {
+            buckets,
+            count: counts.iter().sum(),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Default)]
+struct AsyncNifMetrics {
+    calls: AtomicU64,
+    timeouts: AtomicU64,
+    queue_time: Histogram,
+    run_time: Histogram,
+}
+
+/// Metrics of each async NIF, created on its first call and never freed: there is one per NIF
+/// name, so a call only takes the read lock to find them.
+static ASYNC_NIFS: Lazy<RwLock<BTreeMap<&'static str, &'static AsyncNifMetrics>>> =
+    Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+fn async_nif(name: &'static str) -> &'static AsyncNifMetrics {
+    if let Some(metrics) = ASYNC_NIFS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+    {
+        return metrics;
+    }
+    ASYNC_NIFS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(name)
+        .or_insert_with(|| Box::leak(Box::default()))
+}
+
+/// Measures one async NIF call: the time until its task starts running (queue time),
+/// then the time the task takes, which is mostly the round trip to the worker.
+pub struct AsyncNifTimer {
+    metrics: &'static AsyncNifMetrics,
+    started_at: Instant,
+}
+
+impl AsyncNifTimer {
+    pub fn start(name: &'static str) -> Self {
+        let metrics = async_nif(name);
+        metrics.calls.fetch_add(1, Ordering::Relaxed);
+        Self {
+            metrics,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Called when the task is first polled.
+    pub fn running(self) -> Self {
+        let now = Instant::now();
+        self.metrics.queue_time.record(now - self.started_at);
+        Self {
+            metrics: self.metrics,
+            started_at: now,
+        }
+    }
+
+    pub fn finished(self) {
+        self.metrics.run_time.record(self.started_at.elapsed());
+    }
+}
+
+pub fn async_nif_timed_out(name: &'static str) {
+    async_nif(name).timeouts.fetch_add(1, Ordering::Relaxed);
+}
+
+struct EntityCounters {
+    created: AtomicU64,
+    closed: AtomicU64,
+}
+
+static ENTITIES: [EntityCounters; ENTITY_KINDS] = [const {
+    EntityCounters {
+        created: AtomicU64::new(0),
+        closed: AtomicU64::new(0),
+    }
+}; ENTITY_KINDS];
+
+/// Counts `entity` as created, and as closed once it closes.
+pub fn track_entity<T: Entity>(entity: &T) {
+    let counters = &ENTITIES[T::KIND as usize];
+    counters.created.fetch_add(1, Ordering::Relaxed);
+    if entity.closed() {
+        counters.closed.fetch_add(1, Ordering::Relaxed);
+    } else {
+        entity
+            .add_close_handler(move || {
+                counters.closed.fetch_add(1, Ordering::Relaxed);
+            })
+            .detach();
+    }
+}
+
+#[derive(NifMap)]
+pub struct HistogramMap {
+    /// `{upper_bound_us, cumulative_count}`; `count` includes the values above the last bound.
+    buckets: Vec<(u64, u64)>,
+    count: u64,
+    sum_us: u64,
+}
+
+#[derive(NifMap)]
+pub struct AsyncNifMap {
+    calls: u64,
+    timeouts: u64,
+    queue_time: HistogramMap,
+    run_time: HistogramMap,
+}
+
+#[derive(NifMap)]
+pub struct EntityMap {
+    live: u64,
+    created: u64,
+    closed: u64,
+}
+
+impl EntityMap {
+    fn of(kind: EntityKind) -> Self {
+        let counters = &ENTITIES[kind as usize];
+        let created = counters.created.load(Ordering::Relaxed);
+        let closed = counters.closed.load(Ordering::Relaxed);
+        Self {
+            live: created.saturating_sub(closed),
+            created,
+            closed,
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct EntitiesMap {
+    worker: EntityMap,
+    webrtc_server: EntityMap,
+    router: EntityMap,
+    webrtc_transport: EntityMap,
+    plain_transport: EntityMap,
+    pipe_transport: EntityMap,
+    producer: EntityMap,
+    consumer: EntityMap,
+    data_producer: EntityMap,
+    data_consumer: EntityMap,
+}
+
+#[derive(NifMap)]
+pub struct MetricsSnapshot {
+    async_nifs: HashMap<String, AsyncNifMap>,
+    entities: EntitiesMap,
+}
+
+#[rustler::nif]
+pub fn metrics_snapshot() -> MetricsSnapshot {
+    let async_nifs = ASYNC_NIFS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(name, metrics)| {
+            let map = AsyncNifMap {
+                calls: metrics.calls.load(Ordering::Relaxed),
+                timeouts: metrics.timeouts.load(Ordering::Relaxed),
+                queue_time: metrics.queue_time.snapshot(),
+                run_time: metrics.run_time.snapshot(),
+            };
+            (name.to_string(), map)
+        })
+        .collect();
+
+    MetricsSnapshot {
+        async_nifs,
+        entities: EntitiesMap {
+            worker: EntityMap::of(EntityKind::Worker),
+            webrtc_server: EntityMap::of(EntityKind::WebRtcServer),
+            router: EntityMap::of(EntityKind::Router),
+            webrtc_transport: EntityMap::of(EntityKind::WebRtcTransport),
+            plain_transport: EntityMap::of(EntityKind::PlainTransport),
+            pipe_transport: EntityMap::of(EntityKind::PipeTransport),
+            producer: EntityMap::of(EntityKind::Producer),
+            consumer: EntityMap::of(EntityKind::Consumer),
+            data_producer: EntityMap::of(EntityKind::DataProducer),
+            data_consumer: EntityMap::of(EntityKind::DataConsumer),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let histogram = Histogram::default();
+        histogram.record(Duration::from_micros(50));
+        histogram.record(Duration::from_micros(300));
+        histogram.record(Duration::from_secs(10));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(3, snapshot.count);
+        assert_eq!(10_000_350, snapshot.sum_us);
+        assert_eq!((100, 1), snapshot.buckets[0]);
+        assert_eq!((500, 2), snapshot.buckets[2]);
+        assert_eq!(Some(&(5_000_000, 2)), snapshot.buckets.last());
+    }
+
+    #[test]
+    fn async_nif_metrics_are_registered_once() {
+        let timer = AsyncNifTimer::start("metrics_test_async");
+        assert!(std::ptr::eq(timer.metrics, async_nif("metrics_test_async")));
+        timer.running().finished();
+        async_nif_timed_out("metrics_test_async");
+
+        let metrics = async_nif("metrics_test_async");
+        assert_eq!(1, metrics.calls.load(Ordering::Relaxed));
+        assert_eq!(1, metrics.timeouts.load(Ordering::Relaxed));
+        assert_eq!(1, metrics.queue_time.snapshot().count);
+        assert_eq!(1, metrics.run_time.snapshot().count);
+    }
+}
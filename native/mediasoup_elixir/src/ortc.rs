@@ -0,0 +1,1114 @@
+//! RTP capabilities and parameters negotiation, run without a router or a transport.
+//!
+//! `mediasoup::ortc` keeps these helpers private. Codecs are matched with the same rules here:
+//! mime type, clock rate and channels, then the multiopus streams, the H264 packetization mode
+//! and profile, and the VP9 profile. A mismatch is reported with its reason.
+
+use crate::json_serde::{EnumAtom, JsonSerdeWrap};
+use mediasoup::types::rtp_parameters::{
+    MediaKind, MimeType, MimeTypeAudio, MimeTypeVideo, RtcpFeedback, RtcpParameters,
+    RtpCapabilities, RtpCapabilitiesFinalized, RtpCodecCapability, RtpCodecCapabilityFinalized,
+    RtpCodecParameters, RtpCodecParametersParameters, RtpCodecParametersParametersValue,
+    RtpEncodingParameters, RtpEncodingParametersRtx, RtpHeaderExtensionDirection,
+    RtpHeaderExtensionParameters, RtpHeaderExtensionUri, RtpParameters,
+};
+use rustler::NifStruct;
+use serde::Serialize;
+use std::num::{NonZeroU32, NonZeroU8};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The capabilities or parameters cannot be used with the router.
+    Error,
+    /// A codec, feature or header extension is dropped.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    Codec,
+    RtcpFeedback,
+    HeaderExtension,
+    Encoding,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    /// The codec mime type, header extension uri or encoding the issue is about.
+    pub subject: String,
+    pub reason: String,
+}
+
+impl Issue {
+    fn error(kind: IssueKind, subject: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            kind,
+            subject: subject.into(),
+            reason: reason.into(),
+        }
+    }
+
+    fn warning(kind: IssueKind, subject: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(kind, subject, reason)
+        }
+    }
+}
+
+#[derive(NifStruct)]
+#[rustler(encode)]
+#[module = "Mediasoup.Ortc.Issue"]
+pub struct IssueStruct {
+    severity: EnumAtom,
+    kind: EnumAtom,
+    subject: String,
+    reason: String,
+}
+
+impl From<Issue> for IssueStruct {
+    fn from(issue: Issue) -> Self {
+        Self {
+            severity: EnumAtom::new(&issue.severity),
+            kind: EnumAtom::new(&issue.kind),
+            subject: issue.subject,
+            reason: issue.reason,
+        }
+    }
+}
+
+/// The fields of a codec capability or of codec parameters that the negotiation looks at.
+struct CodecView<'a> {
+    mime_type: MimeType,
+    clock_rate: NonZeroU32,
+    channels: Option<NonZeroU8>,
+    parameters: &'a RtpCodecParametersParameters,
+    rtcp_feedback: &'a [RtcpFeedback],
+    /// The preferred payload type of a capability, the payload type of parameters.
+    payload_type: Option<u8>,
+}
+
+macro_rules! impl_codec_view {
+    ($codec:ident, $payload_type:ident) => {
+        impl<'a> From<&'a $codec> for CodecView<'a> {
+            fn from(codec: &'a $codec) -> Self {
+                match codec {
+                    $codec::Audio {
+                        mime_type,
+                        $payload_type,
+                        clock_rate,
+                        channels,
+                        parameters,
+                        rtcp_feedback,
+                    } => Self {
+                        mime_type: MimeType::Audio(*mime_type),
+                        clock_rate: *clock_rate,
+                        channels: Some(*channels),
+                        parameters,
+                        rtcp_feedback,
+                        payload_type: Option::from(*$payload_type),
+                    },
+                    $codec::Video {
+                        mime_type,
+                        $payload_type,
+                        clock_rate,
+                        parameters,
+                        rtcp_feedback,
+                    } => Self {
+                        mime_type: MimeType::Video(*mime_type),
+                        clock_rate: *clock_rate,
+                        channels: None,
+                        parameters,
+                        rtcp_feedback,
+                        payload_type: Option::from(*$payload_type),
+                    },
+                }
+            }
+        }
+    };
+}
+
+impl_codec_view!(RtpCodecCapability, preferred_payload_type);
+impl_codec_view!(RtpCodecCapabilityFinalized, preferred_payload_type);
+impl_codec_view!(RtpCodecParameters, payload_type);
+
+impl CodecView<'_> {
+    fn is_rtx(&self) -> bool {
+        matches!(
+            self.mime_type,
+            MimeType::Audio(MimeTypeAudio::Rtx) | MimeType::Video(MimeTypeVideo::Rtx)
+        )
+    }
+
+    fn kind(&self) -> MediaKind {
+        match self.mime_type {
+            MimeType::Audio(_) => MediaKind::Audio,
+            MimeType::Video(_) => MediaKind::Video,
+        }
+    }
+
+    fn subject(&self) -> &'static str {
+        self.mime_type.as_str()
+    }
+
+    /// The payload type of the media codec of an RTX codec.
+    fn apt(&self) -> Option<u32> {
+        match self.parameters.get("apt") {
+            Some(RtpCodecParametersParametersValue::Number(apt)) => Some(*apt),
+            _ => None,
+        }
+    }
+
+    fn invalid_apt(&self) -> Option<Issue> {
+        match self.parameters.get("apt") {
+            Some(RtpCodecParametersParametersValue::String(apt)) => Some(Issue::error(
+                IssueKind::Codec,
+                self.subject(),
+                format!("invalid apt parameter {}", apt),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Whether this is the RTX codec of the media codec with `payload_type`.
+    fn is_rtx_of(&self, payload_type: Option<u8>) -> bool {
+        self.is_rtx() && payload_type.is_some_and(|pt| self.apt() == Some(pt.into()))
+    }
+}
+
+fn describe(value: Option<&RtpCodecParametersParametersValue>) -> String {
+    match value {
+        Some(RtpCodecParametersParametersValue::Number(number)) => number.to_string(),
+        Some(RtpCodecParametersParametersValue::String(string)) => string.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+fn same_parameter(
+    codec: &CodecView,
+    reference: &CodecView,
+    name: &str,
+    default: Option<u32>,
+) -> Result<(), String> {
+    let default = default.map(RtpCodecParametersParametersValue::Number);
+    let value = codec.parameters.get(name).or(default.as_ref());
+    let expected = reference.parameters.get(name).or(default.as_ref());
+    if value == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} {}, expected {}",
+            name,
+            describe(value),
+            describe(expected)
+        ))
+    }
+}
+
+fn flag(codec: &CodecView, name: &str) -> bool {
+    codec.parameters.get(name) == Some(&RtpCodecParametersParametersValue::Number(1))
+}
+
+fn string_parameter<'a>(codec: &CodecView<'a>, name: &str) -> Option<&'a str> {
+    match codec.parameters.get(name) {
+        Some(RtpCodecParametersParametersValue::String(string)) => Some(string.as_ref()),
+        _ => None,
+    }
+}
+
+/// Whether `codec` is the same codec as `reference`, or why not. With `strict`, H264 codecs
+/// resolve to the `profile-level-id` `codec` should answer with.
+fn match_codecs(
+    codec: &CodecView,
+    reference: &CodecView,
+    strict: bool,
+) -> Result<Option<String>, String> {
+    if codec.mime_type != reference.mime_type {
+        return Err(format!(
+            "mime type {}, expected {}",
+            codec.mime_type.as_str(),
+            reference.mime_type.as_str()
+        ));
+    }
+    if codec.clock_rate != reference.clock_rate {
+        return Err(format!(
+            "clock rate {}, expected {}",
+            codec.clock_rate, reference.clock_rate
+        ));
+    }
+    if codec.channels != reference.channels {
+        let channels = |codec: &CodecView| codec.channels.map_or(0, NonZeroU8::get);
+        return Err(format!(
+            "{} channels, expected {}",
+            channels(codec),
+            channels(reference)
+        ));
+    }
+
+    match codec.mime_type {
+        MimeType::Audio(MimeTypeAudio::MultiChannelOpus) => {
+            same_parameter(codec, reference, "num_streams", None)?;
+            same_parameter(codec, reference, "coupled_streams", None)?;
+        }
+        MimeType::Video(MimeTypeVideo::H264) if strict => {
+            same_parameter(codec, reference, "packetization-mode", Some(0))?;
+
+            let local = string_parameter(codec, "profile-level-id");
+            let remote = string_parameter(reference, "profile-level-id");
+            let Some((local_id, remote_id)) = h264_profile_level_id::is_same_profile(local, remote)
+            else {
+                return Err(format!(
+                    "profile-level-id {} is not the H264 profile of {}",
+                    local.unwrap_or("none"),
+                    remote.unwrap_or("none")
+                ));
+            };
+            return h264_profile_level_id::generate_profile_level_id_for_answer(
+                Some(local_id),
+                flag(codec, "level-asymmetry-allowed"),
+                Some(remote_id),
+                flag(reference, "level-asymmetry-allowed"),
+            )
+            .map(|selected| Some(selected.to_string()))
+            .map_err(|error| format!("profile-level-id: {}", error));
+        }
+        MimeType::Video(MimeTypeVideo::Vp9) if strict => {
+            same_parameter(codec, reference, "profile-id", Some(0))?;
+        }
+        _ => {}
+    }
+
+    Ok(None)
+}
+
+/// Why none of `candidates` matches `codec`: the reason of the first one with its mime type,
+/// `missing` when there is none.
+fn mismatch<'a>(
+    candidates: impl IntoIterator<Item = CodecView<'a>>,
+    codec: &CodecView,
+    missing: &str,
+) -> String {
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.mime_type == codec.mime_type)
+        .find_map(|candidate| match_codecs(&candidate, codec, true).err())
+        .unwrap_or_else(|| missing.to_string())
+}
+
+fn kind_name(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+    }
+}
+
+fn feedback_name(feedback: &RtcpFeedback) -> String {
+    match feedback.as_type_parameter() {
+        (r#type, "") => r#type.to_string(),
+        (r#type, parameter) => format!("{} {}", r#type, parameter),
+    }
+}
+
+fn has_errors(issues: &[Issue]) -> bool {
+    issues.iter().any(|issue| issue.severity == Severity::Error)
+}
+
+fn describe_errors(issues: &[Issue]) -> String {
+    issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(|issue| format!("{}: {}", issue.subject, issue.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Checks what an endpoint with `capabilities` can receive from the router: the router codecs,
+/// RTCP feedback and header extensions it lacks are warnings; it is an error when it has no
+/// codec in common with the router.
+pub fn validate_rtp_capabilities(
+    router: &RtpCapabilitiesFinalized,
+    capabilities: &RtpCapabilities,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let codecs: Vec<CodecView> = capabilities.codecs.iter().map(CodecView::from).collect();
+
+    for (index, codec) in codecs.iter().enumerate() {
+        issues.extend(codec.invalid_apt());
+        if let Some(payload_type) = codec.payload_type {
+            if codecs[..index]
+                .iter()
+                .any(|previous| previous.payload_type == Some(payload_type))
+            {
+                issues.push(Issue::error(
+                    IssueKind::Codec,
+                    codec.subject(),
+                    format!("duplicated preferred payload type {}", payload_type),
+                ));
+            }
+        }
+    }
+
+    let router_codecs: Vec<CodecView> = router.codecs.iter().map(CodecView::from).collect();
+    let mut unmatched_kinds = Vec::new();
+    let mut matched_kinds = Vec::new();
+    for router_codec in router_codecs.iter().filter(|codec| !codec.is_rtx()) {
+        let kind = router_codec.kind();
+        let matched = codecs
+            .iter()
+            .find(|codec| match_codecs(codec, router_codec, true).is_ok());
+        let Some(codec) = matched else {
+            let reason = mismatch(
+                capabilities.codecs.iter().map(CodecView::from),
+                router_codec,
+                "not supported by the endpoint",
+            );
+            issues.push(Issue::warning(
+                IssueKind::Codec,
+                router_codec.subject(),
+                reason,
+            ));
+            unmatched_kinds.push(kind);
+            continue;
+        };
+        matched_kinds.push(kind);
+
+        for feedback in router_codec.rtcp_feedback {
+            if !codec.rtcp_feedback.contains(feedback) {
+                issues.push(Issue::warning(
+                    IssueKind::RtcpFeedback,
+                    format!("{} {}", router_codec.subject(), feedback_name(feedback)),
+                    "not supported by the endpoint",
+                ));
+            }
+        }
+
+        let router_rtx = router_codecs
+            .iter()
+            .find(|rtx| rtx.is_rtx_of(router_codec.payload_type));
+        if let Some(router_rtx) = router_rtx {
+            if !codecs
+                .iter()
+                .any(|rtx| match_codecs(rtx, router_rtx, true).is_ok())
+            {
+                issues.push(Issue::warning(
+                    IssueKind::Codec,
+                    router_codec.subject(),
+                    "no RTX codec, retransmissions are disabled",
+                ));
+            }
+        }
+    }
+
+    let any_matched = !matched_kinds.is_empty();
+    for kind in [MediaKind::Audio, MediaKind::Video] {
+        if unmatched_kinds.contains(&kind) && !matched_kinds.contains(&kind) {
+            let reason = format!("no {} codec in common with the router", kind_name(kind));
+            issues.push(if any_matched {
+                Issue::warning(IssueKind::Codec, kind_name(kind), reason)
+            } else {
+                Issue::error(IssueKind::Codec, kind_name(kind), reason)
+            });
+        }
+    }
+
+    for router_extension in router.header_extensions.iter().filter(|extension| {
+        matches!(
+            extension.direction,
+            RtpHeaderExtensionDirection::SendRecv | RtpHeaderExtensionDirection::SendOnly
+        )
+    }) {
+        let extension = capabilities.header_extensions.iter().find(|extension| {
+            extension.kind == router_extension.kind && extension.uri == router_extension.uri
+        });
+        let reason = match extension {
+            None => format!(
+                "not supported by the endpoint for {}",
+                kind_name(router_extension.kind)
+            ),
+            Some(extension) if extension.preferred_id != router_extension.preferred_id => format!(
+                "id {} for {}, the router sends it with id {}",
+                extension.preferred_id,
+                kind_name(router_extension.kind),
+                router_extension.preferred_id
+            ),
+            Some(_) => continue,
+        };
+        issues.push(Issue::warning(
+            IssueKind::HeaderExtension,
+            router_extension.uri.as_str(),
+            reason,
+        ));
+    }
+
+    issues
+}
+
+/// Checks that the router can receive a producer with `parameters`: its codecs must be router
+/// codecs, with their RTX codecs. RTCP feedback and header extensions the router ignores are
+/// warnings.
+pub fn validate_rtp_parameters(
+    router: &RtpCapabilitiesFinalized,
+    parameters: &RtpParameters,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let codecs: Vec<CodecView> = parameters.codecs.iter().map(CodecView::from).collect();
+    let router_codecs: Vec<CodecView> = router.codecs.iter().map(CodecView::from).collect();
+
+    for codec in &codecs {
+        issues.extend(codec.invalid_apt());
+        if codec.is_rtx() {
+            continue;
+        }
+        let matched = router_codecs
+            .iter()
+            .find(|router_codec| match_codecs(codec, router_codec, true).is_ok());
+        let Some(router_codec) = matched else {
+            let reason = mismatch(
+                router.codecs.iter().map(CodecView::from),
+                codec,
+                "not supported by the router",
+            );
+            issues.push(Issue::error(IssueKind::Codec, codec.subject(), reason));
+            continue;
+        };
+        for feedback in codec.rtcp_feedback {
+            if !router_codec.rtcp_feedback.contains(feedback) {
+                issues.push(Issue::warning(
+                    IssueKind::RtcpFeedback,
+                    format!("{} {}", codec.subject(), feedback_name(feedback)),
+                    "not supported by the router",
+                ));
+            }
+        }
+    }
+
+    for rtx in codecs.iter().filter(|codec| codec.is_rtx()) {
+        let Some(media_codec) = codecs
+            .iter()
+            .find(|media_codec| rtx.is_rtx_of(media_codec.payload_type))
+        else {
+            issues.push(Issue::error(
+                IssueKind::Codec,
+                rtx.subject(),
+                format!(
+                    "no media codec for RTX payload type {}",
+                    rtx.payload_type.unwrap_or_default()
+                ),
+            ));
+            continue;
+        };
+        let router_codec = router_codecs
+            .iter()
+            .find(|router_codec| match_codecs(media_codec, router_codec, true).is_ok());
+        if let Some(router_codec) = router_codec {
+            if !router_codecs
+                .iter()
+                .any(|router_rtx| router_rtx.is_rtx_of(router_codec.payload_type))
+            {
+                issues.push(Issue::error(
+                    IssueKind::Codec,
+                    rtx.subject(),
+                    format!("the router has no RTX codec for {}", router_codec.subject()),
+                ));
+            }
+        }
+    }
+
+    let Some(kind) = codecs
+        .iter()
+        .find(|codec| !codec.is_rtx())
+        .map(CodecView::kind)
+    else {
+        issues.push(Issue::error(IssueKind::Codec, "codecs", "no media codec"));
+        return issues;
+    };
+
+    for extension in &parameters.header_extensions {
+        let supported = router.header_extensions.iter().any(|router_extension| {
+            router_extension.kind == kind
+                && router_extension.uri == extension.uri
+                && matches!(
+                    router_extension.direction,
+                    RtpHeaderExtensionDirection::SendRecv | RtpHeaderExtensionDirection::RecvOnly
+                )
+        });
+        if !supported {
+            issues.push(Issue::warning(
+                IssueKind::HeaderExtension,
+                extension.uri.as_str(),
+                format!("not received by the router for {}", kind_name(kind)),
+            ));
+        }
+    }
+
+    if parameters.encodings.is_empty() {
+        issues.push(Issue::error(
+            IssueKind::Encoding,
+            "encodings",
+            "no encoding",
+        ));
+    } else if parameters.encodings.len() > 1 {
+        for (index, encoding) in parameters.encodings.iter().enumerate() {
+            if encoding.ssrc.is_none() && encoding.rid.is_none() {
+                issues.push(Issue::error(
+                    IssueKind::Encoding,
+                    format!("encodings[{}]", index),
+                    "simulcast encodings need a ssrc or a rid",
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+fn codec_parameters(
+    capability: &RtpCodecCapabilityFinalized,
+    parameters: RtpCodecParametersParameters,
+) -> RtpCodecParameters {
+    match capability {
+        RtpCodecCapabilityFinalized::Audio {
+            mime_type,
+            preferred_payload_type,
+            clock_rate,
+            channels,
+            rtcp_feedback,
+            ..
+        } => RtpCodecParameters::Audio {
+            mime_type: *mime_type,
+            payload_type: *preferred_payload_type,
+            clock_rate: *clock_rate,
+            channels: *channels,
+            parameters,
+            rtcp_feedback: rtcp_feedback.clone(),
+        },
+        RtpCodecCapabilityFinalized::Video {
+            mime_type,
+            preferred_payload_type,
+            clock_rate,
+            rtcp_feedback,
+            ..
+        } => RtpCodecParameters::Video {
+            mime_type: *mime_type,
+            payload_type: *preferred_payload_type,
+            clock_rate: *clock_rate,
+            parameters,
+            rtcp_feedback: rtcp_feedback.clone(),
+        },
+    }
+}
+
+fn generate_ssrc() -> u32 {
+    fastrand::u32(100_000_000..999_999_999)
+}
+
+/// The parameters the router uses for the consumers of a producer with `parameters`: the codecs
+/// take the router payload types and the encodings new ssrcs, as `produce` would do.
+pub fn consumable_rtp_parameters(
+    router: &RtpCapabilitiesFinalized,
+    parameters: &RtpParameters,
+) -> Result<RtpParameters, String> {
+    let issues = validate_rtp_parameters(router, parameters);
+    if has_errors(&issues) {
+        return Err(describe_errors(&issues));
+    }
+
+    let mut consumable = RtpParameters::default();
+    let mut kind = MediaKind::Audio;
+    for codec in parameters.codecs.iter().filter(|codec| !codec.is_rtx()) {
+        let view = CodecView::from(codec);
+        kind = view.kind();
+        let router_codec = router
+            .codecs
+            .iter()
+            .find(|router_codec| match_codecs(&view, &(*router_codec).into(), true).is_ok())
+            .ok_or_else(|| format!("{}: not supported by the router", view.subject()))?;
+        // The consumable codec keeps the producer codec parameters.
+        consumable
+            .codecs
+            .push(codec_parameters(router_codec, codec.parameters().clone()));
+
+        let router_rtx = router.codecs.iter().find(|rtx| {
+            CodecView::from(*rtx).is_rtx_of(Some(router_codec.preferred_payload_type()))
+        });
+        if let Some(router_rtx) = router_rtx {
+            consumable.codecs.push(codec_parameters(
+                router_rtx,
+                router_rtx.parameters().clone(),
+            ));
+        }
+    }
+
+    consumable.header_extensions = router
+        .header_extensions
+        .iter()
+        .filter(|extension| {
+            extension.kind == kind
+                && matches!(
+                    extension.direction,
+                    RtpHeaderExtensionDirection::SendRecv | RtpHeaderExtensionDirection::SendOnly
+                )
+        })
+        .map(|extension| RtpHeaderExtensionParameters {
+            uri: extension.uri,
+            id: extension.preferred_id,
+            encrypt: extension.preferred_encrypt,
+        })
+        .collect();
+
+    let first_ssrc = generate_ssrc();
+    consumable.encodings = parameters
+        .encodings
+        .iter()
+        .zip(first_ssrc..)
+        .map(|(encoding, ssrc)| RtpEncodingParameters {
+            ssrc: Some(ssrc),
+            rid: None,
+            rtx: None,
+            codec_payload_type: None,
+            ..encoding.clone()
+        })
+        .collect();
+
+    consumable.rtcp = RtcpParameters {
+        cname: parameters.rtcp.cname.clone(),
+        reduced_size: true,
+    };
+
+    Ok(consumable)
+}
+
+/// The parameters of a consumer of `consumable` parameters for an endpoint with `capabilities`,
+/// as `consume` would build them. Fails with the reason of each codec when none matches.
+pub fn consumer_rtp_parameters(
+    consumable: &RtpParameters,
+    capabilities: &RtpCapabilities,
+    enable_rtx: bool,
+) -> Result<RtpParameters, String> {
+    let codecs: Vec<CodecView> = capabilities.codecs.iter().map(CodecView::from).collect();
+    if let Some(issue) = codecs.iter().find_map(CodecView::invalid_apt) {
+        return Err(format!("{}: {}", issue.subject, issue.reason));
+    }
+
+    let mut consumer = RtpParameters {
+        rtcp: consumable.rtcp.clone(),
+        ..RtpParameters::default()
+    };
+
+    for codec in &consumable.codecs {
+        if !enable_rtx && codec.is_rtx() {
+            continue;
+        }
+        let view = CodecView::from(codec);
+        let Some(matched) = codecs
+            .iter()
+            .find(|capability| match_codecs(capability, &view, true).is_ok())
+        else {
+            continue;
+        };
+        let mut codec = codec.clone();
+        *codec.rtcp_feedback_mut() = matched
+            .rtcp_feedback
+            .iter()
+            .filter(|&&feedback| enable_rtx || feedback != RtcpFeedback::Nack)
+            .copied()
+            .collect();
+        consumer.codecs.push(codec);
+    }
+
+    // Drops the RTX codecs whose media codec did not match.
+    let payload_types: Vec<u8> = consumer
+        .codecs
+        .iter()
+        .map(RtpCodecParameters::payload_type)
+        .collect();
+    consumer.codecs.retain(|codec| {
+        !codec.is_rtx()
+            || payload_types
+                .iter()
+                .any(|&pt| CodecView::from(codec).is_rtx_of(Some(pt)))
+    });
+    let rtx_supported = consumer.codecs.iter().any(RtpCodecParameters::is_rtx);
+
+    if consumer
+        .codecs
+        .first()
+        .is_none_or(RtpCodecParameters::is_rtx)
+    {
+        let reasons: Vec<String> = consumable
+            .codecs
+            .iter()
+            .filter(|codec| !codec.is_rtx())
+            .map(|codec| {
+                let view = CodecView::from(codec);
+                let reason = mismatch(
+                    capabilities.codecs.iter().map(CodecView::from),
+                    &view,
+                    "not supported by the endpoint",
+                );
+                format!("{}: {}", view.subject(), reason)
+            })
+            .collect();
+        return Err(format!(
+            "no compatible media codecs ({})",
+            reasons.join("; ")
+        ));
+    }
+
+    consumer.header_extensions = consumable
+        .header_extensions
+        .iter()
+        .filter(|extension| {
+            capabilities.header_extensions.iter().any(|capability| {
+                capability.preferred_id == extension.id && capability.uri == extension.uri
+            })
+        })
+        .cloned()
+        .collect();
+
+    // Transport-CC if available, REMB otherwise.
+    let has_extension = |uri| {
+        consumer
+            .header_extensions
+            .iter()
+            .any(|extension| extension.uri == uri)
+    };
+    let dropped: &[RtcpFeedback] = if has_extension(RtpHeaderExtensionUri::TransportWideCcDraft01) {
+        &[RtcpFeedback::GoogRemb]
+    } else if has_extension(RtpHeaderExtensionUri::AbsSendTime) {
+        &[RtcpFeedback::TransportCc]
+    } else {
+        &[RtcpFeedback::GoogRemb, RtcpFeedback::TransportCc]
+    };
+    for codec in &mut consumer.codecs {
+        codec
+            .rtcp_feedback_mut()
+            .retain(|feedback| !dropped.contains(feedback));
+    }
+
+    let ssrc = generate_ssrc();
+    let mut scalability_mode = consumable
+        .encodings
+        .first()
+        .map(|encoding| encoding.scalability_mode.clone())
+        .unwrap_or_default();
+    // Simulcast streams are sent as spatial layers.
+    if consumable.encodings.len() > 1 {
+        scalability_mode = format!(
+            "L{}T{}",
+            consumable.encodings.len(),
+            scalability_mode.temporal_layers()
+        )
+        .parse()
+        .unwrap_or(scalability_mode);
+    }
+    consumer.encodings.push(RtpEncodingParameters {
+        ssrc: Some(ssrc),
+        rtx: rtx_supported.then_some(RtpEncodingParametersRtx { ssrc: ssrc + 1 }),
+        scalability_mode,
+        max_bitrate: consumable
+            .encodings
+            .iter()
+            .map(|encoding| encoding.max_bitrate)
+            .max()
+            .flatten(),
+        ..RtpEncodingParameters::default()
+    });
+
+    Ok(consumer)
+}
+
+/// The codecs and header extensions of `local` that `remote` supports too, with the payload
+/// types and ids of `local`. H264 codecs take the `profile-level-id` to answer `remote` with,
+/// and RTCP feedback is reduced to the one both support.
+pub fn intersect_rtp_capabilities(
+    local: &RtpCapabilities,
+    remote: &RtpCapabilities,
+) -> RtpCapabilities {
+    let remote_codecs: Vec<CodecView> = remote.codecs.iter().map(CodecView::from).collect();
+    let mut codecs = Vec::new();
+
+    for codec in &local.codecs {
+        let view = CodecView::from(codec);
+        if view.is_rtx() {
+            continue;
+        }
+        let Some((remote_codec, profile_level_id)) = remote_codecs.iter().find_map(|remote| {
+            match_codecs(&view, remote, true)
+                .ok()
+                .map(|profile_level_id| (remote, profile_level_id))
+        }) else {
+            continue;
+        };
+
+        let mut codec = codec.clone();
+        if let Some(profile_level_id) = profile_level_id {
+            codec
+                .parameters_mut()
+                .insert("profile-level-id", profile_level_id);
+        }
+        let (RtpCodecCapability::Audio { rtcp_feedback, .. }
+        | RtpCodecCapability::Video { rtcp_feedback, .. }) = &mut codec;
+        rtcp_feedback.retain(|feedback| remote_codec.rtcp_feedback.contains(feedback));
+        codecs.push(codec);
+
+        let local_rtx = local
+            .codecs
+            .iter()
+            .find(|rtx| CodecView::from(*rtx).is_rtx_of(view.payload_type));
+        let remote_rtx = remote_codecs
+            .iter()
+            .any(|rtx| rtx.is_rtx_of(remote_codec.payload_type));
+        if let (Some(local_rtx), true) = (local_rtx, remote_rtx) {
+            codecs.push(local_rtx.clone());
+        }
+    }
+
+    let header_extensions = local
+        .header_extensions
+        .iter()
+        .filter(|extension| {
+            remote.header_extensions.iter().any(|remote_extension| {
+                remote_extension.kind == extension.kind && remote_extension.uri == extension.uri
+            })
+        })
+        .copied()
+        .collect();
+
+    RtpCapabilities {
+        codecs,
+        header_extensions,
+    }
+}
+
+type Report = Result<Vec<IssueStruct>, Vec<IssueStruct>>;
+
+/// `{:ok, warnings}` without errors, `{:error, issues}` otherwise.
+fn report(issues: Vec<Issue>) -> Report {
+    let failed = has_errors(&issues);
+    let issues = issues.into_iter().map(IssueStruct::from).collect();
+    if failed {
+        Err(issues)
+    } else {
+        Ok(issues)
+    }
+}
+
+#[rustler::nif]
+pub fn ortc_validate_rtp_capabilities(
+    router_rtp_capabilities: JsonSerdeWrap<RtpCapabilitiesFinalized>,
+    rtp_capabilities: JsonSerdeWrap<RtpCapabilities>,
+) -> Report {
+    report(validate_rtp_capabilities(
+        &router_rtp_capabilities,
+        &rtp_capabilities,
+    ))
+}
+
+#[rustler::nif]
+pub fn ortc_validate_rtp_parameters(
+    router_rtp_capabilities: JsonSerdeWrap<RtpCapabilitiesFinalized>,
+    rtp_parameters: JsonSerdeWrap<RtpParameters>,
+) -> Report {
+    report(validate_rtp_parameters(
+        &router_rtp_capabilities,
+        &rtp_parameters,
+    ))
+}
+
+#[rustler::nif]
+pub fn ortc_consumable_rtp_parameters(
+    router_rtp_capabilities: JsonSerdeWrap<RtpCapabilitiesFinalized>,
+    rtp_parameters: JsonSerdeWrap<RtpParameters>,
+) -> Result<JsonSerdeWrap<RtpParameters>, String> {
+    consumable_rtp_parameters(&router_rtp_capabilities, &rtp_parameters).map(JsonSerdeWrap::new)
+}
+
+#[rustler::nif]
+pub fn ortc_consumer_rtp_parameters(
+    consumable_rtp_parameters: JsonSerdeWrap<RtpParameters>,
+    rtp_capabilities: JsonSerdeWrap<RtpCapabilities>,
+    enable_rtx: bool,
+) -> Result<JsonSerdeWrap<RtpParameters>, String> {
+    consumer_rtp_parameters(&consumable_rtp_parameters, &rtp_capabilities, enable_rtx)
+        .map(JsonSerdeWrap::new)
+}
+
+#[rustler::nif]
+pub fn ortc_intersect_rtp_capabilities(
+    local: JsonSerdeWrap<RtpCapabilities>,
+    remote: JsonSerdeWrap<RtpCapabilities>,
+) -> JsonSerdeWrap<RtpCapabilities> {
+    JsonSerdeWrap::new(intersect_rtp_capabilities(&local, &remote))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn router_capabilities() -> RtpCapabilitiesFinalized {
+        serde_json::from_value(json!({
+            "codecs": [
+                {"kind": "audio", "mimeType": "audio/opus", "preferredPayloadType": 100,
+                 "clockRate": 48000, "channels": 2, "parameters": {},
+                 "rtcpFeedback": [{"type": "transport-cc"}]},
+                {"kind": "video", "mimeType": "video/VP8", "preferredPayloadType": 101,
+                 "clockRate": 90000, "parameters": {},
+                 "rtcpFeedback": [{"type": "nack"}, {"type": "nack", "parameter": "pli"},
+                                  {"type": "transport-cc"}]},
+                {"kind": "video", "mimeType": "video/rtx", "preferredPayloadType": 102,
+                 "clockRate": 90000, "parameters": {"apt": 101}, "rtcpFeedback": []}
+            ],
+            "headerExtensions": [
+                {"kind": "video", "uri": "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01",
+                 "preferredId": 5, "preferredEncrypt": false, "direction": "sendrecv"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn capabilities(value: serde_json::Value) -> RtpCapabilities {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn h264(payload_type: u8, profile_level_id: &str) -> serde_json::Value {
+        json!({"kind": "video", "mimeType": "video/H264", "preferredPayloadType": payload_type,
+               "clockRate": 90000,
+               "parameters": {"packetization-mode": 1, "level-asymmetry-allowed": 1,
+                              "profile-level-id": profile_level_id}})
+    }
+
+    #[test]
+    fn validate_rtp_capabilities_reports_mismatches() {
+        let endpoint = capabilities(json!({
+            "codecs": [
+                {"kind": "audio", "mimeType": "audio/opus", "clockRate": 44100, "channels": 2},
+                {"kind": "video", "mimeType": "video/VP8", "preferredPayloadType": 96,
+                 "clockRate": 90000, "rtcpFeedback": [{"type": "nack"}]}
+            ],
+            "headerExtensions": []
+        }));
+        let issues = validate_rtp_capabilities(&router_capabilities(), &endpoint);
+
+        assert!(!has_errors(&issues));
+        assert!(issues.contains(&Issue::warning(
+            IssueKind::Codec,
+            "audio/opus",
+            "clock rate 44100, expected 48000"
+        )));
+        assert!(issues.contains(&Issue::warning(
+            IssueKind::Codec,
+            "audio",
+            "no audio codec in common with the router"
+        )));
+        assert!(issues.contains(&Issue::warning(
+            IssueKind::RtcpFeedback,
+            "video/VP8 nack pli",
+            "not supported by the endpoint"
+        )));
+        assert!(issues.contains(&Issue::warning(
+            IssueKind::Codec,
+            "video/VP8",
+            "no RTX codec, retransmissions are disabled"
+        )));
+        assert!(issues
+            .iter()
+            .any(|issue| issue.kind == IssueKind::HeaderExtension));
+
+        let endpoint = capabilities(json!({"codecs": [], "headerExtensions": []}));
+        let issues = validate_rtp_capabilities(&router_capabilities(), &endpoint);
+        assert!(has_errors(&issues));
+    }
+
+    #[test]
+    fn consumable_and_consumer_rtp_parameters() {
+        let parameters: RtpParameters = serde_json::from_value(json!({
+            "codecs": [
+                {"mimeType": "video/VP8", "payloadType": 120, "clockRate": 90000},
+                {"mimeType": "video/rtx", "payloadType": 121, "clockRate": 90000,
+                 "parameters": {"apt": 120}}
+            ],
+            "headerExtensions": [],
+            "encodings": [{"ssrc": 1111, "rtx": {"ssrc": 2222}}],
+            "rtcp": {"cname": "cname", "reducedSize": false}
+        }))
+        .unwrap();
+        let consumable = consumable_rtp_parameters(&router_capabilities(), &parameters).unwrap();
+        let payload_types: Vec<u8> = consumable
+            .codecs
+            .iter()
+            .map(RtpCodecParameters::payload_type)
+            .collect();
+        assert_eq!(vec![101, 102], payload_types);
+        assert_eq!(None, consumable.encodings[0].rtx);
+        assert!(consumable.rtcp.reduced_size);
+
+        let endpoint = capabilities(json!({
+            "codecs": [{"kind": "video", "mimeType": "video/VP8", "clockRate": 90000,
+                        "rtcpFeedback": [{"type": "nack"}, {"type": "goog-remb"}]}],
+            "headerExtensions": []
+        }));
+        let consumer = consumer_rtp_parameters(&consumable, &endpoint, true).unwrap();
+        assert_eq!(1, consumer.codecs.len());
+        assert_eq!(&[RtcpFeedback::Nack], consumer.codecs[0].rtcp_feedback());
+        assert_eq!(None, consumer.encodings[0].rtx);
+
+        let endpoint = capabilities(json!({
+            "codecs": [{"kind": "video", "mimeType": "video/VP8", "clockRate": 48000}],
+            "headerExtensions": []
+        }));
+        assert_eq!(
+            Err("no compatible media codecs (video/VP8: clock rate 48000, expected 90000)".into()),
+            consumer_rtp_parameters(&consumable, &endpoint, true)
+        );
+
+        let mut unsupported = parameters.clone();
+        unsupported.codecs.truncate(0);
+        assert_eq!(
+            Err("codecs: no media codec".into()),
+            consumable_rtp_parameters(&router_capabilities(), &unsupported)
+        );
+    }
+
+    #[test]
+    fn intersect_rtp_capabilities_negotiates_h264_profile() {
+        let local = capabilities(json!({
+            "codecs": [h264(96, "42e01f"), h264(97, "640032"),
+                       {"kind": "video", "mimeType": "video/rtx", "preferredPayloadType": 98,
+                        "clockRate": 90000, "parameters": {"apt": 96}}],
+            "headerExtensions": []
+        }));
+        let remote = capabilities(json!({
+            "codecs": [h264(100, "42e034"),
+                       {"kind": "video", "mimeType": "video/rtx", "preferredPayloadType": 101,
+                        "clockRate": 90000, "parameters": {"apt": 100}}],
+            "headerExtensions": []
+        }));
+
+        let intersection = intersect_rtp_capabilities(&local, &remote);
+        let payload_types: Vec<Option<u8>> = intersection
+            .codecs
+            .iter()
+            .map(RtpCodecCapability::preferred_payload_type)
+            .collect();
+        assert_eq!(vec![Some(96), Some(98)], payload_types);
+        // Level asymmetry is allowed: the answer keeps the local level.
+        assert_eq!(
+            Some(&RtpCodecParametersParametersValue::String("42e01f".into())),
+            intersection.codecs[0].parameters().get("profile-level-id")
+        );
+
+        let high = CodecView::from(&local.codecs[1]);
+        let baseline = CodecView::from(&remote.codecs[0]);
+        assert_eq!(
+            Err("profile-level-id 640032 is not the H264 profile of 42e034".into()),
+            match_codecs(&high, &baseline, true)
+        );
+    }
+}
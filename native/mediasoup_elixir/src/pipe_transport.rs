@@ -1,26 +1,31 @@
+use crate::close;
 use crate::consumer::{ConsumerOptionsStruct, ConsumerRef};
 use crate::data_consumer::{DataConsumerOptionsStruct, DataConsumerRef};
 use crate::data_producer::{DataProducerOptionsStruct, DataProducerRef};
-use crate::data_structure::SerNumSctpStreams;
-use crate::json_serde::JsonSerdeWrap;
+use crate::data_structure::{SerNumSctpStreams, TransportTupleStruct};
+use crate::json_serde::{AsAtom, JsonSerdeWrap};
 use crate::producer::{ProducerOptionsStruct, ProducerRef};
-use crate::{atoms, send_async_nif_result_with_from, DisposableResourceWrapper};
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::validation::{ensure_positive, validate_listen_info, ValidationResult};
+use crate::{atoms, dump, send_async_nif_result_with_from, stats, DisposableResourceWrapper};
 use mediasoup::prelude::{
     ListenInfo, PipeTransport, PipeTransportOptions, PipeTransportRemoteParameters, Transport,
     TransportGeneric, TransportId,
 };
-use mediasoup::types::data_structures::{SctpState, TransportTuple};
 use mediasoup::types::sctp_parameters::SctpParameters;
 use mediasoup::types::srtp_parameters::SrtpParameters;
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type PipeTransportRef = DisposableResourceWrapper<PipeTransport>;
 
 #[rustler::resource_impl]
-impl rustler::Resource for PipeTransportRef {}
+impl rustler::Resource for PipeTransportRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.PipeTransport.Options"]
 pub struct PipeTransportOptionsStruct {
     /// Listening IP address.
     pub listen_info: JsonSerdeWrap<ListenInfo>,
@@ -46,8 +51,23 @@ pub struct PipeTransportOptionsStruct {
     pub enable_srtp: Option<bool>,
 }
 
+crate::nif_struct_decoder!(
+    PipeTransportOptionsStruct,
+    "Mediasoup.PipeTransport.Options",
+    [
+        listen_info,
+        enable_sctp,
+        num_sctp_streams,
+        max_sctp_message_size,
+        sctp_send_buffer_size,
+        enable_rtx,
+        enable_srtp
+    ]
+);
+
 impl PipeTransportOptionsStruct {
     pub fn try_to_option(self) -> rustler::NifResult<PipeTransportOptions> {
+        self.validate()?;
         let mut option = PipeTransportOptions::new(self.listen_info.clone());
 
         if let Some(enable_sctp) = self.enable_sctp {
@@ -70,14 +90,24 @@ impl PipeTransportOptionsStruct {
         }
         Ok(option)
     }
+
+    fn validate(&self) -> ValidationResult {
+        validate_listen_info(&self.listen_info).map_err(|error| error.within("listen_info"))?;
+        if let Some(num_sctp_streams) = &self.num_sctp_streams {
+            num_sctp_streams
+                .validate()
+                .map_err(|error| error.within("num_sctp_streams"))?;
+        }
+        ensure_positive(self.max_sctp_message_size, "max_sctp_message_size")?;
+        ensure_positive(self.sctp_send_buffer_size, "sctp_send_buffer_size")
+    }
 }
 
 #[rustler::nif]
 pub fn pipe_transport_id(
     transport: ResourceArc<PipeTransportRef>,
 ) -> NifResult<JsonSerdeWrap<TransportId>> {
-    let transport = transport.get_resource()?;
-    Ok(transport.id().into())
+    transport.with_resource(|transport| transport.id().into())
 }
 
 #[rustler::nif]
@@ -86,6 +116,35 @@ pub fn pipe_transport_close(transport: ResourceArc<PipeTransportRef>) -> NifResu
     Ok((atoms::ok(),))
 }
 
+/// Closes the pipe transport and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "pipe_transport_close_async")]
+pub fn pipe_transport_close_confirmed(
+    env: Env,
+    transport: ResourceArc<PipeTransportRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = transport.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
+/// Closes the pipe transport when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn pipe_transport_monitor_owner(
+    env: Env,
+    transport: ResourceArc<PipeTransportRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&transport, env, owner)?;
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif]
 pub fn pipe_transport_closed(transport: ResourceArc<PipeTransportRef>) -> NifResult<bool> {
     match transport.get_resource() {
@@ -97,9 +156,8 @@ pub fn pipe_transport_closed(transport: ResourceArc<PipeTransportRef>) -> NifRes
 #[rustler::nif]
 pub fn pipe_transport_tuple(
     transport: ResourceArc<PipeTransportRef>,
-) -> NifResult<JsonSerdeWrap<TransportTuple>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.tuple()))
+) -> NifResult<TransportTupleStruct> {
+    transport.with_resource(|transport| TransportTupleStruct::from(&transport.tuple()))
 }
 
 #[rustler::nif(name = "pipe_transport_consume_async")]
@@ -108,18 +166,25 @@ pub fn pipe_transport_consume(
     transport: ResourceArc<PipeTransportRef>,
     option: ConsumerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option = option.to_option();
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .consume(option)
-            .await
-            .map(ConsumerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_consume_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .consume(option)
+                .await
+                .map(ConsumerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "pipe_transport_consume_data_async")]
@@ -128,18 +193,25 @@ pub fn pipe_transport_consume_data(
     transport: ResourceArc<PipeTransportRef>,
     option: DataConsumerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option = option.to_option();
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .consume_data(option)
-            .await
-            .map(DataConsumerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_consume_data_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .consume_data(option)
+                .await
+                .map(DataConsumerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "pipe_transport_connect_async")]
@@ -148,17 +220,24 @@ pub fn pipe_transport_connect(
     transport: ResourceArc<PipeTransportRef>,
     option: JsonSerdeWrap<PipeTransportRemoteParameters>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option = option.clone();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .connect(option)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_connect_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .connect(option)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "pipe_transport_produce_async")]
@@ -167,18 +246,25 @@ pub fn pipe_transport_produce(
     transport: ResourceArc<PipeTransportRef>,
     option: ProducerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .produce(option)
-            .await
-            .map(ProducerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_produce_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .produce(option)
+                .await
+                .map(ProducerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "pipe_transport_produce_data_async")]
@@ -187,18 +273,25 @@ pub fn pipe_transport_produce_data(
     transport: ResourceArc<PipeTransportRef>,
     option: DataProducerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .produce_data(option)
-            .await
-            .map(DataProducerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_produce_data_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .produce_data(option)
+                .await
+                .map(DataProducerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "pipe_transport_get_stats_async")]
@@ -206,16 +299,34 @@ pub fn pipe_transport_get_stats(
     env: Env,
     transport: ResourceArc<PipeTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .get_stats()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::PipeTransportStats>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn pipe_transport_stats_subscribe(
+    transport: ResourceArc<PipeTransportRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&transport, pid, interval_ms, delta)
 }
 
 #[rustler::nif(name = "pipe_transport_set_max_incoming_bitrate_async")]
@@ -224,38 +335,42 @@ pub fn pipe_transport_set_max_incoming_bitrate(
     transport: ResourceArc<PipeTransportRef>,
     bitrate: u32,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .set_max_incoming_bitrate(bitrate)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_set_max_incoming_bitrate_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .set_max_incoming_bitrate(bitrate)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
 pub fn pipe_transport_sctp_state(
     transport: ResourceArc<PipeTransportRef>,
-) -> NifResult<JsonSerdeWrap<Option<SctpState>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_state()))
+) -> NifResult<Option<Atom>> {
+    transport.with_resource(|transport| transport.sctp_state().as_ref().map(AsAtom::as_atom))
 }
 #[rustler::nif]
 pub fn pipe_transport_sctp_parameters(
     transport: ResourceArc<PipeTransportRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SctpParameters>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.sctp_parameters()))
 }
 
 #[rustler::nif]
 pub fn pipe_transport_srtp_parameters(
     transport: ResourceArc<PipeTransportRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SrtpParameters>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.srtp_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.srtp_parameters()))
 }
 
 #[rustler::nif(name = "pipe_transport_dump_async")]
@@ -263,16 +378,23 @@ pub fn pipe_transport_dump(
     env: Env,
     transport: ResourceArc<PipeTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .dump()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "pipe_transport_dump_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .dump()
+                .await
+                .map(dump::PipeTransportDumpStruct::from)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
@@ -280,18 +402,32 @@ pub fn pipe_transport_event(
     transport: ResourceArc<PipeTransportRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = transport.close_reason();
     let transport = transport.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback_once!(pid, transport, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &transport,
+        close_reason,
+        &event_types,
+    ));
     if event_types.contains(&atoms::on_sctp_state_change()) {
-        crate::reg_callback_json_param!(pid, transport, on_sctp_state_change);
+        handlers.push(crate::reg_callback_atom_param!(
+            sink,
+            transport,
+            on_sctp_state_change
+        ));
     }
     if event_types.contains(&atoms::on_tuple()) {
-        crate::reg_callback_json_clone_param!(pid, transport, on_tuple);
+        handlers.push(crate::reg_callback_tuple_param!(sink, transport, on_tuple));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
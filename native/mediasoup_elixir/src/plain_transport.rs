@@ -1,26 +1,33 @@
+use crate::close;
 use crate::consumer::{ConsumerOptionsStruct, ConsumerRef};
-use crate::data_structure::SerNumSctpStreams;
-use crate::json_serde::JsonSerdeWrap;
+use crate::data_structure::{SerNumSctpStreams, TransportTupleStruct};
+use crate::json_serde::{AsAtom, InvalidArgument, JsonSerdeWrap};
 use crate::producer::{ProducerOptionsStruct, ProducerRef};
-use crate::{atoms, send_async_nif_result_with_from, DisposableResourceWrapper};
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::validation::{
+    ensure, ensure_positive, validate_listen_info, validate_listen_infos, ValidationResult,
+};
+use crate::{atoms, dump, send_async_nif_result_with_from, stats, DisposableResourceWrapper};
 use mediasoup::consumer::ConsumerOptions;
 use mediasoup::prelude::{
     ListenInfo, PlainTransport, PlainTransportOptions, PlainTransportRemoteParameters, Transport,
     TransportGeneric, TransportId,
 };
 use mediasoup::producer::ProducerOptions;
-use mediasoup::types::data_structures::{SctpState, TransportTuple};
 use mediasoup::types::sctp_parameters::SctpParameters;
 use mediasoup::types::srtp_parameters::SrtpParameters;
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type PlainTransportRef = DisposableResourceWrapper<PlainTransport>;
 
 #[rustler::resource_impl]
-impl rustler::Resource for PlainTransportRef {}
+impl rustler::Resource for PlainTransportRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.PlainTransport.Options"]
 pub struct PlainTransportOptionsStruct {
     pub listen_info: JsonSerdeWrap<ListenInfo>,
     pub rtcp_listen_info: JsonSerdeWrap<Option<ListenInfo>>,
@@ -32,8 +39,26 @@ pub struct PlainTransportOptionsStruct {
     pub sctp_send_buffer_size: Option<u32>,
     pub enable_srtp: Option<bool>,
 }
+
+crate::nif_struct_decoder!(
+    PlainTransportOptionsStruct,
+    "Mediasoup.PlainTransport.Options",
+    [
+        listen_info,
+        rtcp_listen_info,
+        rtcp_mux,
+        comedia,
+        enable_sctp,
+        num_sctp_streams,
+        max_sctp_message_size,
+        sctp_send_buffer_size,
+        enable_srtp
+    ]
+);
 impl PlainTransportOptionsStruct {
-    pub fn try_to_option(&self) -> Result<PlainTransportOptions, &'static str> {
+    pub fn try_to_option(&self) -> NifResult<PlainTransportOptions> {
+        self.validate()?;
+
         let mut option = PlainTransportOptions::new(self.listen_info.clone());
 
         option.rtcp_listen_info.clone_from(&self.rtcp_listen_info);
@@ -58,49 +83,69 @@ impl PlainTransportOptionsStruct {
         if let Some(enable_srtp) = self.enable_srtp {
             option.enable_srtp = enable_srtp;
         }
-
         Ok(option)
     }
+
+    fn validate(&self) -> ValidationResult {
+        validate_listen_info(&self.listen_info).map_err(|error| error.within("listen_info"))?;
+        if let Some(rtcp_listen_info) = &*self.rtcp_listen_info {
+            ensure(
+                self.rtcp_mux == Some(false),
+                "rtcp_listen_info",
+                "must not be set unless rtcp_mux is false",
+            )?;
+            validate_listen_infos(
+                &[self.listen_info.clone(), rtcp_listen_info.clone()],
+                "listen_info",
+            )
+            .map_err(|_| {
+                InvalidArgument::new("must not listen on the same socket as listen_info")
+                    .within("rtcp_listen_info")
+            })?;
+        }
+        if let Some(num_sctp_streams) = &self.num_sctp_streams {
+            num_sctp_streams
+                .validate()
+                .map_err(|error| error.within("num_sctp_streams"))?;
+        }
+        ensure_positive(self.max_sctp_message_size, "max_sctp_message_size")?;
+        ensure_positive(self.sctp_send_buffer_size, "sctp_send_buffer_size")
+    }
 }
 
 #[rustler::nif]
 pub fn plain_transport_id(
     transport: ResourceArc<PlainTransportRef>,
 ) -> NifResult<JsonSerdeWrap<TransportId>> {
-    let transport = transport.get_resource()?;
-    Ok(transport.id().into())
+    transport.with_resource(|transport| transport.id().into())
 }
 
 #[rustler::nif]
 pub fn plain_transport_tuple(
     transport: ResourceArc<PlainTransportRef>,
-) -> NifResult<JsonSerdeWrap<TransportTuple>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.tuple()))
+) -> NifResult<TransportTupleStruct> {
+    transport.with_resource(|transport| TransportTupleStruct::from(&transport.tuple()))
 }
 
 #[rustler::nif]
 pub fn plain_transport_sctp_parameters(
     transport: ResourceArc<PlainTransportRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SctpParameters>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.sctp_parameters()))
 }
 
 #[rustler::nif]
 pub fn plain_transport_sctp_state(
     transport: ResourceArc<PlainTransportRef>,
-) -> NifResult<JsonSerdeWrap<Option<SctpState>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_state()))
+) -> NifResult<Option<Atom>> {
+    transport.with_resource(|transport| transport.sctp_state().as_ref().map(AsAtom::as_atom))
 }
 
 #[rustler::nif]
 pub fn plain_transport_srtp_parameters(
     transport: ResourceArc<PlainTransportRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SrtpParameters>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.srtp_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.srtp_parameters()))
 }
 
 #[rustler::nif(name = "plain_transport_connect_async")]
@@ -109,16 +154,23 @@ pub fn plain_transport_connect(
     transport: ResourceArc<PlainTransportRef>,
     option: JsonSerdeWrap<PlainTransportRemoteParameters>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option: PlainTransportRemoteParameters = option.clone();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .connect(option)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_connect_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .connect(option)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "plain_transport_get_stats_async")]
@@ -126,16 +178,58 @@ pub fn plain_transport_get_stats(
     env: Env,
     transport: ResourceArc<PlainTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .get_stats()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::PlainTransportStats>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn plain_transport_stats_subscribe(
+    transport: ResourceArc<PlainTransportRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&transport, pid, interval_ms, delta)
+}
+
+#[rustler::nif(name = "plain_transport_dump_async")]
+pub fn plain_transport_dump(
+    env: Env,
+    transport: ResourceArc<PlainTransportRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let transport = transport.get_resource()?;
+
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_dump_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .dump()
+                .await
+                .map(dump::PlainTransportDumpStruct::from)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "plain_transport_produce_async")]
@@ -144,19 +238,26 @@ pub fn plain_transport_produce(
     transport: ResourceArc<PlainTransportRef>,
     option: ProducerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option: ProducerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .produce(option)
-            .await
-            .map(ProducerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_produce_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .produce(option)
+                .await
+                .map(ProducerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "plain_transport_consume_async")]
@@ -165,19 +266,26 @@ pub fn plain_transport_consume(
     transport: ResourceArc<PlainTransportRef>,
     option: ConsumerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option: ConsumerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .consume(option)
-            .await
-            .map(ConsumerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_consume_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .consume(option)
+                .await
+                .map(ConsumerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
@@ -186,6 +294,35 @@ pub fn plain_transport_close(transport: ResourceArc<PlainTransportRef>) -> NifRe
     Ok((atoms::ok(),))
 }
 
+/// Closes the plain transport and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "plain_transport_close_async")]
+pub fn plain_transport_close_confirmed(
+    env: Env,
+    transport: ResourceArc<PlainTransportRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = transport.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "plain_transport_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
+/// Closes the plain transport when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn plain_transport_monitor_owner(
+    env: Env,
+    transport: ResourceArc<PlainTransportRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&transport, env, owner)?;
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif]
 pub fn plain_transport_closed(transport: ResourceArc<PlainTransportRef>) -> NifResult<bool> {
     match transport.get_resource() {
@@ -199,18 +336,32 @@ pub fn plain_transport_event(
     transport: ResourceArc<PlainTransportRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = transport.close_reason();
     let transport = transport.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback_once!(pid, transport, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &transport,
+        close_reason,
+        &event_types,
+    ));
     if event_types.contains(&atoms::on_sctp_state_change()) {
-        crate::reg_callback_json_param!(pid, transport, on_sctp_state_change);
+        handlers.push(crate::reg_callback_atom_param!(
+            sink,
+            transport,
+            on_sctp_state_change
+        ));
     }
     if event_types.contains(&atoms::on_tuple()) {
-        crate::reg_callback_json_clone_param!(pid, transport, on_tuple);
+        handlers.push(crate::reg_callback_tuple_param!(sink, transport, on_tuple));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
@@ -1,38 +1,41 @@
-use crate::json_serde::JsonSerdeWrap;
-use crate::{atoms, send_async_nif_result_with_from};
-use crate::{send_msg_from_other_thread, DisposableResourceWrapper};
+use crate::close;
+use crate::json_serde::{AsAtom, JsonSerdeWrap};
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::DisposableResourceWrapper;
+use crate::{atoms, dump, send_async_nif_result_with_from, stats};
 use mediasoup::prelude::{MediaKind, RtpParameters};
-use mediasoup::producer::{Producer, ProducerId, ProducerOptions, ProducerScore, ProducerType};
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use mediasoup::producer::{Producer, ProducerId, ProducerOptions, ProducerScore};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type ProducerRef = DisposableResourceWrapper<Producer>;
 #[rustler::resource_impl]
-impl rustler::Resource for ProducerRef {}
+impl rustler::Resource for ProducerRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn producer_id(producer: ResourceArc<ProducerRef>) -> NifResult<JsonSerdeWrap<ProducerId>> {
-    let producer = producer.get_resource()?;
-    Ok(producer.id().into())
+    producer.with_resource(|producer| producer.id().into())
 }
 
 #[rustler::nif]
-pub fn producer_kind(producer: ResourceArc<ProducerRef>) -> NifResult<JsonSerdeWrap<MediaKind>> {
-    let producer = producer.get_resource()?;
-    Ok(producer.kind().into())
+pub fn producer_kind(producer: ResourceArc<ProducerRef>) -> NifResult<Atom> {
+    producer.with_resource(|producer| producer.kind().as_atom())
 }
 
 #[rustler::nif]
-pub fn producer_type(producer: ResourceArc<ProducerRef>) -> NifResult<JsonSerdeWrap<ProducerType>> {
-    let producer = producer.get_resource()?;
-    Ok(producer.r#type().into())
+pub fn producer_type(producer: ResourceArc<ProducerRef>) -> NifResult<Atom> {
+    producer.with_resource(|producer| producer.r#type().as_atom())
 }
 
 #[rustler::nif]
 pub fn producer_rtp_parameters(
     producer: ResourceArc<ProducerRef>,
 ) -> NifResult<JsonSerdeWrap<RtpParameters>> {
-    let producer = producer.get_resource()?;
-    Ok(producer.rtp_parameters().clone().into())
+    producer.with_resource(|producer| producer.rtp_parameters().clone().into())
 }
 
 #[rustler::nif]
@@ -40,11 +43,39 @@ pub fn producer_close(producer: ResourceArc<ProducerRef>) -> NifResult<(Atom,)>
     producer.close();
     Ok((atoms::ok(),))
 }
+
+/// Closes the producer and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "producer_close_async")]
+pub fn producer_close_confirmed(
+    env: Env,
+    producer: ResourceArc<ProducerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = producer.close_confirmed()?;
+    send_async_nif_result_with_from(env, "producer_close_async", from, timeout_ms, confirmed)
+}
+
+/// Closes the producer when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn producer_monitor_owner(
+    env: Env,
+    producer: ResourceArc<ProducerRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&producer, env, owner)?;
+    Ok((atoms::ok(),))
+}
 #[rustler::nif(name = "producer_pause_async")]
-pub fn producer_pause(env: Env, producer: ResourceArc<ProducerRef>, from: Term) -> NifResult<Atom> {
+pub fn producer_pause(
+    env: Env,
+    producer: ResourceArc<ProducerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let producer = producer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "producer_pause_async", from, timeout_ms, async move {
         producer.pause().await.map_err(|error| format!("{}", error))
     })
 }
@@ -59,16 +90,14 @@ pub fn producer_closed(producer: ResourceArc<ProducerRef>) -> NifResult<bool> {
 
 #[rustler::nif]
 pub fn producer_paused(producer: ResourceArc<ProducerRef>) -> NifResult<bool> {
-    let producer = producer.get_resource()?;
-    Ok(producer.paused())
+    producer.with_resource(|producer| producer.paused())
 }
 
 #[rustler::nif]
 pub fn producer_score(
     producer: ResourceArc<ProducerRef>,
 ) -> NifResult<JsonSerdeWrap<std::vec::Vec<ProducerScore>>> {
-    let producer = producer.get_resource()?;
-    Ok(JsonSerdeWrap::new(producer.score()))
+    producer.with_resource(|producer| JsonSerdeWrap::new(producer.score()))
 }
 
 #[rustler::nif(name = "producer_get_stats_async")]
@@ -76,16 +105,34 @@ pub fn producer_get_stats(
     env: Env,
     producer: ResourceArc<ProducerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let producer = producer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        producer
-            .get_stats()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "producer_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            producer
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::RtpStreamRecv>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn producer_stats_subscribe(
+    producer: ResourceArc<ProducerRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&producer, pid, interval_ms, delta)
 }
 
 #[rustler::nif(name = "producer_resume_async")]
@@ -93,10 +140,11 @@ pub fn producer_resume(
     env: Env,
     producer: ResourceArc<ProducerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let producer = producer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "producer_resume_async", from, timeout_ms, async move {
         producer
             .resume()
             .await
@@ -105,14 +153,19 @@ pub fn producer_resume(
 }
 
 #[rustler::nif(name = "producer_dump_async")]
-pub fn producer_dump(env: Env, producer: ResourceArc<ProducerRef>, from: Term) -> NifResult<Atom> {
+pub fn producer_dump(
+    env: Env,
+    producer: ResourceArc<ProducerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
     let producer = producer.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "producer_dump_async", from, timeout_ms, async move {
         producer
             .dump()
             .await
-            .map(JsonSerdeWrap::new)
+            .map(dump::ProducerDumpStruct::from)
             .map_err(|error| format!("{}", error))
     })
 }
@@ -121,54 +174,48 @@ pub fn producer_event(
     producer: ResourceArc<ProducerRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(rustler::Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(rustler::Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = producer.close_reason();
     let producer = producer.get_resource()?;
-
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, producer, on_close);
-    }
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
+
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &producer,
+        close_reason,
+        &event_types,
+    ));
     if event_types.contains(&atoms::on_pause()) {
-        crate::reg_callback!(pid, producer, on_pause);
+        handlers.push(crate::reg_callback!(sink, producer, on_pause));
     }
     if event_types.contains(&atoms::on_resume()) {
-        crate::reg_callback!(pid, producer, on_resume);
+        handlers.push(crate::reg_callback!(sink, producer, on_resume));
     }
 
     if event_types.contains(&atoms::on_video_orientation_change()) {
-        producer
-            .on_video_orientation_change(move |orientation| {
-                send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_video_orientation_change(),
-                        JsonSerdeWrap::new(orientation),
-                    ),
-                );
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(producer.on_video_orientation_change(move |orientation| {
+            sink.send_value(
+                atoms::on_video_orientation_change(),
+                JsonSerdeWrap::new(orientation),
+            );
+        }));
     }
     if event_types.contains(&atoms::on_score()) {
-        //let pid = pid.clone();
-        producer
-            .on_score(move |score| {
-                send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_score(),
-                        JsonSerdeWrap::new(score.to_vec()),
-                    ),
-                );
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(producer.on_score(move |score| {
+            sink.send_value(atoms::on_score(), JsonSerdeWrap::new(score.to_vec()));
+        }));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.Producer.Options"]
 pub struct ProducerOptionsStruct {
     pub id: Option<JsonSerdeWrap<ProducerId>>,
     pub kind: JsonSerdeWrap<MediaKind>,
@@ -177,6 +224,12 @@ pub struct ProducerOptionsStruct {
     pub key_frame_request_delay: Option<u32>,
 }
 
+crate::nif_struct_decoder!(
+    ProducerOptionsStruct,
+    "Mediasoup.Producer.Options",
+    [id, kind, rtp_parameters, paused, key_frame_request_delay]
+);
+
 impl ProducerOptionsStruct {
     pub fn to_option(&self) -> ProducerOptions {
         let mut option = match &self.id {
@@ -0,0 +1,312 @@
+//! Open entities by id, so they can be listed and found without dumping the worker.
+//!
+//! Every `DisposableResourceWrapper` registers its entity when created. Entries only hold weak
+//! references: the registry never keeps an entity open. A resource returned by a lookup shares
+//! its value with the original resource, so closing either one closes both.
+
+use crate::atoms;
+use crate::consumer::ConsumerRef;
+use crate::data_consumer::DataConsumerRef;
+use crate::data_producer::DataProducerRef;
+use crate::entity::{Entity, EntityKind};
+use crate::json_serde::JsonSerdeWrap;
+use crate::pipe_transport::PipeTransportRef;
+use crate::plain_transport::PlainTransportRef;
+use crate::producer::ProducerRef;
+use crate::resource::{self, DisposableResourceWrapper, Slot};
+use crate::router::RouterRef;
+use crate::task;
+use crate::webrtc_transport::WebRtcTransportRef;
+use crate::worker::WorkerRef;
+use mediasoup::prelude::*;
+use once_cell::sync::Lazy;
+use rustler::{Atom, Encoder, Env, NifMap, NifResult, ResourceArc, Term};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+type AnySlot = dyn Any + Send + Sync;
+
+struct Entry {
+    kind: EntityKind,
+    parent_id: Option<String>,
+    /// Milliseconds since the unix epoch.
+    created_at: u64,
+    app_data: serde_json::Value,
+    /// `Weak<Slot<T>>` for the entity type of `kind`.
+    slot: Box<AnySlot>,
+    closed: fn(&AnySlot) -> bool,
+}
+
+impl Entry {
+    /// False as soon as the entity has closed, before its close handler removes the entry.
+    fn open(&self) -> bool {
+        !(self.closed)(self.slot.as_ref())
+    }
+}
+
+static REGISTRY: Lazy<RwLock<HashMap<String, Entry>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn upgrade<T: Entity>(slot: &AnySlot) -> Option<Arc<Slot<T>>> {
+    slot.downcast_ref::<Weak<Slot<T>>>().and_then(Weak::upgrade)
+}
+
+fn slot_closed<T: Entity>(slot: &AnySlot) -> bool {
+    upgrade::<T>(slot).is_none_or(|slot| {
+        resource::read(&slot)
+            .as_ref()
+            .is_none_or(|entity| entity.closed())
+    })
+}
+
+/// Adds the entity in `slot` to the registry until it closes.
+pub fn register<T: Entity>(slot: &Arc<Slot<T>>) {
+    let value = resource::read(slot);
+    let Some(entity) = value.as_ref() else {
+        return;
+    };
+    if entity.closed() {
+        return;
+    }
+
+    let id = entity.id();
+    let entry = Entry {
+        kind: T::KIND,
+        parent_id: entity.parent_id(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or_default(),
+        app_data: serde_json::Value::Null,
+        slot: Box::new(Arc::downgrade(slot)),
+        closed: slot_closed::<T>,
+    };
+    if let Ok(mut entries) = REGISTRY.write() {
+        entries.insert(id.clone(), entry);
+    }
+    // Removed from a task: the entity may close while the registry is locked, when a lookup
+    // drops the last reference to it.
+    entity
+        .add_close_handler(move || {
+            task::spawn(async move {
+                if let Ok(mut entries) = REGISTRY.write() {
+                    entries.remove(&id);
+                }
+            })
+            .detach();
+        })
+        .detach();
+}
+
+fn find<T: Entity>(id: &str) -> Option<ResourceArc<DisposableResourceWrapper<T>>>
+where
+    DisposableResourceWrapper<T>: rustler::Resource,
+{
+    let entries = REGISTRY.read().ok()?;
+    let entry = entries.get(id)?;
+    if entry.kind != T::KIND || !entry.open() {
+        return None;
+    }
+    let slot = upgrade::<T>(entry.slot.as_ref())?;
+    Some(ResourceArc::new(DisposableResourceWrapper::from_slot(slot)))
+}
+
+/// Ids of the open children of `parent_id` with one of `kinds`, oldest first.
+fn children_ids(parent_id: &str, kinds: &[EntityKind]) -> Vec<(EntityKind, String)> {
+    let Ok(entries) = REGISTRY.read() else {
+        return Vec::new();
+    };
+    let mut children: Vec<_> = entries
+        .iter()
+        .filter(|(_, entry)| {
+            kinds.contains(&entry.kind)
+                && entry.parent_id.as_deref() == Some(parent_id)
+                && entry.open()
+        })
+        .map(|(id, entry)| (entry.created_at, entry.kind, id.clone()))
+        .collect();
+    children.sort_by(|a, b| (a.0, &a.2).cmp(&(b.0, &b.2)));
+    children
+        .into_iter()
+        .map(|(_, kind, id)| (kind, id))
+        .collect()
+}
+
+/// The open children of `parent_id` of type `T`, oldest first, for use after the NIF returns.
+pub fn children_values<T: Entity + Clone>(parent_id: &str) -> Vec<T> {
+    let ids = children_ids(parent_id, &[T::KIND]);
+    let Ok(entries) = REGISTRY.read() else {
+        return Vec::new();
+    };
+    ids.iter()
+        .filter_map(|(_, id)| upgrade::<T>(entries.get(id)?.slot.as_ref()))
+        .filter_map(|slot| resource::read(&slot).clone())
+        .collect()
+}
+
+fn children<T: Entity>(parent_id: &str) -> Vec<ResourceArc<DisposableResourceWrapper<T>>>
+where
+    DisposableResourceWrapper<T>: rustler::Resource,
+{
+    children_ids(parent_id, &[T::KIND])
+        .into_iter()
+        .filter_map(|(_, id)| find::<T>(&id))
+        .collect()
+}
+
+fn kind_atom(env: Env, kind: EntityKind) -> NifResult<Atom> {
+    Atom::from_str(env, kind.name())
+}
+
+/// Encodes the entity `id` as `{kind, resource}`.
+fn encode_entity<'a>(env: Env<'a>, kind: EntityKind, id: &str) -> Option<Term<'a>> {
+    let resource = match kind {
+        EntityKind::Worker => find::<Worker>(id)?.encode(env),
+        EntityKind::WebRtcServer => find::<WebRtcServer>(id)?.encode(env),
+        EntityKind::Router => find::<Router>(id)?.encode(env),
+        EntityKind::WebRtcTransport => find::<WebRtcTransport>(id)?.encode(env),
+        EntityKind::PlainTransport => find::<PlainTransport>(id)?.encode(env),
+        EntityKind::PipeTransport => find::<PipeTransport>(id)?.encode(env),
+        EntityKind::Producer => find::<Producer>(id)?.encode(env),
+        EntityKind::Consumer => find::<Consumer>(id)?.encode(env),
+        EntityKind::DataProducer => find::<DataProducer>(id)?.encode(env),
+        EntityKind::DataConsumer => find::<DataConsumer>(id)?.encode(env),
+    };
+    Some((kind_atom(env, kind).ok()?, resource).encode(env))
+}
+
+/// The id of a webrtc, plain or pipe transport resource.
+fn transport_id(transport: Term) -> NifResult<String> {
+    if let Ok(transport) = transport.decode::<ResourceArc<WebRtcTransportRef>>() {
+        return Ok(Entity::id(&transport.get_resource()?));
+    }
+    if let Ok(transport) = transport.decode::<ResourceArc<PlainTransportRef>>() {
+        return Ok(Entity::id(&transport.get_resource()?));
+    }
+    if let Ok(transport) = transport.decode::<ResourceArc<PipeTransportRef>>() {
+        return Ok(Entity::id(&transport.get_resource()?));
+    }
+    Err(rustler::Error::BadArg)
+}
+
+#[derive(NifMap)]
+pub struct EntityInfo {
+    id: String,
+    kind: Atom,
+    parent_id: Option<String>,
+    created_at: u64,
+    app_data: JsonSerdeWrap<serde_json::Value>,
+}
+
+impl EntityInfo {
+    fn of(env: Env, id: &str, entry: &Entry) -> NifResult<Self> {
+        Ok(Self {
+            id: id.to_string(),
+            kind: kind_atom(env, entry.kind)?,
+            parent_id: entry.parent_id.clone(),
+            created_at: entry.created_at,
+            app_data: JsonSerdeWrap::new(entry.app_data.clone()),
+        })
+    }
+}
+
+/// Lists the open entities, or the open children of `parent_id`, oldest first.
+#[rustler::nif]
+pub fn registry_list(env: Env, parent_id: Option<String>) -> NifResult<Vec<EntityInfo>> {
+    let entries = REGISTRY
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new(atoms::poison_error())))?;
+    let mut infos = entries
+        .iter()
+        .filter(|(_, entry)| parent_id.is_none() || entry.parent_id == parent_id)
+        .filter(|(_, entry)| entry.open())
+        .map(|(id, entry)| EntityInfo::of(env, id, entry))
+        .collect::<NifResult<Vec<_>>>()?;
+    infos.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    Ok(infos)
+}
+
+#[rustler::nif]
+pub fn registry_info(env: Env, id: String) -> NifResult<Option<EntityInfo>> {
+    let entries = REGISTRY
+        .read()
+        .map_err(|_| rustler::Error::Term(Box::new(atoms::poison_error())))?;
+    entries
+        .get(&id)
+        .filter(|entry| entry.open())
+        .map(|entry| EntityInfo::of(env, &id, entry))
+        .transpose()
+}
+
+/// Attaches `app_data` to the open entity, returned in its info.
+#[rustler::nif]
+pub fn registry_set_app_data(
+    env: Env,
+    id: String,
+    app_data: JsonSerdeWrap<serde_json::Value>,
+) -> NifResult<Term> {
+    let mut entries = REGISTRY
+        .write()
+        .map_err(|_| rustler::Error::Term(Box::new(atoms::poison_error())))?;
+    Ok(match entries.get_mut(&id).filter(|entry| entry.open()) {
+        Some(entry) => {
+            entry.app_data = (*app_data).clone();
+            (atoms::ok(),).encode(env)
+        }
+        None => (atoms::error(), atoms::not_found()).encode(env),
+    })
+}
+
+/// Finds an open entity by id; returns `{kind, resource}` or nil.
+#[rustler::nif]
+pub fn registry_find(env: Env, id: String) -> Option<Term> {
+    let kind = REGISTRY.read().ok()?.get(&id)?.kind;
+    encode_entity(env, kind, &id)
+}
+
+#[rustler::nif]
+pub fn worker_list_routers(
+    worker: ResourceArc<WorkerRef>,
+) -> NifResult<Vec<ResourceArc<RouterRef>>> {
+    worker.with_resource(|worker| children::<Router>(&worker.id().to_string()))
+}
+
+/// Lists the transports of the router as `{kind, resource}`.
+#[rustler::nif]
+pub fn router_list_transports(env: Env, router: ResourceArc<RouterRef>) -> NifResult<Vec<Term>> {
+    let router = router.get_resource()?;
+    let kinds = [
+        EntityKind::WebRtcTransport,
+        EntityKind::PlainTransport,
+        EntityKind::PipeTransport,
+    ];
+    Ok(children_ids(&router.id().to_string(), &kinds)
+        .into_iter()
+        .filter_map(|(kind, id)| encode_entity(env, kind, &id))
+        .collect())
+}
+
+#[rustler::nif]
+pub fn transport_list_producers(transport: Term) -> NifResult<Vec<ResourceArc<ProducerRef>>> {
+    Ok(children::<Producer>(&transport_id(transport)?))
+}
+
+#[rustler::nif]
+pub fn transport_list_consumers(transport: Term) -> NifResult<Vec<ResourceArc<ConsumerRef>>> {
+    Ok(children::<Consumer>(&transport_id(transport)?))
+}
+
+#[rustler::nif]
+pub fn transport_list_data_producers(
+    transport: Term,
+) -> NifResult<Vec<ResourceArc<DataProducerRef>>> {
+    Ok(children::<DataProducer>(&transport_id(transport)?))
+}
+
+#[rustler::nif]
+pub fn transport_list_data_consumers(
+    transport: Term,
+) -> NifResult<Vec<ResourceArc<DataConsumerRef>>> {
+    Ok(children::<DataConsumer>(&transport_id(transport)?))
+}
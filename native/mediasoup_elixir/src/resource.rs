@@ -1,35 +1,216 @@
-use crate::atoms;
-use std::sync::Mutex;
-pub struct DisposableResourceWrapper<T>(pub Mutex<Option<T>>);
-impl<T> DisposableResourceWrapper<T> {
+use crate::close::{self, CloseReason, CloseReasonCell};
+use crate::entity::Entity;
+use crate::send_msg_from_other_thread;
+use crate::{atoms, metrics, registry};
+use rustler::{Env, LocalPid, Monitor, NifResult, ResourceArc};
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, Weak};
+
+/// The wrapped value, shared by the resources that refer to the same mediasoup object.
+/// Getters only take the read lock, so they never wait for each other.
+pub struct Slot<T> {
+    value: RwLock<Option<T>>,
+    close_reason: Arc<CloseReasonCell>,
+}
+
+/// Locks `slot` for reading. A panic while it was locked does not poison it: the value is a
+/// handle that is either set or taken, never left half updated.
+pub fn read<T>(slot: &Slot<T>) -> RwLockReadGuard<'_, Option<T>> {
+    slot.value.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn take<T>(slot: &Slot<T>, reason: CloseReason) -> Option<T> {
+    let value = slot
+        .value
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    if value.is_some() {
+        slot.close_reason.set(reason);
+    }
+    value
+}
+
+fn lock_owner(owner: &Mutex<Option<Owner>>) -> MutexGuard<'_, Option<Owner>> {
+    owner.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The process that owns a resource; see `DisposableResourceWrapper::monitor_owner`.
+struct Owner {
+    /// Receives `{:nif_internal_event, :on_owner_down, owner_pid}`.
+    notify: LocalPid,
+    monitor: Monitor,
+}
+
+pub struct DisposableResourceWrapper<T> {
+    value: Arc<Slot<T>>,
+    owner: Mutex<Option<Owner>>,
+}
+impl<T> DisposableResourceWrapper<T>
+where
+    T: Entity,
+{
     pub fn new(value: T) -> Self {
-        Self(Mutex::new(Some(value)))
+        metrics::track_entity(&value);
+        let close_reason = Arc::default();
+        close::watch(&value, &close_reason);
+        let slot = Arc::new(Slot {
+            value: RwLock::new(Some(value)),
+            close_reason,
+        });
+        registry::register(&slot);
+        Self::from_slot(slot)
+    }
+
+    /// Closes the resource like `close`. The future resolves once the worker has confirmed
+    /// the close, see `close::confirm`.
+    pub fn close_confirmed(
+        &self,
+    ) -> NifResult<impl Future<Output = Result<(), String>> + Send + 'static> {
+        let confirmed = self.with_resource(close::confirm)?;
+        self.close();
+        Ok(confirmed)
+    }
+}
+impl<T> DisposableResourceWrapper<T> {
+    /// Another resource for the value of an existing one, see `registry`.
+    /// Closing either of them closes both; the owner is not shared.
+    pub fn from_slot(value: Arc<Slot<T>>) -> Self {
+        Self {
+            value,
+            owner: Mutex::new(None),
+        }
     }
+
     pub fn close(&self) {
-        if let Ok(mut v) = self.0.lock() {
-            *v = None;
+        // Dropped after the lock is released: closing runs the close callbacks of the entity.
+        let value = take(&self.value, CloseReason::Explicit);
+        drop(value);
+    }
+
+    /// The shared value, without keeping it open once every resource for it is dropped.
+    pub fn downgrade(&self) -> Weak<Slot<T>> {
+        Arc::downgrade(&self.value)
+    }
+
+    /// The reason the entity closed, for its `on_close_reason` event.
+    pub fn close_reason(&self) -> Arc<CloseReasonCell> {
+        Arc::clone(&self.value.close_reason)
+    }
+
+    fn closed(&self) -> bool {
+        read(&self.value).is_none()
+    }
+
+    /// Runs `f` with the wrapped value, without cloning it.
+    /// For getters; `f` must not close the resource.
+    pub fn with_resource<R>(&self, f: impl FnOnce(&T) -> R) -> NifResult<R> {
+        read(&self.value)
+            .as_ref()
+            .map(f)
+            .ok_or_else(|| rustler::Error::Term(Box::new(atoms::terminated())))
+    }
+
+    /// Closes the resource if `monitor` is the monitor of its owner.
+    /// Called from `rustler::Resource::down` of the wrapped resources.
+    pub fn owner_down(&self, owner_pid: LocalPid, monitor: Monitor) {
+        let owner = {
+            let mut owner = lock_owner(&self.owner);
+            match owner.as_ref() {
+                Some(current) if current.monitor == monitor => owner.take(),
+                _ => None,
+            }
+        };
+        if let Some(owner) = owner {
+            self.close_for_owner(owner_pid, owner.notify);
+        }
+    }
+
+    fn close_for_owner(&self, owner_pid: LocalPid, notify: LocalPid) {
+        let value = take(&self.value, CloseReason::OwnerDown);
+        if value.is_some() {
+            // Queued before the value is dropped, so it arrives ahead of the `on_close` event.
+            send_msg_from_other_thread(
+                notify,
+                (
+                    atoms::nif_internal_event(),
+                    atoms::on_owner_down(),
+                    owner_pid,
+                ),
+            );
         }
+        drop(value);
     }
 }
 impl<T> DisposableResourceWrapper<T>
 where
-    T: Clone,
+    Self: rustler::Resource,
 {
-    fn read(
-        &self,
-    ) -> Result<Option<T>, std::sync::PoisonError<std::sync::MutexGuard<std::option::Option<T>>>>
-    {
-        match self.0.lock() {
-            Ok(v) => Ok(v.clone()),
-            Err(err) => Err(err),
+    /// Closes the resource when the process `owner` exits, and notifies the calling process
+    /// with `{:nif_internal_event, :on_owner_down, owner}`.
+    /// Replaces the previous owner. If `owner` is not alive the resource is closed right away.
+    pub fn monitor_owner(resource: &ResourceArc<Self>, env: Env, owner: LocalPid) -> NifResult<()> {
+        if resource.closed() {
+            return Err(rustler::Error::Term(Box::new(atoms::terminated())));
         }
+        let notify = env.pid();
+        let mut current = lock_owner(&resource.owner);
+        if let Some(previous) = current.take() {
+            env.demonitor(resource, &previous.monitor);
+        }
+        match env.monitor(resource, &owner) {
+            Some(monitor) => {
+                *current = Some(Owner { notify, monitor });
+            }
+            None => {
+                drop(current);
+                resource.close_for_owner(owner, notify);
+            }
+        }
+        Ok(())
     }
-
+}
+impl<T> DisposableResourceWrapper<T>
+where
+    T: Clone,
+{
+    /// A clone of the wrapped value, for use after the NIF returns, e.g. in an async task.
     pub fn get_resource(&self) -> rustler::NifResult<T> {
-        let resource = self
-            .read()
-            .map_err(|_error| rustler::Error::Term(Box::new(atoms::poison_error())))?
-            .ok_or_else(|| rustler::Error::Term(Box::new(atoms::terminated())))?;
-        Ok(resource)
+        self.with_resource(T::clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot<T>(value: T) -> Arc<Slot<T>> {
+        Arc::new(Slot {
+            value: RwLock::new(Some(value)),
+            close_reason: Arc::default(),
+        })
+    }
+
+    #[test]
+    fn close_is_shared_by_resources_of_a_slot() {
+        let slot = slot(Arc::new(1));
+        let resource = DisposableResourceWrapper::from_slot(slot.clone());
+        let found = DisposableResourceWrapper::from_slot(slot);
+
+        assert_eq!(
+            Ok(2),
+            found.with_resource(|value| **value + 1).map_err(|_| ())
+        );
+        resource.close();
+        // The error atoms can only be made inside a NIF call.
+        assert!(found.closed());
+    }
+
+    #[test]
+    fn first_close_reason_is_kept() {
+        let slot = slot(Arc::new(1));
+        slot.close_reason.set(CloseReason::ParentClosed);
+        DisposableResourceWrapper::from_slot(slot.clone()).close();
+        assert_eq!(CloseReason::ParentClosed, slot.close_reason.get());
     }
 }
@@ -1,31 +1,65 @@
+use crate::close;
+use crate::consumer::ConsumerRef;
+use crate::data_consumer::DataConsumerRef;
+use crate::data_producer::DataProducerRef;
+use crate::data_structure::SerNumSctpStreams;
 use crate::json_serde::JsonSerdeWrap;
 use crate::pipe_transport::{PipeTransportOptionsStruct, PipeTransportRef};
 use crate::plain_transport::{PlainTransportOptionsStruct, PlainTransportRef};
+use crate::producer::ProducerRef;
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::validation::ValidationResult;
 use crate::webrtc_transport::{WebRtcTransportOptionsStruct, WebRtcTransportRef};
-use crate::{atoms, send_async_nif_result_with_from, DisposableResourceWrapper};
+use crate::{atoms, dump, send_async_nif_result_with_from, stats, DisposableResourceWrapper};
+use mediasoup::data_producer::DataProducerId;
 use mediasoup::prelude::{RtpCapabilities, RtpCapabilitiesFinalized, RtpCodecCapability};
 use mediasoup::producer::ProducerId;
-use mediasoup::router::{Router, RouterId, RouterOptions};
-use rustler::{Env, Error, NifResult, NifStruct, ResourceArc, Term};
+use mediasoup::router::{PipeToRouterOptions, Router, RouterId, RouterOptions};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type RouterRef = DisposableResourceWrapper<Router>;
 #[rustler::resource_impl]
-impl rustler::Resource for RouterRef {}
+impl rustler::Resource for RouterRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn router_id(router: ResourceArc<RouterRef>) -> NifResult<JsonSerdeWrap<RouterId>> {
-    let router = router.get_resource()?;
-    Ok(router.id().into())
+    router.with_resource(|router| router.id().into())
 }
 #[rustler::nif]
 pub fn router_close(router: ResourceArc<RouterRef>) -> NifResult<(rustler::Atom,)> {
     router.close();
     Ok((atoms::ok(),))
 }
+
+/// Closes the router and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "router_close_async")]
+pub fn router_close_confirmed(
+    env: Env,
+    router: ResourceArc<RouterRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = router.close_confirmed()?;
+    send_async_nif_result_with_from(env, "router_close_async", from, timeout_ms, confirmed)
+}
+
+/// Closes the router when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn router_monitor_owner(
+    env: Env,
+    router: ResourceArc<RouterRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&router, env, owner)?;
+    Ok((atoms::ok(),))
+}
 #[rustler::nif]
 pub fn router_closed(router: ResourceArc<RouterRef>) -> NifResult<bool> {
-    let router = router.get_resource()?;
-    Ok(router.closed())
+    router.with_resource(|router| router.closed())
 }
 
 #[rustler::nif(name = "router_create_webrtc_transport_async")]
@@ -34,18 +68,25 @@ pub fn router_create_webrtc_transport(
     router: ResourceArc<RouterRef>,
     option: WebRtcTransportOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let router = router.get_resource()?;
     let option = option.try_to_option()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        router
-            .create_webrtc_transport(option)
-            .await
-            .map(WebRtcTransportRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "router_create_webrtc_transport_async",
+        from,
+        timeout_ms,
+        async move {
+            router
+                .create_webrtc_transport(option)
+                .await
+                .map(WebRtcTransportRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "router_create_plain_transport_async")]
@@ -54,20 +95,25 @@ pub fn router_create_plain_transport(
     router: ResourceArc<RouterRef>,
     option: PlainTransportOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let router = router.get_resource()?;
-    let option = option
-        .try_to_option()
-        .map_err(|error| Error::Term(Box::new(error.to_string())))?;
+    let option = option.try_to_option()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        router
-            .create_plain_transport(option)
-            .await
-            .map(PlainTransportRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "router_create_plain_transport_async",
+        from,
+        timeout_ms,
+        async move {
+            router
+                .create_plain_transport(option)
+                .await
+                .map(PlainTransportRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
@@ -85,18 +131,95 @@ pub fn router_create_pipe_transport(
     router: ResourceArc<RouterRef>,
     option: PipeTransportOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let router = router.get_resource()?;
     let option = option.try_to_option()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        router
-            .create_pipe_transport(option)
-            .await
-            .map(PipeTransportRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "router_create_pipe_transport_async",
+        from,
+        timeout_ms,
+        async move {
+            router
+                .create_pipe_transport(option)
+                .await
+                .map(PipeTransportRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+#[rustler::nif(name = "router_pipe_producer_to_router_async")]
+pub fn router_pipe_producer_to_router(
+    env: Env,
+    router: ResourceArc<RouterRef>,
+    producer_id: JsonSerdeWrap<ProducerId>,
+    remote_router: ResourceArc<RouterRef>,
+    option: PipeToRouterOptionsStruct,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<rustler::Atom> {
+    let router = router.get_resource()?;
+    let option = option.try_to_option(remote_router.get_resource()?)?;
+    let producer_id = *producer_id;
+
+    send_async_nif_result_with_from(
+        env,
+        "router_pipe_producer_to_router_async",
+        from,
+        timeout_ms,
+        async move {
+            router
+                .pipe_producer_to_router(producer_id, option)
+                .await
+                .map(|pair| {
+                    (
+                        ResourceArc::new(ConsumerRef::new(pair.pipe_consumer)),
+                        ResourceArc::new(ProducerRef::new(pair.pipe_producer.into_inner())),
+                    )
+                })
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+#[rustler::nif(name = "router_pipe_data_producer_to_router_async")]
+pub fn router_pipe_data_producer_to_router(
+    env: Env,
+    router: ResourceArc<RouterRef>,
+    data_producer_id: JsonSerdeWrap<DataProducerId>,
+    remote_router: ResourceArc<RouterRef>,
+    option: PipeToRouterOptionsStruct,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<rustler::Atom> {
+    let router = router.get_resource()?;
+    let option = option.try_to_option(remote_router.get_resource()?)?;
+    let data_producer_id = *data_producer_id;
+
+    send_async_nif_result_with_from(
+        env,
+        "router_pipe_data_producer_to_router_async",
+        from,
+        timeout_ms,
+        async move {
+            router
+                .pipe_data_producer_to_router(data_producer_id, option)
+                .await
+                .map(|pair| {
+                    (
+                        ResourceArc::new(DataConsumerRef::new(pair.pipe_data_consumer)),
+                        ResourceArc::new(DataProducerRef::new(
+                            pair.pipe_data_producer.into_inner(),
+                        )),
+                    )
+                })
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
@@ -112,19 +235,40 @@ pub fn router_can_consume(
     let can_consume = router.can_consume(&producer_id, &rtp_capabilities);
     Ok(can_consume)
 }
+/// Replies with the stats of every transport of the router and of their children, see
+/// `stats::router_stats`.
+#[rustler::nif(name = "router_get_stats_async")]
+pub fn router_get_stats(
+    env: Env,
+    router: ResourceArc<RouterRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let stats = router.with_resource(|router| stats::router_stats(&router.id().to_string()))?;
+
+    send_async_nif_result_with_from(
+        env,
+        "router_get_stats_async",
+        from,
+        timeout_ms,
+        async move { Ok::<_, String>(stats.await) },
+    )
+}
+
 #[rustler::nif(name = "router_dump_async")]
 pub fn router_dump(
     env: Env,
     router: ResourceArc<RouterRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let router = router.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "router_dump_async", from, timeout_ms, async move {
         router
             .dump()
             .await
-            .map(JsonSerdeWrap::new)
+            .map(dump::RouterDumpStruct::from)
             .map_err(|error| format!("{}", error))
     })
 }
@@ -134,14 +278,21 @@ pub fn router_event(
     router: ResourceArc<RouterRef>,
     pid: rustler::LocalPid,
     event_types: Vec<rustler::Atom>,
-) -> NifResult<(rustler::Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(rustler::Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = router.close_reason();
     let router = router.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, router, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &router,
+        close_reason,
+        &event_types,
+    ));
     if event_types.contains(&atoms::on_worker_close()) {
-        crate::reg_callback!(pid, router, on_worker_close);
+        handlers.push(crate::reg_callback!(sink, router, on_worker_close));
     }
 
     /* TODO:
@@ -175,15 +326,22 @@ pub fn router_event(
     }
     */
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.Router.Options"]
 pub struct RouterOptionsStruct {
     pub media_codecs: Option<JsonSerdeWrap<Vec<RtpCodecCapability>>>,
 }
 
+crate::nif_struct_decoder!(
+    RouterOptionsStruct,
+    "Mediasoup.Router.Options",
+    [media_codecs]
+);
+
 impl RouterOptionsStruct {
     pub fn to_option(&self) -> RouterOptions {
         let mut value = RouterOptions::default();
@@ -193,3 +351,55 @@ impl RouterOptionsStruct {
         value
     }
 }
+
+pub struct PipeToRouterOptionsStruct {
+    /// Create a SCTP association.
+    /// Default true.
+    pub enable_sctp: Option<bool>,
+    /// SCTP streams number.
+    pub num_sctp_streams: Option<JsonSerdeWrap<SerNumSctpStreams>>,
+    /// Enable RTX and NACK for RTP retransmission.
+    /// Default false.
+    pub enable_rtx: Option<bool>,
+    /// Enable SRTP.
+    /// Default false.
+    pub enable_srtp: Option<bool>,
+}
+
+// The target router is passed to the NIF as a separate resource, so the `router` field and the
+// elixir-only callbacks of the struct are not decoded.
+crate::nif_struct_decoder!(
+    PipeToRouterOptionsStruct,
+    "Mediasoup.Router.PipeToRouterOptions",
+    [enable_sctp, num_sctp_streams, enable_rtx, enable_srtp]
+);
+
+impl PipeToRouterOptionsStruct {
+    pub fn try_to_option(self, router: Router) -> NifResult<PipeToRouterOptions> {
+        self.validate()?;
+        let mut option = PipeToRouterOptions::new(router);
+
+        if let Some(enable_sctp) = self.enable_sctp {
+            option.enable_sctp = enable_sctp;
+        }
+        if let Some(num_sctp_streams) = self.num_sctp_streams {
+            option.num_sctp_streams = num_sctp_streams.as_streams();
+        }
+        if let Some(enable_rtx) = self.enable_rtx {
+            option.enable_rtx = enable_rtx;
+        }
+        if let Some(enable_srtp) = self.enable_srtp {
+            option.enable_srtp = enable_srtp;
+        }
+        Ok(option)
+    }
+
+    fn validate(&self) -> ValidationResult {
+        if let Some(num_sctp_streams) = &self.num_sctp_streams {
+            num_sctp_streams
+                .validate()
+                .map_err(|error| error.within("num_sctp_streams"))?;
+        }
+        Ok(())
+    }
+}
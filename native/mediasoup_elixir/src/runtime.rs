@@ -0,0 +1,145 @@
+//! Configuration and stats of the executor thread pools.
+
+use crate::atoms;
+use crate::task::{self, PoolConfig, PoolStats, RuntimeConfig};
+use crate::validation::{ensure, ensure_positive, ValidationResult};
+use rustler::{Encoder, Env, NifMap, NifResult, Term};
+
+pub struct RuntimeOptionsStruct {
+    task_threads: Option<u32>,
+    task_thread_name: Option<String>,
+    worker_manager_threads: Option<u32>,
+    worker_manager_thread_name: Option<String>,
+    stack_size: Option<u32>,
+}
+
+crate::nif_struct_decoder!(
+    RuntimeOptionsStruct,
+    "Mediasoup.Runtime.Options",
+    [
+        task_threads,
+        task_thread_name,
+        worker_manager_threads,
+        worker_manager_thread_name,
+        stack_size
+    ]
+);
+
+impl RuntimeOptionsStruct {
+    fn try_to_config(self) -> NifResult<RuntimeConfig> {
+        self.validate()?;
+        let mut config = RuntimeConfig::default();
+        let stack_size = self.stack_size.map(|size| size as usize);
+
+        apply(
+            &mut config.task,
+            self.task_threads,
+            self.task_thread_name,
+            stack_size,
+        );
+        apply(
+            &mut config.worker_manager,
+            self.worker_manager_threads,
+            self.worker_manager_thread_name,
+            stack_size,
+        );
+        Ok(config)
+    }
+
+    fn validate(&self) -> ValidationResult {
+        ensure_positive(self.task_threads, "task_threads")?;
+        ensure_positive(self.worker_manager_threads, "worker_manager_threads")?;
+        ensure_positive(self.stack_size, "stack_size")?;
+        for (name, field) in [
+            (&self.task_thread_name, "task_thread_name"),
+            (
+                &self.worker_manager_thread_name,
+                "worker_manager_thread_name",
+            ),
+        ] {
+            if let Some(name) = name {
+                ensure(
+                    !name.is_empty() && !name.contains('\0'),
+                    field,
+                    "must be a non-empty string without NUL bytes",
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn apply(
+    pool: &mut PoolConfig,
+    threads: Option<u32>,
+    name: Option<String>,
+    stack_size: Option<usize>,
+) {
+    if let Some(threads) = threads {
+        pool.threads = threads as usize;
+    }
+    if let Some(name) = name {
+        pool.name = name;
+    }
+    pool.stack_size = stack_size;
+}
+
+#[derive(NifMap)]
+pub struct ThreadStatsMap {
+    name: String,
+    busy_time_us: u64,
+}
+
+#[derive(NifMap)]
+pub struct PoolStatsMap {
+    name: String,
+    queued_tasks: usize,
+    active_tasks: usize,
+    panics: u64,
+    restarts: u64,
+    threads: Vec<ThreadStatsMap>,
+}
+
+impl From<&PoolStats> for PoolStatsMap {
+    fn from(stats: &PoolStats) -> Self {
+        Self {
+            name: stats.name.clone(),
+            queued_tasks: stats.queued_tasks(),
+            active_tasks: stats.active_tasks(),
+            panics: stats.panics(),
+            restarts: stats.restarts(),
+            threads: stats
+                .threads
+                .iter()
+                .map(|thread| ThreadStatsMap {
+                    name: thread.name.clone(),
+                    busy_time_us: thread.busy_time_us(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(NifMap)]
+pub struct RuntimeStatsMap {
+    task: PoolStatsMap,
+    worker_manager: PoolStatsMap,
+}
+
+/// Configures the thread pools. Must be called before any worker is created.
+#[rustler::nif]
+pub fn init_runtime(env: Env, options: RuntimeOptionsStruct) -> NifResult<Term> {
+    match task::configure(options.try_to_config()?) {
+        Ok(()) => Ok((atoms::ok(),).encode(env)),
+        Err(_) => Ok((atoms::error(), atoms::already_started()).encode(env)),
+    }
+}
+
+#[rustler::nif]
+pub fn runtime_stats() -> RuntimeStatsMap {
+    let (task, worker_manager) = task::stats();
+    RuntimeStatsMap {
+        task: task.as_ref().into(),
+        worker_manager: worker_manager.as_ref().into(),
+    }
+}
@@ -0,0 +1,1166 @@
+//! SDP offers and answers for endpoints that don't run mediasoup-client, such as SIP gateways,
+//! OBS (WHIP) or embedded devices.
+//!
+//! Every media section is bundled over the same `WebRtcTransport` with `rtcp-mux`, so a
+//! description carries the transport's ICE and DTLS parameters once per section, followed by
+//! the codecs, header extensions and streams of a producer or a consumer. Parsing goes the
+//! other way and yields what `connect`, `produce` and `consume` expect.
+
+use crate::json_serde::JsonSerdeWrap;
+use mediasoup::types::data_structures::{
+    DtlsFingerprint, DtlsParameters, DtlsRole, IceCandidate, IceCandidateTcpType, IceCandidateType,
+    IceParameters, Protocol,
+};
+use mediasoup::types::rtp_parameters::{
+    MediaKind, MimeTypeAudio, MimeTypeVideo, RtcpFeedback, RtcpParameters, RtpCapabilities,
+    RtpCodecCapability, RtpCodecParameters, RtpCodecParametersParameters,
+    RtpCodecParametersParametersValue, RtpEncodingParameters, RtpEncodingParametersRtx,
+    RtpHeaderExtension, RtpHeaderExtensionDirection, RtpHeaderExtensionParameters,
+    RtpHeaderExtensionUri, RtpParameters,
+};
+use mediasoup::webrtc_transport::WebRtcTransportRemoteParameters;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::{NonZeroU32, NonZeroU8};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SdpType {
+    Offer,
+    Answer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    SendRecv,
+    SendOnly,
+    RecvOnly,
+    Inactive,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Direction::SendRecv => "sendrecv",
+            Direction::SendOnly => "sendonly",
+            Direction::RecvOnly => "recvonly",
+            Direction::Inactive => "inactive",
+        }
+    }
+
+    fn from_attribute(name: &str) -> Option<Self> {
+        match name {
+            "sendrecv" => Some(Direction::SendRecv),
+            "sendonly" => Some(Direction::SendOnly),
+            "recvonly" => Some(Direction::RecvOnly),
+            "inactive" => Some(Direction::Inactive),
+            _ => None,
+        }
+    }
+
+    fn sends(self) -> bool {
+        matches!(self, Direction::SendRecv | Direction::SendOnly)
+    }
+
+    fn receives(self) -> bool {
+        matches!(self, Direction::SendRecv | Direction::RecvOnly)
+    }
+}
+
+/// A media section, seen from the transport: `sendonly` for a consumer, `recvonly` for a
+/// producer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaOptions {
+    /// Defaults to the mid of `rtp_parameters`, then to the position of the section.
+    #[serde(default)]
+    pub mid: Option<String>,
+    pub kind: MediaKind,
+    pub direction: Direction,
+    pub rtp_parameters: RtpParameters,
+    #[serde(default)]
+    pub stream_id: Option<String>,
+    #[serde(default)]
+    pub track_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildOptions {
+    #[serde(rename = "type")]
+    pub sdp_type: SdpType,
+    pub ice_parameters: IceParameters,
+    #[serde(default)]
+    pub ice_candidates: Vec<IceCandidate>,
+    pub dtls_parameters: DtlsParameters,
+    pub media: Vec<MediaOptions>,
+    #[serde(default)]
+    pub session_id: Option<u64>,
+    #[serde(default = "default_session_version")]
+    pub session_version: u64,
+}
+
+fn default_session_version() -> u64 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedMedia {
+    pub mid: String,
+    pub kind: MediaKind,
+    /// As written by the remote endpoint: `sendonly` for a stream it produces.
+    pub direction: Direction,
+    pub rtp_parameters: RtpParameters,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedSdp {
+    pub remote_parameters: WebRtcTransportRemoteParameters,
+    pub rtp_capabilities: RtpCapabilities,
+    pub media: Vec<ParsedMedia>,
+}
+
+/// `a=setup` for the local side. An offer leaves the choice to the remote endpoint unless the
+/// role is already settled, an answer to `actpass` takes the client role.
+fn setup(sdp_type: SdpType, role: DtlsRole) -> &'static str {
+    match (sdp_type, role) {
+        (SdpType::Offer, DtlsRole::Auto) => "actpass",
+        (SdpType::Answer, DtlsRole::Auto) | (_, DtlsRole::Client) => "active",
+        (_, DtlsRole::Server) => "passive",
+    }
+}
+
+/// DTLS role of the remote endpoint for its `a=setup`. `actpass` resolves to server, matching
+/// the `active` answer built by [`setup`].
+fn remote_role(setup: &str) -> Result<DtlsRole, String> {
+    match setup {
+        "active" => Ok(DtlsRole::Client),
+        "passive" | "actpass" => Ok(DtlsRole::Server),
+        other => Err(format!("unknown setup {}", other)),
+    }
+}
+
+fn fingerprint_algorithm(fingerprint: &DtlsFingerprint) -> &'static str {
+    match fingerprint {
+        DtlsFingerprint::Sha1 { .. } => "sha-1",
+        DtlsFingerprint::Sha224 { .. } => "sha-224",
+        DtlsFingerprint::Sha256 { .. } => "sha-256",
+        DtlsFingerprint::Sha384 { .. } => "sha-384",
+        DtlsFingerprint::Sha512 { .. } => "sha-512",
+    }
+}
+
+fn parse_fingerprint(value: &str) -> Result<DtlsFingerprint, String> {
+    let (algorithm, fingerprint) = value
+        .split_once(' ')
+        .ok_or_else(|| format!("invalid fingerprint {}", value))?;
+    serde_json::from_value(serde_json::json!({
+        "algorithm": algorithm.to_ascii_lowercase(),
+        "value": fingerprint.trim(),
+    }))
+    .map_err(|error| format!("fingerprint: {}", error))
+}
+
+fn kind_str(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+    }
+}
+
+fn codec_kind(codec: &RtpCodecParameters) -> MediaKind {
+    match codec {
+        RtpCodecParameters::Audio { .. } => MediaKind::Audio,
+        RtpCodecParameters::Video { .. } => MediaKind::Video,
+    }
+}
+
+fn candidate_line(candidate: &IceCandidate) -> String {
+    let protocol = match candidate.protocol {
+        Protocol::Udp => "udp",
+        Protocol::Tcp => "tcp",
+    };
+    let candidate_type = match candidate.r#type {
+        IceCandidateType::Host => "host",
+        IceCandidateType::Srflx => "srflx",
+        IceCandidateType::Prflx => "prflx",
+        IceCandidateType::Relay => "relay",
+    };
+    let mut line = format!(
+        "a=candidate:{} 1 {} {} {} {} typ {}",
+        candidate.foundation,
+        protocol,
+        candidate.priority,
+        candidate.address,
+        candidate.port,
+        candidate_type
+    );
+    if let Some(IceCandidateTcpType::Passive) = candidate.tcp_type {
+        line.push_str(" tcptype passive");
+    }
+    line
+}
+
+fn fmtp_value(value: &RtpCodecParametersParametersValue) -> String {
+    match value {
+        RtpCodecParametersParametersValue::String(value) => value.to_string(),
+        RtpCodecParametersParametersValue::Number(value) => value.to_string(),
+    }
+}
+
+fn codec_lines(lines: &mut Vec<String>, codec: &RtpCodecParameters) {
+    let (mime_type, channels, clock_rate, parameters, rtcp_feedback) = match codec {
+        RtpCodecParameters::Audio {
+            mime_type,
+            clock_rate,
+            channels,
+            parameters,
+            rtcp_feedback,
+            ..
+        } => (
+            mime_type.as_str(),
+            Some(*channels).filter(|channels| channels.get() > 1),
+            clock_rate,
+            parameters,
+            rtcp_feedback,
+        ),
+        RtpCodecParameters::Video {
+            mime_type,
+            clock_rate,
+            parameters,
+            rtcp_feedback,
+            ..
+        } => (
+            mime_type.as_str(),
+            None,
+            clock_rate,
+            parameters,
+            rtcp_feedback,
+        ),
+    };
+    let payload_type = codec.payload_type();
+    let name = mime_type
+        .split_once('/')
+        .map_or(mime_type, |(_, name)| name);
+
+    match channels {
+        Some(channels) => lines.push(format!(
+            "a=rtpmap:{} {}/{}/{}",
+            payload_type, name, clock_rate, channels
+        )),
+        None => lines.push(format!("a=rtpmap:{} {}/{}", payload_type, name, clock_rate)),
+    }
+    for feedback in rtcp_feedback {
+        if *feedback == RtcpFeedback::Unsupported {
+            continue;
+        }
+        match feedback.as_type_parameter() {
+            (r#type, "") => lines.push(format!("a=rtcp-fb:{} {}", payload_type, r#type)),
+            (r#type, parameter) => lines.push(format!(
+                "a=rtcp-fb:{} {} {}",
+                payload_type, r#type, parameter
+            )),
+        }
+    }
+    if parameters.iter().next().is_some() {
+        let parameters = parameters
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, fmtp_value(value)))
+            .collect::<Vec<_>>();
+        lines.push(format!("a=fmtp:{} {}", payload_type, parameters.join(";")));
+    }
+}
+
+fn stream_lines(lines: &mut Vec<String>, media: &MediaOptions, mid: &str) {
+    let parameters = &media.rtp_parameters;
+    let stream_id = media.stream_id.as_deref().unwrap_or("-");
+    let track_id = media
+        .track_id
+        .clone()
+        .unwrap_or_else(|| format!("track-{}", mid));
+    lines.push(format!("a=msid:{} {}", stream_id, track_id));
+
+    let mut primaries = Vec::new();
+    for encoding in &parameters.encodings {
+        let Some(ssrc) = encoding.ssrc else {
+            continue;
+        };
+        primaries.push(ssrc.to_string());
+        let mut ssrcs = vec![ssrc];
+        if let Some(rtx) = encoding.rtx {
+            lines.push(format!("a=ssrc-group:FID {} {}", ssrc, rtx.ssrc));
+            ssrcs.push(rtx.ssrc);
+        }
+        for ssrc in ssrcs {
+            if let Some(cname) = &parameters.rtcp.cname {
+                lines.push(format!("a=ssrc:{} cname:{}", ssrc, cname));
+            }
+            lines.push(format!("a=ssrc:{} msid:{} {}", ssrc, stream_id, track_id));
+        }
+    }
+    if primaries.len() > 1 {
+        lines.push(format!("a=ssrc-group:SIM {}", primaries.join(" ")));
+    }
+}
+
+fn media_section(
+    lines: &mut Vec<String>,
+    options: &BuildOptions,
+    media: &MediaOptions,
+    mid: &str,
+) -> Result<(), String> {
+    let parameters = &media.rtp_parameters;
+    if parameters.codecs.is_empty() {
+        return Err(format!("media {} has no codecs", mid));
+    }
+    if let Some(codec) = parameters
+        .codecs
+        .iter()
+        .find(|codec| codec_kind(codec) != media.kind)
+    {
+        return Err(format!(
+            "media {}: codec {} is not {}",
+            mid,
+            codec.mime_type().as_str(),
+            kind_str(media.kind)
+        ));
+    }
+
+    let payload_types = parameters
+        .codecs
+        .iter()
+        .map(|codec| codec.payload_type().to_string())
+        .collect::<Vec<_>>();
+    let candidate = options
+        .ice_candidates
+        .iter()
+        .find(|candidate| candidate.protocol == Protocol::Udp)
+        .or_else(|| options.ice_candidates.first());
+    let (address, port) = candidate.map_or(("127.0.0.1", 7), |candidate| {
+        (candidate.address.as_str(), candidate.port)
+    });
+    let ip_version = if address.contains(':') { "IP6" } else { "IP4" };
+
+    lines.push(format!(
+        "m={} {} UDP/TLS/RTP/SAVPF {}",
+        kind_str(media.kind),
+        port,
+        payload_types.join(" ")
+    ));
+    lines.push(format!("c=IN {} {}", ip_version, address));
+    lines.push(format!(
+        "a=ice-ufrag:{}",
+        options.ice_parameters.username_fragment
+    ));
+    lines.push(format!("a=ice-pwd:{}", options.ice_parameters.password));
+    lines.extend(options.ice_candidates.iter().map(candidate_line));
+    lines.push("a=end-of-candidates".to_string());
+    for fingerprint in &options.dtls_parameters.fingerprints {
+        lines.push(format!(
+            "a=fingerprint:{} {}",
+            fingerprint_algorithm(fingerprint),
+            fingerprint.value_string()
+        ));
+    }
+    lines.push(format!(
+        "a=setup:{}",
+        setup(options.sdp_type, options.dtls_parameters.role)
+    ));
+    lines.push(format!("a=mid:{}", mid));
+    lines.push(format!("a={}", media.direction.as_str()));
+    lines.push("a=rtcp-mux".to_string());
+    if parameters.rtcp.reduced_size {
+        lines.push("a=rtcp-rsize".to_string());
+    }
+    for codec in &parameters.codecs {
+        codec_lines(lines, codec);
+    }
+    for extension in &parameters.header_extensions {
+        lines.push(format!(
+            "a=extmap:{} {}",
+            extension.id,
+            extension.uri.as_str()
+        ));
+    }
+
+    if media.direction.receives() {
+        let rids = parameters
+            .encodings
+            .iter()
+            .filter_map(|encoding| encoding.rid.as_deref())
+            .collect::<Vec<_>>();
+        if !rids.is_empty() {
+            lines.extend(rids.iter().map(|rid| format!("a=rid:{} recv", rid)));
+            lines.push(format!("a=simulcast:recv {}", rids.join(";")));
+        }
+    }
+    if media.direction.sends() {
+        stream_lines(lines, media, mid);
+    }
+
+    Ok(())
+}
+
+/// Builds an SDP offer or answer describing `options.media` over a single transport.
+pub fn build_sdp(options: &BuildOptions) -> Result<String, String> {
+    if options.media.is_empty() {
+        return Err("no media to describe".to_string());
+    }
+    if options.dtls_parameters.fingerprints.is_empty() {
+        return Err("DTLS parameters have no fingerprint".to_string());
+    }
+
+    let mids = options
+        .media
+        .iter()
+        .enumerate()
+        .map(|(index, media)| {
+            media
+                .mid
+                .clone()
+                .or_else(|| media.rtp_parameters.mid.clone())
+                .unwrap_or_else(|| index.to_string())
+        })
+        .collect::<Vec<_>>();
+    for (index, mid) in mids.iter().enumerate() {
+        if mids[..index].contains(mid) {
+            return Err(format!("duplicate mid {}", mid));
+        }
+    }
+
+    let session_id = options
+        .session_id
+        .unwrap_or_else(|| fastrand::u64(1..i64::MAX as u64));
+    let mut lines = vec![
+        "v=0".to_string(),
+        format!(
+            "o=mediasoup-elixir {} {} IN IP4 0.0.0.0",
+            session_id, options.session_version
+        ),
+        "s=-".to_string(),
+        "t=0 0".to_string(),
+    ];
+    if options.ice_parameters.ice_lite.unwrap_or(false) {
+        lines.push("a=ice-lite".to_string());
+    }
+    lines.push(format!("a=group:BUNDLE {}", mids.join(" ")));
+    lines.push("a=msid-semantic: WMS *".to_string());
+    for (media, mid) in options.media.iter().zip(&mids) {
+        media_section(&mut lines, options, media, mid)?;
+    }
+
+    let mut sdp = lines.join("\r\n");
+    sdp.push_str("\r\n");
+    Ok(sdp)
+}
+
+struct Section<'a> {
+    kind: &'a str,
+    port: &'a str,
+    formats: Vec<&'a str>,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Section<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| *value)
+    }
+
+    fn attributes(&self, name: &'a str) -> impl Iterator<Item = &'a str> + '_ {
+        self.attributes
+            .iter()
+            .filter(move |(attribute, _)| *attribute == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// Splits `sdp` into its session-level attributes and its media sections.
+fn split_sections(sdp: &str) -> Result<(Section<'_>, Vec<Section<'_>>), String> {
+    let mut session = Section {
+        kind: "",
+        port: "",
+        formats: Vec::new(),
+        attributes: Vec::new(),
+    };
+    let mut sections: Vec<Section> = Vec::new();
+
+    for (number, line) in sdp.lines().map(str::trim).enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let (kind, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected <type>=<value>", number + 1))?;
+        if number == 0 && (kind, value) != ("v", "0") {
+            return Err("not an SDP description".to_string());
+        }
+        match kind {
+            "m" => {
+                let mut fields = value.split_whitespace();
+                let (Some(media), Some(port), Some(_protocol)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    return Err(format!("line {}: invalid media {}", number + 1, value));
+                };
+                sections.push(Section {
+                    kind: media,
+                    port,
+                    formats: fields.collect(),
+                    attributes: Vec::new(),
+                });
+            }
+            "a" => {
+                let attribute = value.split_once(':').unwrap_or((value, ""));
+                sections
+                    .last_mut()
+                    .unwrap_or(&mut session)
+                    .attributes
+                    .push(attribute);
+            }
+            _ => {}
+        }
+    }
+
+    Ok((session, sections))
+}
+
+fn parse_payload_type(value: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid payload type {}", value))
+}
+
+/// Codecs of `section` in the order of its `m=` line. Codecs mediasoup doesn't support are
+/// dropped, together with their RTX.
+fn parse_codecs(kind: MediaKind, section: &Section) -> Result<Vec<RtpCodecParameters>, String> {
+    let mut rtpmaps = HashMap::new();
+    let mut fmtps = HashMap::new();
+    let mut feedback = Vec::new();
+    for (name, value) in &section.attributes {
+        if !matches!(*name, "rtpmap" | "fmtp" | "rtcp-fb") {
+            continue;
+        }
+        let (payload_type, rest) = value
+            .split_once(' ')
+            .ok_or_else(|| format!("invalid {} {}", name, value))?;
+        let rest = rest.trim();
+        match *name {
+            "rtpmap" => {
+                rtpmaps.insert(parse_payload_type(payload_type)?, rest);
+            }
+            "fmtp" => {
+                fmtps.insert(parse_payload_type(payload_type)?, rest);
+            }
+            _ => {
+                let payload_type = match payload_type {
+                    "*" => None,
+                    payload_type => Some(parse_payload_type(payload_type)?),
+                };
+                let (r#type, parameter) = rest.split_once(' ').unwrap_or((rest, ""));
+                if let Ok(rtcp_feedback) = RtcpFeedback::from_type_parameter(r#type, parameter) {
+                    feedback.push((payload_type, rtcp_feedback));
+                }
+            }
+        }
+    }
+
+    let mut codecs = Vec::new();
+    for format in &section.formats {
+        let payload_type = parse_payload_type(format)?;
+        let rtpmap = match (rtpmaps.get(&payload_type), kind, payload_type) {
+            (Some(rtpmap), _, _) => *rtpmap,
+            (None, MediaKind::Audio, 0) => "PCMU/8000",
+            (None, MediaKind::Audio, 8) => "PCMA/8000",
+            (None, MediaKind::Audio, 9) => "G722/8000",
+            (None, _, _) => continue,
+        };
+        let mut fields = rtpmap.split('/');
+        let name = fields.next().unwrap_or_default();
+        let clock_rate = fields
+            .next()
+            .and_then(|clock_rate| clock_rate.parse::<NonZeroU32>().ok())
+            .ok_or_else(|| format!("rtpmap {}: invalid clock rate", payload_type))?;
+
+        let mut parameters = RtpCodecParametersParameters::default();
+        for parameter in fmtps
+            .get(&payload_type)
+            .into_iter()
+            .flat_map(|f| f.split(';'))
+        {
+            let Some((key, value)) = parameter.trim().split_once('=') else {
+                continue;
+            };
+            match value.parse::<u32>() {
+                Ok(number) if key != "profile-level-id" => {
+                    parameters.insert(key.to_string(), number);
+                }
+                _ => {
+                    parameters.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        let mut rtcp_feedback = Vec::new();
+        for (feedback_payload_type, item) in &feedback {
+            if feedback_payload_type.is_none_or(|pt| pt == payload_type)
+                && !rtcp_feedback.contains(item)
+            {
+                rtcp_feedback.push(*item);
+            }
+        }
+
+        let mime_type = format!("{}/{}", kind_str(kind), name);
+        let codec = match kind {
+            MediaKind::Audio => {
+                let Ok(mime_type) = MimeTypeAudio::from_str(&mime_type) else {
+                    continue;
+                };
+                let channels = match fields.next() {
+                    Some(channels) => channels
+                        .parse::<NonZeroU8>()
+                        .map_err(|_| format!("rtpmap {}: invalid channels", payload_type))?,
+                    None => NonZeroU8::new(1).unwrap(),
+                };
+                RtpCodecParameters::Audio {
+                    mime_type,
+                    payload_type,
+                    clock_rate,
+                    channels,
+                    parameters,
+                    rtcp_feedback,
+                }
+            }
+            MediaKind::Video => {
+                let Ok(mime_type) = MimeTypeVideo::from_str(&mime_type) else {
+                    continue;
+                };
+                RtpCodecParameters::Video {
+                    mime_type,
+                    payload_type,
+                    clock_rate,
+                    parameters,
+                    rtcp_feedback,
+                }
+            }
+        };
+        codecs.push(codec);
+    }
+
+    let payload_types = codecs
+        .iter()
+        .map(RtpCodecParameters::payload_type)
+        .collect::<Vec<_>>();
+    codecs.retain(|codec| {
+        !codec.is_rtx()
+            || matches!(
+                codec.parameters().get("apt"),
+                Some(RtpCodecParametersParametersValue::Number(apt))
+                    if u8::try_from(*apt).is_ok_and(|apt| payload_types.contains(&apt))
+            )
+    });
+    Ok(codecs)
+}
+
+fn parse_header_extensions(section: &Section) -> Result<Vec<RtpHeaderExtensionParameters>, String> {
+    let mut header_extensions = Vec::new();
+    for value in section.attributes("extmap") {
+        let mut fields = value.split_whitespace();
+        let (Some(id), Some(uri)) = (fields.next(), fields.next()) else {
+            return Err(format!("invalid extmap {}", value));
+        };
+        let id = id
+            .split('/')
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| format!("invalid extmap {}", value))?;
+        if let Ok(uri) = RtpHeaderExtensionUri::from_str(uri) {
+            header_extensions.push(RtpHeaderExtensionParameters {
+                uri,
+                id,
+                encrypt: false,
+            });
+        }
+    }
+    Ok(header_extensions)
+}
+
+/// Streams sent by the remote endpoint: its simulcast rids, or else its SSRCs paired with their
+/// RTX through `FID` groups. Also returns the CNAME of the first SSRC that declares one.
+fn parse_encodings(section: &Section) -> (Vec<RtpEncodingParameters>, Option<String>) {
+    let mut rids = section
+        .attributes("simulcast")
+        .flat_map(|value| {
+            let fields = value.split_whitespace().collect::<Vec<_>>();
+            fields
+                .chunks(2)
+                .filter(|pair| pair.len() == 2 && pair[0] == "send")
+                .flat_map(|pair| pair[1].split(';'))
+                .filter_map(|alternatives| alternatives.split(',').next())
+                .map(|rid| rid.trim_start_matches('~'))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if rids.is_empty() {
+        rids = section
+            .attributes("rid")
+            .filter_map(|value| {
+                let mut fields = value.split_whitespace();
+                let rid = fields.next()?;
+                (fields.next() == Some("send")).then_some(rid)
+            })
+            .collect();
+    }
+
+    let mut ssrcs = Vec::new();
+    let mut cname = None;
+    for value in section.attributes("ssrc") {
+        let (ssrc, attribute) = value.split_once(' ').unwrap_or((value, ""));
+        let Ok(ssrc) = ssrc.parse::<u32>() else {
+            continue;
+        };
+        if !ssrcs.contains(&ssrc) {
+            ssrcs.push(ssrc);
+        }
+        if let (None, Some(("cname", value))) = (&cname, attribute.split_once(':')) {
+            cname = Some(value.to_string());
+        }
+    }
+
+    if !rids.is_empty() {
+        let encodings = rids
+            .into_iter()
+            .map(|rid| RtpEncodingParameters {
+                rid: Some(rid.to_string()),
+                ..RtpEncodingParameters::default()
+            })
+            .collect();
+        return (encodings, cname);
+    }
+
+    let mut rtx = HashMap::new();
+    let mut simulcast = None;
+    for value in section.attributes("ssrc-group") {
+        let mut fields = value.split_whitespace();
+        let semantics = fields.next();
+        let group = fields
+            .filter_map(|ssrc| ssrc.parse::<u32>().ok())
+            .collect::<Vec<_>>();
+        match (semantics, group.as_slice()) {
+            (Some("FID"), [ssrc, rtx_ssrc]) => {
+                rtx.insert(*ssrc, *rtx_ssrc);
+            }
+            (Some("SIM"), _) => simulcast = Some(group),
+            _ => {}
+        }
+    }
+    let primaries = simulcast.unwrap_or_else(|| {
+        ssrcs
+            .into_iter()
+            .filter(|ssrc| !rtx.values().any(|rtx_ssrc| rtx_ssrc == ssrc))
+            .collect()
+    });
+    let encodings = primaries
+        .into_iter()
+        .map(|ssrc| RtpEncodingParameters {
+            ssrc: Some(ssrc),
+            rtx: rtx
+                .get(&ssrc)
+                .map(|ssrc| RtpEncodingParametersRtx { ssrc: *ssrc }),
+            ..RtpEncodingParameters::default()
+        })
+        .collect();
+    (encodings, cname)
+}
+
+fn codec_capability(codec: &RtpCodecParameters) -> RtpCodecCapability {
+    match codec.clone() {
+        RtpCodecParameters::Audio {
+            mime_type,
+            payload_type,
+            clock_rate,
+            channels,
+            parameters,
+            rtcp_feedback,
+        } => RtpCodecCapability::Audio {
+            mime_type,
+            preferred_payload_type: Some(payload_type),
+            clock_rate,
+            channels,
+            parameters,
+            rtcp_feedback,
+        },
+        RtpCodecParameters::Video {
+            mime_type,
+            payload_type,
+            clock_rate,
+            parameters,
+            rtcp_feedback,
+        } => RtpCodecCapability::Video {
+            mime_type,
+            preferred_payload_type: Some(payload_type),
+            clock_rate,
+            parameters,
+            rtcp_feedback,
+        },
+    }
+}
+
+/// Parses a remote SDP into the DTLS parameters to connect the transport with, the RTP
+/// capabilities of the endpoint and the RTP parameters of each audio and video section.
+/// Rejected sections (port 0) and data channels are skipped.
+pub fn parse_sdp(sdp: &str) -> Result<ParsedSdp, String> {
+    let (session, sections) = split_sections(sdp)?;
+    let mut media = Vec::new();
+    let mut rtp_capabilities = RtpCapabilities::default();
+    let mut fingerprints = Vec::new();
+    let mut role = None;
+
+    for (index, section) in sections.iter().enumerate() {
+        let kind = match section.kind {
+            "audio" => MediaKind::Audio,
+            "video" => MediaKind::Video,
+            _ => continue,
+        };
+        if section.port == "0" {
+            continue;
+        }
+        let mid = section
+            .attribute("mid")
+            .map_or_else(|| index.to_string(), str::to_string);
+
+        if fingerprints.is_empty() {
+            fingerprints = section
+                .attributes("fingerprint")
+                .chain(session.attributes("fingerprint"))
+                .map(parse_fingerprint)
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        if role.is_none() {
+            role = section
+                .attribute("setup")
+                .or_else(|| session.attribute("setup"))
+                .map(remote_role)
+                .transpose()?;
+        }
+
+        let direction = section
+            .attributes
+            .iter()
+            .chain(&session.attributes)
+            .find_map(|(name, _)| Direction::from_attribute(name))
+            .unwrap_or(Direction::SendRecv);
+        let codecs = parse_codecs(kind, section).map_err(|error| format!("{}: {}", mid, error))?;
+        let header_extensions =
+            parse_header_extensions(section).map_err(|error| format!("{}: {}", mid, error))?;
+        let (encodings, cname) = parse_encodings(section);
+
+        for codec in &codecs {
+            let capability = codec_capability(codec);
+            if !rtp_capabilities.codecs.contains(&capability) {
+                rtp_capabilities.codecs.push(capability);
+            }
+        }
+        for extension in &header_extensions {
+            if !rtp_capabilities
+                .header_extensions
+                .iter()
+                .any(|known| known.kind == kind && known.uri == extension.uri)
+            {
+                rtp_capabilities.header_extensions.push(RtpHeaderExtension {
+                    kind,
+                    uri: extension.uri,
+                    preferred_id: extension.id,
+                    preferred_encrypt: false,
+                    direction: RtpHeaderExtensionDirection::SendRecv,
+                });
+            }
+        }
+
+        media.push(ParsedMedia {
+            mid: mid.clone(),
+            kind,
+            direction,
+            rtp_parameters: RtpParameters {
+                mid: Some(mid),
+                codecs,
+                header_extensions,
+                encodings,
+                rtcp: RtcpParameters {
+                    cname,
+                    reduced_size: section.attribute("rtcp-rsize").is_some(),
+                },
+            },
+        });
+    }
+
+    if media.is_empty() {
+        return Err("no audio or video section".to_string());
+    }
+    if fingerprints.is_empty() {
+        return Err("no DTLS fingerprint".to_string());
+    }
+
+    Ok(ParsedSdp {
+        remote_parameters: WebRtcTransportRemoteParameters {
+            dtls_parameters: DtlsParameters {
+                role: role.unwrap_or(DtlsRole::Server),
+                fingerprints,
+            },
+        },
+        rtp_capabilities,
+        media,
+    })
+}
+
+#[rustler::nif]
+pub fn sdp_build(options: JsonSerdeWrap<BuildOptions>) -> Result<String, String> {
+    build_sdp(&options)
+}
+
+#[rustler::nif]
+pub fn sdp_parse(sdp: String) -> Result<JsonSerdeWrap<ParsedSdp>, String> {
+    parse_sdp(&sdp).map(JsonSerdeWrap::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn build_options(media: serde_json::Value) -> BuildOptions {
+        serde_json::from_value(json!({
+            "type": "offer",
+            "sessionId": 42,
+            "iceParameters": {"usernameFragment": "ufrag", "password": "pwd", "iceLite": true},
+            "iceCandidates": [
+                {"foundation": "udpcandidate", "priority": 1076302079, "address": "10.0.0.1",
+                 "protocol": "udp", "port": 40000, "type": "host"},
+                {"foundation": "tcpcandidate", "priority": 1076276479, "address": "10.0.0.1",
+                 "protocol": "tcp", "port": 40001, "type": "host", "tcpType": "passive"}
+            ],
+            "dtlsParameters": {
+                "role": "auto",
+                "fingerprints": [{"algorithm": "sha-256", "value": "1B:EA:BF:33:B8:11:26:6D:91:AD:1B:A0:16:FD:5D:60:59:33:F7:46:A3:BA:99:2A:1D:04:99:A6:F2:C6:2D:43"}]
+            },
+            "media": media
+        }))
+        .unwrap()
+    }
+
+    fn consumer_media() -> serde_json::Value {
+        json!({
+            "kind": "video",
+            "direction": "sendonly",
+            "trackId": "consumer-1",
+            "rtpParameters": {
+                "mid": "1",
+                "codecs": [
+                    {"mimeType": "video/VP8", "payloadType": 101, "clockRate": 90000,
+                     "parameters": {}, "rtcpFeedback": [{"type": "nack"},
+                                                         {"type": "nack", "parameter": "pli"}]},
+                    {"mimeType": "video/rtx", "payloadType": 102, "clockRate": 90000,
+                     "parameters": {"apt": 101}, "rtcpFeedback": []}
+                ],
+                "headerExtensions": [
+                    {"uri": "urn:ietf:params:rtp-hdrext:sdes:mid", "id": 1, "encrypt": false}
+                ],
+                "encodings": [{"ssrc": 1111, "rtx": {"ssrc": 2222}}],
+                "rtcp": {"cname": "consumer-cname", "reducedSize": true}
+            }
+        })
+    }
+
+    #[test]
+    fn build_then_parse_roundtrips() {
+        let options = build_options(json!([
+            {"kind": "audio", "direction": "recvonly", "rtpParameters": {
+                "mid": "0",
+                "codecs": [{"mimeType": "audio/opus", "payloadType": 100, "clockRate": 48000,
+                            "channels": 2, "parameters": {"useinbandfec": 1},
+                            "rtcpFeedback": [{"type": "transport-cc"}]}],
+                "headerExtensions": [],
+                "encodings": [],
+                "rtcp": {"reducedSize": true}
+            }},
+            consumer_media()
+        ]));
+        let sdp = build_sdp(&options).unwrap();
+
+        assert!(sdp.contains("a=ice-lite\r\na=group:BUNDLE 0 1\r\n"));
+        assert!(sdp.contains("m=video 40000 UDP/TLS/RTP/SAVPF 101 102\r\nc=IN IP4 10.0.0.1\r\n"));
+        assert!(sdp.contains(
+            "a=candidate:tcpcandidate 1 tcp 1076276479 10.0.0.1 40001 typ host tcptype passive\r\n"
+        ));
+        assert!(sdp.contains("a=setup:actpass\r\n"));
+        assert!(sdp.contains("a=rtpmap:100 opus/48000/2\r\na=rtcp-fb:100 transport-cc\r\na=fmtp:100 useinbandfec=1\r\n"));
+        assert!(sdp.contains("a=ssrc-group:FID 1111 2222\r\na=ssrc:1111 cname:consumer-cname\r\n"));
+
+        let parsed = parse_sdp(&sdp).unwrap();
+        assert_eq!(
+            parsed.remote_parameters.dtls_parameters.fingerprints,
+            options.dtls_parameters.fingerprints
+        );
+        assert_eq!(
+            parsed.remote_parameters.dtls_parameters.role,
+            DtlsRole::Server
+        );
+        assert_eq!(parsed.media.len(), 2);
+        assert_eq!(parsed.media[0].direction, Direction::RecvOnly);
+        for (parsed, built) in parsed.media.iter().zip(&options.media) {
+            assert_eq!(parsed.kind, built.kind);
+            assert_eq!(parsed.rtp_parameters.codecs, built.rtp_parameters.codecs);
+            assert_eq!(
+                parsed.rtp_parameters.header_extensions,
+                built.rtp_parameters.header_extensions
+            );
+            assert_eq!(
+                parsed.rtp_parameters.encodings,
+                built.rtp_parameters.encodings
+            );
+            assert_eq!(parsed.rtp_parameters.rtcp, built.rtp_parameters.rtcp);
+        }
+        assert_eq!(parsed.rtp_capabilities.codecs.len(), 3);
+    }
+
+    #[test]
+    fn build_rejects_inconsistent_media() {
+        let mut duplicated = consumer_media();
+        duplicated["trackId"] = json!("consumer-2");
+        let error = build_sdp(&build_options(json!([consumer_media(), duplicated]))).unwrap_err();
+        assert_eq!(error, "duplicate mid 1");
+
+        let mut audio = consumer_media();
+        audio["kind"] = json!("audio");
+        let error = build_sdp(&build_options(json!([audio]))).unwrap_err();
+        assert_eq!(error, "media 1: codec video/VP8 is not audio");
+    }
+
+    #[test]
+    fn parses_remote_offer() {
+        let sdp = [
+            "v=0",
+            "o=- 1 2 IN IP4 127.0.0.1",
+            "s=-",
+            "t=0 0",
+            "a=group:BUNDLE a v d",
+            "a=fingerprint:SHA-256 1b:ea:bf:33:b8:11:26:6d:91:ad:1b:a0:16:fd:5d:60:59:33:f7:46:a3:ba:99:2a:1d:04:99:a6:f2:c6:2d:43",
+            "m=audio 9 UDP/TLS/RTP/SAVPF 111 0 13",
+            "c=IN IP4 0.0.0.0",
+            "a=setup:active",
+            "a=mid:a",
+            "a=sendonly",
+            "a=rtpmap:111 opus/48000/2",
+            "a=rtpmap:13 CN/8000",
+            "a=fmtp:111 minptime=10;useinbandfec=1",
+            "a=extmap:1/sendonly urn:ietf:params:rtp-hdrext:ssrc-audio-level",
+            "a=extmap:2 urn:example:unknown",
+            "a=ssrc:4444 cname:obs",
+            "m=video 9 UDP/TLS/RTP/SAVPF 96 97 98 99",
+            "a=mid:v",
+            "a=sendonly",
+            "a=rtcp-rsize",
+            "a=rtpmap:96 H264/90000",
+            "a=rtpmap:97 rtx/90000",
+            "a=rtpmap:98 HEVC/90000",
+            "a=rtpmap:99 rtx/90000",
+            "a=rtcp-fb:* nack",
+            "a=rtcp-fb:96 nack pli",
+            "a=rtcp-fb:96 unknown-feedback",
+            "a=fmtp:96 packetization-mode=1;profile-level-id=42e01f",
+            "a=fmtp:97 apt=96",
+            "a=fmtp:99 apt=98",
+            "a=rid:h send",
+            "a=rid:l send",
+            "a=simulcast:send l;~h",
+            "m=application 9 UDP/DTLS/SCTP webrtc-datachannel",
+            "a=mid:d",
+        ]
+        .join("\r\n");
+
+        let parsed = parse_sdp(&sdp).unwrap();
+        assert_eq!(
+            parsed.remote_parameters.dtls_parameters.role,
+            DtlsRole::Client
+        );
+        assert_eq!(parsed.media.len(), 2);
+
+        let audio = &parsed.media[0].rtp_parameters;
+        assert_eq!(parsed.media[0].direction, Direction::SendOnly);
+        assert_eq!(audio.mid.as_deref(), Some("a"));
+        assert_eq!(
+            audio
+                .codecs
+                .iter()
+                .map(|codec| codec.mime_type().as_str())
+                .collect::<Vec<_>>(),
+            ["audio/opus", "audio/PCMU", "audio/CN"]
+        );
+        assert_eq!(
+            audio.codecs[0].parameters().get("minptime"),
+            Some(&RtpCodecParametersParametersValue::Number(10))
+        );
+        assert_eq!(audio.header_extensions.len(), 1);
+        assert_eq!(
+            audio.encodings,
+            [RtpEncodingParameters {
+                ssrc: Some(4444),
+                ..RtpEncodingParameters::default()
+            }]
+        );
+        assert_eq!(audio.rtcp.cname.as_deref(), Some("obs"));
+        assert!(!audio.rtcp.reduced_size);
+
+        let video = &parsed.media[1].rtp_parameters;
+        assert_eq!(
+            video
+                .codecs
+                .iter()
+                .map(RtpCodecParameters::payload_type)
+                .collect::<Vec<_>>(),
+            [96, 97]
+        );
+        assert_eq!(
+            video.codecs[0].parameters().get("profile-level-id"),
+            Some(&RtpCodecParametersParametersValue::String("42e01f".into()))
+        );
+        assert_eq!(
+            video.codecs[0].rtcp_feedback(),
+            [RtcpFeedback::Nack, RtcpFeedback::NackPli]
+        );
+        assert_eq!(
+            video
+                .encodings
+                .iter()
+                .map(|encoding| encoding.rid.as_deref())
+                .collect::<Vec<_>>(),
+            [Some("l"), Some("h")]
+        );
+        assert!(video.rtcp.reduced_size);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_descriptions() {
+        assert_eq!(
+            parse_sdp("hello").unwrap_err(),
+            "line 1: expected <type>=<value>"
+        );
+        assert_eq!(
+            parse_sdp("v=0\r\nm=audio 9 RTP/AVP 0\r\n").unwrap_err(),
+            "no DTLS fingerprint"
+        );
+        assert_eq!(
+            parse_sdp("v=0\r\na=fingerprint:sha-256 00\r\nm=audio 9 RTP/AVP 0\r\n")
+                .unwrap_err()
+                .split(':')
+                .next(),
+            Some("fingerprint")
+        );
+    }
+}
@@ -0,0 +1,676 @@
+//! Stats returned by the `*_get_stats_async` NIFs, as the structs of `Mediasoup.Stats`.
+//!
+//! Keys are the snake_case field names and enums are atoms, see `AsAtom` and `EnumAtom`.
+//! `Delta` turns a sample into the change since the previous one, for `stats_subscription`.
+//! `EntityStats` requests the stats of any entity, for subscriptions and `router_get_stats_async`.
+
+use crate::data_structure::TransportTupleStruct;
+use crate::entity::Entity;
+use crate::json_serde::{reserialize, AsAtom, EnumAtom};
+use crate::{registry, task};
+use futures_lite::future;
+use mediasoup::consumer::{Consumer, ConsumerStat, ConsumerStats};
+use mediasoup::data_consumer::{DataConsumer, DataConsumerStat};
+use mediasoup::data_producer::{DataProducer, DataProducerStat};
+use mediasoup::pipe_transport::{PipeTransport, PipeTransportStat};
+use mediasoup::plain_transport::{PlainTransport, PlainTransportStat};
+use mediasoup::producer::{Producer, ProducerStat};
+use mediasoup::transport::TransportGeneric;
+use mediasoup::types::rtp_parameters::MimeType;
+use mediasoup::webrtc_transport::{WebRtcTransport, WebRtcTransportStat};
+use rustler::{Atom, Encoder, Env, NifStruct, Term};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::future::Future;
+
+#[derive(Deserialize)]
+struct LayerBitrate {
+    layer: String,
+    bitrate: u32,
+}
+
+/// The mime type as in the RTP parameters, e.g. `"video/VP8"`.
+fn mime_type(mime_type: &MimeType) -> String {
+    match serde_json::to_value(mime_type) {
+        Ok(serde_json::Value::String(mime_type)) => mime_type,
+        _ => String::new(),
+    }
+}
+
+/// Stats of a stream received by a producer.
+#[derive(Clone, NifStruct)]
+#[module = "Mediasoup.Stats.RtpStreamRecv"]
+pub struct RtpStreamRecv {
+    timestamp: u64,
+    ssrc: u32,
+    rtx_ssrc: Option<u32>,
+    rid: Option<String>,
+    kind: Atom,
+    mime_type: String,
+    packets_lost: u64,
+    fraction_lost: u8,
+    packets_discarded: u64,
+    packets_retransmitted: u64,
+    packets_repaired: u64,
+    nack_count: u64,
+    nack_packet_count: u64,
+    pli_count: u64,
+    fir_count: u64,
+    score: u8,
+    packet_count: u64,
+    byte_count: u64,
+    bitrate: u32,
+    round_trip_time: Option<f32>,
+    rtx_packets_discarded: Option<u64>,
+    jitter: u32,
+    /// Bitrate by `"spatial.temporal"` layer.
+    bitrate_by_layer: HashMap<String, u32>,
+}
+
+impl From<&ProducerStat> for RtpStreamRecv {
+    fn from(stat: &ProducerStat) -> Self {
+        let bitrate_by_layer: Vec<LayerBitrate> =
+            reserialize(&stat.bitrate_by_layer).unwrap_or_default();
+        Self {
+            timestamp: stat.timestamp,
+            ssrc: stat.ssrc,
+            rtx_ssrc: stat.rtx_ssrc,
+            rid: stat.rid.clone(),
+            kind: stat.kind.as_atom(),
+            mime_type: mime_type(&stat.mime_type),
+            packets_lost: stat.packets_lost,
+            fraction_lost: stat.fraction_lost,
+            packets_discarded: stat.packets_discarded,
+            packets_retransmitted: stat.packets_retransmitted,
+            packets_repaired: stat.packets_repaired,
+            nack_count: stat.nack_count,
+            nack_packet_count: stat.nack_packet_count,
+            pli_count: stat.pli_count,
+            fir_count: stat.fir_count,
+            score: stat.score,
+            packet_count: stat.packet_count,
+            byte_count: stat.byte_count,
+            bitrate: stat.bitrate,
+            round_trip_time: stat.round_trip_time,
+            rtx_packets_discarded: stat.rtx_packets_discarded,
+            jitter: stat.jitter,
+            bitrate_by_layer: bitrate_by_layer
+                .into_iter()
+                .map(|layer| (layer.layer, layer.bitrate))
+                .collect(),
+        }
+    }
+}
+
+/// Stats of a stream sent by a consumer.
+#[derive(Clone, NifStruct)]
+#[module = "Mediasoup.Stats.RtpStreamSend"]
+pub struct RtpStreamSend {
+    timestamp: u64,
+    ssrc: u32,
+    rtx_ssrc: Option<u32>,
+    kind: Atom,
+    mime_type: String,
+    packets_lost: u64,
+    fraction_lost: u8,
+    packets_discarded: u64,
+    packets_retransmitted: u64,
+    packets_repaired: u64,
+    nack_count: u64,
+    nack_packet_count: u64,
+    pli_count: u64,
+    fir_count: u64,
+    score: u8,
+    packet_count: u64,
+    byte_count: u64,
+    bitrate: u32,
+    round_trip_time: Option<f32>,
+}
+
+impl From<&ConsumerStat> for RtpStreamSend {
+    fn from(stat: &ConsumerStat) -> Self {
+        Self {
+            timestamp: stat.timestamp,
+            ssrc: stat.ssrc,
+            rtx_ssrc: stat.rtx_ssrc,
+            kind: stat.kind.as_atom(),
+            mime_type: mime_type(&stat.mime_type),
+            packets_lost: stat.packets_lost,
+            fraction_lost: stat.fraction_lost,
+            packets_discarded: stat.packets_discarded,
+            packets_retransmitted: stat.packets_retransmitted,
+            packets_repaired: stat.packets_repaired,
+            nack_count: stat.nack_count,
+            nack_packet_count: stat.nack_packet_count,
+            pli_count: stat.pli_count,
+            fir_count: stat.fir_count,
+            score: stat.score,
+            packet_count: stat.packet_count,
+            byte_count: stat.byte_count,
+            bitrate: stat.bitrate,
+            round_trip_time: stat.round_trip_time,
+        }
+    }
+}
+
+/// An item of the stats of a consumer: its own streams, then the stream of its producer.
+#[derive(Clone)]
+pub enum RtpStreamStats {
+    Send(RtpStreamSend),
+    Recv(RtpStreamRecv),
+}
+
+impl Encoder for RtpStreamStats {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            RtpStreamStats::Send(stats) => stats.encode(env),
+            RtpStreamStats::Recv(stats) => stats.encode(env),
+        }
+    }
+}
+
+pub fn consumer_stats(stats: &ConsumerStats) -> Vec<RtpStreamStats> {
+    match stats {
+        ConsumerStats::JustConsumer((consumer,)) => {
+            vec![RtpStreamStats::Send(consumer.into())]
+        }
+        ConsumerStats::WithProducer((consumer, producer)) => vec![
+            RtpStreamStats::Send(consumer.into()),
+            RtpStreamStats::Recv(producer.into()),
+        ],
+        ConsumerStats::MultipleConsumers(consumers) => consumers
+            .iter()
+            .map(|consumer| RtpStreamStats::Send(consumer.into()))
+            .collect(),
+    }
+}
+
+/// Defines the stats struct of a transport: the fields common to all transports, then the
+/// fields listed in the call.
+macro_rules! transport_stats {
+    (
+        $name: ident, $module: literal, |$stat: ident: &$stat_type: ty|
+        {$($field: ident: $t: ty = $value: expr),* $(,)?}
+    ) => {
+        #[derive(Clone, NifStruct)]
+        #[module = $module]
+        pub struct $name {
+            transport_id: String,
+            timestamp: u64,
+            sctp_state: Option<Atom>,
+            bytes_received: u64,
+            recv_bitrate: u32,
+            bytes_sent: u64,
+            send_bitrate: u32,
+            rtp_bytes_received: u64,
+            rtp_recv_bitrate: u32,
+            rtp_bytes_sent: u64,
+            rtp_send_bitrate: u32,
+            rtx_bytes_received: u64,
+            rtx_recv_bitrate: u32,
+            rtx_bytes_sent: u64,
+            rtx_send_bitrate: u32,
+            probation_bytes_sent: u64,
+            probation_send_bitrate: u32,
+            available_outgoing_bitrate: Option<u32>,
+            available_incoming_bitrate: Option<u32>,
+            max_incoming_bitrate: Option<u32>,
+            max_outgoing_bitrate: Option<u32>,
+            min_outgoing_bitrate: Option<u32>,
+            rtp_packet_loss_received: Option<f64>,
+            rtp_packet_loss_sent: Option<f64>,
+            $($field: $t,)*
+        }
+
+        impl From<&$stat_type> for $name {
+            fn from($stat: &$stat_type) -> Self {
+                Self {
+                    transport_id: $stat.transport_id.to_string(),
+                    timestamp: $stat.timestamp,
+                    sctp_state: $stat.sctp_state.as_ref().map(AsAtom::as_atom),
+                    bytes_received: $stat.bytes_received,
+                    recv_bitrate: $stat.recv_bitrate,
+                    bytes_sent: $stat.bytes_sent,
+                    send_bitrate: $stat.send_bitrate,
+                    rtp_bytes_received: $stat.rtp_bytes_received,
+                    rtp_recv_bitrate: $stat.rtp_recv_bitrate,
+                    rtp_bytes_sent: $stat.rtp_bytes_sent,
+                    rtp_send_bitrate: $stat.rtp_send_bitrate,
+                    rtx_bytes_received: $stat.rtx_bytes_received,
+                    rtx_recv_bitrate: $stat.rtx_recv_bitrate,
+                    rtx_bytes_sent: $stat.rtx_bytes_sent,
+                    rtx_send_bitrate: $stat.rtx_send_bitrate,
+                    probation_bytes_sent: $stat.probation_bytes_sent,
+                    probation_send_bitrate: $stat.probation_send_bitrate,
+                    available_outgoing_bitrate: $stat.available_outgoing_bitrate,
+                    available_incoming_bitrate: $stat.available_incoming_bitrate,
+                    max_incoming_bitrate: $stat.max_incoming_bitrate,
+                    max_outgoing_bitrate: $stat.max_outgoing_bitrate,
+                    min_outgoing_bitrate: $stat.min_outgoing_bitrate,
+                    rtp_packet_loss_received: $stat.rtp_packet_loss_received,
+                    rtp_packet_loss_sent: $stat.rtp_packet_loss_sent,
+                    $($field: $value,)*
+                }
+            }
+        }
+    };
+}
+
+transport_stats!(
+    WebRtcTransportStats,
+    "Mediasoup.Stats.WebRtcTransportStats",
+    |stat: &WebRtcTransportStat| {
+        ice_role: Atom = stat.ice_role.as_atom(),
+        ice_state: Atom = stat.ice_state.as_atom(),
+        ice_selected_tuple: Option<TransportTupleStruct> =
+            stat.ice_selected_tuple.as_ref().map(Into::into),
+        dtls_state: Atom = stat.dtls_state.as_atom(),
+    }
+);
+
+transport_stats!(
+    PlainTransportStats,
+    "Mediasoup.Stats.PlainTransportStats",
+    |stat: &PlainTransportStat| {
+        rtcp_mux: bool = stat.rtcp_mux,
+        comedia: bool = stat.comedia,
+        tuple: TransportTupleStruct = (&stat.tuple).into(),
+        rtcp_tuple: Option<TransportTupleStruct> = stat.rtcp_tuple.as_ref().map(Into::into),
+    }
+);
+
+transport_stats!(
+    PipeTransportStats,
+    "Mediasoup.Stats.PipeTransportStats",
+    |stat: &PipeTransportStat| {
+        tuple: TransportTupleStruct = (&stat.tuple).into(),
+    }
+);
+
+#[derive(Clone, NifStruct)]
+#[module = "Mediasoup.Stats.DataProducerStats"]
+pub struct DataProducerStats {
+    timestamp: u64,
+    label: String,
+    protocol: String,
+    messages_received: u64,
+    bytes_received: u64,
+}
+
+impl From<&DataProducerStat> for DataProducerStats {
+    fn from(stat: &DataProducerStat) -> Self {
+        Self {
+            timestamp: stat.timestamp,
+            label: stat.label.clone(),
+            protocol: stat.protocol.clone(),
+            messages_received: stat.messages_received,
+            bytes_received: stat.bytes_received,
+        }
+    }
+}
+
+#[derive(Clone, NifStruct)]
+#[module = "Mediasoup.Stats.DataConsumerStats"]
+pub struct DataConsumerStats {
+    timestamp: u64,
+    label: String,
+    protocol: String,
+    messages_sent: u64,
+    bytes_sent: u64,
+    buffered_amount: u32,
+}
+
+impl From<&DataConsumerStat> for DataConsumerStats {
+    fn from(stat: &DataConsumerStat) -> Self {
+        Self {
+            timestamp: stat.timestamp,
+            label: stat.label.clone(),
+            protocol: stat.protocol.clone(),
+            messages_sent: stat.messages_sent,
+            bytes_sent: stat.bytes_sent,
+            buffered_amount: stat.buffered_amount,
+        }
+    }
+}
+
+/// Converts each of `stats`.
+pub fn convert<'a, S: 'a, T: From<&'a S>>(stats: &'a [S]) -> Vec<T> {
+    stats.iter().map(T::from).collect()
+}
+
+/// An entity whose stats can be requested from the worker.
+pub trait EntityStats: Entity + Clone {
+    type Stats: Delta + Encoder + Send + Sync + 'static;
+
+    /// Requests the stats, converted like the `*_get_stats_async` NIFs do.
+    fn stats(self) -> future::Boxed<Result<Vec<Self::Stats>, String>>;
+}
+
+macro_rules! impl_entity_stats {
+    ($($t: ty => $stats: ty, |$list: ident| $convert: expr),* $(,)?) => {
+        $(
+            impl EntityStats for $t {
+                type Stats = $stats;
+
+                fn stats(self) -> future::Boxed<Result<Vec<$stats>, String>> {
+                    Box::pin(async move {
+                        self.get_stats()
+                            .await
+                            .map(|$list| $convert)
+                            .map_err(|error| format!("{}", error))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_entity_stats!(
+    Producer => RtpStreamRecv, |list| convert(&list),
+    Consumer => RtpStreamStats, |list| consumer_stats(&list),
+    DataProducer => DataProducerStats, |list| convert(&list),
+    DataConsumer => DataConsumerStats, |list| convert(&list),
+    WebRtcTransport => WebRtcTransportStats, |list| convert(&list),
+    PlainTransport => PlainTransportStats, |list| convert(&list),
+    PipeTransport => PipeTransportStats, |list| convert(&list),
+);
+
+/// The stats of each entity by id, or why they could not be read.
+type StatsById<S> = HashMap<String, Result<Vec<S>, String>>;
+
+/// Requests the stats of each of `entities`, concurrently. The requests start right away.
+fn stats_by_id<T: EntityStats>(entities: Vec<T>) -> impl Future<Output = StatsById<T::Stats>> {
+    let ids: Vec<String> = entities.iter().map(Entity::id).collect();
+    let stats = task::join_all(entities.into_iter().map(EntityStats::stats));
+    async move {
+        ids.into_iter()
+            .zip(stats.await)
+            .map(|(id, stats)| {
+                (
+                    id,
+                    stats.unwrap_or_else(|| Err("stats task panicked".into())),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The stats of a transport, whichever its type.
+pub enum TransportStats {
+    WebRtc(Result<Vec<WebRtcTransportStats>, String>),
+    Plain(Result<Vec<PlainTransportStats>, String>),
+    Pipe(Result<Vec<PipeTransportStats>, String>),
+}
+
+impl Encoder for TransportStats {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            TransportStats::WebRtc(stats) => stats.encode(env),
+            TransportStats::Plain(stats) => stats.encode(env),
+            TransportStats::Pipe(stats) => stats.encode(env),
+        }
+    }
+}
+
+/// The stats of a transport of a router and of its producers, consumers, data producers and
+/// data consumers, each as `{:ok, stats}` or `{:error, reason}`.
+#[derive(NifStruct)]
+#[rustler(encode)]
+#[module = "Mediasoup.Router.TransportStats"]
+pub struct RouterTransportStats {
+    kind: EnumAtom,
+    stats: TransportStats,
+    producers: StatsById<RtpStreamRecv>,
+    consumers: StatsById<RtpStreamStats>,
+    data_producers: StatsById<DataProducerStats>,
+    data_consumers: StatsById<DataConsumerStats>,
+}
+
+fn router_transport_stats<T: EntityStats>(
+    transport: T,
+    stats: fn(Result<Vec<T::Stats>, String>) -> TransportStats,
+) -> impl Future<Output = (String, RouterTransportStats)> {
+    let id = transport.id();
+    let transport_stats = stats_by_id(vec![transport]);
+    let producers = stats_by_id(registry::children_values::<Producer>(&id));
+    let consumers = stats_by_id(registry::children_values::<Consumer>(&id));
+    let data_producers = stats_by_id(registry::children_values::<DataProducer>(&id));
+    let data_consumers = stats_by_id(registry::children_values::<DataConsumer>(&id));
+    async move {
+        let transport_stats = transport_stats
+            .await
+            .remove(&id)
+            .unwrap_or_else(|| Err("transport closed".into()));
+        let stats = RouterTransportStats {
+            kind: EnumAtom::new(&T::KIND.name()),
+            stats: stats(transport_stats),
+            producers: producers.await,
+            consumers: consumers.await,
+            data_producers: data_producers.await,
+            data_consumers: data_consumers.await,
+        };
+        (id, stats)
+    }
+}
+
+/// Requests the stats of every transport of the router `router_id` and of their children,
+/// all at once. The result is keyed by transport id. An entity that fails, e.g. because it
+/// closed meanwhile, has its error in place of its stats.
+pub fn router_stats(
+    router_id: &str,
+) -> impl Future<Output = HashMap<String, RouterTransportStats>> {
+    let webrtc_transports: Vec<_> = registry::children_values::<WebRtcTransport>(router_id)
+        .into_iter()
+        .map(|transport| router_transport_stats(transport, TransportStats::WebRtc))
+        .collect();
+    let plain_transports: Vec<_> = registry::children_values::<PlainTransport>(router_id)
+        .into_iter()
+        .map(|transport| router_transport_stats(transport, TransportStats::Plain))
+        .collect();
+    let pipe_transports: Vec<_> = registry::children_values::<PipeTransport>(router_id)
+        .into_iter()
+        .map(|transport| router_transport_stats(transport, TransportStats::Pipe))
+        .collect();
+    async move {
+        let mut stats = HashMap::new();
+        for transport in webrtc_transports {
+            let (id, transport) = transport.await;
+            stats.insert(id, transport);
+        }
+        for transport in plain_transports {
+            let (id, transport) = transport.await;
+            stats.insert(id, transport);
+        }
+        for transport in pipe_transports {
+            let (id, transport) = transport.await;
+            stats.insert(id, transport);
+        }
+        stats
+    }
+}
+
+/// Stats that can be reported as the change since a previous sample.
+pub trait Delta: Clone {
+    /// Whether `previous` was sampled from the same stream as `self`.
+    fn same_source(&self, previous: &Self) -> bool;
+    /// Subtracts the counters of `previous`. Gauges such as bitrates and scores are kept.
+    fn subtract(&mut self, previous: &Self);
+}
+
+macro_rules! impl_delta {
+    (
+        $t: ty, |$current: ident, $previous: ident| $same_source: expr,
+        [$($counter: ident),*] $(, optional [$($optional: ident),*])?
+    ) => {
+        impl Delta for $t {
+            fn same_source(&self, previous: &Self) -> bool {
+                let ($current, $previous) = (self, previous);
+                $same_source
+            }
+
+            fn subtract(&mut self, previous: &Self) {
+                $(self.$counter = self.$counter.saturating_sub(previous.$counter);)*
+                $($(
+                    if let (Some(current), Some(previous)) = (&mut self.$optional, previous.$optional) {
+                        *current = current.saturating_sub(previous);
+                    }
+                )*)?
+            }
+        }
+    };
+}
+
+impl_delta!(
+    RtpStreamRecv,
+    |current, previous| current.ssrc == previous.ssrc,
+    [
+        packets_lost,
+        packets_discarded,
+        packets_retransmitted,
+        packets_repaired,
+        nack_count,
+        nack_packet_count,
+        pli_count,
+        fir_count,
+        packet_count,
+        byte_count
+    ],
+    optional[rtx_packets_discarded]
+);
+
+impl_delta!(
+    RtpStreamSend,
+    |current, previous| current.ssrc == previous.ssrc,
+    [
+        packets_lost,
+        packets_discarded,
+        packets_retransmitted,
+        packets_repaired,
+        nack_count,
+        nack_packet_count,
+        pli_count,
+        fir_count,
+        packet_count,
+        byte_count
+    ]
+);
+
+impl Delta for RtpStreamStats {
+    fn same_source(&self, previous: &Self) -> bool {
+        match (self, previous) {
+            (RtpStreamStats::Send(current), RtpStreamStats::Send(previous)) => {
+                current.same_source(previous)
+            }
+            (RtpStreamStats::Recv(current), RtpStreamStats::Recv(previous)) => {
+                current.same_source(previous)
+            }
+            _ => false,
+        }
+    }
+
+    fn subtract(&mut self, previous: &Self) {
+        match (self, previous) {
+            (RtpStreamStats::Send(current), RtpStreamStats::Send(previous)) => {
+                current.subtract(previous)
+            }
+            (RtpStreamStats::Recv(current), RtpStreamStats::Recv(previous)) => {
+                current.subtract(previous)
+            }
+            _ => {}
+        }
+    }
+}
+
+macro_rules! impl_transport_delta {
+    ($($t: ty),*) => {
+        $(
+            impl_delta!(
+                $t,
+                |current, previous| current.transport_id == previous.transport_id,
+                [
+                    bytes_received,
+                    bytes_sent,
+                    rtp_bytes_received,
+                    rtp_bytes_sent,
+                    rtx_bytes_received,
+                    rtx_bytes_sent,
+                    probation_bytes_sent
+                ]
+            );
+        )*
+    };
+}
+
+impl_transport_delta!(
+    WebRtcTransportStats,
+    PlainTransportStats,
+    PipeTransportStats
+);
+
+impl_delta!(
+    DataProducerStats,
+    |current, previous| current.label == previous.label,
+    [messages_received, bytes_received]
+);
+
+impl_delta!(
+    DataConsumerStats,
+    |current, previous| current.label == previous.label,
+    [messages_sent, bytes_sent]
+);
+
+/// The change of each of `current` since the item of the same source in `previous`.
+/// An item without one, e.g. a simulcast stream that just started, is kept as is.
+pub fn delta<T: Delta>(current: &[T], previous: &[T]) -> Vec<T> {
+    current
+        .iter()
+        .map(|item| {
+            let mut item = item.clone();
+            if let Some(previous) = previous.iter().find(|previous| item.same_source(previous)) {
+                item.subtract(previous);
+            }
+            item
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_producer_stats(label: &str, messages_received: u64) -> DataProducerStats {
+        DataProducerStats {
+            timestamp: 0,
+            label: label.into(),
+            protocol: String::new(),
+            messages_received,
+            bytes_received: messages_received * 10,
+        }
+    }
+
+    #[test]
+    fn delta_subtracts_counters_of_the_same_source() {
+        let previous = [data_producer_stats("chat", 3)];
+        let current = [
+            data_producer_stats("chat", 5),
+            data_producer_stats("file", 2),
+        ];
+
+        let delta = delta(&current, &previous);
+
+        assert_eq!(2, delta[0].messages_received);
+        assert_eq!(20, delta[0].bytes_received);
+        // No previous sample of this source: kept as is.
+        assert_eq!(2, delta[1].messages_received);
+    }
+
+    #[test]
+    fn delta_saturates_when_counters_reset() {
+        let delta = delta(
+            &[data_producer_stats("chat", 1)],
+            &[data_producer_stats("chat", 4)],
+        );
+
+        assert_eq!(0, delta[0].messages_received);
+    }
+}
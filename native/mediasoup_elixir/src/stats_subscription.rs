@@ -0,0 +1,171 @@
+//! Stats polled on a timer and pushed to a process, see `subscribe`.
+//!
+//! Each tick is scheduled on the event dispatcher and the request to the worker runs on the
+//! task executor, so thousands of subscriptions share the same threads. Samples are sent
+//! unbatched: the listener may be any process, not only one that handles event batches.
+
+use crate::resource::{self, DisposableResourceWrapper, Slot};
+use crate::stats::{self, EntityStats};
+use crate::{atoms, event_dispatcher, task};
+use rustler::{Atom, Encoder, Env, LocalPid, NifResult, ResourceArc, Term};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+/// Sent as `{:on_stats, %Mediasoup.Stats.Sample{}}`.
+struct Sample<S> {
+    id: String,
+    entity: &'static str,
+    delta: bool,
+    /// Time since the previous sample, `None` for the first one.
+    elapsed_ms: Option<u64>,
+    stats: Vec<S>,
+}
+
+impl<S: Encoder> Encoder for Sample<S> {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let keys = [
+            rustler::types::atom::__struct__().encode(env),
+            atoms::id().encode(env),
+            atoms::entity().encode(env),
+            atoms::delta().encode(env),
+            atoms::elapsed_ms().encode(env),
+            atoms::stats().encode(env),
+        ];
+        let values = [
+            Atom::from_str(env, "Elixir.Mediasoup.Stats.Sample")
+                .map_or_else(|_| atoms::error().encode(env), |module| module.encode(env)),
+            self.id.encode(env),
+            Atom::from_str(env, self.entity)
+                .map_or_else(|_| atoms::error().encode(env), |entity| entity.encode(env)),
+            self.delta.encode(env),
+            self.elapsed_ms.encode(env),
+            self.stats.encode(env),
+        ];
+        Term::map_from_term_arrays(env, &keys, &values)
+            .unwrap_or_else(|_| atoms::error().encode(env))
+    }
+}
+
+/// A stats subscription. Polling stops on `stats_unsubscribe`, when the resource is
+/// garbage-collected, or when the entity closes.
+pub struct StatsSubscriptionRef {
+    stopped: Arc<AtomicBool>,
+}
+#[rustler::resource_impl]
+impl rustler::Resource for StatsSubscriptionRef {}
+
+impl Drop for StatsSubscriptionRef {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Poller<T: EntityStats> {
+    slot: Weak<Slot<T>>,
+    id: String,
+    entity: &'static str,
+    pid: LocalPid,
+    interval: Duration,
+    delta: bool,
+    stopped: Arc<AtomicBool>,
+}
+
+type Previous<T> = Option<(Instant, Vec<<T as EntityStats>::Stats>)>;
+
+impl<T: EntityStats> Poller<T> {
+    fn schedule(self: Arc<Self>, deadline: Instant, previous: Previous<T>) {
+        event_dispatcher::schedule(deadline, move || {
+            if !self.stopped.load(Ordering::Relaxed) {
+                task::spawn(self.poll(deadline, previous)).detach();
+            }
+        });
+    }
+
+    async fn poll(self: Arc<Self>, deadline: Instant, previous: Previous<T>) {
+        let entity = self
+            .slot
+            .upgrade()
+            .and_then(|slot| resource::read(&slot).clone());
+        let Some(entity) = entity else {
+            // Closed: `{:on_stats_end, id}` lets the process forget the subscription.
+            self.stopped.store(true, Ordering::Relaxed);
+            event_dispatcher::dispatch(self.pid, (atoms::on_stats_end(), self.id.clone()));
+            return;
+        };
+
+        // A failed request, e.g. while the entity is closing, skips the sample.
+        let previous = match entity.stats().await {
+            Ok(current) => {
+                let sampled_at = Instant::now();
+                if !self.stopped.load(Ordering::Relaxed) {
+                    self.send(sampled_at, &current, previous.as_ref());
+                }
+                Some((sampled_at, current))
+            }
+            Err(_) => previous,
+        };
+
+        // A worker slower than the interval skips ticks instead of queueing them.
+        let next = (deadline + self.interval).max(Instant::now());
+        self.schedule(next, previous);
+    }
+
+    fn send(
+        &self,
+        sampled_at: Instant,
+        current: &[T::Stats],
+        previous: Option<&(Instant, Vec<T::Stats>)>,
+    ) {
+        let stats = match previous {
+            Some((_, previous)) if self.delta => stats::delta(current, previous),
+            _ => current.to_vec(),
+        };
+        let sample = Sample {
+            id: self.id.clone(),
+            entity: self.entity,
+            delta: self.delta && previous.is_some(),
+            elapsed_ms: previous
+                .map(|(previous_at, _)| sampled_at.duration_since(*previous_at).as_millis() as u64),
+            stats,
+        };
+        event_dispatcher::dispatch(self.pid, (atoms::on_stats(), sample));
+    }
+}
+
+/// Sends `{:on_stats, %Mediasoup.Stats.Sample{}}` with the stats of the entity to `pid` every
+/// `interval_ms`. With `delta`, counters are the change since the previous sample.
+/// Once the entity closes, `{:on_stats_end, id}` is sent and polling stops.
+pub fn subscribe<T: EntityStats>(
+    resource: &DisposableResourceWrapper<T>,
+    pid: LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    if interval_ms == 0 {
+        return Err(rustler::Error::BadArg);
+    }
+    let id = resource.with_resource(crate::entity::Entity::id)?;
+    let stopped = Arc::new(AtomicBool::new(false));
+    let poller = Arc::new(Poller {
+        slot: resource.downgrade(),
+        id,
+        entity: T::KIND.name(),
+        pid,
+        interval: Duration::from_millis(interval_ms.into()),
+        delta,
+        stopped: Arc::clone(&stopped),
+    });
+    poller.schedule(Instant::now(), None);
+
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(StatsSubscriptionRef { stopped }),
+    ))
+}
+
+#[rustler::nif]
+pub fn stats_unsubscribe(subscription: ResourceArc<StatsSubscriptionRef>) -> (Atom,) {
+    subscription.stopped.store(true, Ordering::Relaxed);
+    (atoms::ok(),)
+}
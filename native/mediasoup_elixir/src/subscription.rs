@@ -0,0 +1,200 @@
+use crate::atoms;
+use crate::event_dispatcher::{self, BoxedEvent, Envelope};
+use event_listener_primitives::HandlerId;
+use rustler::{Atom, Encoder, LocalPid, NifResult, ResourceArc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limits applied to the value events (scores, layers, state changes) of a subscription.
+/// Lifecycle events such as `on_close` are never delayed or dropped. The mailbox limit is not
+/// applied here but by the processes forwarding events to their listeners, see
+/// `Mediasoup.EventListener`.
+pub struct EventOptionsStruct {
+    /// Minimum interval between two events of the same type. Within the interval only the
+    /// last value is kept, and it is sent when the interval has passed.
+    pub min_interval_ms: Option<u32>,
+    /// Replace a value that is still queued for the process instead of queueing another one.
+    pub coalesce: Option<bool>,
+    /// Send events queued together as one `{:nif_internal_event_batch, events}` message.
+    pub batch: Option<bool>,
+}
+
+crate::nif_struct_decoder!(
+    EventOptionsStruct,
+    "Mediasoup.EventOptions",
+    [min_interval_ms, coalesce, batch]
+);
+
+struct Throttle {
+    event: Atom,
+    last_sent: Instant,
+    pending: Option<BoxedEvent>,
+}
+
+/// Sends the events of one subscription to its pid, applying the subscription options.
+pub struct EventSink {
+    id: usize,
+    pid: LocalPid,
+    min_interval: Option<Duration>,
+    coalesce: bool,
+    batch: bool,
+    throttles: Mutex<Vec<Throttle>>,
+    closed: AtomicBool,
+}
+
+impl EventSink {
+    pub fn new(pid: LocalPid, options: EventOptionsStruct) -> Arc<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            pid,
+            min_interval: options
+                .min_interval_ms
+                .filter(|interval| *interval > 0)
+                .map(|interval| Duration::from_millis(interval.into())),
+            coalesce: options.coalesce.unwrap_or(false),
+            batch: options.batch.unwrap_or(false),
+            throttles: Mutex::new(Vec::new()),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// Sends `{:nif_internal_event, event}`.
+    pub fn send(&self, event: Atom) {
+        self.dispatch(Envelope::new(Box::new((
+            atoms::nif_internal_event(),
+            event,
+        ))));
+    }
+
+    /// Sends `{:nif_internal_event, event, payload}` without applying the options.
+    pub fn send_payload<T>(&self, event: Atom, payload: T)
+    where
+        T: Encoder + Send + 'static,
+    {
+        self.dispatch(Envelope::new(Box::new((
+            atoms::nif_internal_event(),
+            event,
+            payload,
+        ))));
+    }
+
+    /// Sends `{:nif_internal_event, event, value}`, applying the options.
+    pub fn send_value<T>(self: &Arc<Self>, event: Atom, value: T)
+    where
+        T: Encoder + Send + 'static,
+    {
+        let message: BoxedEvent = Box::new((atoms::nif_internal_event(), event, value));
+        let Some(min_interval) = self.min_interval else {
+            self.deliver(event, message);
+            return;
+        };
+
+        let Ok(mut throttles) = self.throttles.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        match throttles
+            .iter_mut()
+            .find(|throttle| throttle.event == event)
+        {
+            None => {
+                throttles.push(Throttle {
+                    event,
+                    last_sent: now,
+                    pending: None,
+                });
+                drop(throttles);
+                self.deliver(event, message);
+            }
+            Some(throttle) if throttle.pending.is_some() => {
+                throttle.pending = Some(message);
+            }
+            Some(throttle) if now >= throttle.last_sent + min_interval => {
+                throttle.last_sent = now;
+                drop(throttles);
+                self.deliver(event, message);
+            }
+            Some(throttle) => {
+                throttle.pending = Some(message);
+                let sink = Arc::clone(self);
+                event_dispatcher::schedule(throttle.last_sent + min_interval, move || {
+                    sink.flush(event)
+                });
+            }
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    fn flush(&self, event: Atom) {
+        let Ok(mut throttles) = self.throttles.lock() else {
+            return;
+        };
+        let Some(throttle) = throttles
+            .iter_mut()
+            .find(|throttle| throttle.event == event)
+        else {
+            return;
+        };
+        throttle.last_sent = Instant::now();
+        let pending = throttle.pending.take();
+        drop(throttles);
+
+        if let Some(message) = pending {
+            self.deliver(event, message);
+        }
+    }
+
+    fn deliver(&self, event: Atom, message: BoxedEvent) {
+        if self.closed.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut envelope = Envelope::new(message);
+        if self.coalesce {
+            envelope = envelope.coalesce_by((self.id, event));
+        }
+        self.dispatch(envelope);
+    }
+
+    fn dispatch(&self, envelope: Envelope) {
+        event_dispatcher::dispatch_envelope(self.pid, envelope.batchable(self.batch));
+    }
+}
+
+/// Event handlers registered by a single `*_event` call.
+/// The handlers are removed by `unsubscribe`, or when the resource is garbage-collected.
+pub struct SubscriptionRef {
+    handlers: Mutex<Vec<HandlerId>>,
+    sink: Arc<EventSink>,
+}
+#[rustler::resource_impl]
+impl rustler::Resource for SubscriptionRef {}
+
+impl SubscriptionRef {
+    pub fn new(handlers: Vec<HandlerId>, sink: Arc<EventSink>) -> Self {
+        Self {
+            handlers: Mutex::new(handlers),
+            sink,
+        }
+    }
+}
+
+impl Drop for SubscriptionRef {
+    fn drop(&mut self) {
+        self.sink.close();
+    }
+}
+
+#[rustler::nif]
+pub fn unsubscribe(subscription: ResourceArc<SubscriptionRef>) -> NifResult<(Atom,)> {
+    if let Ok(mut handlers) = subscription.handlers.lock() {
+        handlers.clear();
+    }
+    subscription.sink.close();
+    Ok((atoms::ok(),))
+}
@@ -1,51 +1,434 @@
 use async_executor::{Executor, Task};
-use futures_lite::future;
+use futures_lite::{future, FutureExt};
 use mediasoup::worker_manager::WorkerManager;
 use once_cell::sync::Lazy;
+use std::any::Any;
 use std::future::Future;
-use std::sync::Arc;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
 
-static EXECUTOR: Lazy<Arc<Executor<'static>>> = Lazy::new(|| {
-    let executor = Arc::new(Executor::new());
-    let thread_count = std::cmp::max(2, num_cpus::get());
+/// Settings of one executor thread pool.
+#[derive(Clone)]
+pub struct PoolConfig {
+    pub threads: usize,
+    /// Threads are named `<name>-<index>`.
+    pub name: String,
+    pub stack_size: Option<usize>,
+}
+
+impl PoolConfig {
+    fn with_name(name: &str) -> Self {
+        Self {
+            threads: std::cmp::max(2, num_cpus::get()),
+            name: name.into(),
+            stack_size: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RuntimeConfig {
+    pub task: PoolConfig,
+    pub worker_manager: PoolConfig,
+}
 
-    for _ in 0..thread_count {
-        create_thread_for_executor(Arc::clone(&executor), "ex-mediasoup-task".into());
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            task: PoolConfig::with_name("ex-mediasoup-task"),
+            worker_manager: PoolConfig::with_name("ex-mediasoup-wm"),
+        }
     }
-    executor
+}
+
+struct ConfigState {
+    config: RuntimeConfig,
+    started: bool,
+}
+
+static CONFIG: Lazy<Mutex<ConfigState>> = Lazy::new(|| {
+    Mutex::new(ConfigState {
+        config: RuntimeConfig::default(),
+        started: false,
+    })
 });
 
-static WORKER_MANAGER: Lazy<Arc<WorkerManager>> = Lazy::new(|| {
-    let executor = Arc::new(Executor::new());
-    let thread_count = std::cmp::max(2, num_cpus::get());
+pub struct ThreadStats {
+    pub name: String,
+    busy_ns: AtomicU64,
+}
+
+impl ThreadStats {
+    pub fn busy_time_us(&self) -> u64 {
+        self.busy_ns.load(Ordering::Relaxed) / 1000
+    }
+}
+
+pub struct PoolStats {
+    pub name: String,
+    queued: AtomicUsize,
+    active: AtomicUsize,
+    panics: AtomicU64,
+    restarts: AtomicU64,
+    pub threads: Vec<Arc<ThreadStats>>,
+}
+
+impl PoolStats {
+    /// Stats of a pool that has not run anything yet.
+    fn new(config: &PoolConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            queued: AtomicUsize::new(0),
+            active: AtomicUsize::new(0),
+            panics: AtomicU64::new(0),
+            restarts: AtomicU64::new(0),
+            threads: (0..config.threads)
+                .map(|index| {
+                    Arc::new(ThreadStats {
+                        name: format!("{}-{}", config.name, index),
+                        busy_ns: AtomicU64::new(0),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Tasks spawned through `spawn` that have not been polled yet.
+    pub fn queued_tasks(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+    /// Tasks spawned through `spawn` that have not completed yet, queued ones included.
+    pub fn active_tasks(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+    /// Panics caught in tasks or in the executor of the pool.
+    pub fn panics(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+    /// Times a pool thread restarted its executor after a panic.
+    pub fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+}
 
-    for _ in 0..thread_count {
-        create_thread_for_executor(Arc::clone(&executor), "ex-mediasoup-wm".into());
+struct Pool {
+    executor: Arc<Executor<'static>>,
+    stats: Arc<PoolStats>,
+}
+
+impl Pool {
+    fn start(config: &PoolConfig) -> Self {
+        let executor = Arc::new(Executor::new());
+        let stats = Arc::new(PoolStats::new(config));
+
+        for thread in &stats.threads {
+            create_thread_for_executor(
+                Arc::clone(&executor),
+                Arc::clone(&stats),
+                Arc::clone(thread),
+                config.stack_size,
+            );
+        }
+
+        Self { executor, stats }
     }
+}
 
-    Arc::new(WorkerManager::with_executor(executor))
+fn start_pool(select: fn(&RuntimeConfig) -> &PoolConfig) -> Pool {
+    let mut state = CONFIG
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    state.started = true;
+    Pool::start(select(&state.config))
+}
+
+static EXECUTOR: Lazy<Pool> = Lazy::new(|| start_pool(|config| &config.task));
+
+static WORKER_MANAGER: Lazy<(Arc<WorkerManager>, Arc<PoolStats>)> = Lazy::new(|| {
+    let pool = start_pool(|config| &config.worker_manager);
+    (
+        Arc::new(WorkerManager::with_executor(pool.executor)),
+        pool.stats,
+    )
 });
 
+/// Replaces the pool settings. Fails once any pool has been started.
+pub fn configure(config: RuntimeConfig) -> Result<(), ()> {
+    let mut state = CONFIG
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if state.started {
+        return Err(());
+    }
+    state.config = config;
+    Ok(())
+}
+
 pub fn worker_manager() -> Arc<WorkerManager> {
-    WORKER_MANAGER.clone()
+    WORKER_MANAGER.0.clone()
 }
 pub fn executor() -> Arc<Executor<'static>> {
-    EXECUTOR.clone()
+    EXECUTOR.executor.clone()
+}
+
+/// Stats of the task pool and the worker manager pool.
+/// A pool that has not been used yet is not started: it reports zeros for its configuration.
+pub fn stats() -> (Arc<PoolStats>, Arc<PoolStats>) {
+    let idle = |select: fn(&RuntimeConfig) -> &PoolConfig| {
+        let state = CONFIG
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::new(PoolStats::new(select(&state.config)))
+    };
+    (
+        Lazy::get(&EXECUTOR).map_or_else(|| idle(|config| &config.task), |pool| pool.stats.clone()),
+        Lazy::get(&WORKER_MANAGER).map_or_else(
+            || idle(|config| &config.worker_manager),
+            |(_, stats)| stats.clone(),
+        ),
+    )
 }
 
 pub fn spawn<T>(task: T) -> Task<()>
 where
     T: Future<Output = ()> + Send + 'static,
 {
-    executor().spawn(task)
+    let stats = EXECUTOR.stats.clone();
+    stats.queued.fetch_add(1, Ordering::Relaxed);
+    stats.active.fetch_add(1, Ordering::Relaxed);
+    executor().spawn(Tracked {
+        inner: Box::pin(task),
+        context: std::any::type_name::<T>(),
+        stats,
+        polled: false,
+    })
 }
 
-fn create_thread_for_executor(executor: Arc<Executor<'static>>, name: String) {
-    let builder = std::thread::Builder::new().name(name);
+/// Runs each of `futures` as its own task and resolves to their outputs, in order.
+/// The tasks start right away, before the returned future is polled. `None` is the output of a
+/// task that panicked.
+pub fn join_all<T, F>(futures: impl IntoIterator<Item = F>) -> impl Future<Output = Vec<Option<T>>>
+where
+    T: Send + Sync + 'static,
+    F: Future<Output = T> + Send + 'static,
+{
+    let receivers: Vec<_> = futures
+        .into_iter()
+        .map(|future| {
+            let (mut sender, receiver) = async_oneshot::oneshot();
+            spawn(async move {
+                let _ = sender.send(future.await);
+            })
+            .detach();
+            receiver
+        })
+        .collect();
+
+    async move {
+        let mut outputs = Vec::with_capacity(receivers.len());
+        for receiver in receivers {
+            outputs.push(receiver.await.ok());
+        }
+        outputs
+    }
+}
+
+/// Runs `future` and returns its output, or `None` if it panicked. The panic is reported and
+/// counted in the task pool stats like a panicking task, but the caller can still reply.
+pub async fn catch_panic<T>(context: &'static str, future: impl Future<Output = T>) -> Option<T> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(output) => Some(output),
+        Err(payload) => {
+            EXECUTOR.stats.panics.fetch_add(1, Ordering::Relaxed);
+            report_panic(&format!("task {}", context), payload.as_ref());
+            None
+        }
+    }
+}
+
+/// Counts a task in its pool stats.
+/// A panicking task is reported and completed, so it doesn't unwind through the executor.
+struct Tracked<T> {
+    inner: Pin<Box<T>>,
+    /// Type name of the future, which names the NIF that spawned it.
+    context: &'static str,
+    stats: Arc<PoolStats>,
+    polled: bool,
+}
+
+impl<T> Future for Tracked<T>
+where
+    T: Future<Output = ()>,
+{
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if !self.polled {
+            self.polled = true;
+            self.stats.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        let inner = &mut self.inner;
+        panic::catch_unwind(AssertUnwindSafe(|| inner.as_mut().poll(cx))).unwrap_or_else(
+            |payload| {
+                self.stats.panics.fetch_add(1, Ordering::Relaxed);
+                report_panic(&format!("task {}", self.context), payload.as_ref());
+                Poll::Ready(())
+            },
+        )
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        if !self.polled {
+            self.stats.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.stats.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn create_thread_for_executor(
+    executor: Arc<Executor<'static>>,
+    stats: Arc<PoolStats>,
+    thread: Arc<ThreadStats>,
+    stack_size: Option<usize>,
+) {
+    let mut builder = std::thread::Builder::new().name(thread.name.clone());
+    if let Some(stack_size) = stack_size {
+        builder = builder.stack_size(stack_size);
+    }
     let _ = builder.spawn(move || {
-        future::block_on(executor.run(async {
-            let future = future::pending();
-            let () = future.await;
-        }));
+        // The executor only returns by unwinding; the panicking task is dropped by then.
+        loop {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut run = pin!(executor.run(future::pending::<()>()));
+                // The executor runs its tasks within these polls, also the tasks mediasoup
+                // spawns on the worker manager pool, so their time is the busy time.
+                future::block_on(future::poll_fn(|cx| {
+                    let started_at = Instant::now();
+                    let poll = run.as_mut().poll(cx);
+                    let elapsed = started_at.elapsed().as_nanos() as u64;
+                    thread.busy_ns.fetch_add(elapsed, Ordering::Relaxed);
+                    poll
+                }))
+            }));
+            if let Err(payload) = result {
+                stats.panics.fetch_add(1, Ordering::Relaxed);
+                stats.restarts.fetch_add(1, Ordering::Relaxed);
+                report_panic(&format!("executor of {}", stats.name), payload.as_ref());
+            }
+        }
     });
 }
+
+fn report_panic(context: &str, payload: &(dyn Any + Send)) {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic payload");
+    let thread = std::thread::current();
+    log::error!(
+        "{} panicked on thread {}: {}",
+        context,
+        thread.name().unwrap_or("unnamed"),
+        message
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn pool_keeps_running_after_a_panicking_task() {
+        let panics = EXECUTOR.stats.panics();
+        spawn(async { panic!("task panic in test") }).detach();
+
+        let (sender, receiver) = mpsc::channel();
+        spawn(async move {
+            let _ = sender.send(());
+        })
+        .detach();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while EXECUTOR.stats.panics() == panics && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(EXECUTOR.stats.panics() > panics);
+        assert_eq!(0, EXECUTOR.stats.restarts());
+    }
+
+    #[test]
+    fn busy_time_counts_running_tasks() {
+        let busy_us = |stats: &PoolStats| -> u64 {
+            stats
+                .threads
+                .iter()
+                .map(|thread| thread.busy_time_us())
+                .sum()
+        };
+        let before = busy_us(&EXECUTOR.stats);
+
+        let (sender, receiver) = mpsc::channel();
+        spawn(async move {
+            std::thread::sleep(Duration::from_millis(20));
+            let _ = sender.send(());
+        })
+        .detach();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+
+        // The poll time is added once the poll that ran the task returns.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while busy_us(&EXECUTOR.stats) < before + 20_000 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(busy_us(&EXECUTOR.stats) >= before + 20_000);
+    }
+
+    #[test]
+    fn idle_pool_stats_are_zero() {
+        let config = PoolConfig {
+            threads: 3,
+            name: "idle".into(),
+            stack_size: None,
+        };
+        let stats = PoolStats::new(&config);
+        assert_eq!(
+            (0, 0, 0, 0),
+            (
+                stats.queued_tasks(),
+                stats.active_tasks(),
+                stats.panics(),
+                stats.restarts()
+            )
+        );
+        let names: Vec<_> = stats
+            .threads
+            .iter()
+            .map(|thread| thread.name.as_str())
+            .collect();
+        assert_eq!(vec!["idle-0", "idle-1", "idle-2"], names);
+        assert!(stats
+            .threads
+            .iter()
+            .all(|thread| thread.busy_time_us() == 0));
+    }
+
+    #[test]
+    fn catch_panic_returns_none() {
+        let panics = EXECUTOR.stats.panics();
+        let output = future::block_on(catch_panic("test", async { panic!("caught in test") }));
+        assert_eq!(None::<()>, output);
+        assert!(EXECUTOR.stats.panics() > panics);
+        assert_eq!(Some(1), future::block_on(catch_panic("test", async { 1 })));
+    }
+}
@@ -0,0 +1,129 @@
+//! Semantic checks for option structs, run before any request reaches the worker.
+//! Errors are raised to elixir as `ArgumentError` naming the offending field.
+
+use crate::json_serde::InvalidArgument;
+use mediasoup::prelude::ListenInfo;
+use std::ops::RangeInclusive;
+
+pub type ValidationResult = Result<(), InvalidArgument>;
+
+pub fn ensure(condition: bool, field: &str, message: &str) -> ValidationResult {
+    if condition {
+        Ok(())
+    } else {
+        Err(InvalidArgument::new(message).within(field))
+    }
+}
+
+pub fn ensure_positive<T>(value: Option<T>, field: &str) -> ValidationResult
+where
+    T: Default + PartialOrd,
+{
+    match value {
+        Some(value) => ensure(value > T::default(), field, "must be greater than 0"),
+        None => Ok(()),
+    }
+}
+
+pub fn ensure_not_empty_range(range: &RangeInclusive<u16>, field: &str) -> ValidationResult {
+    ensure(
+        !range.is_empty(),
+        field,
+        &format!("port range {}..{} is empty", range.start(), range.end()),
+    )
+}
+
+/// Checks each listen info and rejects entries listening on the same socket.
+pub fn validate_listen_infos(infos: &[ListenInfo], field: &str) -> ValidationResult {
+    for (index, info) in infos.iter().enumerate() {
+        validate_listen_info(info)
+            .map_err(|error| error.within(&format!("{}[{}]", field, index)))?;
+
+        if let Some(previous) = infos[..index]
+            .iter()
+            .position(|previous| same_socket(previous, info))
+        {
+            return Err(
+                InvalidArgument::new(format!("duplicates {}[{}]", field, previous))
+                    .within(&format!("{}[{}]", field, index)),
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_listen_info(info: &ListenInfo) -> ValidationResult {
+    if let Some(port_range) = &info.port_range {
+        ensure_not_empty_range(port_range, "portRange")?;
+    }
+    Ok(())
+}
+
+fn same_socket(a: &ListenInfo, b: &ListenInfo) -> bool {
+    if a == b {
+        return true;
+    }
+    // Random ports (none or 0) never collide.
+    let explicit_port = |info: &ListenInfo| info.port.filter(|port| *port != 0);
+    a.protocol == b.protocol
+        && a.ip == b.ip
+        && a.port_range.is_none()
+        && b.port_range.is_none()
+        && explicit_port(a).is_some()
+        && explicit_port(a) == explicit_port(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mediasoup::prelude::Protocol;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn listen_info(protocol: Protocol, port: Option<u16>) -> ListenInfo {
+        ListenInfo {
+            protocol,
+            ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            announced_address: None,
+            expose_internal_ip: false,
+            port,
+            port_range: None,
+            flags: None,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+        }
+    }
+
+    #[test]
+    fn listen_infos_rejects_duplicates() {
+        let infos = [
+            listen_info(Protocol::Udp, Some(40000)),
+            listen_info(Protocol::Tcp, Some(40000)),
+            listen_info(Protocol::Udp, Some(40000)),
+        ];
+        assert_eq!(
+            "listen_infos[2]: duplicates listen_infos[0]",
+            validate_listen_infos(&infos, "listen_infos")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let infos = [
+            listen_info(Protocol::Udp, None),
+            listen_info(Protocol::Udp, Some(0)),
+            listen_info(Protocol::Udp, Some(40000)),
+        ];
+        assert!(validate_listen_infos(&infos, "listen_infos").is_ok());
+    }
+
+    #[test]
+    fn listen_infos_rejects_empty_port_range() {
+        let mut info = listen_info(Protocol::Udp, None);
+        info.port_range = Some(RangeInclusive::new(40010, 40000));
+        assert_eq!(
+            "listen_infos[0].portRange: port range 40010..40000 is empty",
+            validate_listen_infos(&[info], "listen_infos")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -1,26 +1,36 @@
+use crate::validation::validate_listen_infos;
 use crate::{
-    atoms, json_serde::JsonSerdeWrap, send_async_nif_result_with_from, DisposableResourceWrapper,
+    atoms, dump, json_serde::JsonSerdeWrap, send_async_nif_result_with_from,
+    DisposableResourceWrapper,
 };
 use mediasoup::prelude::{
     ListenInfo, WebRtcServer, WebRtcServerId, WebRtcServerListenInfos, WebRtcServerOptions,
 };
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type WebRtcServerRef = DisposableResourceWrapper<WebRtcServer>;
 
 #[rustler::resource_impl]
 impl rustler::Resource for WebRtcServerRef {}
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.WebRtcServer.Options"]
 pub struct WebRtcServerOptionsStruct {
     listen_infos: JsonSerdeWrap<Vec<ListenInfo>>,
 }
 
+crate::nif_struct_decoder!(
+    WebRtcServerOptionsStruct,
+    "Mediasoup.WebRtcServer.Options",
+    [listen_infos]
+);
+
 impl WebRtcServerOptionsStruct {
-    pub fn try_to_option(&self) -> Result<WebRtcServerOptions, &'static str> {
+    pub fn try_to_option(&self) -> NifResult<WebRtcServerOptions> {
+        validate_listen_infos(&self.listen_infos, "listen_infos")?;
+
         let infos = match self.listen_infos.first() {
-            None => Err("Rquired least one listen info"),
+            None => Err(rustler::Error::Term(Box::new(
+                "Rquired least one listen info".to_string(),
+            ))),
             Some(info) => Ok(WebRtcServerListenInfos::new(info.clone())),
         }?;
 
@@ -36,8 +46,7 @@ impl WebRtcServerOptionsStruct {
 pub fn webrtc_server_id(
     server: ResourceArc<WebRtcServerRef>,
 ) -> NifResult<JsonSerdeWrap<WebRtcServerId>> {
-    let server = server.get_resource()?;
-    Ok(server.id().into())
+    server.with_resource(|server| server.id().into())
 }
 
 #[rustler::nif]
@@ -46,6 +55,24 @@ pub fn webrtc_server_close(server: ResourceArc<WebRtcServerRef>) -> NifResult<(A
     Ok((atoms::ok(),))
 }
 
+/// Closes the WebRTC server and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "webrtc_server_close_async")]
+pub fn webrtc_server_close_confirmed(
+    env: Env,
+    server: ResourceArc<WebRtcServerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = server.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_server_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
 #[rustler::nif]
 pub fn webrtc_server_closed(server: ResourceArc<WebRtcServerRef>) -> NifResult<bool> {
     match server.get_resource() {
@@ -59,14 +86,21 @@ pub fn webrtc_server_dump(
     env: Env,
     server: ResourceArc<WebRtcServerRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let server = server.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        server
-            .dump()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_server_dump_async",
+        from,
+        timeout_ms,
+        async move {
+            server
+                .dump()
+                .await
+                .map(dump::WebRtcServerDumpStruct::from)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
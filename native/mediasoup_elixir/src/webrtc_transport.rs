@@ -1,37 +1,41 @@
-use crate::consumer::{ConsumerOptionsStruct, ConsumerRef};
+use crate::close;
+use crate::consumer::{self, ConsumeBatchItemStruct, ConsumerOptionsStruct, ConsumerRef};
 use crate::data_consumer::{DataConsumerOptionsStruct, DataConsumerRef};
 use crate::data_producer::{DataProducerOptionsStruct, DataProducerRef};
-use crate::data_structure::SerNumSctpStreams;
-use crate::json_serde::JsonSerdeWrap;
+use crate::data_structure::{SerNumSctpStreams, TransportTupleStruct};
+use crate::json_serde::{AsAtom, JsonSerdeWrap};
 use crate::producer::{ProducerOptionsStruct, ProducerRef};
+use crate::stats_subscription::{self, StatsSubscriptionRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
+use crate::validation::{ensure, ensure_positive, validate_listen_infos, ValidationResult};
 use crate::webrtc_server::WebRtcServerRef;
-use crate::{
-    atoms, send_async_nif_result_with_from, send_msg_from_other_thread, DisposableResourceWrapper,
-};
+use crate::{atoms, dump, send_async_nif_result_with_from, stats, DisposableResourceWrapper};
 use mediasoup::prelude::{
     ConsumerOptions, DataConsumerOptions, DataProducerOptions, DtlsParameters, IceParameters,
-    ListenInfo, Transport, TransportGeneric, WebRtcTransport,
+    ListenInfo, RtpCapabilities, Transport, TransportGeneric, WebRtcTransport,
 };
 use mediasoup::producer::ProducerOptions;
 use mediasoup::transport::TransportId;
-use mediasoup::types::data_structures::{DtlsState, IceRole, IceState, SctpState, TransportTuple};
 use mediasoup::types::sctp_parameters::SctpParameters;
 use mediasoup::webrtc_transport::{
     WebRtcTransportListenInfos, WebRtcTransportOptions, WebRtcTransportRemoteParameters,
 };
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Env, NifResult, ResourceArc, Term};
 
 pub type WebRtcTransportRef = DisposableResourceWrapper<WebRtcTransport>;
 
 #[rustler::resource_impl]
-impl rustler::Resource for WebRtcTransportRef {}
+impl rustler::Resource for WebRtcTransportRef {
+    fn down<'a>(&'a self, _env: Env<'a>, pid: rustler::LocalPid, monitor: rustler::Monitor) {
+        self.owner_down(pid, monitor);
+    }
+}
 
 #[rustler::nif]
 pub fn webrtc_transport_id(
     transport: ResourceArc<WebRtcTransportRef>,
 ) -> NifResult<JsonSerdeWrap<TransportId>> {
-    let transport = transport.get_resource()?;
-    Ok(transport.id().into())
+    transport.with_resource(|transport| transport.id().into())
 }
 
 #[rustler::nif]
@@ -40,6 +44,35 @@ pub fn webrtc_transport_close(transport: ResourceArc<WebRtcTransportRef>) -> Nif
     Ok((atoms::ok(),))
 }
 
+/// Closes the WebRTC transport and replies once the worker has confirmed it, see `close::confirm`.
+#[rustler::nif(name = "webrtc_transport_close_async")]
+pub fn webrtc_transport_close_confirmed(
+    env: Env,
+    transport: ResourceArc<WebRtcTransportRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = transport.close_confirmed()?;
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_close_async",
+        from,
+        timeout_ms,
+        confirmed,
+    )
+}
+
+/// Closes the WebRTC transport when `owner` exits, see `DisposableResourceWrapper::monitor_owner`.
+#[rustler::nif]
+pub fn webrtc_transport_monitor_owner(
+    env: Env,
+    transport: ResourceArc<WebRtcTransportRef>,
+    owner: rustler::LocalPid,
+) -> NifResult<(rustler::Atom,)> {
+    DisposableResourceWrapper::monitor_owner(&transport, env, owner)?;
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif]
 pub fn webrtc_transport_closed(transport: ResourceArc<WebRtcTransportRef>) -> NifResult<bool> {
     match transport.get_resource() {
@@ -54,19 +87,49 @@ pub fn webrtc_transport_consume(
     transport: ResourceArc<WebRtcTransportRef>,
     option: ConsumerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option: ConsumerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .consume(option)
-            .await
-            .map(ConsumerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_consume_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .consume(option)
+                .await
+                .map(ConsumerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Creates a consumer for each producer of `items`, see `consumer::consume_batch`.
+/// Replies with a list of `{:ok, consumer}` or `{:error, reason}`, in the order of `items`.
+#[rustler::nif(name = "webrtc_transport_consume_batch_async")]
+pub fn webrtc_transport_consume_batch(
+    env: Env,
+    transport: ResourceArc<WebRtcTransportRef>,
+    rtp_capabilities: JsonSerdeWrap<RtpCapabilities>,
+    items: Vec<ConsumeBatchItemStruct>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let transport = transport.get_resource()?;
+    let consumers = consumer::consume_batch(transport, &rtp_capabilities, &items);
+
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_consume_batch_async",
+        from,
+        timeout_ms,
+        async move { Ok::<_, String>(consumers.await) },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_consume_data_async")]
@@ -75,19 +138,26 @@ pub fn webrtc_transport_consume_data(
     transport: ResourceArc<WebRtcTransportRef>,
     option: DataConsumerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
     let option: DataConsumerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .consume_data(option)
-            .await
-            .map(DataConsumerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_consume_data_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .consume_data(option)
+                .await
+                .map(DataConsumerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_connect_async")]
@@ -96,16 +166,23 @@ pub fn webrtc_transport_connect(
     transport: ResourceArc<WebRtcTransportRef>,
     option: JsonSerdeWrap<WebRtcTransportRemoteParameters>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option: WebRtcTransportRemoteParameters = option.clone();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .connect(option)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_connect_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .connect(option)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_produce_async")]
@@ -114,18 +191,25 @@ pub fn webrtc_transport_produce(
     transport: ResourceArc<WebRtcTransportRef>,
     option: ProducerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option: ProducerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .produce(option)
-            .await
-            .map(ProducerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_produce_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .produce(option)
+                .await
+                .map(ProducerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_produce_data_async")]
@@ -134,50 +218,51 @@ pub fn webrtc_transport_produce_data(
     transport: ResourceArc<WebRtcTransportRef>,
     option: DataProducerOptionsStruct,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
     let option: DataProducerOptions = option.to_option();
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .produce_data(option)
-            .await
-            .map(DataProducerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_produce_data_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .produce_data(option)
+                .await
+                .map(DataProducerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
 pub fn webrtc_transport_ice_parameters(
     transport: ResourceArc<WebRtcTransportRef>,
 ) -> NifResult<JsonSerdeWrap<IceParameters>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.ice_parameters().clone()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.ice_parameters().clone()))
 }
 
 #[rustler::nif]
 pub fn webrtc_transport_sctp_parameters(
     transport: ResourceArc<WebRtcTransportRef>,
 ) -> NifResult<JsonSerdeWrap<Option<SctpParameters>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.sctp_parameters()))
 }
 
 #[rustler::nif]
 pub fn webrtc_transport_ice_candidates(
     transport: ResourceArc<WebRtcTransportRef>,
 ) -> NifResult<JsonSerdeWrap<std::vec::Vec<mediasoup::prelude::IceCandidate>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.ice_candidates().clone()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.ice_candidates().clone()))
 }
 
 #[rustler::nif]
-pub fn webrtc_transport_ice_role(
-    transport: ResourceArc<WebRtcTransportRef>,
-) -> NifResult<JsonSerdeWrap<IceRole>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.ice_role()))
+pub fn webrtc_transport_ice_role(transport: ResourceArc<WebRtcTransportRef>) -> NifResult<Atom> {
+    transport.with_resource(|transport| transport.ice_role().as_atom())
 }
 
 #[rustler::nif(name = "webrtc_transport_set_max_incoming_bitrate_async")]
@@ -186,15 +271,22 @@ pub fn webrtc_transport_set_max_incoming_bitrate(
     transport: ResourceArc<WebRtcTransportRef>,
     bitrate: u32,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .set_max_incoming_bitrate(bitrate)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_set_max_incoming_bitrate_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .set_max_incoming_bitrate(bitrate)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_set_max_outgoing_bitrate_async")]
@@ -203,23 +295,27 @@ pub fn webrtc_transport_set_max_outgoing_bitrate(
     transport: ResourceArc<WebRtcTransportRef>,
     bitrate: u32,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .set_max_outgoing_bitrate(bitrate)
-            .await
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_set_max_outgoing_bitrate_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .set_max_outgoing_bitrate(bitrate)
+                .await
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
-pub fn webrtc_transport_ice_state(
-    transport: ResourceArc<WebRtcTransportRef>,
-) -> NifResult<JsonSerdeWrap<IceState>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.ice_state()))
+pub fn webrtc_transport_ice_state(transport: ResourceArc<WebRtcTransportRef>) -> NifResult<Atom> {
+    transport.with_resource(|transport| transport.ice_state().as_atom())
 }
 
 #[rustler::nif(name = "webrtc_transport_restart_ice_async")]
@@ -227,16 +323,23 @@ pub fn webrtc_transport_restart_ice(
     env: Env,
     transport: ResourceArc<WebRtcTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .restart_ice()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_restart_ice_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .restart_ice()
+                .await
+                .map(JsonSerdeWrap::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "webrtc_transport_get_stats_async")]
@@ -244,16 +347,34 @@ pub fn webrtc_transport_get_stats(
     env: Env,
     transport: ResourceArc<WebRtcTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .get_stats()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_get_stats_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .get_stats()
+                .await
+                .map(|list| stats::convert::<_, stats::WebRtcTransportStats>(&list))
+                .map_err(|error| format!("{}", error))
+        },
+    )
+}
+
+/// Pushes the stats every `interval_ms`, see `stats_subscription::subscribe`.
+#[rustler::nif]
+pub fn webrtc_transport_stats_subscribe(
+    transport: ResourceArc<WebRtcTransportRef>,
+    pid: rustler::LocalPid,
+    interval_ms: u32,
+    delta: bool,
+) -> NifResult<(Atom, ResourceArc<StatsSubscriptionRef>)> {
+    stats_subscription::subscribe(&transport, pid, interval_ms, delta)
 }
 
 #[rustler::nif(name = "webrtc_transport_dump_async")]
@@ -261,90 +382,106 @@ pub fn webrtc_transport_dump(
     env: Env,
     transport: ResourceArc<WebRtcTransportRef>,
     from: Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<Atom> {
     let transport = transport.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        transport
-            .dump()
-            .await
-            .map(JsonSerdeWrap::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "webrtc_transport_dump_async",
+        from,
+        timeout_ms,
+        async move {
+            transport
+                .dump()
+                .await
+                .map(dump::WebRtcTransportDumpStruct::from)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif]
 pub fn webrtc_transport_ice_selected_tuple(
     transport: ResourceArc<WebRtcTransportRef>,
-) -> NifResult<JsonSerdeWrap<Option<TransportTuple>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.ice_selected_tuple()))
+) -> NifResult<Option<TransportTupleStruct>> {
+    transport.with_resource(|transport| transport.ice_selected_tuple().as_ref().map(Into::into))
 }
 
 #[rustler::nif]
 pub fn webrtc_transport_dtls_parameters(
     transport: ResourceArc<WebRtcTransportRef>,
 ) -> NifResult<JsonSerdeWrap<DtlsParameters>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.dtls_parameters()))
+    transport.with_resource(|transport| JsonSerdeWrap::new(transport.dtls_parameters()))
 }
 
 #[rustler::nif]
-pub fn webrtc_transport_dtls_state(
-    transport: ResourceArc<WebRtcTransportRef>,
-) -> NifResult<JsonSerdeWrap<DtlsState>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.dtls_state()))
+pub fn webrtc_transport_dtls_state(transport: ResourceArc<WebRtcTransportRef>) -> NifResult<Atom> {
+    transport.with_resource(|transport| transport.dtls_state().as_atom())
 }
 #[rustler::nif]
 pub fn webrtc_transport_sctp_state(
     transport: ResourceArc<WebRtcTransportRef>,
-) -> NifResult<JsonSerdeWrap<Option<SctpState>>> {
-    let transport = transport.get_resource()?;
-    Ok(JsonSerdeWrap::new(transport.sctp_state()))
+) -> NifResult<Option<Atom>> {
+    transport.with_resource(|transport| transport.sctp_state().as_ref().map(AsAtom::as_atom))
 }
 #[rustler::nif]
 pub fn webrtc_transport_event(
     transport: ResourceArc<WebRtcTransportRef>,
     pid: rustler::LocalPid,
     event_types: Vec<Atom>,
-) -> NifResult<(Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = transport.close_reason();
     let transport = transport.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback_once!(pid, transport, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &transport,
+        close_reason,
+        &event_types,
+    ));
 
     if event_types.contains(&atoms::on_sctp_state_change()) {
-        crate::reg_callback_json_param!(pid, transport, on_sctp_state_change);
+        handlers.push(crate::reg_callback_atom_param!(
+            sink,
+            transport,
+            on_sctp_state_change
+        ));
     }
     if event_types.contains(&atoms::on_ice_state_change()) {
-        crate::reg_callback_json_param!(pid, transport, on_ice_state_change);
+        handlers.push(crate::reg_callback_atom_param!(
+            sink,
+            transport,
+            on_ice_state_change
+        ));
     }
     if event_types.contains(&atoms::on_dtls_state_change()) {
-        crate::reg_callback_json_param!(pid, transport, on_dtls_state_change);
+        handlers.push(crate::reg_callback_atom_param!(
+            sink,
+            transport,
+            on_dtls_state_change
+        ));
     }
 
     if event_types.contains(&atoms::on_ice_selected_tuple_change()) {
-        transport
-            .on_ice_selected_tuple_change(move |arg| {
-                send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_ice_selected_tuple_change(),
-                        JsonSerdeWrap::new(arg.clone()),
-                    ),
-                );
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(transport.on_ice_selected_tuple_change(move |tuple| {
+            sink.send_value(
+                atoms::on_ice_selected_tuple_change(),
+                TransportTupleStruct::from(tuple),
+            );
+        }));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.WebRtcTransport.Options"]
 pub struct WebRtcTransportOptionsStruct {
     listen_infos: Option<JsonSerdeWrap<Vec<ListenInfo>>>,
     webrtc_server: Option<ResourceArc<WebRtcServerRef>>,
@@ -358,6 +495,24 @@ pub struct WebRtcTransportOptionsStruct {
     max_sctp_message_size: Option<u32>,
     sctp_send_buffer_size: Option<u32>,
 }
+
+crate::nif_struct_decoder!(
+    WebRtcTransportOptionsStruct,
+    "Mediasoup.WebRtcTransport.Options",
+    [
+        listen_infos,
+        webrtc_server,
+        enable_udp,
+        enable_tcp,
+        prefer_udp,
+        prefer_tcp,
+        initial_available_outgoing_bitrate,
+        enable_sctp,
+        num_sctp_streams,
+        max_sctp_message_size,
+        sctp_send_buffer_size
+    ]
+);
 impl WebRtcTransportOptionsStruct {
     pub fn try_to_option(&self) -> NifResult<WebRtcTransportOptions> {
         let mut option = if let Some(webrtc_server) = &self.webrtc_server {
@@ -407,6 +562,44 @@ impl WebRtcTransportOptionsStruct {
         if let Some(sctp_send_buffer_size) = self.sctp_send_buffer_size {
             option.sctp_send_buffer_size = sctp_send_buffer_size;
         }
+        self.validate(&option)?;
         Ok(option)
     }
+
+    fn validate(&self, option: &WebRtcTransportOptions) -> ValidationResult {
+        if let Some(listen_infos) = &self.listen_infos {
+            validate_listen_infos(listen_infos, "listen_infos")?;
+        }
+        ensure(
+            option.enable_udp || option.enable_tcp,
+            "enable_udp",
+            "either enable_udp or enable_tcp must be true",
+        )?;
+        ensure(
+            !(option.prefer_udp && option.prefer_tcp),
+            "prefer_udp",
+            "must not be set together with prefer_tcp",
+        )?;
+        ensure(
+            !option.prefer_udp || option.enable_udp,
+            "prefer_udp",
+            "requires enable_udp",
+        )?;
+        ensure(
+            !option.prefer_tcp || option.enable_tcp,
+            "prefer_tcp",
+            "requires enable_tcp",
+        )?;
+        ensure_positive(
+            self.initial_available_outgoing_bitrate,
+            "initial_available_outgoing_bitrate",
+        )?;
+        if let Some(num_sctp_streams) = &self.num_sctp_streams {
+            num_sctp_streams
+                .validate()
+                .map_err(|error| error.within("num_sctp_streams"))?;
+        }
+        ensure_positive(self.max_sctp_message_size, "max_sctp_message_size")?;
+        ensure_positive(self.sctp_send_buffer_size, "sctp_send_buffer_size")
+    }
 }
@@ -1,15 +1,20 @@
+use crate::async_request::AsyncRequestRef;
 use crate::atoms;
+use crate::close;
 use crate::json_serde::JsonSerdeWrap;
+use crate::logger::{self, LogSinkOptionsStruct};
 use crate::router::{RouterOptionsStruct, RouterRef};
+use crate::subscription::{EventOptionsStruct, EventSink, SubscriptionRef};
 use crate::task;
+use crate::validation::ensure;
 use crate::webrtc_server::{WebRtcServerOptionsStruct, WebRtcServerRef};
 use crate::DisposableResourceWrapper;
-use crate::{send_async_nif_result, send_async_nif_result_with_from, send_msg_from_other_thread};
+use crate::{dump, send_async_nif_result, send_async_nif_result_with_from};
 use mediasoup::worker::{
     Worker, WorkerDtlsFiles, WorkerId, WorkerLogLevel, WorkerLogTag, WorkerSettings,
     WorkerUpdateSettings,
 };
-use rustler::{Env, Error, NifResult, NifStruct, ResourceArc};
+use rustler::{Atom, Env, Error, LocalPid, NifResult, ResourceArc, Term};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -37,24 +42,57 @@ pub fn worker_close(worker: ResourceArc<WorkerRef>) -> NifResult<(rustler::Atom,
     Ok((atoms::ok(),))
 }
 
+/// Closes the worker and replies once it is closed, see `close::confirm`.
+#[rustler::nif(name = "worker_close_async")]
+pub fn worker_close_confirmed(
+    env: Env,
+    worker: ResourceArc<WorkerRef>,
+    from: Term,
+    timeout_ms: Option<u32>,
+) -> NifResult<Atom> {
+    let confirmed = worker.close_confirmed()?;
+    send_async_nif_result_with_from(env, "worker_close_async", from, timeout_ms, confirmed)
+}
+
+/// Sends the log records of this worker to `pid` as well, filtered by `options`.
+/// A `nil` pid removes the sink; it is also removed when the worker closes.
+#[rustler::nif]
+pub fn worker_set_log_sink(
+    worker: ResourceArc<WorkerRef>,
+    pid: Option<LocalPid>,
+    options: LogSinkOptionsStruct,
+) -> NifResult<(rustler::Atom,)> {
+    let worker = worker.get_resource()?;
+    // The sink is removed by the close handler registered in `create_worker_impl`.
+    logger::set_worker_sink(worker.id().to_string(), pid, Some(options));
+    Ok((atoms::ok(),))
+}
+
 #[rustler::nif(name = "worker_create_router_async")]
 pub fn worker_create_router(
     env: Env,
     worker: ResourceArc<WorkerRef>,
     option: RouterOptionsStruct,
     from: rustler::Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let worker = worker.get_resource()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        let option = option.to_option();
-        worker
-            .create_router(option)
-            .await
-            .map(RouterRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    send_async_nif_result_with_from(
+        env,
+        "worker_create_router_async",
+        from,
+        timeout_ms,
+        async move {
+            let option = option.to_option();
+            worker
+                .create_router(option)
+                .await
+                .map(RouterRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "worker_create_webrtc_server_async")]
@@ -63,19 +101,24 @@ pub fn worker_create_webrtc_server(
     worker: ResourceArc<WorkerRef>,
     option: WebRtcServerOptionsStruct,
     from: rustler::Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let worker = worker.get_resource()?;
-    let option = option
-        .try_to_option()
-        .map_err(|error| Error::Term(Box::new(error.to_string())))?;
-    send_async_nif_result_with_from(env, from, async move {
-        worker
-            .create_webrtc_server(option)
-            .await
-            .map(WebRtcServerRef::new)
-            .map(ResourceArc::new)
-            .map_err(|error| format!("{}", error))
-    })
+    let option = option.try_to_option()?;
+    send_async_nif_result_with_from(
+        env,
+        "worker_create_webrtc_server_async",
+        from,
+        timeout_ms,
+        async move {
+            worker
+                .create_webrtc_server(option)
+                .await
+                .map(WebRtcServerRef::new)
+                .map(ResourceArc::new)
+                .map_err(|error| format!("{}", error))
+        },
+    )
 }
 
 #[rustler::nif(name = "worker_dump_async")]
@@ -83,13 +126,14 @@ pub fn worker_dump(
     env: Env,
     worker: ResourceArc<WorkerRef>,
     from: rustler::Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let worker = worker.get_resource()?;
-    send_async_nif_result_with_from(env, from, async move {
+    send_async_nif_result_with_from(env, "worker_dump_async", from, timeout_ms, async move {
         worker
             .dump()
             .await
-            .map(JsonSerdeWrap::new)
+            .map(dump::WorkerDumpStruct::from)
             .map_err(|error| format!("{}", error))
     })
 }
@@ -105,17 +149,24 @@ pub fn worker_update_settings(
     worker: ResourceArc<WorkerRef>,
     settings: WorkerUpdateableSettingsStruct,
     from: rustler::Term,
+    timeout_ms: Option<u32>,
 ) -> NifResult<rustler::Atom> {
     let worker = worker.get_resource()?;
 
     let settings = settings.try_to_setting()?;
 
-    send_async_nif_result_with_from(env, from, async move {
-        match worker.update_settings(settings).await {
-            Ok(_) => (atoms::ok(),),
-            Err(_err) => (atoms::error(),),
-        }
-    })
+    send_async_nif_result_with_from(
+        env,
+        "worker_update_settings_async",
+        from,
+        timeout_ms,
+        async move {
+            match worker.update_settings(settings).await {
+                Ok(_) => (atoms::ok(),),
+                Err(_err) => (atoms::error(),),
+            }
+        },
+    )
 }
 
 #[rustler::nif]
@@ -123,8 +174,12 @@ pub fn worker_event(
     worker: ResourceArc<WorkerRef>,
     pid: rustler::LocalPid,
     event_types: Vec<rustler::Atom>,
-) -> NifResult<(rustler::Atom,)> {
+    options: EventOptionsStruct,
+) -> NifResult<(rustler::Atom, ResourceArc<SubscriptionRef>)> {
+    let close_reason = worker.close_reason();
     let worker = worker.get_resource()?;
+    let sink = EventSink::new(pid, options);
+    let mut handlers = Vec::new();
 
     /* TODO: Can not create multiple instance for disposable
     If we need this, implement at elixir side.
@@ -139,52 +194,47 @@ pub fn worker_event(
             })
             .detach();
     }*/
-    if event_types.contains(&atoms::on_close()) {
-        crate::reg_callback!(pid, worker, on_close);
-    }
+    handlers.extend(close::reg_close_events(
+        &sink,
+        &worker,
+        close_reason,
+        &event_types,
+    ));
 
     if event_types.contains(&atoms::on_dead()) {
-        worker
-            .on_dead(move |reason| match reason {
-                Ok(_) => send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_dead(),
-                        "exit".to_string(),
-                    ),
-                ),
-                Err(err) => send_msg_from_other_thread(
-                    pid,
-                    (
-                        atoms::nif_internal_event(),
-                        atoms::on_dead(),
-                        err.to_string(),
-                    ),
-                ),
-            })
-            .detach();
+        let sink = sink.clone();
+        handlers.push(worker.on_dead(move |reason| match reason {
+            Ok(_) => sink.send_payload(atoms::on_dead(), "exit".to_string()),
+            Err(err) => sink.send_payload(atoms::on_dead(), err.to_string()),
+        }));
     }
 
-    Ok((atoms::ok(),))
+    Ok((
+        atoms::ok(),
+        ResourceArc::new(SubscriptionRef::new(handlers, sink)),
+    ))
 }
 
 fn create_worker_impl(
     env: Env,
     settings: WorkerSettings,
-) -> NifResult<(rustler::Atom, rustler::Atom)> {
-    send_async_nif_result(env, async move {
+    timeout_ms: Option<u32>,
+) -> NifResult<(rustler::Atom, ResourceArc<AsyncRequestRef>)> {
+    send_async_nif_result(env, "create_worker_async", timeout_ms, async move {
         let worker_manager = task::worker_manager();
         worker_manager
             .create_worker(settings)
             .await
             .map(|worker| {
                 GLOBAL_WORKER_COUNT.fetch_add(1, Ordering::Relaxed);
+                let worker_id = worker.id().to_string();
                 worker
                     .on_close(|| {
                         GLOBAL_WORKER_COUNT.fetch_sub(1, Ordering::Relaxed);
+                        logger::set_worker_sink(worker_id, None, None);
                     })
                     .detach();
+                close::track_worker(&worker);
                 ResourceArc::new(WorkerRef::new(worker))
             })
             .map_err(|error| format!("{}", error))
@@ -192,26 +242,40 @@ fn create_worker_impl(
 }
 
 #[rustler::nif(name = "create_worker_async")]
-pub fn create_worker_no_arg(env: Env) -> NifResult<(rustler::Atom, rustler::Atom)> {
-    create_worker_impl(env, WorkerSettings::default())
+pub fn create_worker_no_arg(env: Env) -> NifResult<(rustler::Atom, ResourceArc<AsyncRequestRef>)> {
+    create_worker_impl(env, WorkerSettings::default(), None)
 }
 
 #[rustler::nif(name = "create_worker_async")]
 pub fn create_worker(
     env: Env,
     settings: WorkerSettingsStruct,
-) -> NifResult<(rustler::Atom, rustler::Atom)> {
+) -> NifResult<(rustler::Atom, ResourceArc<AsyncRequestRef>)> {
     let settings = settings.try_to_setting()?;
-    create_worker_impl(env, settings)
+    create_worker_impl(env, settings, None)
+}
+
+#[rustler::nif(name = "create_worker_async")]
+pub fn create_worker_with_timeout(
+    env: Env,
+    settings: WorkerSettingsStruct,
+    timeout_ms: u32,
+) -> NifResult<(rustler::Atom, ResourceArc<AsyncRequestRef>)> {
+    let settings = settings.try_to_setting()?;
+    create_worker_impl(env, settings, Some(timeout_ms))
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.Worker.UpdateableSettings"]
 pub struct WorkerUpdateableSettingsStruct {
     pub log_level: Option<JsonSerdeWrap<String>>,
     pub log_tags: Option<JsonSerdeWrap<Vec<String>>>,
 }
 
+crate::nif_struct_decoder!(
+    WorkerUpdateableSettingsStruct,
+    "Mediasoup.Worker.UpdateableSettings",
+    [log_level, log_tags]
+);
+
 impl WorkerUpdateableSettingsStruct {
     fn try_to_setting(&self) -> Result<WorkerUpdateSettings, Error> {
         let mut value = WorkerUpdateSettings::default();
@@ -226,8 +290,6 @@ impl WorkerUpdateableSettingsStruct {
     }
 }
 
-#[derive(NifStruct)]
-#[module = "Mediasoup.Worker.Settings"]
 pub struct WorkerSettingsStruct {
     pub log_level: Option<JsonSerdeWrap<String>>,
     pub log_tags: Option<JsonSerdeWrap<Vec<String>>>,
@@ -237,6 +299,19 @@ pub struct WorkerSettingsStruct {
     pub dtls_private_key_file: Option<String>,
 }
 
+crate::nif_struct_decoder!(
+    WorkerSettingsStruct,
+    "Mediasoup.Worker.Settings",
+    [
+        log_level,
+        log_tags,
+        rtc_min_port,
+        rtc_max_port,
+        dtls_certificate_file,
+        dtls_private_key_file
+    ]
+);
+
 impl WorkerSettingsStruct {
     fn try_to_setting(&self) -> Result<WorkerSettings, Error> {
         let mut value = WorkerSettings::default();
@@ -249,8 +324,18 @@ impl WorkerSettingsStruct {
         let default_range = value.rtc_port_range;
         let minport = self.rtc_min_port.unwrap_or(*default_range.start());
         let maxport = self.rtc_max_port.unwrap_or(*default_range.end());
+        ensure(
+            minport <= maxport,
+            "rtc_min_port",
+            &format!("must not be greater than rtc_max_port ({})", maxport),
+        )?;
         value.rtc_port_range = minport..=maxport;
 
+        ensure(
+            self.dtls_certificate_file.is_some() == self.dtls_private_key_file.is_some(),
+            "dtls_private_key_file",
+            "dtls_certificate_file and dtls_private_key_file must be set together",
+        )?;
         if let (Some(cert), Some(private)) =
             (&self.dtls_certificate_file, &self.dtls_private_key_file)
         {
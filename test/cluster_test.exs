@@ -47,10 +47,10 @@ defmodule ClusterTest do
         router: router2
       })
 
-    assert 2 == Router.dump(router1)["transportIds"] |> length
-    assert 2 == Router.dump(router2)["transportIds"] |> length
+    assert 2 == Router.dump(router1).transport_ids |> length
+    assert 2 == Router.dump(router2).transport_ids |> length
 
-    assert "audio" == pipe_consumer.kind
+    assert :audio == pipe_consumer.kind
     refute pipe_consumer.rtp_parameters["mid"]
 
     assert [
@@ -87,7 +87,7 @@ defmodule ClusterTest do
              }
            ] === pipe_consumer.rtp_parameters["headerExtensions"]
 
-    assert "pipe" == pipe_consumer.type
+    assert :pipe == pipe_consumer.type
 
     assert Consumer.paused?(pipe_consumer) === false
     assert Consumer.producer_paused?(pipe_consumer) === false
@@ -99,7 +99,7 @@ defmodule ClusterTest do
            }
 
     assert pipe_producer.id === audio_producer.id
-    assert "audio" === pipe_producer.kind
+    assert :audio === pipe_producer.kind
     refute pipe_producer.rtp_parameters["mid"]
 
     assert [
@@ -139,6 +139,31 @@ defmodule ClusterTest do
     assert Producer.paused?(pipe_producer) === false
   end
 
+  @tag :cluster
+  test "bulk operations on consumers of another node", %{nodes: [node1]} do
+    {_worker, _worker2, router1, router2, transport1, _transport2} = init(node1)
+
+    {:ok, audio_producer} = WebRtcTransport.produce(transport1, audio_producer_options())
+
+    {:ok, %{pipe_consumer: pipe_consumer}} =
+      Router.pipe_producer_to_router(router1, audio_producer.id, %Router.PipeToRouterOptions{
+        router: router2
+      })
+
+    assert node(pipe_consumer.pid) == node1
+
+    assert {:ok} === Consumer.pause_all([pipe_consumer])
+    assert Consumer.paused?(pipe_consumer) === true
+
+    assert {:ok} === Consumer.resume_all([pipe_consumer])
+    assert Consumer.paused?(pipe_consumer) === false
+
+    pipe_consumer_id = pipe_consumer.id
+    Consumer.close(pipe_consumer)
+
+    assert {:error, %{^pipe_consumer_id => :terminated}} = Consumer.pause_all([pipe_consumer])
+  end
+
   @tag :cluster
   test "get_remote_node_ip", %{nodes: [node1]} do
     {:ok, remote_ip} = Mediasoup.Utility.get_remote_node_ip(Node.self(), node1)
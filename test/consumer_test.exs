@@ -20,6 +20,14 @@ defmodule ConsumerTest do
     IntegrateTest.ConsumerTest.close(worker)
   end
 
+  test "consume_batch_succeeds", %{worker: worker} do
+    IntegrateTest.ConsumerTest.consume_batch_succeeds(worker)
+  end
+
+  test "bulk_operations_succeed", %{worker: worker} do
+    IntegrateTest.ConsumerTest.bulk_operations_succeed(worker)
+  end
+
   test "consume_incompatible_rtp_capabilities", %{
     worker: worker
   } do
@@ -64,6 +72,10 @@ defmodule ConsumerTest do
     IntegrateTest.ConsumerTest.payload_events(worker)
   end
 
+  test "batched_events", %{worker: worker} do
+    IntegrateTest.ConsumerTest.batched_events(worker)
+  end
+
   test "id/1 returns the correct id", %{worker: worker} do
     {_worker, _router, transport_1, transport_2} = IntegrateTest.ConsumerTest.init(worker)
 
@@ -119,7 +131,7 @@ defmodule ConsumerTest do
         rtpCapabilities: IntegrateTest.ConsumerTest.consumer_device_capabilities()
       })
 
-    assert Mediasoup.Consumer.kind(audio_consumer) == "audio"
+    assert Mediasoup.Consumer.kind(audio_consumer) == :audio
 
     Mediasoup.Consumer.close(audio_consumer)
   end
@@ -161,7 +173,7 @@ defmodule ConsumerTest do
         rtpCapabilities: IntegrateTest.ConsumerTest.consumer_device_capabilities()
       })
 
-    assert Mediasoup.Consumer.type(audio_consumer) == "simple"
+    assert Mediasoup.Consumer.type(audio_consumer) == :simple
 
     Mediasoup.Consumer.close(audio_consumer)
   end
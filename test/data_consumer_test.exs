@@ -109,7 +109,7 @@ defmodule DataConsumerTest do
         ordered: true
       })
 
-    assert Mediasoup.DataConsumer.type(data_consumer) == "sctp"
+    assert Mediasoup.DataConsumer.type(data_consumer) == :sctp
 
     Mediasoup.DataConsumer.close(data_consumer)
   end
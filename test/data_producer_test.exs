@@ -26,6 +26,14 @@ defmodule DataProducerTest do
     IntegrateTest.DataProducerTest.data_produce_succeeds(worker)
   end
 
+  test "get_stats_succeeds", %{worker: worker} do
+    IntegrateTest.DataProducerTest.get_stats_succeeds(worker)
+  end
+
+  test "stats_subscribe_delta_succeeds", %{worker: worker} do
+    IntegrateTest.DataProducerTest.stats_subscribe_delta_succeeds(worker)
+  end
+
   test "close", %{worker: worker} do
     IntegrateTest.DataProducerTest.close(worker)
   end
@@ -66,7 +74,7 @@ defmodule DataProducerTest do
     {:ok, data_producer} =
       Mediasoup.WebRtcTransport.produce_data(transport, data_producer_options())
 
-    assert Mediasoup.DataProducer.type(data_producer) == "sctp"
+    assert Mediasoup.DataProducer.type(data_producer) == :sctp
 
     Mediasoup.DataProducer.close(data_producer)
   end
@@ -298,7 +298,7 @@ defmodule IntegrateTest.ConsumerTest do
       })
 
     assert audio_producer |> Producer.id() === audio_consumer |> Consumer.producer_id()
-    assert "audio" === audio_consumer.kind
+    assert :audio === audio_consumer.kind
     assert "0" === audio_consumer.rtp_parameters["mid"]
 
     assert [
@@ -331,13 +331,13 @@ defmodule IntegrateTest.ConsumerTest do
     assert Consumer.preferred_layers(audio_consumer) === nil
     assert Consumer.current_layers(audio_consumer) === nil
 
-    assert Router.dump(router)["mapProducerIdConsumerIds"] === %{
+    assert Router.dump(router).map_producer_id_consumer_ids === %{
              (audio_producer |> Producer.id()) => [audio_consumer |> Consumer.id()],
              (video_producer |> Producer.id()) => []
            }
 
-    assert WebRtcTransport.dump(transport_2)["producerIds"] === []
-    assert WebRtcTransport.dump(transport_2)["consumerIds"] === [audio_consumer.id]
+    assert WebRtcTransport.dump(transport_2).producer_ids === []
+    assert WebRtcTransport.dump(transport_2).consumer_ids === [audio_consumer.id]
 
     ## Video
     assert true === Router.can_consume?(router, video_producer.id, consumer_device_capabilities())
@@ -349,7 +349,7 @@ defmodule IntegrateTest.ConsumerTest do
       })
 
     assert video_producer.id === Consumer.producer_id(video_consumer)
-    assert "video" === Consumer.kind(video_consumer)
+    assert :video === Consumer.kind(video_consumer)
     assert "1" === Consumer.rtp_parameters(video_consumer)["mid"]
 
     assert [
@@ -397,15 +397,15 @@ defmodule IntegrateTest.ConsumerTest do
 
     assert Consumer.current_layers(video_consumer) === nil
 
-    assert Router.dump(router)["mapProducerIdConsumerIds"] === %{
+    assert Router.dump(router).map_producer_id_consumer_ids === %{
              audio_producer.id => [audio_consumer.id],
              video_producer.id => [video_consumer.id]
            }
 
-    assert WebRtcTransport.dump(transport_2)["producerIds"] === []
+    assert WebRtcTransport.dump(transport_2).producer_ids === []
 
-    assert WebRtcTransport.dump(transport_2)["consumerIds"] |> Enum.member?(video_consumer.id)
-    assert WebRtcTransport.dump(transport_2)["consumerIds"] |> Enum.member?(audio_consumer.id)
+    assert WebRtcTransport.dump(transport_2).consumer_ids |> Enum.member?(video_consumer.id)
+    assert WebRtcTransport.dump(transport_2).consumer_ids |> Enum.member?(audio_consumer.id)
 
     ## Video pipe
     assert true === Router.can_consume?(router, video_producer.id, consumer_device_capabilities())
@@ -418,7 +418,7 @@ defmodule IntegrateTest.ConsumerTest do
       })
 
     assert video_producer.id === video_pipe_consumer.producer_id
-    assert "video" === video_pipe_consumer.kind
+    assert :video === video_pipe_consumer.kind
     assert nil === video_pipe_consumer.rtp_parameters["mid"]
 
     assert [
@@ -462,15 +462,15 @@ defmodule IntegrateTest.ConsumerTest do
     assert Consumer.preferred_layers(video_pipe_consumer) === nil
     assert Consumer.current_layers(video_pipe_consumer) === nil
 
-    video_producer_ids = Router.dump(router)["mapProducerIdConsumerIds"][video_producer.id]
+    video_producer_ids = Router.dump(router).map_producer_id_consumer_ids[video_producer.id]
     assert video_consumer.id in video_producer_ids
     assert video_pipe_consumer.id in video_producer_ids
 
-    assert WebRtcTransport.dump(transport_2)["producerIds"] === []
-    assert audio_producer.id in WebRtcTransport.dump(transport_1)["producerIds"]
-    assert video_producer.id in WebRtcTransport.dump(transport_1)["producerIds"]
+    assert WebRtcTransport.dump(transport_2).producer_ids === []
+    assert audio_producer.id in WebRtcTransport.dump(transport_1).producer_ids
+    assert video_producer.id in WebRtcTransport.dump(transport_1).producer_ids
 
-    consumer_ids = WebRtcTransport.dump(transport_2)["consumerIds"]
+    consumer_ids = WebRtcTransport.dump(transport_2).consumer_ids
     assert video_consumer.id in consumer_ids
     assert video_pipe_consumer.id in consumer_ids
     assert audio_consumer.id in consumer_ids
@@ -481,6 +481,79 @@ defmodule IntegrateTest.ConsumerTest do
     Mediasoup.Worker.close(worker)
   end
 
+  def consume_batch_succeeds(worker) do
+    {worker, router, transport_1, transport_2} = init(worker)
+
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+    {:ok, video_producer} = WebRtcTransport.produce(transport_1, video_producer_options())
+
+    assert [{:ok, audio_consumer}, {:ok, video_consumer}, {:error, _message}, {:error, message}] =
+             WebRtcTransport.consume_batch(transport_2, consumer_device_capabilities(), [
+               %Consumer.BatchItem{producer_id: audio_producer.id},
+               %{producerId: video_producer.id, paused: true},
+               %Consumer.BatchItem{producer_id: "00000000-0000-0000-0000-000000000000"},
+               %Consumer.BatchItem{producer_id: "not-a-uuid"}
+             ])
+
+    assert message =~ "invalid producer id"
+
+    assert audio_producer.id === Consumer.producer_id(audio_consumer)
+    assert :audio === audio_consumer.kind
+    assert false === Consumer.paused?(audio_consumer)
+
+    assert video_producer.id === Consumer.producer_id(video_consumer)
+    assert :video === video_consumer.kind
+    assert true === Consumer.paused?(video_consumer)
+
+    assert [] === WebRtcTransport.consume_batch(transport_2, consumer_device_capabilities(), [])
+
+    Mediasoup.WebRtcTransport.close(transport_1)
+    Mediasoup.WebRtcTransport.close(transport_2)
+    Mediasoup.Router.close(router)
+    Mediasoup.Worker.close(worker)
+  end
+
+  def bulk_operations_succeed(worker) do
+    {worker, router, transport_1, transport_2} = init(worker)
+
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+    {:ok, video_producer} = WebRtcTransport.produce(transport_1, video_producer_options())
+
+    [{:ok, audio_consumer}, {:ok, video_consumer}] =
+      WebRtcTransport.consume_batch(transport_2, consumer_device_capabilities(), [
+        %Consumer.BatchItem{producer_id: audio_producer.id},
+        %Consumer.BatchItem{producer_id: video_producer.id}
+      ])
+
+    consumers = [audio_consumer, video_consumer]
+
+    assert {:ok} === Consumer.pause_all(consumers)
+    assert true === Consumer.paused?(audio_consumer)
+    assert true === Consumer.paused?(video_consumer)
+
+    assert {:ok} === Consumer.resume_all(consumers)
+    assert false === Consumer.paused?(audio_consumer)
+    assert false === Consumer.paused?(video_consumer)
+
+    assert {:ok} === Consumer.set_priority_all(consumers, 3)
+    assert 3 === Consumer.priority(audio_consumer)
+    assert 3 === Consumer.priority(video_consumer)
+
+    assert {:ok} === Consumer.request_key_frame_all([video_consumer])
+    assert {:ok} === Consumer.pause_all([])
+
+    video_consumer_id = video_consumer.id
+    Consumer.close(video_consumer)
+
+    assert {:error, %{^video_consumer_id => :terminated}} = Consumer.pause_all(consumers)
+    assert true === Consumer.paused?(audio_consumer)
+
+    Mediasoup.WebRtcTransport.close(transport_1)
+    Mediasoup.WebRtcTransport.close(transport_2)
+    Mediasoup.Router.close(router)
+    Mediasoup.Worker.close(worker)
+  end
+
   def consume_incompatible_rtp_capabilities(worker) do
     {worker, router, transport_1, transport_2} = init(worker)
     {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
@@ -550,10 +623,10 @@ defmodule IntegrateTest.ConsumerTest do
 
     dump = Consumer.dump(audio_consumer)
 
-    assert dump["producerId"] === audio_consumer.producer_id
-    assert dump["kind"] === audio_consumer.kind
+    assert dump.producer_id === audio_consumer.producer_id
+    assert dump.kind === :audio
 
-    assert dump["rtpParameters"]["codecs"] == [
+    assert dump.rtp_parameters["codecs"] == [
              %{
                "channels" => 2,
                "clockRate" => 48000,
@@ -569,7 +642,7 @@ defmodule IntegrateTest.ConsumerTest do
              }
            ]
 
-    assert dump["rtpParameters"]["headerExtensions"] == [
+    assert dump.rtp_parameters["headerExtensions"] == [
              %{"encrypt" => false, "id" => 1, "uri" => "urn:ietf:params:rtp-hdrext:sdes:mid"},
              %{
                "encrypt" => false,
@@ -584,7 +657,7 @@ defmodule IntegrateTest.ConsumerTest do
            ]
 
     ssrc = List.first(audio_consumer.rtp_parameters["encodings"])["ssrc"]
-    assert dump["rtpParameters"]["encodings"] == [%{"codecPayloadType" => 100, "ssrc" => ssrc}]
+    assert dump.rtp_parameters["encodings"] == [%{"codecPayloadType" => 100, "ssrc" => ssrc}]
 
     Mediasoup.WebRtcTransport.close(transport_1)
     Mediasoup.WebRtcTransport.close(transport_2)
@@ -609,8 +682,8 @@ defmodule IntegrateTest.ConsumerTest do
 
     [consumer_stat | _producer_stat] = Consumer.get_stats(audio_consumer)
 
-    assert consumer_stat["kind"] == "audio"
-    assert consumer_stat["mimeType"] == "audio/opus"
+    assert consumer_stat.kind == :audio
+    assert consumer_stat.mime_type == "audio/opus"
 
     {:ok, video_producer} = WebRtcTransport.produce(transport_1, video_producer_options())
     assert {:ok} === Producer.pause(video_producer)
@@ -623,10 +696,10 @@ defmodule IntegrateTest.ConsumerTest do
 
     [consumer_stat | _producer_stat] = Consumer.get_stats(video_consumer)
 
-    assert "simulcast" == Consumer.type(video_consumer)
+    assert :simulcast == Consumer.type(video_consumer)
 
-    assert consumer_stat["kind"] == "video"
-    assert consumer_stat["mimeType"] == "video/H264"
+    assert consumer_stat.kind == :video
+    assert consumer_stat.mime_type == "video/H264"
     Mediasoup.WebRtcTransport.close(transport_1)
     Mediasoup.WebRtcTransport.close(transport_2)
     Mediasoup.Router.close(router)
@@ -652,8 +725,8 @@ defmodule IntegrateTest.ConsumerTest do
         enable_rtx: false
       })
 
-    assert List.first(Consumer.dump(video_consumer)["rtpStreams"])["params"]["useNack"] == true
-    assert List.first(Consumer.dump(video_consumer2)["rtpStreams"])["params"]["useNack"] == false
+    assert List.first(Consumer.dump(video_consumer).rtp_streams)["params"]["useNack"] == true
+    assert List.first(Consumer.dump(video_consumer2).rtp_streams)["params"]["useNack"] == false
 
     Mediasoup.WebRtcTransport.close(transport_1)
     Mediasoup.WebRtcTransport.close(transport_2)
@@ -691,12 +764,12 @@ defmodule IntegrateTest.ConsumerTest do
     assert {:ok} === Consumer.pause(audio_consumer)
 
     consumer_dump = Consumer.dump(audio_consumer)
-    assert consumer_dump["paused"] == true
+    assert consumer_dump.paused == true
 
     assert {:ok} === Consumer.resume(audio_consumer)
 
     consumer_dump = Consumer.dump(audio_consumer)
-    assert consumer_dump["paused"] == false
+    assert consumer_dump.paused == false
     Mediasoup.WebRtcTransport.close(transport_1)
     Mediasoup.WebRtcTransport.close(transport_2)
     Mediasoup.Router.close(router)
@@ -807,11 +880,11 @@ defmodule IntegrateTest.ConsumerTest do
     Process.sleep(50)
     router_dump = Router.dump(router)
 
-    assert router_dump["mapProducerIdConsumerIds"] === %{audio_producer.id => []}
+    assert router_dump.map_producer_id_consumer_ids === %{audio_producer.id => []}
 
     transport_1_dump = WebRtcTransport.dump(transport_1)
-    assert transport_1_dump["producerIds"] == [audio_producer.id]
-    assert transport_1_dump["consumerIds"] == []
+    assert transport_1_dump.producer_ids == [audio_producer.id]
+    assert transport_1_dump.consumer_ids == []
   end
 
   def payload_events(worker) do
@@ -862,4 +935,35 @@ defmodule IntegrateTest.ConsumerTest do
     assert Map.has_key?(layers, "spatialLayer")
     assert Map.has_key?(layers, "temporalLayer")
   end
+
+  def batched_events(worker) do
+    {_worker, _router, transport_1, transport_2} = init(worker)
+    {:ok, video_producer} = WebRtcTransport.produce(transport_1, video_producer_options())
+
+    {:ok, video_consumer} =
+      WebRtcTransport.consume(transport_2, %{
+        producerId: video_producer.id,
+        rtpCapabilities: consumer_device_capabilities()
+      })
+
+    assert {:ok} = Consumer.event(video_consumer, self(), [:on_pause, :on_layers_change])
+
+    send(
+      video_consumer.pid,
+      {:nif_internal_event_batch,
+       [
+         {:nif_internal_event, :on_layers_change, %{"spatialLayer" => 1, "temporalLayer" => 0}},
+         {:nif_internal_event, :on_pause},
+         {:nif_internal_event, :on_layers_change, %{"spatialLayer" => 2, "temporalLayer" => 0}}
+       ]}
+    )
+
+    assert_receive {:on_layers_change, %{"spatialLayer" => 1}}, 1000
+    assert_receive {:on_pause}, 1000
+    assert_receive {:on_layers_change, %{"spatialLayer" => 2}}, 1000
+
+    ref = Process.monitor(video_consumer.pid)
+    send(video_consumer.pid, {:nif_internal_event_batch, [{:nif_internal_event, :on_close}]})
+    assert_receive {:DOWN, ^ref, :process, _pid, :normal}, 1000
+  end
 end
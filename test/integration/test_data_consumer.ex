@@ -43,15 +43,15 @@ defmodule IntegrateTest.DataConsumerTest do
       })
 
     assert data_producer |> DataProducer.id() === data_consumer |> DataConsumer.data_producer_id()
-    assert DataConsumer.type(data_consumer) === "sctp"
+    assert DataConsumer.type(data_consumer) === :sctp
     assert DataConsumer.closed?(data_consumer) === false
 
-    assert Router.dump(router)["mapDataProducerIdDataConsumerIds"] === %{
+    assert Router.dump(router).map_data_producer_id_data_consumer_ids === %{
              (data_producer |> DataProducer.id()) => [data_consumer |> DataConsumer.id()]
            }
 
-    assert WebRtcTransport.dump(transport)["dataProducerIds"] === [data_producer.id]
-    assert WebRtcTransport.dump(transport)["dataConsumerIds"] === [data_consumer.id]
+    assert WebRtcTransport.dump(transport).data_producer_ids === [data_producer.id]
+    assert WebRtcTransport.dump(transport).data_consumer_ids === [data_consumer.id]
 
     DataConsumer.close(data_consumer)
     DataProducer.close(data_producer)
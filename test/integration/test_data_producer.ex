@@ -39,18 +39,59 @@ defmodule IntegrateTest.DataProducerTest do
     {:ok, data_producer} = WebRtcTransport.produce_data(transport, data_producer_options())
 
     assert DataProducer.closed?(data_producer) === false
-    assert DataProducer.type(data_producer) === "sctp"
+    assert DataProducer.type(data_producer) === :sctp
     assert DataProducer.id(data_producer) |> String.length() >= 1
 
     router_dump = Router.dump(router)
 
-    assert router_dump["mapDataProducerIdDataConsumerIds"] === %{
+    assert router_dump.map_data_producer_id_data_consumer_ids === %{
              data_producer.id => []
            }
 
     transport_dump = WebRtcTransport.dump(transport)
-    assert transport_dump["dataProducerIds"] === [data_producer.id]
-    assert transport_dump["consumerIds"] === []
+    assert transport_dump.data_producer_ids === [data_producer.id]
+    assert transport_dump.consumer_ids === []
+    DataProducer.close(data_producer)
+  end
+
+  def get_stats_succeeds(worker) do
+    {_worker, _router, transport} = init(worker)
+
+    {:ok, data_producer} = WebRtcTransport.produce_data(transport, data_producer_options())
+
+    assert [
+             %Mediasoup.Stats.DataProducerStats{
+               label: "",
+               protocol: "",
+               messages_received: 0,
+               bytes_received: 0
+             }
+           ] = DataProducer.get_stats(data_producer)
+
+    DataProducer.close(data_producer)
+  end
+
+  def stats_subscribe_delta_succeeds(worker) do
+    {_worker, _router, transport} = init(worker)
+
+    {:ok, data_producer} = WebRtcTransport.produce_data(transport, data_producer_options())
+
+    {:ok, subscription} = DataProducer.stats_subscribe(data_producer, self(), 10, delta: true)
+
+    assert_receive {:on_stats,
+                    %Mediasoup.Stats.Sample{
+                      entity: :data_producer,
+                      delta: false,
+                      stats: [%Mediasoup.Stats.DataProducerStats{}]
+                    }}
+
+    assert_receive {:on_stats,
+                    %Mediasoup.Stats.Sample{
+                      delta: true,
+                      stats: [%Mediasoup.Stats.DataProducerStats{messages_received: 0}]
+                    }}
+
+    Mediasoup.Stats.unsubscribe(subscription)
     DataProducer.close(data_producer)
   end
 
@@ -272,10 +272,10 @@ defmodule IntegrateTest.PipeTransportTest do
         router: router2
       })
 
-    assert 2 == Mediasoup.Router.dump(router1)["transportIds"] |> length
-    assert 2 == Mediasoup.Router.dump(router2)["transportIds"] |> length
+    assert 2 == Mediasoup.Router.dump(router1).transport_ids |> length
+    assert 2 == Mediasoup.Router.dump(router2).transport_ids |> length
 
-    assert "audio" == pipe_consumer.kind
+    assert :audio == pipe_consumer.kind
     refute pipe_consumer.rtp_parameters["mid"]
 
     assert [
@@ -312,7 +312,7 @@ defmodule IntegrateTest.PipeTransportTest do
              }
            ] === pipe_consumer.rtp_parameters["headerExtensions"]
 
-    assert "pipe" == pipe_consumer.type
+    assert :pipe == pipe_consumer.type
 
     assert Consumer.paused?(pipe_consumer) === false
     assert Consumer.producer_paused?(pipe_consumer) === false
@@ -324,7 +324,7 @@ defmodule IntegrateTest.PipeTransportTest do
            }
 
     assert pipe_producer.id === audio_producer.id
-    assert "audio" === pipe_producer.kind
+    assert :audio === pipe_producer.kind
     refute pipe_producer.rtp_parameters["mid"]
 
     assert [
@@ -382,10 +382,10 @@ defmodule IntegrateTest.PipeTransportTest do
         router: router2
       })
 
-    assert 2 == Mediasoup.Router.dump(router1)["transportIds"] |> length
-    assert 2 == Mediasoup.Router.dump(router2)["transportIds"] |> length
+    assert 2 == Mediasoup.Router.dump(router1).transport_ids |> length
+    assert 2 == Mediasoup.Router.dump(router2).transport_ids |> length
 
-    assert "video" == pipe_consumer.kind
+    assert :video == pipe_consumer.kind
     refute pipe_consumer.rtp_parameters["mid"]
 
     assert [
@@ -416,7 +416,7 @@ defmodule IntegrateTest.PipeTransportTest do
              }
            ] === pipe_consumer.rtp_parameters["headerExtensions"]
 
-    assert "pipe" == pipe_consumer.type
+    assert :pipe == pipe_consumer.type
 
     assert Consumer.paused?(pipe_consumer) === false
     assert Consumer.producer_paused?(pipe_consumer) === true
@@ -428,7 +428,7 @@ defmodule IntegrateTest.PipeTransportTest do
            }
 
     assert pipe_producer.id === video_producer.id
-    assert "video" === pipe_producer.kind
+    assert :video === pipe_producer.kind
     refute pipe_producer.rtp_parameters["mid"]
 
     assert [
@@ -477,8 +477,8 @@ defmodule IntegrateTest.PipeTransportTest do
         }
       )
 
-    assert 2 == Mediasoup.Router.dump(router1)["transportIds"] |> length
-    assert 2 == Mediasoup.Router.dump(router2)["transportIds"] |> length
+    assert 2 == Mediasoup.Router.dump(router1).transport_ids |> length
+    assert 2 == Mediasoup.Router.dump(router2).transport_ids |> length
 
     assert pipe_data_consumer.data_producer_id === data_producer.id
 
@@ -528,7 +528,7 @@ defmodule IntegrateTest.PipeTransportTest do
         rtp_capabilities: consumer_device_capabilities()
       })
 
-    assert "video" == pipe_consumer.kind
+    assert :video == pipe_consumer.kind
     refute pipe_consumer.rtp_parameters["mid"]
 
     assert [
@@ -567,7 +567,7 @@ defmodule IntegrateTest.PipeTransportTest do
              }
            ] === pipe_consumer.rtp_parameters["headerExtensions"]
 
-    assert "pipe" == pipe_consumer.type
+    assert :pipe == pipe_consumer.type
 
     assert Consumer.paused?(pipe_consumer) === false
     assert Consumer.producer_paused?(pipe_consumer) === true
@@ -657,7 +657,7 @@ defmodule IntegrateTest.PipeTransportTest do
         rtpCapabilities: consumer_device_capabilities()
       })
 
-    assert "video" == video_consumer.kind
+    assert :video == video_consumer.kind
     assert "0" == video_consumer.rtp_parameters["mid"]
 
     assert [
@@ -722,11 +722,11 @@ defmodule IntegrateTest.PipeTransportTest do
         ordered: true
       })
 
-    assert Router.dump(router1)["mapDataProducerIdDataConsumerIds"] === %{
+    assert Router.dump(router1).map_data_producer_id_data_consumer_ids === %{
              data_producer.id => [pipe_data_consumer.id]
            }
 
-    assert Router.dump(router2)["mapDataProducerIdDataConsumerIds"] === %{
+    assert Router.dump(router2).map_data_producer_id_data_consumer_ids === %{
              pipe_data_producer.id => [data_consumer.id]
            }
   end
@@ -818,8 +818,8 @@ defmodule IntegrateTest.PipeTransportTest do
       router: router_b
     })
 
-    assert 3 == Mediasoup.Router.dump(router_a)["transportIds"] |> length
-    assert 1 == Mediasoup.Router.dump(router_b)["transportIds"] |> length
+    assert 3 == Mediasoup.Router.dump(router_a).transport_ids |> length
+    assert 1 == Mediasoup.Router.dump(router_b).transport_ids |> length
   end
 
   def pipe_produce_consume(worker) do
@@ -874,7 +874,7 @@ defmodule IntegrateTest.PipeTransportTest do
     assert PipeTransport.dump(pipe_transport_local)
     PipeTransport.event(pipe_transport_local, self())
 
-    assert "video" == video_consumer.kind
+    assert :video == video_consumer.kind
     assert "0" == video_consumer.rtp_parameters["mid"]
 
     assert [
@@ -911,7 +911,7 @@ defmodule IntegrateTest.PipeTransportTest do
         rtpCapabilities: consumer_device_capabilities()
       })
 
-    assert "video" == pipe_consumer.kind
+    assert :video == pipe_consumer.kind
     refute pipe_consumer.rtp_parameters["mid"]
 
     {:ok, _pipe_producer} =
@@ -952,9 +952,9 @@ defmodule IntegrateTest.PipeTransportTest do
         router: router3
       })
 
-    assert 3 == Mediasoup.Router.dump(router1)["transportIds"] |> length
-    assert 2 == Mediasoup.Router.dump(router2)["transportIds"] |> length
-    assert 1 == Mediasoup.Router.dump(router3)["transportIds"] |> length
+    assert 3 == Mediasoup.Router.dump(router1).transport_ids |> length
+    assert 2 == Mediasoup.Router.dump(router2).transport_ids |> length
+    assert 1 == Mediasoup.Router.dump(router3).transport_ids |> length
   end
 
   def pipe_data_produce_consume(worker) do
@@ -1003,24 +1003,24 @@ defmodule IntegrateTest.PipeTransportTest do
              Transport.sctp_parameters(pipe_transport_local)
            )
 
-    if Transport.sctp_state(pipe_transport_local) == "connecting" do
+    if Transport.sctp_state(pipe_transport_local) == :connecting do
       Process.sleep(1000)
     end
 
-    assert "connected" === Transport.sctp_state(pipe_transport_local)
+    assert :connected === Transport.sctp_state(pipe_transport_local)
     assert Transport.get_stats(pipe_transport_local)
 
-    assert PipeTransport.dump(pipe_transport_local)["dataConsumerIds"] === [pipe_data_consumer.id]
+    assert PipeTransport.dump(pipe_transport_local).data_consumer_ids === [pipe_data_consumer.id]
 
-    assert PipeTransport.dump(pipe_transport_remote)["dataProducerIds"] === [
+    assert PipeTransport.dump(pipe_transport_remote).data_producer_ids === [
              pipe_data_producer.id
            ]
 
-    assert Router.dump(router1)["mapDataProducerIdDataConsumerIds"] === %{
+    assert Router.dump(router1).map_data_producer_id_data_consumer_ids === %{
              data_producer.id => [pipe_data_consumer.id]
            }
 
-    assert Router.dump(router2)["mapDataProducerIdDataConsumerIds"] === %{
+    assert Router.dump(router2).map_data_producer_id_data_consumer_ids === %{
              pipe_data_producer.id => [data_consumer.id]
            }
   end
@@ -1136,4 +1136,17 @@ defmodule IntegrateTest.PipeTransportTest do
         router: router2
       })
   end
+
+  def pipe_to_same_router_fails(worker) do
+    {_worker, router1, _router2, transport1, _transport2} = init(worker)
+
+    {:ok, audio_producer} = WebRtcTransport.produce(transport1, audio_producer_options())
+
+    option = %Router.PipeToRouterOptions{router: router1}
+
+    assert {:error, "Destination router must be different"} =
+             Router.pipe_producer_to_router(router1, audio_producer.id, option)
+
+    assert 1 == Mediasoup.Router.dump(router1).transport_ids |> length
+  end
 end
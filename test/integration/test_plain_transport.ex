@@ -246,7 +246,7 @@ defmodule IntegrateTest.PlainTransportTest do
     assert %{"MIS" => 1024, "OS" => 1024, "maxMessageSize" => 262_144, "port" => 5000} ==
              PlainTransport.sctp_parameters(transport)
 
-    assert PlainTransport.sctp_state(transport) == "new"
+    assert PlainTransport.sctp_state(transport) == :new
   end
 
   defp test_create_succeeds_with_srtp_enabled(worker) do
@@ -318,31 +318,31 @@ defmodule IntegrateTest.PlainTransportTest do
 
     assert match?(
              [
-               %{
-                 "bytesReceived" => 0,
-                 "bytesSent" => 0,
-                 "comedia" => false,
-                 "probationBytesSent" => 0,
-                 "probationSendBitrate" => 0,
-                 "recvBitrate" => 0,
-                 "rtcpMux" => true,
-                 "rtcpTuple" => nil,
-                 "rtpBytesReceived" => 0,
-                 "rtpBytesSent" => 0,
-                 "rtpRecvBitrate" => 0,
-                 "rtpSendBitrate" => 0,
-                 "rtxBytesReceived" => 0,
-                 "rtxBytesSent" => 0,
-                 "rtxRecvBitrate" => 0,
-                 "rtxSendBitrate" => 0,
-                 "sctpState" => nil,
-                 "sendBitrate" => 0,
-                 "timestamp" => _,
-                 "transportId" => ^transport_id,
-                 "tuple" => %{
-                   "localAddress" => "127.0.0.1",
-                   "localPort" => _,
-                   "protocol" => "udp"
+               %Mediasoup.Stats.PlainTransportStats{
+                 bytes_received: 0,
+                 bytes_sent: 0,
+                 comedia: false,
+                 probation_bytes_sent: 0,
+                 probation_send_bitrate: 0,
+                 recv_bitrate: 0,
+                 rtcp_mux: true,
+                 rtcp_tuple: nil,
+                 rtp_bytes_received: 0,
+                 rtp_bytes_sent: 0,
+                 rtp_recv_bitrate: 0,
+                 rtp_send_bitrate: 0,
+                 rtx_bytes_received: 0,
+                 rtx_bytes_sent: 0,
+                 rtx_recv_bitrate: 0,
+                 rtx_send_bitrate: 0,
+                 sctp_state: nil,
+                 send_bitrate: 0,
+                 timestamp: _,
+                 transport_id: ^transport_id,
+                 tuple: %TransportTuple{
+                   local_address: "127.0.0.1",
+                   local_port: _,
+                   protocol: :udp
                  }
                }
              ],
@@ -350,6 +350,31 @@ defmodule IntegrateTest.PlainTransportTest do
            )
   end
 
+  def dump_succeeds(worker) do
+    {_worker, router} = init(worker)
+
+    {:ok, transport} =
+      Router.create_plain_transport(router, %{
+        listenIp: %{
+          ip: "127.0.0.1"
+        }
+      })
+
+    transport_id = transport.id
+
+    assert %PlainTransport.Dump{
+             id: ^transport_id,
+             comedia: false,
+             rtcp_mux: true,
+             producer_ids: [],
+             consumer_ids: [],
+             srtp_parameters: nil,
+             tuple: %TransportTuple{local_address: "127.0.0.1", protocol: :udp}
+           } = PlainTransport.dump(transport)
+
+    PlainTransport.close(transport)
+  end
+
   def connect_succeeds(worker) do
     {_worker, router} = init(worker)
 
@@ -380,6 +405,9 @@ defmodule IntegrateTest.PlainTransportTest do
              %{local_port: 9999, protocol: :udp, local_address: "127.0.0.1"},
              PlainTransport.tuple(transport)
            )
+
+    assert {:error, "ip, port and rtcp_port must not be set on a comedia transport"} =
+             PlainTransport.connect(transport, %{ip: "127.0.0.1", port: 1234, rtcpPort: 1235})
   end
 
   def close_event(worker) do
@@ -190,39 +190,39 @@ defmodule IntegrateTest.ProducerTest do
     {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
 
     assert Producer.closed?(audio_producer) === false
-    assert Producer.kind(audio_producer) === "audio"
-    assert Producer.type(audio_producer) === "simple"
+    assert Producer.kind(audio_producer) === :audio
+    assert Producer.type(audio_producer) === :simple
     assert Producer.paused?(audio_producer) === false
     assert Producer.score(audio_producer) === []
 
     router_dump = Router.dump(router)
 
-    assert router_dump["mapProducerIdConsumerIds"] === %{
+    assert router_dump.map_producer_id_consumer_ids === %{
              audio_producer.id => []
            }
 
     transport_1_dump = WebRtcTransport.dump(transport_1)
-    assert transport_1_dump["producerIds"] === [audio_producer.id]
-    assert transport_1_dump["consumerIds"] === []
+    assert transport_1_dump.producer_ids === [audio_producer.id]
+    assert transport_1_dump.consumer_ids === []
     Producer.close(audio_producer)
 
     {:ok, video_producer} = WebRtcTransport.produce(transport_2, video_producer_options())
 
     assert Producer.closed?(video_producer) === false
-    assert Producer.kind(video_producer) === "video"
-    assert Producer.type(video_producer) === "simulcast"
+    assert Producer.kind(video_producer) === :video
+    assert Producer.type(video_producer) === :simulcast
     assert Producer.paused?(video_producer) === false
     assert Producer.score(video_producer) === []
 
     router_dump = Router.dump(router)
 
-    assert router_dump["mapProducerIdConsumerIds"] === %{
+    assert router_dump.map_producer_id_consumer_ids === %{
              video_producer.id => []
            }
 
     transport_2_dump = WebRtcTransport.dump(transport_2)
-    assert transport_2_dump["producerIds"] === [video_producer.id]
-    assert transport_2_dump["consumerIds"] === []
+    assert transport_2_dump.producer_ids === [video_producer.id]
+    assert transport_2_dump.consumer_ids === []
   end
 
   def close(worker) do
@@ -307,6 +307,40 @@ defmodule IntegrateTest.ProducerTest do
     "RTP mapping error:" <> _ = message
   end
 
+  def produce_invalid_rtp_parameters(worker) do
+    {_worker, _router, transport_1, _transport_2} = init(worker)
+
+    error =
+      assert_raise ArgumentError, fn ->
+        WebRtcTransport.produce(transport_1, %{
+          kind: "video",
+          rtpParameters: %{
+            mid: "VIDEO",
+            codecs: [
+              %{
+                mimeType: "video/VP8",
+                payloadType: 112,
+                clockRate: 90000,
+                parameters: %{},
+                rtcpFeedback: []
+              }
+            ],
+            headerExtensions: [],
+            encodings: [
+              %{ssrc: 6666},
+              %{ssrc: 7777, scalabilityMode: "INVALID"}
+            ],
+            rtcp: %{
+              cname: "FOOBAR",
+              reducedSize: true
+            }
+          }
+        })
+      end
+
+    assert error.message =~ "rtp_parameters.encodings[1].scalabilityMode: "
+  end
+
   def produce_unsupported_codecs(worker) do
     {_worker, _router, transport_1, _transport_2} = init(worker)
 
@@ -523,10 +557,10 @@ defmodule IntegrateTest.ProducerTest do
 
     dump = Producer.dump(audio_producer)
 
-    assert dump["id"] == Producer.id(audio_producer)
-    assert dump["kind"] == Producer.kind(audio_producer)
+    assert dump.id == Producer.id(audio_producer)
+    assert dump.kind == :audio
 
-    assert dump["rtpParameters"] == %{
+    assert dump.rtp_parameters == %{
              "codecs" => [
                %{
                  "channels" => 2,
@@ -555,17 +589,17 @@ defmodule IntegrateTest.ProducerTest do
              "rtcp" => %{"cname" => "FOOBAR", "reducedSize" => true}
            }
 
-    assert Producer.rtp_parameters(audio_producer) == dump["rtpParameters"]
-    assert dump["type"] == "simple"
+    assert Producer.rtp_parameters(audio_producer) == dump.rtp_parameters
+    assert dump.type == :simple
 
     {:ok, video_producer} = WebRtcTransport.produce(transport_2, video_producer_options())
 
     dump = Producer.dump(video_producer)
 
-    assert dump["id"] == Producer.id(video_producer)
-    assert dump["kind"] == Producer.kind(video_producer)
+    assert dump.id == Producer.id(video_producer)
+    assert dump.kind == :video
 
-    assert dump["rtpParameters"] == %{
+    assert dump.rtp_parameters == %{
              "codecs" => [
                %{
                  "clockRate" => 90000,
@@ -616,7 +650,7 @@ defmodule IntegrateTest.ProducerTest do
              "rtcp" => %{"cname" => "FOOBAR", "reducedSize" => true}
            }
 
-    assert dump["type"] == "simulcast"
+    assert dump.type == :simulcast
   end
 
   def get_stats_succeeds(worker) do
@@ -632,6 +666,43 @@ defmodule IntegrateTest.ProducerTest do
     assert [] == stats
   end
 
+  def stats_subscribe_succeeds(worker) do
+    {_worker, _router, transport_1, _transport_2} = init(worker)
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+    producer_id = audio_producer.id
+
+    {:ok, subscription} = Producer.stats_subscribe(audio_producer, self(), 10)
+
+    assert_receive {:on_stats,
+                    %Mediasoup.Stats.Sample{
+                      id: ^producer_id,
+                      entity: :producer,
+                      delta: false,
+                      elapsed_ms: nil,
+                      stats: []
+                    }}
+
+    assert_receive {:on_stats, %Mediasoup.Stats.Sample{id: ^producer_id, elapsed_ms: elapsed}}
+    assert is_integer(elapsed)
+    refute_received {:nif_internal_event_batch, _}
+
+    assert {:ok} = Mediasoup.Stats.unsubscribe(subscription)
+
+    Producer.close(audio_producer)
+  end
+
+  def stats_subscribe_ends_on_close(worker) do
+    {_worker, _router, transport_1, _transport_2} = init(worker)
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+    producer_id = audio_producer.id
+
+    {:ok, _subscription} = Producer.stats_subscribe(audio_producer, self(), 10)
+    assert_receive {:on_stats, %Mediasoup.Stats.Sample{id: ^producer_id}}
+
+    Producer.close(audio_producer)
+    assert_receive {:on_stats_end, ^producer_id}
+  end
+
   def pause_resume_succeeds(worker) do
     {_worker, _router, transport_1, _transport_2} = init(worker)
     {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
@@ -639,11 +710,11 @@ defmodule IntegrateTest.ProducerTest do
     Producer.pause(audio_producer)
     dump = Producer.dump(audio_producer)
 
-    assert dump["paused"]
+    assert dump.paused
 
     Producer.resume(audio_producer)
     dump = Producer.dump(audio_producer)
-    assert dump["paused"] == false
+    assert dump.paused == false
   end
 
   def close_event(worker) do
@@ -660,11 +731,36 @@ defmodule IntegrateTest.ProducerTest do
 
     router_dump = Router.dump(router)
 
-    assert router_dump["mapProducerIdConsumerIds"] === %{}
+    assert router_dump.map_producer_id_consumer_ids === %{}
 
     transport_1_dump = WebRtcTransport.dump(transport_1)
-    assert transport_1_dump["producerIds"] == []
-    assert transport_1_dump["consumerIds"] == []
+    assert transport_1_dump.producer_ids == []
+    assert transport_1_dump.consumer_ids == []
+  end
+
+  def close_and_wait_succeeds(worker) do
+    {_worker, router, transport_1, _transport_2} = init(worker)
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+
+    Producer.event(audio_producer, self(), [:on_close, :on_close_reason])
+    assert {:ok} == Producer.close_and_wait(audio_producer)
+
+    assert_receive {:on_close_reason, :explicit}
+    assert_receive {:on_close}
+    assert Producer.closed?(audio_producer)
+
+    # The worker has confirmed the close, no need to wait for the router.
+    assert Router.dump(router).map_producer_id_consumer_ids === %{}
+  end
+
+  def close_reason_event_on_transport_close(worker) do
+    {_worker, _router, transport_1, _transport_2} = init(worker)
+    {:ok, audio_producer} = WebRtcTransport.produce(transport_1, audio_producer_options())
+
+    Producer.event(audio_producer, self(), [:on_close_reason])
+    WebRtcTransport.close(transport_1)
+
+    assert_receive {:on_close_reason, :parent_closed}
   end
 
   def producer_event(worker) do
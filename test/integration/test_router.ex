@@ -64,11 +64,64 @@ defmodule IntegrateTest.RouterTest do
              capabilitycodec
            )
 
-    assert match?(%{"rtpObserverIds" => [], "transportIds" => []}, Mediasoup.Router.dump(router))
+    assert match?(
+             %Mediasoup.Router.Dump{rtp_observer_ids: [], transport_ids: []},
+             Mediasoup.Router.dump(router)
+           )
     Mediasoup.Router.close(router)
     Mediasoup.Worker.close(worker)
   end
 
+  def get_stats_succeeds(worker) do
+    alias Mediasoup.{Router, WebRtcTransport}
+
+    {:ok, router} =
+      Mediasoup.Worker.create_router(worker, %{
+        mediaCodecs: [
+          %{
+            kind: "audio",
+            mimeType: "audio/opus",
+            clockRate: 48000,
+            channels: 2,
+            parameters: %{},
+            rtcpFeedback: []
+          }
+        ]
+      })
+
+    assert %{} == Router.get_stats(router)
+
+    {:ok, transport} =
+      Router.create_webrtc_transport(router, %{
+        listenIps: [%{ip: "127.0.0.1"}],
+        enableSctp: true
+      })
+
+    {:ok, data_producer} =
+      WebRtcTransport.produce_data(transport, %Mediasoup.DataProducer.Options{
+        sctp_stream_parameters: %{streamId: 0, ordered: true}
+      })
+
+    transport_id = transport.id
+    data_producer_id = data_producer.id
+
+    assert %{
+             ^transport_id => %Router.TransportStats{
+               kind: :webrtc_transport,
+               stats: {:ok, [%Mediasoup.Stats.WebRtcTransportStats{transport_id: ^transport_id}]},
+               producers: %{},
+               consumers: %{},
+               data_producers: %{
+                 ^data_producer_id => {:ok, [%Mediasoup.Stats.DataProducerStats{}]}
+               },
+               data_consumers: %{}
+             }
+           } = Router.get_stats(router)
+
+    Router.close(router)
+    assert {:error, :terminated} = Router.get_stats(router)
+  end
+
   def close_event(worker) do
     {:ok, router} =
       Mediasoup.Worker.create_router(worker, %{
@@ -98,6 +151,53 @@ defmodule IntegrateTest.RouterTest do
     assert_receive {:on_close}
   end
 
+  def close_and_wait_succeeds(worker) do
+    {:ok, router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+    ref = Process.monitor(router.pid)
+
+    assert {:ok} == Mediasoup.Router.close_and_wait(router)
+    assert_receive {:DOWN, ^ref, :process, _, :normal}
+    assert Mediasoup.Router.closed?(router)
+    assert Mediasoup.Worker.dump(worker).router_ids == []
+  end
+
+  def close_and_wait_with_transport_succeeds(worker) do
+    {:ok, router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+
+    {:ok, transport} =
+      Mediasoup.Router.create_webrtc_transport(router, %{listenIps: [%{ip: "127.0.0.1"}]})
+
+    ref = Process.monitor(router.pid)
+    transport_ref = Process.monitor(transport.pid)
+
+    assert {:ok} == Mediasoup.Router.close_and_wait(router)
+    assert_receive {:DOWN, ^transport_ref, :process, _, {:shutdown, :parent_closed}}
+    assert_receive {:DOWN, ^ref, :process, _, :normal}
+    assert Mediasoup.WebRtcTransport.closed?(transport)
+    assert Mediasoup.Router.closed?(router)
+    assert Mediasoup.Worker.dump(worker).router_ids == []
+  end
+
+  def close_when_owner_exits(worker) do
+    {:ok, router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+    owner = spawn(fn -> Process.sleep(:infinity) end)
+
+    assert {:ok} = Mediasoup.Router.monitor_owner(router, owner)
+    Mediasoup.Router.event(router, self())
+    refute Mediasoup.Router.closed?(router)
+
+    Process.exit(owner, :kill)
+    assert_receive {:on_owner_down, ^owner}
+    assert_receive {:on_close}
+    assert Mediasoup.Router.closed?(router)
+
+    {:ok, router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+    Mediasoup.Router.event(router, self())
+    assert {:ok} = Mediasoup.Router.monitor_owner(router, owner)
+    assert_receive {:on_owner_down, ^owner}
+    assert_receive {:on_close}
+  end
+
   def close_worker(worker) do
     {:ok, router} =
       Mediasoup.Worker.create_router(worker, %{
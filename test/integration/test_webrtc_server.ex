@@ -105,7 +105,7 @@ defmodule IntegrateTest.WebRtcServerTest do
         max_sctp_message_size: 1_000_000
       })
 
-    assert WebRtcTransport.ice_role(transport1) === "controlled"
+    assert WebRtcTransport.ice_role(transport1) === :controlled
     assert WebRtcTransport.ice_parameters(transport1)["iceLite"] === true
 
     ice_candidates = Mediasoup.WebRtcTransport.ice_candidates(transport1)
@@ -210,10 +210,10 @@ defmodule IntegrateTest.WebRtcServerTest do
       })
 
     dump = WebRtcServer.dump(webrtc_server)
-    assert dump["id"] == WebRtcServer.id(webrtc_server)
-    assert List.first(dump["udpSockets"])["ip"] == "127.0.0.1"
-    assert List.first(dump["tcpServers"])["ip"] == "127.0.0.1"
-    assert List.first(dump["tcpServers"])["ip"] == "127.0.0.1"
+    assert dump.id == WebRtcServer.id(webrtc_server)
+    assert List.first(dump.udp_sockets).ip == "127.0.0.1"
+    assert List.first(dump.tcp_servers).ip == "127.0.0.1"
+    assert List.first(dump.tcp_servers).ip == "127.0.0.1"
 
     #  assert_eq!(dump.webrtc_transport_ids, HashedSet::default());
     #  assert_eq!(dump.local_ice_username_fragments, vec![]);
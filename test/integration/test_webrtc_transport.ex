@@ -90,7 +90,7 @@ defmodule IntegrateTest.WebRtcTransportTest do
         maxSctpMessageSize: 1_000_000
       })
 
-    assert WebRtcTransport.ice_role(transport1) === "controlled"
+    assert WebRtcTransport.ice_role(transport1) === :controlled
     assert WebRtcTransport.ice_parameters(transport1)["iceLite"] === true
 
     assert WebRtcTransport.sctp_parameters(transport1) === %{
@@ -174,11 +174,11 @@ defmodule IntegrateTest.WebRtcTransportTest do
     assert priority4 > priority5
     assert priority5 > priority6
 
-    assert "new" == WebRtcTransport.ice_state(transport1)
+    assert :new == WebRtcTransport.ice_state(transport1)
     assert nil == WebRtcTransport.ice_selected_tuple(transport1)
     assert match?(%{"role" => "auto"}, WebRtcTransport.dtls_parameters(transport1))
-    assert "new" == WebRtcTransport.dtls_state(transport1)
-    assert "new" == WebRtcTransport.sctp_state(transport1)
+    assert :new == WebRtcTransport.dtls_state(transport1)
+    assert :new == WebRtcTransport.sctp_state(transport1)
   end
 
   def close(worker) do
@@ -217,6 +217,23 @@ defmodule IntegrateTest.WebRtcTransportTest do
       })
   end
 
+  def create_conflicting_preferences(worker) do
+    {_worker, router} = init(worker)
+
+    error =
+      assert_raise ArgumentError, fn ->
+        Router.create_webrtc_transport(router, %{
+          listenIps: [%{ip: "127.0.0.1"}],
+          enableTcp: true,
+          preferUdp: true,
+          preferTcp: true
+        })
+      end
+
+    assert error.message =~ "prefer_udp: "
+    assert Router.closed?(router) == false
+  end
+
   def get_stats_succeeds(worker) do
     {_worker, router} = init(worker)
 
@@ -235,26 +252,27 @@ defmodule IntegrateTest.WebRtcTransportTest do
     transport_id = transport.id
 
     [
-      %{
-        "bytesReceived" => 0,
-        "bytesSent" => 0,
-        "dtlsState" => "new",
-        "iceRole" => "controlled",
-        "iceState" => "new",
-        "probationBytesSent" => 0,
-        "probationSendBitrate" => 0,
-        "recvBitrate" => 0,
-        "rtpBytesReceived" => 0,
-        "rtpBytesSent" => 0,
-        "rtpRecvBitrate" => 0,
-        "rtpSendBitrate" => 0,
-        "rtxBytesReceived" => 0,
-        "rtxBytesSent" => 0,
-        "rtxRecvBitrate" => 0,
-        "rtxSendBitrate" => 0,
-        "sctpState" => nil,
-        "sendBitrate" => 0,
-        "transportId" => ^transport_id
+      %Mediasoup.Stats.WebRtcTransportStats{
+        bytes_received: 0,
+        bytes_sent: 0,
+        dtls_state: :new,
+        ice_role: :controlled,
+        ice_state: :new,
+        ice_selected_tuple: nil,
+        probation_bytes_sent: 0,
+        probation_send_bitrate: 0,
+        recv_bitrate: 0,
+        rtp_bytes_received: 0,
+        rtp_bytes_sent: 0,
+        rtp_recv_bitrate: 0,
+        rtp_send_bitrate: 0,
+        rtx_bytes_received: 0,
+        rtx_bytes_sent: 0,
+        rtx_recv_bitrate: 0,
+        rtx_send_bitrate: 0,
+        sctp_state: nil,
+        send_bitrate: 0,
+        transport_id: ^transport_id
       }
     ] = stats
   end
@@ -384,6 +402,27 @@ defmodule IntegrateTest.WebRtcTransportTest do
     assert_receive {:on_close}
   end
 
+  def close_and_wait_succeeds(worker) do
+    {_worker, router} = init(worker)
+
+    {:ok, transport} =
+      Router.create_webrtc_transport(router, %{
+        listenIps: [
+          %{
+            ip: "127.0.0.1",
+            announcedIp: "9.9.9.1"
+          }
+        ]
+      })
+
+    ref = Process.monitor(transport.pid)
+
+    assert {:ok} == WebRtcTransport.close_and_wait(transport)
+    assert_receive {:DOWN, ^ref, :process, _, :normal}
+    assert WebRtcTransport.closed?(transport)
+    assert Router.dump(router).transport_ids == []
+  end
+
   def close_router_event(worker) do
     {_worker, router} = init(worker)
 
@@ -504,7 +543,7 @@ defmodule IntegrateTest.WebRtcTransportTest do
         max_sctp_message_size: 1_000_000
       })
 
-    assert WebRtcTransport.ice_role(transport1) === "controlled"
+    assert WebRtcTransport.ice_role(transport1) === :controlled
     assert WebRtcTransport.ice_parameters(transport1)["iceLite"] === true
 
     ice_candidates = Mediasoup.WebRtcTransport.ice_candidates(transport1)
@@ -585,33 +624,33 @@ defmodule IntegrateTest.WebRtcTransportTest do
     ])
 
     # on_sctp_state_change
-    send(transport2.pid, {:nif_internal_event, :on_sctp_state_change, "new"})
-    assert_receive {:on_sctp_state_change, "new"}
+    send(transport2.pid, {:nif_internal_event, :on_sctp_state_change, :new})
+    assert_receive {:on_sctp_state_change, :new}
 
     # on_ice_state_change
-    send(transport2.pid, {:nif_internal_event, :on_ice_state_change, "new"})
-    assert_receive {:on_ice_state_change, "new"}
+    send(transport2.pid, {:nif_internal_event, :on_ice_state_change, :new})
+    assert_receive {:on_ice_state_change, :new}
 
     # on_dtls_state_change
-    send(transport2.pid, {:nif_internal_event, :on_dtls_state_change, "new"})
-    assert_receive {:on_dtls_state_change, "new"}
+    send(transport2.pid, {:nif_internal_event, :on_dtls_state_change, :new})
+    assert_receive {:on_dtls_state_change, :new}
 
     # on_ice_selected_tuple_change
     send(
       transport2.pid,
       {:nif_internal_event, :on_ice_selected_tuple_change,
-       %{
-         "localAddress" => "127.0.0.1",
-         "localPort" => 12345,
-         "protocol" => "udp"
+       %TransportTuple{
+         local_address: "127.0.0.1",
+         local_port: 12345,
+         protocol: :udp
        }}
     )
 
     assert_receive {:on_ice_selected_tuple_change,
-                    %{
-                      "localAddress" => "127.0.0.1",
-                      "localPort" => 12345,
-                      "protocol" => "udp"
+                    %TransportTuple{
+                      local_address: "127.0.0.1",
+                      local_port: 12345,
+                      protocol: :udp
                     }}
   end
 
@@ -68,15 +68,20 @@ defmodule IntegrateTest.WorkerTest do
   def worker_with_wrong_settings_port() do
     Process.flag(:trap_exit, true)
 
-    assert match?(
-             {:error, _},
+    assert {:error, {%ArgumentError{message: message}, _stacktrace}} =
              Worker.start_link(
                settings: %Worker.Settings{
                  rtc_min_port: 1000,
                  rtc_max_port: 999
                }
              )
-           )
+
+    assert message =~ ~r/rtc_min_port: must not be greater than rtc_max_port \(999\)/
+
+    assert {:error, {%ArgumentError{message: message}, _stacktrace}} =
+             Worker.start_link(settings: %{rtcMinPort: 60000})
+
+    assert message =~ ~r/rtc_min_port: must not be greater than rtc_max_port/
   end
 
   def update_settings_succeeds() do
@@ -101,7 +106,10 @@ defmodule IntegrateTest.WorkerTest do
         }
       )
 
-    assert match?(%{"routerIds" => []}, Worker.dump(worker))
+    assert match?(%Worker.Dump{router_ids: []}, Worker.dump(worker))
+    assert match?(%Worker.Dump{router_ids: []}, Worker.dump(worker, 1_000))
+    # nil waits without a deadline
+    assert match?(%Worker.Dump{router_ids: []}, Worker.dump(worker, nil))
     Mediasoup.Worker.close(worker)
   end
 
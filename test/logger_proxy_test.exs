@@ -202,4 +202,34 @@ defmodule Mediasoup.LoggerProxyTest do
              end) =~ "should be logged"
     end
   end
+
+  describe "sinks" do
+    test "target_prefixes filters by record target" do
+      LoggerProxy.start_link(max_level: :info, target_prefixes: ["mediasoup::"])
+
+      refute capture_log(fn ->
+               Mediasoup.Nif.debug_logger(:info, "test")
+               Process.sleep(10)
+             end) =~ "test"
+    end
+
+    test "each sink applies its own filters" do
+      alias LoggerProxy.SinkOptions
+
+      {:ok, info_sink} = Mediasoup.Nif.logger_add_sink(self(), %SinkOptions{max_level: :info})
+      {:ok, error_sink} = Mediasoup.Nif.logger_add_sink(self(), %SinkOptions{max_level: :error})
+
+      Mediasoup.Nif.debug_logger(:info, "sink test")
+      assert_receive %LoggerProxy.Record{body: "sink test", level: :info, metadata: %{}}
+      refute_receive %LoggerProxy.Record{body: "sink test"}, 100
+
+      assert :ok = Mediasoup.Nif.logger_remove_sink(info_sink)
+      Mediasoup.Nif.debug_logger(:error, "sink test")
+      assert_receive %LoggerProxy.Record{body: "sink test", level: :error}
+      refute_receive %LoggerProxy.Record{body: "sink test"}, 100
+
+      assert Mediasoup.Nif.logger_sink_dropped(error_sink) == 0
+      Mediasoup.Nif.logger_remove_sink(error_sink)
+    end
+  end
 end
@@ -0,0 +1,23 @@
+defmodule MetricsTest do
+  use ExUnit.Case
+
+  alias Mediasoup.{Metrics, Worker}
+
+  test "snapshot/0 counts async nifs and entities" do
+    {:ok, worker} = Worker.start_link()
+    {:ok, router} = Worker.create_router(worker, %{mediaCodecs: []})
+
+    %{async_nifs: async_nifs, entities: entities} = Metrics.snapshot()
+
+    assert %{calls: calls, queue_time: %{count: count}, run_time: %{buckets: buckets}} =
+             async_nifs["worker_create_router_async"]
+
+    assert calls >= 1 and count >= 1
+    assert [{_bound, _count} | _] = buckets
+    assert entities.router.live >= 1
+    assert entities.router.created >= entities.router.closed
+
+    Mediasoup.Router.close(router)
+    Worker.close(worker)
+  end
+end
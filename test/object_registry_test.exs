@@ -0,0 +1,34 @@
+defmodule ObjectRegistryTest do
+  use ExUnit.Case
+
+  alias Mediasoup.{Nif, ObjectRegistry, Router, Worker}
+
+  test "lists and finds routers of a worker" do
+    {:ok, worker} = Worker.start_link()
+    worker_id = Worker.id(worker)
+    {:ok, router} = Worker.create_router(worker, %{mediaCodecs: []})
+
+    assert [%{id: router_id, kind: :router, parent_id: ^worker_id}] =
+             ObjectRegistry.list(worker_id)
+
+    assert router_id == Router.id(router)
+    assert {:ok} = ObjectRegistry.put_app_data(router_id, %{"room" => "a"})
+    assert %{app_data: %{"room" => "a"}} = ObjectRegistry.info(router_id)
+    assert {:error, :not_found} = ObjectRegistry.put_app_data("unknown", %{})
+
+    assert {:router, reference} = ObjectRegistry.find(router_id)
+    assert Nif.router_id(reference) == router_id
+    assert ObjectRegistry.find_producer(router_id) == nil
+
+    {:worker, worker_reference} = ObjectRegistry.find(worker_id)
+    assert [found] = Nif.worker_list_routers(worker_reference)
+    assert Nif.router_id(found) == router_id
+
+    Router.close(router)
+    assert ObjectRegistry.find(router_id) == nil
+    assert ObjectRegistry.info(router_id) == nil
+    assert ObjectRegistry.list(worker_id) == []
+    assert {:error, :not_found} = ObjectRegistry.put_app_data(router_id, %{})
+    Worker.close(worker)
+  end
+end
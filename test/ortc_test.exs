@@ -0,0 +1,67 @@
+defmodule OrtcTest do
+  use ExUnit.Case
+
+  alias IntegrateTest.ConsumerTest
+  alias Mediasoup.{Ortc, Router, Worker}
+  alias Mediasoup.Ortc.Issue
+
+  import Mediasoup.TestUtil
+  setup_all :worker_leak_setup_all
+  setup :verify_worker_leak_on_exit!
+
+  setup do
+    {:ok, worker} = Worker.start_link()
+    {:ok, router} = Worker.create_router(worker, %{mediaCodecs: ConsumerTest.media_codecs()})
+
+    on_exit(fn -> Worker.close(worker) end)
+    %{router: router}
+  end
+
+  test "validate_rtp_capabilities/2 reports why codecs do not match", %{router: router} do
+    assert {:ok, _warnings} =
+             Ortc.validate_rtp_capabilities(router, ConsumerTest.consumer_device_capabilities())
+
+    capabilities = %{
+      codecs: [%{kind: "audio", mimeType: "audio/opus", clockRate: 44100, channels: 2}],
+      headerExtensions: []
+    }
+
+    assert {:error, issues} = Ortc.validate_rtp_capabilities(router, capabilities)
+
+    assert %Issue{severity: :warning, kind: :codec, reason: "clock rate 44100, expected 48000"} =
+             Enum.find(issues, &(&1.subject == "audio/opus"))
+
+    assert Enum.any?(issues, &match?(%Issue{severity: :error, subject: "audio"}, &1))
+  end
+
+  test "computes consumable and consumer rtp parameters", %{router: router} do
+    %{rtpParameters: rtp_parameters} = ConsumerTest.video_producer_options()
+
+    assert {:ok, _warnings} = Ortc.validate_rtp_parameters(router, rtp_parameters)
+    assert {:ok, consumable} = Ortc.consumable_rtp_parameters(router, rtp_parameters)
+    assert [%{"mimeType" => "video/H264"}, %{"mimeType" => "video/rtx"}] = consumable["codecs"]
+
+    assert {:ok, %{"codecs" => [%{"mimeType" => "video/H264"} | _]}} =
+             Ortc.consumer_rtp_parameters(consumable, ConsumerTest.consumer_device_capabilities())
+
+    assert {:error, "no compatible media codecs" <> _} =
+             Ortc.consumer_rtp_parameters(consumable, %{codecs: [], headerExtensions: []})
+
+    vp9_codec = %{mimeType: "video/VP9", payloadType: 98, clockRate: 90000}
+    vp9 = %{rtp_parameters | codecs: [vp9_codec]}
+
+    assert {:error, [%Issue{kind: :codec, subject: "video/VP9"}]} =
+             Ortc.validate_rtp_parameters(router, vp9)
+
+    assert {:error, "video/VP9: not supported by the router"} =
+             Ortc.consumable_rtp_parameters(router, vp9)
+  end
+
+  test "intersect_rtp_capabilities/2 keeps the codecs of both", %{router: router} do
+    capabilities = ConsumerTest.consumer_device_capabilities()
+    intersection = Ortc.intersect_rtp_capabilities(Router.rtp_capabilities(router), capabilities)
+
+    assert Enum.any?(intersection["codecs"], &(&1["mimeType"] == "video/H264"))
+    refute Enum.any?(intersection["codecs"], &(&1["mimeType"] == "video/VP8"))
+  end
+end
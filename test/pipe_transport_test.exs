@@ -146,6 +146,12 @@ defmodule PipeTransportTest do
     Mediasoup.PipeTransport.close(transport)
   end
 
+  test "pipe_to_same_router_fails", %{
+    worker: worker
+  } do
+    IntegrateTest.PipeTransportTest.pipe_to_same_router_fails(worker)
+  end
+
   test "struct_from_pid/1 returns the correct struct", %{worker: worker} do
     {:ok, router} = Mediasoup.Worker.create_router(worker, %{})
 
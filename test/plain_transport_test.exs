@@ -27,6 +27,10 @@ defmodule MediasoupElixirPlainTransportTest do
     IntegrateTest.PlainTransportTest.get_stats_succeeds(worker)
   end
 
+  test "dump_succeeds", %{worker: worker} do
+    IntegrateTest.PlainTransportTest.dump_succeeds(worker)
+  end
+
   test "create_with_port", %{worker: worker} do
     IntegrateTest.PlainTransportTest.create_with_port(worker)
   end
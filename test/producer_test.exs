@@ -24,6 +24,10 @@ defmodule ProducerTest do
     IntegrateTest.ProducerTest.produce_wrong_arguments(worker)
   end
 
+  test "produce_invalid_rtp_parameters", %{worker: worker} do
+    IntegrateTest.ProducerTest.produce_invalid_rtp_parameters(worker)
+  end
+
   test "produce_unsupported_codecs", %{
     worker: worker
   } do
@@ -50,6 +54,14 @@ defmodule ProducerTest do
     IntegrateTest.ProducerTest.get_stats_succeeds(worker)
   end
 
+  test "stats_subscribe_succeeds", %{worker: worker} do
+    IntegrateTest.ProducerTest.stats_subscribe_succeeds(worker)
+  end
+
+  test "stats_subscribe_ends_on_close", %{worker: worker} do
+    IntegrateTest.ProducerTest.stats_subscribe_ends_on_close(worker)
+  end
+
   test "pause_resume_succeeds", %{worker: worker} do
     IntegrateTest.ProducerTest.pause_resume_succeeds(worker)
   end
@@ -58,6 +70,14 @@ defmodule ProducerTest do
     IntegrateTest.ProducerTest.close_event(worker)
   end
 
+  test "close_and_wait_succeeds", %{worker: worker} do
+    IntegrateTest.ProducerTest.close_and_wait_succeeds(worker)
+  end
+
+  test "close_reason_event_on_transport_close", %{worker: worker} do
+    IntegrateTest.ProducerTest.close_reason_event_on_transport_close(worker)
+  end
+
   test "producer_event", %{worker: worker} do
     IntegrateTest.ProducerTest.producer_event(worker)
   end
@@ -99,7 +119,7 @@ defmodule ProducerTest do
         IntegrateTest.ProducerTest.audio_producer_options()
       )
 
-    assert Mediasoup.Producer.kind(audio_producer) == "audio"
+    assert Mediasoup.Producer.kind(audio_producer) == :audio
 
     Mediasoup.Producer.close(audio_producer)
   end
@@ -129,7 +149,7 @@ defmodule ProducerTest do
         IntegrateTest.ProducerTest.audio_producer_options()
       )
 
-    assert Mediasoup.Producer.type(audio_producer) == "simple"
+    assert Mediasoup.Producer.type(audio_producer) == :simple
 
     Mediasoup.Producer.close(audio_producer)
   end
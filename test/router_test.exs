@@ -26,10 +26,26 @@ defmodule RouterTest do
     IntegrateTest.RouterTest.router_dump(worker)
   end
 
+  test "get_stats_succeeds", %{worker: worker} do
+    IntegrateTest.RouterTest.get_stats_succeeds(worker)
+  end
+
   test "close_event", %{worker: worker} do
     IntegrateTest.RouterTest.close_event(worker)
   end
 
+  test "close_and_wait_succeeds", %{worker: worker} do
+    IntegrateTest.RouterTest.close_and_wait_succeeds(worker)
+  end
+
+  test "close_and_wait_with_transport_succeeds", %{worker: worker} do
+    IntegrateTest.RouterTest.close_and_wait_with_transport_succeeds(worker)
+  end
+
+  test "close_when_owner_exits", %{worker: worker} do
+    IntegrateTest.RouterTest.close_when_owner_exits(worker)
+  end
+
   test "close_worker", %{worker: worker} do
     IntegrateTest.RouterTest.close_worker(worker)
   end
@@ -0,0 +1,35 @@
+defmodule RuntimeTest do
+  use ExUnit.Case
+
+  alias Mediasoup.Runtime
+
+  test "stats/0 counts the work of both pools" do
+    before = Runtime.stats()
+
+    {:ok, worker} = Mediasoup.Worker.start_link()
+    {:ok, _router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+    Mediasoup.Worker.close(worker)
+
+    stats = Runtime.stats()
+
+    for pool <- [:task, :worker_manager] do
+      assert %{name: name, panics: 0, restarts: 0, threads: [_ | _] = threads} = stats[pool]
+      assert Enum.all?(threads, &String.starts_with?(&1.name, name))
+      assert busy_time_us(stats[pool]) > busy_time_us(before[pool])
+    end
+  end
+
+  test "init/1 fails once the pools are running" do
+    {:ok, worker} = Mediasoup.Worker.start_link()
+    Mediasoup.Worker.close(worker)
+    assert {:error, :already_started} = Runtime.init(%Runtime.Options{task_threads: 2})
+  end
+
+  test "init/1 validates options" do
+    assert_raise ArgumentError, ~r/task_threads/, fn ->
+      Runtime.init(%Runtime.Options{task_threads: 0})
+    end
+  end
+
+  defp busy_time_us(pool), do: pool.threads |> Enum.map(& &1.busy_time_us) |> Enum.sum()
+end
@@ -0,0 +1,97 @@
+defmodule SdpTest do
+  use ExUnit.Case
+
+  alias IntegrateTest.ConsumerTest
+  alias Mediasoup.{Consumer, Producer, Sdp, WebRtcTransport, Worker}
+
+  import Mediasoup.TestUtil
+  setup_all :worker_leak_setup_all
+  setup :verify_worker_leak_on_exit!
+
+  setup do
+    {:ok, worker} = Worker.start_link()
+    {_worker, _router, transport_1, transport_2} = ConsumerTest.init(worker)
+
+    on_exit(fn -> Worker.close(worker) end)
+    %{transport_1: transport_1, transport_2: transport_2}
+  end
+
+  @remote_offer """
+  v=0
+  o=- 4611731400430051336 2 IN IP4 127.0.0.1
+  s=-
+  t=0 0
+  a=group:BUNDLE 0
+  m=audio 9 UDP/TLS/RTP/SAVPF 111 0
+  c=IN IP4 0.0.0.0
+  a=ice-ufrag:remote
+  a=ice-pwd:remotepassword
+  a=fingerprint:sha-1 1B:EA:BF:33:B8:11:26:6D:91:AD:1B:A0:16:FD:5D:60:59:33:F7:46
+  a=setup:active
+  a=mid:0
+  a=sendonly
+  a=rtcp-mux
+  a=rtcp-rsize
+  a=rtpmap:111 opus/48000/2
+  a=fmtp:111 minptime=10;useinbandfec=1
+  a=extmap:1 urn:ietf:params:rtp-hdrext:sdes:mid
+  a=ssrc:22222222 cname:remote
+  """
+
+  test "offer describes a consumer that parse/1 reads back", %{
+    transport_1: transport_1,
+    transport_2: transport_2
+  } do
+    {:ok, producer} = WebRtcTransport.produce(transport_1, ConsumerTest.audio_producer_options())
+
+    {:ok, consumer} =
+      WebRtcTransport.consume(transport_2, %{
+        producerId: Producer.id(producer),
+        rtpCapabilities: ConsumerTest.consumer_device_capabilities()
+      })
+
+    assert {:ok, sdp} = Sdp.offer(transport_2, [consumer], session_id: 1)
+    assert sdp =~ "o=mediasoup-elixir 1 1 IN IP4 0.0.0.0\r\n"
+    assert sdp =~ "a=ice-lite\r\n"
+    assert sdp =~ "a=setup:actpass\r\n"
+    assert sdp =~ "a=sendonly\r\n"
+    assert sdp =~ "a=msid:- #{consumer.id}\r\n"
+
+    assert {:ok, %{"remoteParameters" => remote_parameters, "media" => [media]}} = Sdp.parse(sdp)
+
+    assert remote_parameters["dtlsParameters"]["fingerprints"] ==
+             WebRtcTransport.dtls_parameters(transport_2)["fingerprints"]
+
+    assert media["mid"] == Consumer.rtp_parameters(consumer)["mid"]
+
+    assert Enum.map(media["rtpParameters"]["encodings"], & &1["ssrc"]) ==
+             Enum.map(Consumer.rtp_parameters(consumer)["encodings"], & &1["ssrc"])
+
+    assert Enum.map(media["rtpParameters"]["codecs"], & &1["payloadType"]) ==
+             Enum.map(Consumer.rtp_parameters(consumer)["codecs"], & &1["payloadType"])
+  end
+
+  test "parse/1 reads a remote offer to connect, produce and answer", %{transport_1: transport} do
+    assert {:ok, parsed} = Sdp.parse(@remote_offer)
+    assert %{"dtlsParameters" => %{"role" => "client"}} = parsed["remoteParameters"]
+    assert [%{"kind" => "audio", "direction" => "sendonly"} = media] = parsed["media"]
+
+    assert %{"encodings" => [%{"ssrc" => 22_222_222}], "rtcp" => %{"cname" => "remote"}} =
+             media["rtpParameters"]
+
+    assert {:ok} = WebRtcTransport.connect(transport, parsed["remoteParameters"])
+
+    {:ok, producer} =
+      WebRtcTransport.produce(transport, %{kind: "audio", rtpParameters: media["rtpParameters"]})
+
+    assert {:ok, answer} = Sdp.answer(transport, [producer])
+    assert answer =~ "a=setup:passive\r\n"
+    assert answer =~ "a=mid:0\r\na=recvonly\r\n"
+    assert answer =~ "a=rtpmap:111 opus/48000/2\r\n"
+  end
+
+  test "reports invalid descriptions", %{transport_1: transport} do
+    assert {:error, "no DTLS fingerprint"} = Sdp.parse("v=0\r\nm=audio 9 RTP/AVP 0\r\n")
+    assert {:error, "no media to describe"} = Sdp.offer(transport, [])
+  end
+end
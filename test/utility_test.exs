@@ -75,4 +75,21 @@ defmodule Mediasoup.EventListenerTest do
     # Should have updated event types
     assert listener.listeners[self()].event_types == [:on_pause]
   end
+
+  test "send/3 drops value events while the mailbox holds max_queue_len messages" do
+    listener = Mediasoup.EventListener.new()
+
+    listener =
+      Mediasoup.EventListener.add(listener, self(), [:on_score, :on_close], max_queue_len: 2)
+
+    for score <- 1..3, do: Mediasoup.EventListener.send(listener, :on_score, {:on_score, score})
+    # lifecycle events are never dropped
+    Mediasoup.EventListener.send(listener, :on_close, {:on_close})
+
+    assert_received {:on_score, 1}
+    assert_received {:on_score, 2}
+    refute_received {:on_score, 3}
+    assert_received {:on_close}
+    assert Mediasoup.EventListener.dropped(listener, self()) == 1
+  end
 end
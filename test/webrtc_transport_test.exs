@@ -137,6 +137,10 @@ defmodule WebRtcTransportTest do
     IntegrateTest.WebRtcTransportTest.create_non_bindable_ip(worker)
   end
 
+  test "create_conflicting_preferences", %{worker: worker} do
+    IntegrateTest.WebRtcTransportTest.create_conflicting_preferences(worker)
+  end
+
   test "get_stats_succeeds", %{worker: worker} do
     IntegrateTest.WebRtcTransportTest.get_stats_succeeds(worker)
   end
@@ -161,6 +165,10 @@ defmodule WebRtcTransportTest do
     IntegrateTest.WebRtcTransportTest.close_event(worker)
   end
 
+  test "close_and_wait_succeeds", %{worker: worker} do
+    IntegrateTest.WebRtcTransportTest.close_and_wait_succeeds(worker)
+  end
+
   test "close_router_event", %{worker: worker} do
     IntegrateTest.WebRtcTransportTest.close_router_event(worker)
   end
@@ -210,7 +218,7 @@ defmodule WebRtcTransportTest do
         listen_ips: [%{ip: "127.0.0.1"}]
       })
 
-    assert Mediasoup.WebRtcTransport.ice_state(transport) == "new"
+    assert Mediasoup.WebRtcTransport.ice_state(transport) == :new
     Mediasoup.WebRtcTransport.close(transport)
   end
 
@@ -248,7 +256,7 @@ defmodule WebRtcTransportTest do
         listen_ips: [%{ip: "127.0.0.1"}]
       })
 
-    assert Mediasoup.WebRtcTransport.dtls_state(transport) == "new"
+    assert Mediasoup.WebRtcTransport.dtls_state(transport) == :new
     Mediasoup.WebRtcTransport.close(transport)
   end
 
@@ -261,7 +269,7 @@ defmodule WebRtcTransportTest do
         enable_sctp: true
       })
 
-    assert Mediasoup.WebRtcTransport.sctp_state(transport) == "new"
+    assert Mediasoup.WebRtcTransport.sctp_state(transport) == :new
     Mediasoup.WebRtcTransport.close(transport)
   end
 
@@ -317,7 +325,7 @@ defmodule WebRtcTransportTest do
         listen_ips: [%{ip: "127.0.0.1"}]
       })
 
-    assert Mediasoup.WebRtcTransport.ice_role(transport) == "controlled"
+    assert Mediasoup.WebRtcTransport.ice_role(transport) == :controlled
     Mediasoup.WebRtcTransport.close(transport)
   end
 end
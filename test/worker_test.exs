@@ -157,4 +157,103 @@ defmodule WorkerTest do
       # Also cover normal terminate
     end)
   end
+
+  test "unsubscribe stops nif events" do
+    {:ok, worker} = Mediasoup.Nif.create_worker()
+    {:ok, subscription} = Mediasoup.Nif.worker_event(worker, self(), [:on_close])
+    {:ok, other_subscription} = Mediasoup.Nif.worker_event(worker, self(), [:on_close])
+    assert {:ok} = Mediasoup.Nif.unsubscribe(subscription)
+
+    Mediasoup.Nif.worker_close(worker)
+    assert_receive {:nif_internal_event, :on_close}
+    refute_receive {:nif_internal_event, :on_close}
+    assert {:ok} = Mediasoup.Nif.unsubscribe(other_subscription)
+  end
+
+  test "nif event subscription options" do
+    {:ok, worker} = Mediasoup.Nif.create_worker()
+
+    options = %Mediasoup.EventOptions{min_interval_ms: 100, coalesce: true}
+    {:ok, subscription} = Mediasoup.Nif.worker_event(worker, self(), [:on_close], options)
+
+    assert_raise ArgumentError, ~r/coalesce: /, fn ->
+      Mediasoup.Nif.worker_event(worker, self(), [:on_close], %Mediasoup.EventOptions{
+        coalesce: "yes"
+      })
+    end
+
+    # lifecycle events are never dropped
+    Mediasoup.Nif.worker_close(worker)
+    assert_receive {:nif_internal_event, :on_close}
+    assert {:ok} = Mediasoup.Nif.unsubscribe(subscription)
+  end
+
+  test "nif events are sent one by one unless the subscription opts in to batches" do
+    {:ok, worker} = Mediasoup.Nif.create_worker()
+    {:ok, _} = Mediasoup.Nif.worker_event(worker, self(), [:on_close_reason, :on_close])
+
+    Mediasoup.Nif.worker_close(worker)
+    assert_receive {:nif_internal_event, :on_close_reason, :explicit}
+    assert_receive {:nif_internal_event, :on_close}
+    refute_received {:nif_internal_event_batch, _}
+  end
+
+  test "create_worker_async replies with the request reference" do
+    {:ok, first} = Mediasoup.Nif.create_worker_async()
+    {:ok, second} = Mediasoup.Nif.create_worker_async()
+    assert first != second
+
+    assert_receive {:mediasoup_async_nif_result, ^second, {:ok, worker}}, 5000
+    assert_receive {:mediasoup_async_nif_result, ^first, {:ok, other_worker}}, 5000
+    assert {:error, :completed} = Mediasoup.Nif.async_request_cancel(first)
+
+    Mediasoup.Nif.worker_close(worker)
+    Mediasoup.Nif.worker_close(other_worker)
+  end
+
+  test "cancelled create_worker_async does not reply" do
+    {:ok, request} = Mediasoup.Nif.create_worker_async()
+
+    case Mediasoup.Nif.async_request_cancel(request) do
+      {:ok} ->
+        refute_receive {:mediasoup_async_nif_result, ^request, _}, 1000
+        assert {:error, :cancelled} = Mediasoup.Nif.async_request_cancel(request)
+
+      {:error, :completed} ->
+        assert_receive {:mediasoup_async_nif_result, ^request, {:ok, worker}}
+        Mediasoup.Nif.worker_close(worker)
+    end
+  end
+
+  test "async nif replies {:error, :timeout} and drops the late result" do
+    {:ok, worker} = Mediasoup.Worker.start_link()
+    {:ok, router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+
+    {:ok, transport} =
+      Mediasoup.Router.create_webrtc_transport(router, %{listenIps: [%{ip: "127.0.0.1"}]})
+
+    # The transport keeps the native router open, so the close is not confirmed in time.
+    reference = Mediasoup.Router.to_ref(router)
+    assert :ok == Mediasoup.Nif.router_close_async(reference, :close, 50)
+    assert_receive {:mediasoup_async_nif_result, :close, {:error, :timeout}}, 1_000
+
+    Mediasoup.WebRtcTransport.close(transport)
+    refute_receive {:mediasoup_async_nif_result, :close, _}, 500
+
+    Mediasoup.Worker.close(worker)
+  end
+
+  test "set_log_sink routes the worker logs to a pid" do
+    {:ok, worker} = Mediasoup.Worker.start_link(settings: %{logLevel: :debug})
+    worker_id = Mediasoup.Worker.id(worker)
+
+    options = %Mediasoup.LoggerProxy.SinkOptions{max_level: :debug}
+    assert {:ok} = Mediasoup.Worker.set_log_sink(worker, self(), options)
+    {:ok, _router} = Mediasoup.Worker.create_router(worker, %{mediaCodecs: []})
+
+    assert_receive %Mediasoup.LoggerProxy.Record{worker_id: ^worker_id}, 1000
+
+    assert {:ok} = Mediasoup.Worker.set_log_sink(worker, nil)
+    Mediasoup.Worker.close(worker)
+  end
 end